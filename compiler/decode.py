@@ -26,13 +26,15 @@ def decode_bytecode(data):
     global_table_offset = int.from_bytes(data[17:21], 'big')
     const_pool_offset = int.from_bytes(data[21:25], 'big')
     function_table_offset = int.from_bytes(data[25:29], 'big')
-    bytecode_offset = int.from_bytes(data[29:33], 'big')
-    bytecode_size = int.from_bytes(data[33:37], 'big')
+    vtable_offset = int.from_bytes(data[29:33], 'big')
+    bytecode_offset = int.from_bytes(data[33:37], 'big')
+    bytecode_size = int.from_bytes(data[37:41], 'big')
     
     print(f"Type table offset: {type_table_offset}")
     print(f"Global table offset: {global_table_offset}")
     print(f"Const pool offset: {const_pool_offset}")
     print(f"Function table offset: {function_table_offset}")
+    print(f"Vtable offset: {vtable_offset}")
     print(f"Bytecode offset: {bytecode_offset}")
     print(f"Bytecode size: {bytecode_size}")
     
@@ -58,9 +60,67 @@ def decode_bytecode(data):
             type_table.append({ "type": type_id, "size": 16, "points_to": to })
             pos += 5
         elif type_id == 0x04:
-            size = data[pos+1]
-            type_table.append({ "type": type_id, "size": size })
-            pos += 2
+            size = int.from_bytes(data[pos+1:pos+5], 'big')
+            field_count = int.from_bytes(data[pos+5:pos+7], 'big')
+            pos += 7
+            fields = []
+            for _ in range(field_count):
+                offset = int.from_bytes(data[pos:pos+4], 'big')
+                field_type = int.from_bytes(data[pos+4:pos+8], 'big')
+                fields.append((offset, field_type))
+                pos += 8
+            type_table.append({ "type": type_id, "size": size, "fields": fields })
+        elif type_id == 0x05:
+            element = int.from_bytes(data[pos+1:pos+5], 'big')
+            length = int.from_bytes(data[pos+5:pos+9], 'big')
+            size = type_table[element]["size"] * length
+            type_table.append({ "type": type_id, "size": size, "element": element, "length": length })
+            pos += 9
+        elif type_id == 0x06:
+            element = int.from_bytes(data[pos+1:pos+5], 'big')
+            type_table.append({ "type": type_id, "size": 16, "element": element })
+            pos += 5
+        elif type_id == 0x07:
+            size = int.from_bytes(data[pos+1:pos+5], 'big')
+            variant_count = int.from_bytes(data[pos+5:pos+7], 'big')
+            pos += 7
+            variants = []
+            for _ in range(variant_count):
+                field_count = int.from_bytes(data[pos:pos+2], 'big')
+                pos += 2
+                fields = []
+                for _ in range(field_count):
+                    offset = int.from_bytes(data[pos:pos+4], 'big')
+                    field_type = int.from_bytes(data[pos+4:pos+8], 'big')
+                    fields.append((offset, field_type))
+                    pos += 8
+                variants.append(fields)
+            type_table.append({ "type": type_id, "size": size, "variants": variants })
+        elif type_id == 0x08:
+            type_table.append({ "type": type_id, "size": 24 })
+            pos += 1
+        elif type_id == 0x09:
+            value = int.from_bytes(data[pos+1:pos+5], 'big')
+            size = 8 + type_table[value]["size"]
+            type_table.append({ "type": type_id, "size": size, "value": value })
+            pos += 5
+        elif type_id == 0x0A:
+            value = int.from_bytes(data[pos+1:pos+5], 'big')
+            error = int.from_bytes(data[pos+5:pos+9], 'big')
+            size = 8 + max(type_table[value]["size"], type_table[error]["size"])
+            type_table.append({ "type": type_id, "size": size, "value": value, "error": error })
+            pos += 9
+        elif type_id == 0x0B:
+            element_count = int.from_bytes(data[pos+1:pos+3], 'big')
+            pos += 3
+            elements = []
+            size = 0
+            for _ in range(element_count):
+                element = int.from_bytes(data[pos:pos+4], 'big')
+                elements.append((size, element))
+                size += type_table[element]["size"]
+                pos += 4
+            type_table.append({ "type": type_id, "size": size, "fields": elements })
         else:
             print("BAD TYPE TABLE")
             exit(0)
@@ -112,6 +172,15 @@ def decode_bytecode(data):
                 value = bool(raw_value)
                 pos += size
                 const_pool.append({ "type_id": type_id, "value": value })
+            elif primitive_id == 0x05:  # char
+                value = chr(int.from_bytes(data[pos:pos+4], 'big'))
+                pos += 4
+                const_pool.append({ "type_id": type_id, "value": value })
+            elif 0x06 <= primitive_id <= 0x0C:  # i8..u64
+                size = type_table[type_id]["size"]
+                value = int.from_bytes(data[pos:pos+size], 'big', signed=primitive_id <= 0x08)
+                pos += size
+                const_pool.append({ "type_id": type_id, "value": value })
             else:
                 size = type_table[type_id]["size"]
                 value = int.from_bytes(data[pos:pos+size], 'big')
@@ -125,19 +194,42 @@ def decode_bytecode(data):
     
     function_table = []
     pos = function_table_offset
-    bytecode_start = function_table_offset + (len(data) - function_table_offset - bytecode_size)
-    while pos < bytecode_start:
+    while pos < vtable_offset:
         code_offset = int.from_bytes(data[pos:pos+8], 'big')
         param_count = int.from_bytes(data[pos+8:pos+10], 'big')
-        local_count = int.from_bytes(data[pos+10:pos+12], 'big')
+        return_type = int.from_bytes(data[pos+10:pos+14], 'big')
+        local_count = int.from_bytes(data[pos+14:pos+16], 'big')
         localss = []
-        pos += 12
+        pos += 16
         for _ in range(local_count):
             local_type = int.from_bytes(data[pos:pos+4], 'big')
             localss.append(local_type)
             pos += 4
 
-        function_table.append({ "offset": code_offset, "param_count": param_count, "local_cout": local_count, "locals": localss })
+        function_table.append({ "offset": code_offset, "param_count": param_count, "return_type": return_type, "local_cout": local_count, "locals": localss })
+
+    # param count of each trait method, then (type, [(method, function)]) per implementation
+    pos = vtable_offset
+    method_count = int.from_bytes(data[pos:pos+4], 'big')
+    pos += 4
+    param_counts = []
+    for _ in range(method_count):
+        param_counts.append(int.from_bytes(data[pos:pos+2], 'big'))
+        pos += 2
+    vtable_count = int.from_bytes(data[pos:pos+4], 'big')
+    pos += 4
+    vtables = []
+    for _ in range(vtable_count):
+        type_id = int.from_bytes(data[pos:pos+4], 'big')
+        entry_count = int.from_bytes(data[pos+4:pos+6], 'big')
+        pos += 6
+        entries = []
+        for _ in range(entry_count):
+            method = int.from_bytes(data[pos:pos+4], 'big')
+            func = int.from_bytes(data[pos+4:pos+8], 'big')
+            entries.append((method, func))
+            pos += 8
+        vtables.append({ "type_id": type_id, "methods": entries })
     
     # Define instruction parameter sizes
     instruction_info = {
@@ -148,9 +240,25 @@ def decode_bytecode(data):
         0x12: {"name": "STORE_LOCAL", "size": 3},
         0x13: {"name": "LOAD_GLOBAL", "size": 3},
         0x14: {"name": "STORE_GLOBAL", "size": 3},
+        0x15: {"name": "PUSH_ADDR_GLOBAL", "size": 3},
         0x31: {"name": "LOAD", "size": 1}, 
         0x32: {"name": "STORE", "size": 1}, 
+        0x33: {"name": "FIELD", "size": 5},
+        0x34: {"name": "SET_FIELD", "size": 5},
+        0x35: {"name": "INDEX", "size": 1},
+        0x36: {"name": "SET_INDEX", "size": 5},
+        0x37: {"name": "FILL", "size": 1},
+        0x38: {"name": "IS_VARIANT", "size": 5},
+        0x39: {"name": "SET_VARIANT", "size": 5},
         0x40: {"name": "BOX_ALLOC", "size": 5},
+        0x41: {"name": "NEW_AGGREGATE", "size": 5},
+        0x42: {"name": "VEC_NEW", "size": 5},
+        0x43: {"name": "VEC_PUSH", "size": 1},
+        0x44: {"name": "VEC_POP", "size": 1},
+        0x45: {"name": "VEC_LEN", "size": 1},
+        0x46: {"name": "STR_LEN", "size": 1},
+        0x47: {"name": "STR_SLICE", "size": 1},
+        0x48: {"name": "STR_NEXT", "size": 1},
         0x50: {"name": "ADD", "size": 1}, 
         0x51: {"name": "SUB", "size": 1}, 
         0x52: {"name": "MUL", "size": 1}, 
@@ -158,11 +266,25 @@ def decode_bytecode(data):
         0x54: {"name": "NEG", "size": 1}, 
         0x55: {"name": "INC", "size": 1},
         0x56: {"name": "MOD", "size": 1},
+        0x57: {"name": "TO_INT", "size": 1},
+        0x58: {"name": "TO_FLOAT", "size": 1},
+        0x59: {"name": "BIT_AND", "size": 1},
+        0x5A: {"name": "BIT_OR", "size": 1},
+        0x5B: {"name": "BIT_XOR", "size": 1},
+        0x5C: {"name": "SHL", "size": 1},
+        0x5D: {"name": "SHR", "size": 1},
+        0x5E: {"name": "BIT_NOT", "size": 1},
+        0x5F: {"name": "TO_CHAR", "size": 1},
         0x60: {"name": "JMP", "size": 9}, 
         0x61: {"name": "JMP_IF_TRUE", "size": 9}, 
         0x62: {"name": "JMP_IF_FALSE", "size": 9},
+        0x63: {"name": "JMP_TABLE", "size": 21},
         0x70: {"name": "CALL", "size": 5}, 
         0x71: {"name": "RET", "size": 1},
+        0x72: {"name": "FUNC_REF", "size": 5},
+        0x73: {"name": "CLOSURE", "size": 5},
+        0x74: {"name": "CALL_INDIRECT", "size": 1},
+        0x75: {"name": "CALL_VIRTUAL", "size": 5},
         0x80: {"name": "EQ", "size": 1}, 
         0x81: {"name": "NEQ", "size": 1}, 
         0x82: {"name": "LT", "size": 1}, 
@@ -174,6 +296,14 @@ def decode_bytecode(data):
         0x88: {"name": "NOT", "size": 1},
         0x90: {"name": "POP", "size": 1}, 
         0x91: {"name": "DUP", "size": 1}, 
+        0x92: {"name": "SWAP", "size": 1},
+        0xA0: {"name": "ADD_WRAP", "size": 1},
+        0xA1: {"name": "SUB_WRAP", "size": 1},
+        0xA2: {"name": "MUL_WRAP", "size": 1},
+        0xA3: {"name": "ADD_SAT", "size": 1},
+        0xA4: {"name": "SUB_SAT", "size": 1},
+        0xA5: {"name": "MUL_SAT", "size": 1},
+        0xA6: {"name": "TO_SIZED", "size": 2},
         0xFF: {"name": "HALT", "size": 1}
     }
 
@@ -190,24 +320,33 @@ def decode_bytecode(data):
         if opcode == 0x01:  # LOAD_CONST
             const_idx = int.from_bytes(data[pos+1:pos+5], 'big')
             bytecode.append({"opcode": name, "param": const_idx, "size": size, "byte_offset": pos - bytecode_start})
-        elif opcode in [0x10, 0x11, 0x12, 0x13, 0x14]:  # LOCAL and GLOBAL operations
+        elif opcode in [0x10, 0x11, 0x12, 0x13, 0x14, 0x15]:  # LOCAL and GLOBAL operations
             addr = int.from_bytes(data[pos+1:pos+3], 'big')
             bytecode.append({"opcode": name, "param": addr, "size": size, "byte_offset": pos - bytecode_start})
-        elif opcode == 0x40:
-            type_id = int.from_bytes(data[pos+1:pos+5], 'big')
-            bytecode.append({ "opcode": name, "param": type_id, "size": size, "byte_offset": pos - bytecode_start })
+        elif opcode in [0x33, 0x34, 0x36, 0x38, 0x39, 0x40, 0x41, 0x42]:  # type, field, element and variant operands
+            operand = int.from_bytes(data[pos+1:pos+5], 'big')
+            bytecode.append({ "opcode": name, "param": operand, "size": size, "byte_offset": pos - bytecode_start })
         elif opcode in [0x60, 0x61, 0x62]:  # JMP operations
             target = int.from_bytes(data[pos+1:pos+9], 'big')
             bytecode.append({"opcode": name, "param": target, "size": size, "byte_offset": pos - bytecode_start})
-        elif opcode == 0x70:  # CALL
+        elif opcode == 0x63:  # JMP_TABLE: len, lowest value, default, then len targets
+            count = int.from_bytes(data[pos+1:pos+5], 'big')
+            low = int.from_bytes(data[pos+5:pos+13], 'big', signed=True)
+            default = int.from_bytes(data[pos+13:pos+21], 'big')
+            targets = [int.from_bytes(data[pos+21+i*8:pos+29+i*8], 'big') for i in range(count)]
+            size += count * 8
+            bytecode.append({"opcode": name, "param": (low, default, targets), "size": size, "byte_offset": pos - bytecode_start})
+        elif opcode in [0x70, 0x72, 0x73, 0x75]:  # CALL, FUNC_REF, CLOSURE and CALL_VIRTUAL
             func_idx = int.from_bytes(data[pos+1:pos+5], 'big')
             bytecode.append({"opcode": name, "param": func_idx, "size": size, "byte_offset": pos - bytecode_start})
+        elif opcode == 0xA6:  # TO_SIZED: the primitive id to cast to
+            bytecode.append({"opcode": name, "param": data[pos+1], "size": size, "byte_offset": pos - bytecode_start})
         else:
             bytecode.append({"opcode": name, "param": None, "size": size, "byte_offset": pos - bytecode_start})
         
         pos += size
 
-    return type_table, global_table, const_pool, function_table, bytecode
+    return type_table, global_table, const_pool, function_table, (param_counts, vtables), bytecode
 
 def print_type_table(tt):
     print(f"\n=== TYPE TABLE ===")
@@ -220,7 +359,14 @@ def print_type_table(tt):
             0x01: "Primitive", 
             0x02: "Boxed",
             0x03: "Reference",
-            0x04: "Custom"
+            0x04: "Custom",
+            0x05: "Array",
+            0x06: "Vector",
+            0x07: "Enum",
+            0x08: "Function",
+            0x09: "Optional",
+            0x0A: "Result",
+            0x0B: "Tuple"
         }.get(idx, f"Unknown(0x{idx:02x})")
         
         print(f"Type {i}: {type_name}, Size: {size} bytes", end="")
@@ -230,12 +376,32 @@ def print_type_table(tt):
                 0x01: "int",
                 0x02: "float", 
                 0x03: "str",
-                0x04: "bool"
+                0x04: "bool",
+                0x05: "char",
+                0x06: "i8",
+                0x07: "i16",
+                0x08: "i32",
+                0x09: "u8",
+                0x0A: "u16",
+                0x0B: "u32",
+                0x0C: "u64"
             }.get(ty["primitive_id"], f"Unknown(0x{ty['primitive_id']:02x})")
             print(f", Primitive: {prim_name}")
         elif "points_to" in ty:
             pt = ty["points_to"]
             print(f", Points to: {pt}")
+        elif "fields" in ty:
+            print(f", Fields (offset, type): {ty['fields']}")
+        elif "variants" in ty:
+            print(f", Variants (offset, type): {ty['variants']}")
+        elif "error" in ty:
+            print(f", Value: {ty['value']}, Error: {ty['error']}")
+        elif "value" in ty:
+            print(f", Value: {ty['value']}")
+        elif "length" in ty:
+            print(f", Element: {ty['element']}, Length: {ty['length']}")
+        elif "element" in ty:
+            print(f", Element: {ty['element']}")
         else:
             print()
 
@@ -262,10 +428,19 @@ def print_function_table(ft):
         print(f"Function {i}:")
         print(f"    Code offset: 0x{offset:04x}")
         print(f"    Param count: {param_count}")
+        print(f"    Return type: {func['return_type']}")
         print(f"    Local count: {local_count}")
         if locals_types:
             print(f"    Local types: {locals_types}")
 
+def print_vtables(vt):
+    param_counts, vtables = vt
+    print(f"\n=== VTABLES ===")
+    for i, count in enumerate(param_counts):
+        print(f"Method {i}: {count} params")
+    for vtable in vtables:
+        print(f"Type {vtable['type_id']}: (method, function) {vtable['methods']}")
+
 def print_bytecode(bc):
     print(f"\n=== BYTECODE ===")
     for instr in bc:
@@ -291,7 +466,7 @@ if __name__ == "__main__":
     data = file.read()
     file.close()
     
-    tt, gt, cp, ft, bc = decode_bytecode(data)
+    tt, gt, cp, ft, vt, bc = decode_bytecode(data)
     
     print_type_table(tt)
     print_global_table(gt, tt)
@@ -299,6 +474,7 @@ if __name__ == "__main__":
     print_const_pool(cp, tt)
     print()
     print_function_table(ft)
+    print_vtables(vt)
     
     print_bytecode(bc)
 
@@ -6,7 +6,7 @@ use crate::{
         span::{Position, Span},
     },
     semantic_analyzer::err::SemanticError,
-    source::SourceFile,
+    source::{SourceFile, SourceMap},
 };
 
 #[derive(Debug)]
@@ -54,6 +54,10 @@ impl HarpyError {
         &self.kind
     }
 
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
     fn highlight_err(line: &str, span: Span) -> String {
         let line = line.trim_end();
         let line_number = span.start.line.to_string();
@@ -84,6 +88,7 @@ impl HarpyError {
                     line: span.start.line,
                     column: first_line.len(),
                     byte: 0,
+                    file: span.start.file,
                 },
             },
         ));
@@ -99,11 +104,13 @@ impl HarpyError {
                         line: current_line_num,
                         column: 1,
                         byte: 0,
+                        file: span.start.file,
                     },
                     end: Position {
                         line: current_line_num,
                         column: span.end.column,
                         byte: 0,
+                        file: span.start.file,
                     },
                 }
             } else {
@@ -112,11 +119,13 @@ impl HarpyError {
                         line: current_line_num,
                         column: 1,
                         byte: 0,
+                        file: span.start.file,
                     },
                     end: Position {
                         line: current_line_num,
                         column: line.len(),
                         byte: 0,
+                        file: span.start.file,
                     },
                 }
             };
@@ -128,13 +137,14 @@ impl HarpyError {
         result
     }
 
-    fn format_error(src: &SourceFile, span: Span, err_msg: &str) -> String {
+    fn format_error(src: &SourceFile, path: &str, span: Span, err_msg: &str) -> String {
         let start_idx = span.start.line.saturating_sub(1);
         let end_idx = span.end.line.min(src.line_count());
+        let location = format!(" --> {path}:{}:{}\n", span.start.line, span.start.column);
 
         if start_idx >= src.line_count() {
             return format!(
-                "{}{}Error!{} {err_msg} at EOF\n",
+                "{}{}Error!{} {err_msg} at EOF of {path}\n",
                 Color::Bold,
                 Color::Red,
                 Color::Reset
@@ -148,7 +158,7 @@ impl HarpyError {
 
         if lines.is_empty() {
             return format!(
-                "{}{}Error!{} {err_msg} at EOF\n",
+                "{}{}Error!{} {err_msg} at EOF of {path}\n",
                 Color::Bold,
                 Color::Red,
                 Color::Reset
@@ -156,13 +166,13 @@ impl HarpyError {
         }
 
         if lines.len() > 1 {
-            return Self::format_multiline(lines, span);
+            return location + &Self::format_multiline(lines, span);
         }
 
         let line = lines[0];
 
         format!(
-            "{}{}Error!{} {err_msg}:\n{} {err_msg}\n\n",
+            "{}{}Error!{} {err_msg}:\n{location}{} {err_msg}\n\n",
             Color::Bold,
             Color::Red,
             Color::Reset,
@@ -174,13 +184,20 @@ impl HarpyError {
         format!("IO Error: {:?}", err)
     }
 
-    pub fn show(&self, source: &SourceFile) {
+    pub fn show(&self, sources: &SourceMap) {
         let msg = match &self.kind {
             HarpyErrorKind::LexerError(e) => e.to_string(),
             HarpyErrorKind::SemanticError(e) => e.to_string(),
             HarpyErrorKind::IO(e) => self.io_msg(e),
         };
 
-        println!("{}", Self::format_error(source, self.span, &msg))
+        let file = self.span.start.file;
+        let Some(source) = sources.get(file) else {
+            println!("{}{}Error!{} {msg}\n", Color::Bold, Color::Red, Color::Reset);
+            return;
+        };
+        let path = sources.path(file).unwrap().display().to_string();
+
+        println!("{}", Self::format_error(source, &path, self.span, &msg))
     }
 }
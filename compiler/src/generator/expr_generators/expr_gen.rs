@@ -1,9 +1,25 @@
 use crate::{
-    generator::{generator::Generator, instruction::Instruction},
-    lexer::tokens::{Ident, Literal},
+    generator::{
+        generator::Generator,
+        instruction::{ElementIndex, Instruction, PrimitiveTypeId},
+    },
+    lexer::tokens::{Ident, Lit, Literal},
     parser::{
-        expr::{expr::CallExpr, infix::InfixOp, prefix::PrefixOp, Expr},
+        expr::{
+            expr::{
+                ArrayExpr, CallExpr, CastExpr, MethodCallExpr, OptionalExpr, ResultExpr,
+                StructExpr, TryExpr, TupleExpr, VariantExpr, VecExpr,
+            },
+            infix::{InfixOp, InfixOpKind},
+            prefix::{PrefixOp, PrefixOpKind},
+            Expr,
+        },
         node::Node,
+        types::{BaseType, PrimitiveType, RuntimeType, TypeInner},
+    },
+    semantic_analyzer::struct_table::{
+        FieldIndex, ERR_FIELD, ERR_VARIANT, NONE_VARIANT, OK_FIELD, OK_VARIANT, SOME_FIELD,
+        SOME_VARIANT,
     },
 };
 
@@ -13,7 +29,13 @@ pub struct ExprGenerator;
 
 impl ExprGenerator {
     fn generate_ident(node: &Node<Ident>, generator: &mut Generator) {
-        if generator.is_global(node.id()) {
+        if generator.is_const(node.id()) {
+            let value = generator.get_const_mapping(node.id());
+            generator.push_instruction(Instruction::LOAD_CONST(value));
+        } else if generator.is_function(node.id()) {
+            let func = generator.get_call_mapping(node.id());
+            generator.push_instruction(Instruction::FUNC_REF(func));
+        } else if generator.is_global(node.id()) {
             let global = generator.get_global_mapping(node.id());
             generator.push_instruction(Instruction::LOAD_GLOBAL(global));
         } else {
@@ -23,23 +45,85 @@ impl ExprGenerator {
     }
 
     fn generate_infix(lhs: &Expr, op: &InfixOp, rhs: &Expr, generator: &mut Generator) {
+        if matches!(op.op, InfixOpKind::And | InfixOpKind::Or) {
+            return Self::generate_logical(lhs, op, rhs, generator);
+        }
+
         Self::generate(lhs, generator);
         Self::generate(rhs, generator);
 
         generator.gen(op);
     }
 
+    // the right operand is only evaluated when the left one does not already
+    // decide the result, in which case the left value is the result
+    fn generate_logical(lhs: &Expr, op: &InfixOp, rhs: &Expr, generator: &mut Generator) {
+        let end_label = generator.create_label();
+
+        Self::generate(lhs, generator);
+        generator.push_instruction(Instruction::DUP);
+        if op.op == InfixOpKind::And {
+            generator.push_instruction(Instruction::JMP_IF_FALSE(end_label));
+        } else {
+            generator.push_instruction(Instruction::JMP_IF_TRUE(end_label));
+        }
+
+        generator.push_instruction(Instruction::POP);
+        Self::generate(rhs, generator);
+        generator.place_label(end_label);
+    }
+
     fn generate_prefix(op: &PrefixOp, rhs: &Expr, generator: &mut Generator) {
+        // a negated int literal is already negative in the const pool
+        if let (PrefixOpKind::Minus, Literal(lit)) = (&op.op, rhs) {
+            if matches!(lit.value(), Lit::LitInt(_)) {
+                return Self::generate_lit(lit, generator);
+            }
+        }
+
         Self::generate(rhs, generator);
         generator.gen(op);
     }
 
+    fn generate_cast(cast: &CastExpr, generator: &mut Generator) {
+        Self::generate(&cast.expr, generator);
+        match generator.lower_type(&cast.ty).inner {
+            TypeInner::Base(BaseType::Primitive(PrimitiveType::Float)) => {
+                generator.push_instruction(Instruction::TO_FLOAT)
+            }
+            TypeInner::Base(BaseType::Primitive(PrimitiveType::Char)) => {
+                generator.push_instruction(Instruction::TO_CHAR)
+            }
+            TypeInner::Base(BaseType::Primitive(p)) if p.is_sized_int() => {
+                generator.push_instruction(Instruction::TO_SIZED(PrimitiveTypeId(p.type_id())))
+            }
+            TypeInner::Base(BaseType::Primitive(PrimitiveType::Int)) => {
+                generator.push_instruction(Instruction::TO_INT)
+            }
+            // a newtype and what it wraps are the same value
+            _ => (),
+        }
+    }
+
     fn generate_call(call: &Node<CallExpr>, generator: &mut Generator) {
         for ele in &call.args {
             Self::generate(ele, generator);
         }
 
-        generator.push_instruction(Instruction::CALL(generator.get_call_mapping(call.id())));
+        if generator.is_function(call.id()) {
+            generator.push_instruction(Instruction::CALL(generator.get_call_mapping(call.id())));
+            return;
+        }
+
+        // the callee is a function value held in a variable
+        if generator.is_global(call.id()) {
+            let global = generator.get_global_mapping(call.id());
+            generator.push_instruction(Instruction::LOAD_GLOBAL(global));
+        } else {
+            let local = generator.get_local_mapping(call.id());
+            generator.push_instruction(Instruction::LOAD_LOCAL(local));
+        }
+        generator.push_instruction(Instruction::CALL_INDIRECT);
     }
 
     fn generate_borrow(expr: &Expr, generator: &mut Generator) {
@@ -59,6 +143,325 @@ impl ExprGenerator {
         generator.push_instruction(Instruction::BOX_ALLOC(id));
     }
 
+    fn is_place(expr: &Expr, generator: &Generator) -> bool {
+        match expr {
+            Ident(i) => generator.is_global(i.id()) || generator.is_local(i.id()),
+            Prefix(PrefixOp { op, .. }, _) => *op == PrefixOpKind::Star,
+            Index(base, _) if generator.is_vector(generator.get_expr_type(base.id())) => true,
+            Field(base, _) | Index(base, _) => {
+                generator.get_pointer_depth(generator.get_expr_type(base.id())) > 0
+                    || Self::is_place(base, generator)
+            }
+            _ => false,
+        }
+    }
+
+    // pushes the address of the struct or array `base` evaluates to, following refs and boxes
+    fn generate_container_address(base: &Node<Expr>, generator: &mut Generator) {
+        let depth = generator.get_pointer_depth(generator.get_expr_type(base.id()));
+        if depth == 0 {
+            Self::generate_place(base, generator);
+            return;
+        }
+
+        Self::generate(base, generator);
+        for _ in 1..depth {
+            generator.push_instruction(Instruction::LOAD);
+        }
+    }
+
+    // pushes the value `base` evaluates to, following refs and boxes
+    fn generate_receiver(base: &Node<Expr>, generator: &mut Generator) {
+        let depth = generator.get_pointer_depth(generator.get_expr_type(base.id()));
+        Self::generate(base, generator);
+        for _ in 0..depth {
+            generator.push_instruction(Instruction::LOAD);
+        }
+    }
+
+    fn generate_field(base: &Node<Expr>, field: &Node<Ident>, generator: &mut Generator) {
+        let idx = generator.get_field_mapping(field.id());
+
+        if Self::is_place(base, generator)
+            || generator.get_pointer_depth(generator.get_expr_type(base.id())) > 0
+        {
+            Self::generate_container_address(base, generator);
+            generator.push_instruction(Instruction::FIELD(idx));
+            generator.push_instruction(Instruction::LOAD);
+        } else {
+            Self::generate(base, generator);
+            generator.push_instruction(Instruction::FIELD(idx));
+        }
+    }
+
+    // the index is evaluated before the container, so no address into the heap
+    // is left on the stack while the index expression may allocate
+    fn generate_index(base: &Node<Expr>, index: &Node<Expr>, generator: &mut Generator) {
+        Self::generate(index, generator);
+
+        if generator.is_vector(generator.get_expr_type(base.id())) {
+            Self::generate_receiver(base, generator);
+            generator.push_instruction(Instruction::INDEX);
+            generator.push_instruction(Instruction::LOAD);
+        } else if Self::is_place(base, generator)
+            || generator.get_pointer_depth(generator.get_expr_type(base.id())) > 0
+        {
+            Self::generate_container_address(base, generator);
+            generator.push_instruction(Instruction::INDEX);
+            generator.push_instruction(Instruction::LOAD);
+        } else {
+            Self::generate(base, generator);
+            generator.push_instruction(Instruction::INDEX);
+        }
+    }
+
+    fn generate_array(expr: &Node<ArrayExpr>, generator: &mut Generator) {
+        let id = generator.get_expr_type(expr.id());
+        generator.push_instruction(Instruction::NEW_AGGREGATE(id));
+
+        match &**expr {
+            ArrayExpr::List(elements) => {
+                for (i, element) in elements.iter().enumerate() {
+                    Self::generate(element, generator);
+                    generator.push_instruction(Instruction::SET_INDEX(ElementIndex(i as u32)));
+                }
+            }
+            ArrayExpr::Repeat(value, _) => {
+                Self::generate(value, generator);
+                generator.push_instruction(Instruction::FILL);
+            }
+        }
+    }
+
+    fn generate_vec(expr: &Node<VecExpr>, generator: &mut Generator) {
+        let id = generator.get_expr_type(expr.id());
+        generator.push_instruction(Instruction::VEC_NEW(id));
+
+        for element in &expr.elements {
+            generator.push_instruction(Instruction::DUP);
+            Self::generate(element, generator);
+            generator.push_instruction(Instruction::VEC_PUSH);
+        }
+    }
+
+    fn generate_method_call(call: &MethodCallExpr, generator: &mut Generator) {
+        if generator.is_virtual(call.method.id()) {
+            Self::generate_virtual_method(call, generator);
+        } else if generator.is_function(call.method.id()) {
+            Self::generate_impl_method(call, generator);
+        } else if generator.is_vector(generator.get_expr_type(call.receiver.id())) {
+            Self::generate_vec_method(call, generator);
+        } else {
+            Self::generate_str_method(call, generator);
+        }
+    }
+
+    // the receiver goes first, as an address if the method takes `&self` or `&mut self`
+    fn generate_impl_method(call: &MethodCallExpr, generator: &mut Generator) {
+        let func = generator.get_call_mapping(call.method.id());
+        let receiver_param = generator.get_function(func).params[0];
+        if generator.get_pointer_depth(receiver_param) > 0 {
+            Self::generate_container_address(&call.receiver, generator);
+        } else {
+            Self::generate_receiver(&call.receiver, generator);
+        }
+
+        for arg in &call.args {
+            Self::generate(arg, generator);
+        }
+        generator.push_instruction(Instruction::CALL(func));
+    }
+
+    // the box of the trait object is the receiver, the vm finds the function
+    // to call from the type of the value in it
+    fn generate_virtual_method(call: &MethodCallExpr, generator: &mut Generator) {
+        Self::generate_container_address(&call.receiver, generator);
+        for arg in &call.args {
+            Self::generate(arg, generator);
+        }
+        let idx = generator.get_virtual_mapping(call.method.id());
+        generator.push_instruction(Instruction::CALL_VIRTUAL(idx));
+    }
+
+    fn generate_str_method(call: &MethodCallExpr, generator: &mut Generator) {
+        Self::generate_receiver(&call.receiver, generator);
+        for arg in &call.args {
+            Self::generate(arg, generator);
+        }
+
+        match call.method.value().as_str() {
+            "len" => generator.push_instruction(Instruction::STR_LEN),
+            "substring" => generator.push_instruction(Instruction::STR_SLICE),
+            method => panic!("unknown str method {method}"),
+        }
+    }
+
+    // get and set go through the element's address like indexing does, so
+    // the arguments are evaluated before the vector handle is pushed
+    fn generate_vec_method(call: &MethodCallExpr, generator: &mut Generator) {
+        let receiver = &call.receiver;
+        match call.method.value().as_str() {
+            "push" => {
+                Self::generate_receiver(receiver, generator);
+                Self::generate(&call.args[0], generator);
+                generator.push_instruction(Instruction::VEC_PUSH);
+            }
+            "pop" => {
+                Self::generate_receiver(receiver, generator);
+                generator.push_instruction(Instruction::VEC_POP);
+            }
+            "len" => {
+                Self::generate_receiver(receiver, generator);
+                generator.push_instruction(Instruction::VEC_LEN);
+            }
+            "get" => {
+                Self::generate(&call.args[0], generator);
+                Self::generate_receiver(receiver, generator);
+                generator.push_instruction(Instruction::INDEX);
+                generator.push_instruction(Instruction::LOAD);
+            }
+            "set" => {
+                Self::generate(&call.args[1], generator);
+                Self::generate(&call.args[0], generator);
+                Self::generate_receiver(receiver, generator);
+                generator.push_instruction(Instruction::INDEX);
+                generator.push_instruction(Instruction::STORE);
+            }
+            method => panic!("unknown vec method {method}"),
+        }
+    }
+
+    fn generate_struct(expr: &Node<StructExpr>, generator: &mut Generator) {
+        let id = generator.get_expr_type(expr.id());
+        generator.push_instruction(Instruction::NEW_AGGREGATE(id));
+
+        for field in &expr.fields {
+            Self::generate(&field.value, generator);
+            let idx = generator.get_field_mapping(field.name.id());
+            generator.push_instruction(Instruction::SET_FIELD(idx));
+        }
+    }
+
+    fn generate_tuple(expr: &Node<TupleExpr>, generator: &mut Generator) {
+        let id = generator.get_expr_type(expr.id());
+        generator.push_instruction(Instruction::NEW_AGGREGATE(id));
+
+        for (i, element) in expr.elements.iter().enumerate() {
+            Self::generate(element, generator);
+            generator.push_instruction(Instruction::SET_FIELD(FieldIndex(i as u32)));
+        }
+    }
+
+    fn generate_variant(expr: &Node<VariantExpr>, generator: &mut Generator) {
+        let id = generator.get_expr_type(expr.id());
+        generator.push_instruction(Instruction::NEW_AGGREGATE(id));
+        generator.push_instruction(Instruction::SET_VARIANT(generator.get_variant_mapping(expr.id())));
+
+        for arg in &expr.args {
+            Self::generate(arg, generator);
+            let idx = generator.get_field_mapping(arg.id());
+            generator.push_instruction(Instruction::SET_FIELD(idx));
+        }
+    }
+
+    fn generate_optional(expr: &Node<OptionalExpr>, generator: &mut Generator) {
+        let id = generator.get_expr_type(expr.id());
+        generator.push_instruction(Instruction::NEW_AGGREGATE(id));
+
+        let Some(value) = &expr.wrapped else {
+            generator.push_instruction(Instruction::SET_VARIANT(NONE_VARIANT));
+            return;
+        };
+
+        generator.push_instruction(Instruction::SET_VARIANT(SOME_VARIANT));
+        Self::generate(value, generator);
+        generator.push_instruction(Instruction::SET_FIELD(SOME_FIELD));
+    }
+
+    fn generate_result(expr: &Node<ResultExpr>, generator: &mut Generator) {
+        let id = generator.get_expr_type(expr.id());
+        generator.push_instruction(Instruction::NEW_AGGREGATE(id));
+
+        let (variant, field) = match &**expr {
+            ResultExpr::Ok(_) => (OK_VARIANT, OK_FIELD),
+            ResultExpr::Err(_) => (ERR_VARIANT, ERR_FIELD),
+        };
+        generator.push_instruction(Instruction::SET_VARIANT(variant));
+        Self::generate(expr.wrapped(), generator);
+        generator.push_instruction(Instruction::SET_FIELD(field));
+    }
+
+    fn generate_try(expr: &Node<TryExpr>, generator: &mut Generator) {
+        let operand_type = generator.get_expr_type(expr.expr.id());
+        let return_type = generator.get_expr_type(expr.id());
+        let is_result = matches!(
+            generator.get_type_info(operand_type).ttype,
+            RuntimeType::Result(_, _)
+        );
+        let (missing, field) = match is_result {
+            true => (ERR_VARIANT, OK_FIELD),
+            false => (NONE_VARIANT, SOME_FIELD),
+        };
+
+        let present = generator.create_label();
+        Self::generate(&expr.expr, generator);
+        generator.push_instruction(Instruction::DUP);
+        generator.push_instruction(Instruction::IS_VARIANT(missing));
+        generator.push_instruction(Instruction::JMP_IF_FALSE(present));
+
+        // the value is returned as is when the function returns the same type,
+        // otherwise the error moves into the function's own result
+        if operand_type != return_type {
+            if is_result {
+                generator.push_instruction(Instruction::FIELD(ERR_FIELD));
+            } else {
+                generator.push_instruction(Instruction::POP);
+            }
+            generator.push_instruction(Instruction::NEW_AGGREGATE(return_type));
+            generator.push_instruction(Instruction::SET_VARIANT(missing));
+            if is_result {
+                generator.push_instruction(Instruction::SWAP);
+                generator.push_instruction(Instruction::SET_FIELD(ERR_FIELD));
+            }
+        }
+        generator.push_instruction(Instruction::RET);
+
+        generator.place_label(present);
+        generator.push_instruction(Instruction::FIELD(field));
+    }
+
+    pub fn generate_place(expr: &Expr, generator: &mut Generator) {
+        match expr {
+            Ident(i) => {
+                if generator.is_global(i.id()) {
+                    let global = generator.get_global_mapping(i.id());
+                    generator.push_instruction(Instruction::PUSH_ADDR_GLOBAL(global));
+                } else {
+                    let local = generator.get_local_mapping(i.id());
+                    generator.push_instruction(Instruction::PUSH_ADDR_LOCAL(local));
+                }
+            }
+            Prefix(PrefixOp { op, .. }, rhs) if *op == PrefixOpKind::Star => {
+                Self::generate(rhs, generator)
+            }
+            Field(base, field) => {
+                let idx = generator.get_field_mapping(field.id());
+                Self::generate_container_address(base, generator);
+                generator.push_instruction(Instruction::FIELD(idx));
+            }
+            Index(base, index) => {
+                Self::generate(index, generator);
+                if generator.is_vector(generator.get_expr_type(base.id())) {
+                    Self::generate_receiver(base, generator);
+                } else {
+                    Self::generate_container_address(base, generator);
+                }
+                generator.push_instruction(Instruction::INDEX);
+            }
+            _ => panic!("Invalid place expression"),
+        }
+    }
+
     pub fn generate(expr: &Expr, generator: &mut Generator) {
         match expr {
             Ident(i) => Self::generate_ident(i, generator),
@@ -68,6 +471,22 @@ impl ExprGenerator {
             Borrow(expr, _) => Self::generate_borrow(expr, generator),
             Literal(l) => Self::generate_lit(l, generator),
             Box(expr) => Self::generate_box(expr, generator),
+            Field(base, field) => Self::generate_field(base, field, generator),
+            Struct(expr) => Self::generate_struct(expr, generator),
+            Index(base, index) => Self::generate_index(base, index, generator),
+            Array(expr) => Self::generate_array(expr, generator),
+            Vec(expr) => Self::generate_vec(expr, generator),
+            MethodCall(call) => Self::generate_method_call(call, generator),
+            Variant(expr) => Self::generate_variant(expr, generator),
+            Closure(closure) => generator.gen(&***closure),
+            Cast(cast) => Self::generate_cast(cast, generator),
+            Optional(expr) => Self::generate_optional(expr, generator),
+            Result(expr) => Self::generate_result(expr, generator),
+            Try(expr) => Self::generate_try(expr, generator),
+            Tuple(expr) => Self::generate_tuple(expr, generator),
+            If(expr) => expr.generate_value(generator),
+            Switch(expr) => expr.generate_value(generator),
+            Block(expr) => expr.generate_value(generator),
         }
     }
 }
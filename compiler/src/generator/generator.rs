@@ -2,10 +2,19 @@ use std::collections::HashMap;
 
 use crate::{
     aliases::{MAGIC_NUMBER, VERSION},
-    lexer::tokens::Lit,
-    parser::{expr::Expr, node::NodeId, program::Program, types::runtime::RuntimeType},
+    lexer::tokens::{Ident, Lit},
+    parser::{
+        expr::Expr,
+        node::{Node, NodeId},
+        program::Program,
+        statements::JumpKind,
+        types::{runtime::RuntimeType, Type},
+    },
     semantic_analyzer::{
         const_pool::ConstIndex, function_table::FuncIndex, result::RuntimeAnalysisResult,
+        struct_table::{FieldIndex, VariantIndex},
+        symbol_info::RuntimeFunctionInfo,
+        trait_table::VirtualIndex,
         type_table::RuntimeTypeIndex,
     },
 };
@@ -13,7 +22,7 @@ use crate::{
 use super::{
     compile_trait::Generate,
     expr_generators::expr_gen::ExprGenerator,
-    instruction::{GlobalAddress, Instruction, Label, LocalAddress},
+    instruction::{GlobalAddress, Instruction, JumpTable, JumpTableIndex, Label, LocalAddress},
 };
 
 static PREALLOC_CODE_BUFFER: usize = 4096;
@@ -24,11 +33,20 @@ pub enum BytecodeNode {
     Label(Label),
 }
 
+#[derive(Debug)]
+struct LoopTargets {
+    name: Option<String>,
+    break_label: Label,
+    continue_label: Label,
+}
+
 #[derive(Debug)]
 pub struct Generator {
     code: Vec<BytecodeNode>,
     function_entry_points: HashMap<FuncIndex, Label>,
     analysis_result: RuntimeAnalysisResult,
+    loops: Vec<LoopTargets>,
+    jump_tables: Vec<JumpTable>,
 
     next_label: u64,
 }
@@ -39,6 +57,8 @@ impl Generator {
             code: Vec::with_capacity(PREALLOC_CODE_BUFFER),
             function_entry_points: HashMap::new(),
             analysis_result: analysis,
+            loops: vec![],
+            jump_tables: vec![],
             next_label: 0,
         }
     }
@@ -51,6 +71,10 @@ impl Generator {
         ExprGenerator::generate(expr, self);
     }
 
+    pub fn gen_place(&mut self, expr: &Expr) {
+        ExprGenerator::generate_place(expr, self);
+    }
+
     pub fn create_label(&mut self) -> Label {
         let label = Label(self.next_label);
         self.next_label += 1;
@@ -65,10 +89,43 @@ impl Generator {
         self.code.push(BytecodeNode::Label(label));
     }
 
+    pub fn push_loop(&mut self, label: Option<&Node<Ident>>, break_label: Label, continue_label: Label) {
+        self.loops.push(LoopTargets {
+            name: label.map(|l| l.value().clone()),
+            break_label,
+            continue_label,
+        });
+    }
+
+    pub fn pop_loop(&mut self) {
+        self.loops.pop();
+    }
+
+    // the analyzer already made sure the target loop exists
+    pub fn get_loop_target(&self, kind: JumpKind, label: Option<&Node<Ident>>) -> Label {
+        let target = self
+            .loops
+            .iter()
+            .rev()
+            .find(|l| label.is_none_or(|label| l.name.as_ref() == Some(label.value())))
+            .unwrap();
+
+        match kind {
+            JumpKind::Break => target.break_label,
+            JumpKind::Continue => target.continue_label,
+        }
+    }
+
     pub fn push_instruction(&mut self, instruction: Instruction) {
         self.code.push(BytecodeNode::Instruction(instruction));
     }
 
+    pub fn push_jump_table(&mut self, table: JumpTable) {
+        let idx = JumpTableIndex(self.jump_tables.len() as u32);
+        self.jump_tables.push(table);
+        self.push_instruction(Instruction::JMP_TABLE(idx));
+    }
+
     pub fn get_local_mapping(&self, id: NodeId) -> LocalAddress {
         self.analysis_result.locals_map[&id]
     }
@@ -85,10 +142,32 @@ impl Generator {
         self.analysis_result.locals_map.contains_key(&id)
     }
 
+    // whether `id` names a function directly, as a call or as a value
+    pub fn is_function(&self, id: NodeId) -> bool {
+        self.analysis_result.function_table.contains_mapping(id)
+    }
+
+    pub fn get_closure_env(&self, id: NodeId) -> (Option<RuntimeTypeIndex>, Vec<LocalAddress>) {
+        let info = &self.analysis_result.closures[&id];
+        (info.env, info.captures.clone())
+    }
+
+    pub fn is_virtual(&self, id: NodeId) -> bool {
+        self.analysis_result.trait_table.contains_mapping(id)
+    }
+
+    pub fn get_virtual_mapping(&self, id: NodeId) -> VirtualIndex {
+        self.analysis_result.trait_table.get_mapping(id)
+    }
+
     pub fn get_call_mapping(&self, id: NodeId) -> FuncIndex {
         self.analysis_result.function_table.get_mapping(id)
     }
 
+    pub fn get_function(&self, idx: FuncIndex) -> &RuntimeFunctionInfo {
+        self.analysis_result.function_table.get(idx)
+    }
+
     pub fn get_main_index(&self) -> FuncIndex {
         self.analysis_result.main_id
     }
@@ -97,10 +176,54 @@ impl Generator {
         self.analysis_result.constants.get_mapping(id)
     }
 
+    pub fn get_const_value(&self, id: NodeId) -> &Lit {
+        &self.analysis_result.constants.get(self.get_const_mapping(id)).lit
+    }
+
+    // whether `id` has a value computed at compile time
+    pub fn is_const(&self, id: NodeId) -> bool {
+        self.analysis_result.constants.contains_mapping(id)
+    }
+
     pub fn get_expr_type(&self, id: NodeId) -> RuntimeTypeIndex {
         self.analysis_result.expr_map[&id]
     }
 
+    pub fn get_field_mapping(&self, id: NodeId) -> FieldIndex {
+        self.analysis_result.fields_map[&id]
+    }
+
+    pub fn get_variant_mapping(&self, id: NodeId) -> VariantIndex {
+        self.analysis_result.variants_map[&id]
+    }
+
+    pub fn get_pointer_depth(&self, type_idx: RuntimeTypeIndex) -> usize {
+        let mut depth = 0;
+        let mut idx = type_idx;
+        while let RuntimeType::Boxed(inner) | RuntimeType::Ref(inner) = self.get_type_info(idx).ttype {
+            depth += 1;
+            idx = inner;
+        }
+        depth
+    }
+
+    pub fn is_void(&self, type_idx: RuntimeTypeIndex) -> bool {
+        self.get_type_info(type_idx).ttype == RuntimeType::Void
+    }
+
+    pub fn is_vector(&self, type_idx: RuntimeTypeIndex) -> bool {
+        let mut idx = type_idx;
+        while let RuntimeType::Boxed(inner) | RuntimeType::Ref(inner) = self.get_type_info(idx).ttype {
+            idx = inner;
+        }
+        matches!(self.get_type_info(idx).ttype, RuntimeType::Vector(_))
+    }
+
+    // a type as written in the source, as it is at runtime
+    pub fn lower_type(&self, ty: &Type) -> Type {
+        self.analysis_result.type_table.names().lower(ty)
+    }
+
     pub fn get_type_info(
         &self,
         type_idx: RuntimeTypeIndex,
@@ -127,18 +250,37 @@ impl Generator {
 
         for ty in self.analysis_result.type_table.iter() {
             match &ty.ttype {
-                RuntimeType::Base(b) => {
-                    match &b {
-                        crate::parser::types::BaseType::Primitive(p) => {
-                            data.push(0x01);
-                            data.push(p.type_id());
+                RuntimeType::Base(b) => match &b {
+                    crate::parser::types::BaseType::Primitive(p) => {
+                        data.push(0x01);
+                        data.push(p.type_id());
+                        data.push(ty.size);
+                    }
+                    crate::parser::types::BaseType::Custom(c) => {
+                        if let Some(layout) = self.analysis_result.struct_table.get_enum(c.name()) {
+                            data.push(0x07);
+                            data.extend(layout.size.to_be_bytes());
+                            data.extend((layout.variants.len() as u16).to_be_bytes());
+                            for fields in &layout.variants {
+                                data.extend((fields.len() as u16).to_be_bytes());
+                                for field in fields {
+                                    data.extend(field.offset.to_be_bytes());
+                                    data.extend(field.type_index.0.to_be_bytes());
+                                }
+                            }
+                            continue;
                         }
-                        crate::parser::types::BaseType::Custom(_) => {
-                            data.push(0x04);
+
+                        let layout = self.analysis_result.struct_table.get(c.name());
+                        data.push(0x04);
+                        data.extend(layout.size.to_be_bytes());
+                        data.extend((layout.fields.len() as u16).to_be_bytes());
+                        for field in &layout.fields {
+                            data.extend(field.offset.to_be_bytes());
+                            data.extend(field.type_index.0.to_be_bytes());
                         }
                     }
-                    data.push(ty.size);
-                }
+                },
                 RuntimeType::Boxed(i) => {
                     data.push(0x02);
                     data.extend(i.0.to_be_bytes());
@@ -147,6 +289,34 @@ impl Generator {
                     data.push(0x03);
                     data.extend(i.0.to_be_bytes());
                 }
+                RuntimeType::Array(i, len) => {
+                    data.push(0x05);
+                    data.extend(i.0.to_be_bytes());
+                    data.extend(len.to_be_bytes());
+                }
+                RuntimeType::Vector(i) => {
+                    data.push(0x06);
+                    data.extend(i.0.to_be_bytes());
+                }
+                RuntimeType::Optional(i) => {
+                    data.push(0x09);
+                    data.extend(i.0.to_be_bytes());
+                }
+                RuntimeType::Result(value, error) => {
+                    data.push(0x0A);
+                    data.extend(value.0.to_be_bytes());
+                    data.extend(error.0.to_be_bytes());
+                }
+                RuntimeType::Tuple(elements) => {
+                    data.push(0x0B);
+                    data.extend((elements.len() as u16).to_be_bytes());
+                    for element in elements {
+                        data.extend(element.0.to_be_bytes());
+                    }
+                }
+                RuntimeType::Function => {
+                    data.push(0x08);
+                }
                 RuntimeType::Void => {
                     data.push(0x00);
                 }
@@ -176,6 +346,12 @@ impl Generator {
                 Lit::LitInt(i) => data.extend(i.to_be_bytes()),
                 Lit::LitBool(b) => data.push(*b as u8),
                 Lit::LitFloat(f) => data.extend(f.to_be_bytes()),
+                Lit::LitChar(c) => data.extend((*c as u32).to_be_bytes()),
+                // the low bytes of the two's complement value
+                Lit::LitSized(s) => {
+                    let size = s.ty.int_bits().unwrap_or(64) as usize / 8;
+                    data.extend(&s.value.to_be_bytes()[16 - size..]);
+                }
                 Lit::LitStr(s) => {
                     data.extend((s.len() as u64).to_be_bytes());
                     data.extend(s.as_bytes());
@@ -194,6 +370,7 @@ impl Generator {
             let code_offset = label_positions.get(&label).unwrap_or(&0);
             data.extend(code_offset.to_be_bytes());
             data.extend((func.params.len() as u16).to_be_bytes());
+            data.extend(func.return_type.0.to_be_bytes());
             data.extend((func.locals.len() as u16).to_be_bytes());
             for ty in &func.locals {
                 data.extend(ty.0.to_be_bytes())
@@ -203,6 +380,30 @@ impl Generator {
         data
     }
 
+    // the param count of every trait method, then the functions each type
+    // implementing a trait calls for its methods
+    fn generate_vtables(&self) -> Vec<u8> {
+        let mut data: Vec<u8> = Vec::new();
+        let traits = &self.analysis_result.trait_table;
+
+        data.extend((traits.param_counts.len() as u32).to_be_bytes());
+        for count in &traits.param_counts {
+            data.extend(count.to_be_bytes());
+        }
+
+        data.extend((traits.vtables.len() as u32).to_be_bytes());
+        for (ty, methods) in &traits.vtables {
+            data.extend(ty.0.to_be_bytes());
+            data.extend((methods.len() as u16).to_be_bytes());
+            for (method, func) in methods {
+                data.extend(method.0.to_be_bytes());
+                data.extend(func.0.to_be_bytes());
+            }
+        }
+
+        data
+    }
+
     fn generate_bytecode(&self) -> (Vec<u8>, HashMap<Label, u64>) {
         let mut data = Vec::new();
         let mut label_positions: HashMap<Label, u64> = HashMap::new();
@@ -232,21 +433,50 @@ impl Generator {
                     | Instruction::STORE_LOCAL(addr) => {
                         data.extend(addr.0.to_be_bytes());
                     }
-                    Instruction::LOAD_GLOBAL(addr) | Instruction::STORE_GLOBAL(addr) => {
+                    Instruction::LOAD_GLOBAL(addr)
+                    | Instruction::STORE_GLOBAL(addr)
+                    | Instruction::PUSH_ADDR_GLOBAL(addr) => {
                         data.extend(addr.0.to_be_bytes());
                     }
-                    Instruction::BOX_ALLOC(rti) => {
+                    Instruction::BOX_ALLOC(rti)
+                    | Instruction::NEW_AGGREGATE(rti)
+                    | Instruction::VEC_NEW(rti) => {
                         data.extend(rti.0.to_be_bytes());
                     }
+                    Instruction::FIELD(idx) | Instruction::SET_FIELD(idx) => {
+                        data.extend(idx.0.to_be_bytes());
+                    }
+                    Instruction::SET_INDEX(idx) => {
+                        data.extend(idx.0.to_be_bytes());
+                    }
+                    Instruction::IS_VARIANT(idx) | Instruction::SET_VARIANT(idx) => {
+                        data.extend(idx.0.to_be_bytes());
+                    }
                     Instruction::JMP(label)
                     | Instruction::JMP_IF_TRUE(label)
                     | Instruction::JMP_IF_FALSE(label) => {
                         let target_pos = label_positions.get(label).unwrap_or(&0);
                         data.extend(target_pos.to_be_bytes());
                     }
-                    Instruction::CALL(func_idx) => {
+                    Instruction::JMP_TABLE(idx) => {
+                        let table = &self.jump_tables[idx.0 as usize];
+                        let position = |label| *label_positions.get(label).unwrap_or(&0);
+                        data.extend((table.targets.len() as u32).to_be_bytes());
+                        data.extend(table.low.to_be_bytes());
+                        data.extend(position(&table.default).to_be_bytes());
+                        for target in &table.targets {
+                            data.extend(position(target).to_be_bytes());
+                        }
+                    }
+                    Instruction::CALL(func_idx)
+                    | Instruction::FUNC_REF(func_idx)
+                    | Instruction::CLOSURE(func_idx) => {
                         data.extend(func_idx.0.to_be_bytes());
                     }
+                    Instruction::CALL_VIRTUAL(idx) => {
+                        data.extend(idx.0.to_be_bytes());
+                    }
+                    Instruction::TO_SIZED(id) => data.push(id.0),
                     _ => {}
                 }
             }
@@ -262,12 +492,22 @@ impl Generator {
             | Instruction::LOAD_LOCAL(_)
             | Instruction::STORE_LOCAL(_)
             | Instruction::LOAD_GLOBAL(_)
-            | Instruction::STORE_GLOBAL(_) => 1 + 2,
-            Instruction::BOX_ALLOC(_) => 1 + 4,
+            | Instruction::STORE_GLOBAL(_)
+            | Instruction::PUSH_ADDR_GLOBAL(_) => 1 + 2,
+            Instruction::BOX_ALLOC(_) | Instruction::NEW_AGGREGATE(_) | Instruction::VEC_NEW(_) => {
+                1 + 4
+            }
+            Instruction::FIELD(_) | Instruction::SET_FIELD(_) | Instruction::SET_INDEX(_) => 1 + 4,
+            Instruction::IS_VARIANT(_) | Instruction::SET_VARIANT(_) => 1 + 4,
             Instruction::JMP(_) | Instruction::JMP_IF_TRUE(_) | Instruction::JMP_IF_FALSE(_) => {
                 1 + 8
             }
-            Instruction::CALL(_) => 1 + 4,
+            Instruction::JMP_TABLE(idx) => {
+                1 + 4 + 8 + 8 + 8 * self.jump_tables[idx.0 as usize].targets.len() as u64
+            }
+            Instruction::CALL(_) | Instruction::FUNC_REF(_) | Instruction::CLOSURE(_) => 1 + 4,
+            Instruction::CALL_VIRTUAL(_) => 1 + 4,
+            Instruction::TO_SIZED(_) => 1 + 1,
             _ => 1,
         }
     }
@@ -280,17 +520,19 @@ impl Generator {
         let const_pool = self.generate_const_pool();
         let (bytecode, label_positions) = self.generate_bytecode();
         let function_table = self.generate_function_table(&label_positions);
+        let vtables = self.generate_vtables();
 
         output.extend(MAGIC_NUMBER);
         output.extend(VERSION.to_be_bytes());
         output.extend(0x0000u16.to_be_bytes());
 
-        let header_size = 37u32; // 5 + 2 + 2 + 4 + 4 + 4 + 4 + 4 + 4 + 4
+        let header_size = 41u32; // 5 + 2 + 2 + 4 + 4 + 4 + 4 + 4 + 4 + 4 + 4
         let type_table_offset = header_size;
         let global_table_offset = type_table_offset + type_table.len() as u32;
         let const_pool_offset = global_table_offset + global_table.len() as u32;
         let function_table_offset = const_pool_offset + const_pool.len() as u32;
-        let bytecode_offset = function_table_offset + function_table.len() as u32;
+        let vtable_offset = function_table_offset + function_table.len() as u32;
+        let bytecode_offset = vtable_offset + vtables.len() as u32;
 
         output.extend((self.analysis_result.main_id.0).to_be_bytes());
 
@@ -298,6 +540,7 @@ impl Generator {
         output.extend(global_table_offset.to_be_bytes());
         output.extend(const_pool_offset.to_be_bytes());
         output.extend(function_table_offset.to_be_bytes());
+        output.extend(vtable_offset.to_be_bytes());
         output.extend(bytecode_offset.to_be_bytes());
 
         output.extend((bytecode.len() as u32).to_be_bytes());
@@ -306,6 +549,7 @@ impl Generator {
         output.extend(global_table);
         output.extend(const_pool);
         output.extend(function_table);
+        output.extend(vtables);
         output.extend(bytecode);
 
         output
@@ -322,28 +566,59 @@ impl Generator {
 mod tests {
     use super::*;
     use crate::{
-        lexer::Lexer, parser::parser::Parser, semantic_analyzer::analyzer::Analyzer,
-        source::SourceFile,
+        parser::{generics::Instantiations, module::ModuleGraph},
+        semantic_analyzer::analyzer::Analyzer,
     };
-    use std::io::Cursor;
+    use std::path::Path;
 
     fn create_generator_with_ast(
         source_code: &str,
     ) -> Result<Generator, Vec<crate::err::HarpyError>> {
-        let reader = Cursor::new(source_code);
-        let source = SourceFile::new(reader).map_err(|e| vec![e])?;
+        create_generator_with_modules(&[("main.hrpy", source_code)])
+    }
 
-        let lexer = Lexer::new(&source).map_err(|e| vec![e])?;
-        let parser = Parser::new(lexer);
+    // the first file is the root module
+    fn create_generator_with_modules(
+        files: &[(&str, &str)],
+    ) -> Result<Generator, Vec<crate::err::HarpyError>> {
+        let (modules, errors) = ModuleGraph::load_with(Path::new(files[0].0), |path| {
+            files
+                .iter()
+                .find(|(name, _)| Path::new(name) == path)
+                .map(|(_, text)| text.to_string())
+                .ok_or_else(|| std::io::ErrorKind::NotFound.into())
+        });
+        if !errors.is_empty() {
+            return Err(errors);
+        }
 
-        let ast = parser.build_ast()?;
-        let analysis_result = Analyzer::analyze(&ast)?.into_runtime()?;
+        let mut ast = modules.parse(&Instantiations::new())?;
+        let analysis_result = Analyzer::analyze(&mut ast, &modules)?.into_runtime()?;
 
         let mut generator = Generator::new(analysis_result);
         ast.generate(&mut generator);
         Ok(generator)
     }
 
+    fn instructions(generator: &Generator) -> Vec<Instruction> {
+        generator
+            .code
+            .iter()
+            .filter_map(|node| match node {
+                BytecodeNode::Instruction(i) => Some(*i),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn count_instructions(generator: &Generator, f: impl Fn(&Instruction) -> bool) -> usize {
+        generator
+            .code
+            .iter()
+            .filter(|node| matches!(node, BytecodeNode::Instruction(i) if f(i)))
+            .count()
+    }
+
     #[test]
     fn test_generate_empty_main() {
         let source = "fn main() {}";
@@ -490,7 +765,7 @@ mod tests {
     fn test_bytecode_size() {
         let generator = create_generator_with_ast("fn main() {}").unwrap();
         let bytecode = generator.finalize();
-        let bytecode_offset_pos = 5 + 2 + 2 + 4 + 4 + 4 + 4 + 4;
+        let bytecode_offset_pos = 5 + 2 + 2 + 4 + 4 + 4 + 4 + 4 + 4;
         let bytecode_offset = u32::from_be_bytes([
             bytecode[bytecode_offset_pos],
             bytecode[bytecode_offset_pos + 1],
@@ -539,4 +814,793 @@ mod tests {
 
         assert_eq!(instructions, expected)
     }
+
+    #[test]
+    fn test_generate_struct_literal_and_field() {
+        let source = r#"
+            struct Point { x: int, y: int }
+            fn main() -> int {
+                let p: mut Point = Point { x: 1, y: 2 };
+                p.y = 5;
+                return p.x + p.y;
+            }
+        "#;
+        let generator = create_generator_with_ast(source).unwrap();
+
+
+        assert_eq!(count_instructions(&generator, |i| matches!(i, Instruction::NEW_AGGREGATE(_))), 1);
+        assert_eq!(count_instructions(&generator, |i| matches!(i, Instruction::SET_FIELD(_))), 2);
+        assert_eq!(count_instructions(&generator, |i| matches!(i, Instruction::FIELD(_))), 3);
+    }
+
+    #[test]
+    fn test_generate_array_literal_and_index() {
+        let source = r#"
+            fn main() -> int {
+                let a: mut [int; 3] = [1, 2, 3];
+                let b = [0; 4];
+                a[2] = b[1];
+                return a[0];
+            }
+        "#;
+        let generator = create_generator_with_ast(source).unwrap();
+
+
+        assert_eq!(count_instructions(&generator, |i| matches!(i, Instruction::NEW_AGGREGATE(_))), 2);
+        assert_eq!(count_instructions(&generator, |i| matches!(i, Instruction::SET_INDEX(_))), 3);
+        assert_eq!(count_instructions(&generator, |i| matches!(i, Instruction::FILL)), 1);
+        assert_eq!(count_instructions(&generator, |i| matches!(i, Instruction::INDEX)), 3);
+    }
+
+    #[test]
+    fn test_generate_vec_methods() {
+        let source = r#"
+            fn main() -> int {
+                let v: mut vec<int> = vec[1, 2];
+                v.push(3);
+                v[0] = v.pop();
+                v.set(1, v.get(0));
+                return v.len();
+            }
+        "#;
+        let generator = create_generator_with_ast(source).unwrap();
+
+
+        assert_eq!(count_instructions(&generator, |i| matches!(i, Instruction::VEC_NEW(_))), 1);
+        assert_eq!(count_instructions(&generator, |i| matches!(i, Instruction::VEC_PUSH)), 3);
+        assert_eq!(count_instructions(&generator, |i| matches!(i, Instruction::VEC_POP)), 1);
+        assert_eq!(count_instructions(&generator, |i| matches!(i, Instruction::VEC_LEN)), 1);
+        assert_eq!(count_instructions(&generator, |i| matches!(i, Instruction::INDEX)), 3);
+    }
+
+    #[test]
+    fn test_expr_stmt_value_is_discarded() {
+        let source = r#"
+            fn f() -> int { return 1; }
+            fn g() {}
+            fn main() -> int {
+                f();
+                g();
+                return 0;
+            }
+        "#;
+        let generator = create_generator_with_ast(source).unwrap();
+        assert_eq!(count_instructions(&generator, |i| *i == Instruction::POP), 1);
+    }
+
+    #[test]
+    fn test_generate_enum_switch() {
+        let source = r#"
+            enum Shape { Circle(float), Rect(float, float), Empty }
+            fn area(s: Shape) -> float {
+                switch s {
+                    Shape::Circle(r) -> return r * r;
+                    Shape::Rect(_, h) -> return h;
+                    Shape::Empty -> return 0f;
+                }
+            }
+            fn main() -> float { return area(Shape::Rect(2f, 3f)); }
+        "#;
+        let generator = create_generator_with_ast(source).unwrap();
+        let instructions = instructions(&generator);
+
+        let variants: Vec<_> = instructions
+            .iter()
+            .filter_map(|i| match i {
+                Instruction::IS_VARIANT(idx) => Some(idx.0),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(variants, vec![0, 1, 2]);
+        assert!(instructions.contains(&Instruction::SET_VARIANT(VariantIndex(1))));
+        // `r` reads field 0, `h` the second field of Rect; `_` binds nothing
+        assert!(instructions.contains(&Instruction::FIELD(FieldIndex(0))));
+        assert!(instructions.contains(&Instruction::FIELD(FieldIndex(2))));
+        assert!(!instructions.contains(&Instruction::FIELD(FieldIndex(1))));
+    }
+
+    #[test]
+    fn test_labeled_break_leaves_outer_loop() {
+        let source = r#"
+            fn main() -> int {
+                let n: mut int = 0;
+                'outer: loop {
+                    while true {
+                        n += 1;
+                        if n > 3 { break 'outer; }
+                        continue 'outer;
+                    }
+                }
+                return n;
+            }
+        "#;
+        let generator = create_generator_with_ast(source).unwrap();
+
+        // label 0 is main's entry, the outer loop owns the next two
+        let outer_start = Label(1);
+        let outer_end = Label(2);
+        assert!(generator.code.contains(&BytecodeNode::Label(outer_end)));
+        let jumps: Vec<_> = generator
+            .code
+            .iter()
+            .filter_map(|node| match node {
+                BytecodeNode::Instruction(Instruction::JMP(l)) => Some(*l),
+                _ => None,
+            })
+            .collect();
+        assert!(jumps.contains(&outer_end));
+        assert!(jumps.contains(&outer_start));
+    }
+
+    #[test]
+    fn test_generate_closure_with_capture() {
+        let source = r#"
+            fn twice(x: int) -> int { return x * 2; }
+            fn main() -> int {
+                let n = 3;
+                let add = fn(x: int) -> int { return x + n; };
+                let f: fn(int) -> int = twice;
+                return add(1) + f(2);
+            }
+        "#;
+        let generator = create_generator_with_ast(source).unwrap();
+
+
+        assert_eq!(count_instructions(&generator, |i| matches!(i, Instruction::CLOSURE(_))), 1);
+        assert_eq!(count_instructions(&generator, |i| matches!(i, Instruction::FUNC_REF(_))), 1);
+        assert_eq!(count_instructions(&generator, |i| matches!(i, Instruction::CALL_INDIRECT)), 2);
+    }
+
+    #[test]
+    fn test_generic_function_specializations() {
+        let source = r#"
+            fn max<T>(a: T, b: T) -> T {
+                if a > b { return a; }
+                return b;
+            }
+            fn main() -> float {
+                let i = max(1, 2);
+                let j = max(3, 4);
+                return max(0.5, 1.5);
+            }
+        "#;
+        let generator = create_generator_with_ast(source).unwrap();
+
+        // main, max<int> and max<float>; the generic declaration itself is not compiled
+        assert_eq!(generator.analysis_result.function_table.iter().count(), 3);
+        let calls: std::collections::HashSet<_> = generator
+            .code
+            .iter()
+            .filter_map(|node| match node {
+                BytecodeNode::Instruction(Instruction::CALL(f)) => Some(*f),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(calls.len(), 3);
+    }
+
+    #[test]
+    fn test_generate_modules() {
+        let main = r#"
+            import "lib/geo.hrpy" as g;
+            fn scale(x: int) -> int { return x * 1000; }
+            fn main() -> int {
+                let p: g::Point = g::Point { x: 1, y: 2 };
+                return scale(g::norm(p)) + g::first(3, 4);
+            }
+        "#;
+        let geo = r#"
+            pub struct Point { x: int, y: int }
+            fn scale(x: int) -> int { return x * 2; }
+            pub fn norm(p: Point) -> int {
+                let scaled: int = scale(p.x);
+                return scaled + scale(p.y);
+            }
+            pub fn first<T>(a: T, b: T) -> T { return a; }
+        "#;
+        let generator =
+            create_generator_with_modules(&[("main.hrpy", main), ("lib/geo.hrpy", geo)]).unwrap();
+
+        // main, both scales, norm and first<int>
+        assert_eq!(generator.analysis_result.function_table.iter().count(), 5);
+    }
+
+    #[test]
+    fn test_generate_casts() {
+        let source = r#"
+            fn main() -> float {
+                let b = true;
+                let i = 2.5 as int + b as int;
+                return i as float;
+            }
+        "#;
+        let generator = create_generator_with_ast(source).unwrap();
+        let casts = count_instructions(&generator, |i| {
+            matches!(i, Instruction::TO_INT | Instruction::TO_FLOAT)
+        });
+        assert_eq!(casts, 3);
+    }
+
+    #[test]
+    fn test_generate_bitwise() {
+        let source = r#"
+            fn main() -> int {
+                let flags: mut int = 1 << 4 | 3;
+                flags &= ~1;
+                flags ^= 255 >> 2;
+                return flags;
+            }
+        "#;
+        let generator = create_generator_with_ast(source).unwrap();
+        let ops = count_instructions(&generator, |i| {
+            matches!(
+                i,
+                Instruction::BIT_AND
+                    | Instruction::BIT_OR
+                    | Instruction::BIT_XOR
+                    | Instruction::SHL
+                    | Instruction::SHR
+                    | Instruction::BIT_NOT
+            )
+        });
+        assert_eq!(ops, 6);
+    }
+
+    #[test]
+    fn test_logical_ops_short_circuit() {
+        let source = r#"
+            fn check(i: int) -> bool {
+                return i > 2;
+            }
+
+            fn main() -> bool {
+                let i = 3;
+                return i < 10 && check(i) || false;
+            }
+        "#;
+        let generator = create_generator_with_ast(source).unwrap();
+        let code = &generator.code;
+        assert!(!code.iter().any(|node| matches!(
+            node,
+            BytecodeNode::Instruction(Instruction::AND | Instruction::OR)
+        )));
+
+        // the call to `check` is jumped over when `i < 10` is false
+        let jump = code
+            .iter()
+            .position(|node| matches!(node, BytecodeNode::Instruction(Instruction::JMP_IF_FALSE(_))))
+            .unwrap();
+        assert!(code[jump..]
+            .iter()
+            .any(|node| matches!(node, BytecodeNode::Instruction(Instruction::CALL(_)))));
+        assert!(code
+            .iter()
+            .any(|node| matches!(node, BytecodeNode::Instruction(Instruction::JMP_IF_TRUE(_)))));
+    }
+
+    #[test]
+    fn test_generate_optionals() {
+        let source = r#"
+            fn find(n: int) -> int? {
+                if n > 3 {
+                    return some(n);
+                }
+                return none;
+            }
+            fn main() -> int {
+                let total: mut int = 0;
+                if let some(x) = find(5) {
+                    total += x;
+                }
+                switch find(1) {
+                    some(_) -> total += 1;
+                    none -> total += 2;
+                }
+                return total;
+            }
+        "#;
+        let generator = create_generator_with_ast(source).unwrap();
+        let instructions = instructions(&generator);
+
+        let set: Vec<_> = instructions
+            .iter()
+            .filter_map(|i| match i {
+                Instruction::SET_VARIANT(idx) => Some(idx.0),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(set, vec![1, 0]);
+
+        let tested: Vec<_> = instructions
+            .iter()
+            .filter_map(|i| match i {
+                Instruction::IS_VARIANT(idx) => Some(idx.0),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(tested, vec![1, 1, 0]);
+        // only `x` is read out, `some(_)` binds nothing
+        let reads = instructions
+            .iter()
+            .filter(|i| **i == Instruction::FIELD(FieldIndex(0)))
+            .count();
+        assert_eq!(reads, 1);
+    }
+
+    #[test]
+    fn test_generate_results() {
+        let source = r#"
+            fn parse(n: int) -> result<int, str> {
+                if n < 0 {
+                    return err("negative");
+                }
+                return ok(n);
+            }
+            fn double(n: int) -> result<float, str> {
+                let x = parse(n)?;
+                return ok(x as float * 2.0);
+            }
+            fn main() -> int {
+                if let err(_) = double(-1) {
+                    return 1;
+                }
+                return 0;
+            }
+        "#;
+        let generator = create_generator_with_ast(source).unwrap();
+        let instructions = instructions(&generator);
+
+        // err, ok, the converted early return in `double`, then ok
+        let set: Vec<_> = instructions
+            .iter()
+            .filter_map(|i| match i {
+                Instruction::SET_VARIANT(idx) => Some(idx.0),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(set, vec![1, 0, 1, 0]);
+
+        // the error is moved into a result<float, str> before returning
+        let try_at = instructions
+            .iter()
+            .position(|i| *i == Instruction::IS_VARIANT(VariantIndex(1)))
+            .unwrap();
+        assert!(matches!(
+            instructions[try_at + 1..try_at + 8],
+            [
+                Instruction::JMP_IF_FALSE(_),
+                Instruction::FIELD(FieldIndex(1)),
+                Instruction::NEW_AGGREGATE(_),
+                Instruction::SET_VARIANT(VariantIndex(1)),
+                Instruction::SWAP,
+                Instruction::SET_FIELD(FieldIndex(1)),
+                Instruction::RET,
+            ]
+        ));
+    }
+
+    #[test]
+    fn test_generate_methods() {
+        let source = r#"
+            struct Counter { n: int }
+            impl Counter {
+                fn get(&self) -> int { return self.n; }
+                fn tick(&mut self, by: int) { self.n += by; }
+                fn reset(self) -> Self { return Counter { n: 0 }; }
+            }
+            fn main() -> int {
+                let c: mut Counter = Counter { n: 1 };
+                c.tick(2);
+                let r = &c;
+                return r.get() + c.reset().n;
+            }
+        "#;
+        let generator = create_generator_with_ast(source).unwrap();
+        let instructions = instructions(&generator);
+
+        // `&mut self` takes the address of the local, the argument follows it
+        let tick_at = instructions
+            .iter()
+            .position(|i| matches!(i, Instruction::PUSH_ADDR_LOCAL(_)))
+            .unwrap();
+        assert!(matches!(
+            instructions[tick_at + 1..tick_at + 3],
+            [Instruction::LOAD_CONST(_), Instruction::CALL(_)]
+        ));
+
+        let calls = instructions
+            .iter()
+            .filter(|i| matches!(i, Instruction::CALL(_)))
+            .count();
+        // the three methods and the entry call into main
+        assert_eq!(calls, 4);
+    }
+
+    #[test]
+    fn test_generate_virtual_call() {
+        let source = r#"
+            trait Shape { fn area(&self) -> int; }
+            struct Square { side: int }
+            impl Shape for Square {
+                fn area(&self) -> int { return self.side * self.side; }
+            }
+            fn main() -> int {
+                let s: boxed dyn Shape = box Square { side: 3 };
+                return s.area();
+            }
+        "#;
+        let generator = create_generator_with_ast(source).unwrap();
+        let instructions = instructions(&generator);
+
+        // the box itself is the receiver
+        let call_at = instructions
+            .iter()
+            .position(|i| matches!(i, Instruction::CALL_VIRTUAL(VirtualIndex(0))))
+            .unwrap();
+        assert!(matches!(instructions[call_at - 1], Instruction::LOAD_LOCAL(_)));
+
+        let traits = &generator.analysis_result.trait_table;
+        assert_eq!(traits.param_counts, [1]);
+        assert_eq!(traits.vtables.len(), 1);
+        assert_eq!(traits.vtables[0].1, [(VirtualIndex(0), FuncIndex(0))]);
+    }
+
+    #[test]
+    fn test_generate_tuple_destructure() {
+        let source = r#"
+            fn divmod(a: int, b: int) -> (int, int) {
+                return (a / b, a % b);
+            }
+            fn main() -> int {
+                let (q, r) = divmod(7, 2);
+                return q + r;
+            }
+        "#;
+        let generator = create_generator_with_ast(source).unwrap();
+        let instructions = instructions(&generator);
+
+        // the tuple is duplicated for every element but the last
+        let call_at = instructions
+            .iter()
+            .rposition(|i| matches!(i, Instruction::CALL(_)))
+            .unwrap();
+        assert!(matches!(
+            instructions[call_at + 1..call_at + 6],
+            [
+                Instruction::DUP,
+                Instruction::FIELD(FieldIndex(0)),
+                Instruction::STORE_LOCAL(_),
+                Instruction::FIELD(FieldIndex(1)),
+                Instruction::STORE_LOCAL(_),
+            ]
+        ));
+    }
+
+    #[test]
+    fn test_generate_constants() {
+        let source = r#"
+            const SIZE: int = 4 * 8;
+            const HALF: float = SIZE as float / 2.0;
+            global count: mut int = SIZE + 1;
+            global other: int = count;
+            fn main() -> float {
+                return HALF;
+            }
+        "#;
+        let generator = create_generator_with_ast(source).unwrap();
+        let instructions = instructions(&generator);
+
+        // the first global is folded, the second one still reads the first
+        assert!(matches!(
+            instructions[..5],
+            [
+                Instruction::LOAD_CONST(_),
+                Instruction::STORE_GLOBAL(GlobalAddress(0)),
+                Instruction::LOAD_GLOBAL(GlobalAddress(0)),
+                Instruction::STORE_GLOBAL(GlobalAddress(1)),
+                Instruction::CALL(_),
+            ]
+        ));
+
+        let Instruction::LOAD_CONST(count) = instructions[0] else { unreachable!() };
+        assert_eq!(generator.analysis_result.constants.get(count).lit, Lit::LitInt(33));
+
+        let Some(Instruction::LOAD_CONST(half)) = instructions
+            .iter()
+            .rev()
+            .find(|i| matches!(i, Instruction::LOAD_CONST(_)))
+        else {
+            unreachable!()
+        };
+        assert_eq!(
+            generator.analysis_result.constants.get(*half).lit,
+            Lit::LitFloat(16f64.to_bits())
+        );
+    }
+
+    #[test]
+    fn test_generate_descending_range() {
+        let source = r#"
+            fn main() -> int {
+                let total: mut int = 0;
+                for i in 10..=0 step -2 {
+                    total += i;
+                }
+                return total;
+            }
+        "#;
+        let generator = create_generator_with_ast(source).unwrap();
+        let instructions = instructions(&generator);
+
+        // a constant negative step counts down, guarding against stepping below the smallest int
+        assert!(instructions.contains(&Instruction::GTE));
+        let add_at = instructions
+            .iter()
+            .rposition(|i| matches!(i, Instruction::ADD))
+            .unwrap();
+        let [Instruction::LOAD_CONST(min), Instruction::LT, Instruction::JMP_IF_TRUE(_), _, Instruction::LOAD_CONST(step)] =
+            instructions[add_at - 5..add_at]
+        else {
+            panic!("unexpected step code {:?}", &instructions[add_at - 5..add_at]);
+        };
+        assert_eq!(generator.analysis_result.constants.get(step).lit, Lit::LitInt(-2i64 as u64));
+        assert_eq!(
+            generator.analysis_result.constants.get(min).lit,
+            Lit::LitInt((i64::MIN + 2) as u64)
+        );
+    }
+
+    #[test]
+    fn test_generate_if_expression() {
+        let source = r#"
+            fn main() -> int {
+                let x = if true { 1 } else { 2 };
+                { x + 1 }
+                return x;
+            }
+        "#;
+        let generator = create_generator_with_ast(source).unwrap();
+        let instructions = instructions(&generator);
+
+        // each branch leaves its value for the let to store
+        let [Instruction::CALL(_), Instruction::HALT, Instruction::LOAD_CONST(_), Instruction::JMP_IF_FALSE(_), Instruction::LOAD_CONST(_), Instruction::JMP(_), Instruction::LOAD_CONST(_), Instruction::STORE_LOCAL(_), ..] =
+            instructions[..]
+        else {
+            panic!("unexpected if expression code {:?}", instructions);
+        };
+
+        // the value of a block used as a statement is thrown away
+        let [Instruction::LOAD_LOCAL(_), Instruction::LOAD_CONST(_), Instruction::ADD, Instruction::POP, ..] =
+            instructions[8..]
+        else {
+            panic!("unexpected block code {:?}", &instructions[8..]);
+        };
+    }
+
+    #[test]
+    fn test_generate_switch_jump_table() {
+        let source = r#"
+            fn dense(n: int) -> int {
+                switch n {
+                    0 -> return 10;
+                    1, 2 -> return 11;
+                    4..=5 -> return 12;
+                    . -> return 13;
+                }
+            }
+            fn sparse(n: int) -> int {
+                switch n {
+                    0 -> return 10;
+                    1 -> return 11;
+                    2 -> return 12;
+                    100 -> return 13;
+                }
+                return 0;
+            }
+            fn guarded(n: int) -> int {
+                switch n {
+                    0 -> return 10;
+                    1 -> return 11;
+                    2 -> return 12;
+                    x if x > 2 -> return x;
+                }
+                return 0;
+            }
+            fn main() -> int { return dense(1) + sparse(2) + guarded(3); }
+        "#;
+        let generator = create_generator_with_ast(source).unwrap();
+
+        // only the dense switch gets one
+        let tables: Vec<_> = generator
+            .code
+            .iter()
+            .filter(|node| matches!(node, BytecodeNode::Instruction(Instruction::JMP_TABLE(_))))
+            .collect();
+        assert_eq!(tables.len(), 1);
+        assert_eq!(generator.jump_tables.len(), 1);
+
+        let table = &generator.jump_tables[0];
+        assert_eq!(table.low, 0);
+        assert_eq!(table.targets.len(), 6);
+        assert_eq!(table.targets[1], table.targets[2]);
+        assert_eq!(table.targets[3], table.default);
+        assert_eq!(table.targets[4], table.targets[5]);
+        assert_ne!(table.targets[0], table.targets[1]);
+
+        // the table is written out after the instruction
+        let (bytecode, _) = generator.generate_bytecode();
+        let total: u64 = generator
+            .code
+            .iter()
+            .filter_map(|node| match node {
+                BytecodeNode::Instruction(instr) => Some(generator.instruction_size(instr)),
+                _ => None,
+            })
+            .sum();
+        assert_eq!(bytecode.len() as u64, total);
+    }
+
+    #[test]
+    fn test_generate_chars() {
+        let source = r#"
+            fn main() -> int {
+                let digits: mut int = 0;
+                for c in "a1é2" {
+                    if c >= '0' && c <= '9' {
+                        digits += c as int - '0' as int;
+                    }
+                }
+                let next = (digits + 96) as char;
+                return next as int;
+            }
+        "#;
+        let generator = create_generator_with_ast(source).unwrap();
+        assert_eq!(count_instructions(&generator, |i| *i == Instruction::STR_NEXT), 1);
+        assert_eq!(count_instructions(&generator, |i| *i == Instruction::STR_LEN), 1);
+        assert_eq!(count_instructions(&generator, |i| *i == Instruction::TO_CHAR), 1);
+        assert_eq!(count_instructions(&generator, |i| *i == Instruction::TO_INT), 3);
+    }
+
+    #[test]
+    fn test_generate_sized_ints() {
+        let source = r#"
+            const SMALLEST: i8 = -128;
+            fn main() -> int {
+                let a: u8 = 200;
+                let b = a +% 100;
+                let c = a -| 201;
+                let d = 3 *% a;
+                let e = b + c;
+                let f = (e as i16) << 2;
+                return f as int + SMALLEST as int;
+            }
+        "#;
+        use crate::{
+            generator::instruction::PrimitiveTypeId,
+            parser::types::{PrimitiveType, SizedInt},
+        };
+
+        let generator = create_generator_with_ast(source).unwrap();
+        assert_eq!(count_instructions(&generator, |i| *i == Instruction::ADD_WRAP), 1);
+        assert_eq!(count_instructions(&generator, |i| *i == Instruction::SUB_SAT), 1);
+        assert_eq!(count_instructions(&generator, |i| *i == Instruction::MUL_WRAP), 1);
+        assert_eq!(count_instructions(&generator, |i| *i == Instruction::ADD), 2);
+        assert_eq!(count_instructions(&generator, |i| *i == Instruction::TO_SIZED(PrimitiveTypeId(PrimitiveType::I16.type_id()))), 1);
+        // the negative literal is stored negative rather than negated at runtime
+        assert_eq!(count_instructions(&generator, |i| *i == Instruction::NEG), 0);
+
+        let constants = &generator.analysis_result.constants;
+        let sized = |value: i128, ty: PrimitiveType| {
+            constants.iter().any(|c| c.lit == Lit::LitSized(SizedInt { value, ty }))
+        };
+        assert!(sized(-128, PrimitiveType::I8));
+        assert!(sized(200, PrimitiveType::U8));
+        assert!(sized(3, PrimitiveType::U8));
+        // the shift amount takes the type of the value shifted
+        assert!(sized(2, PrimitiveType::I16));
+    }
+
+    #[test]
+    fn test_generate_type_aliases() {
+        let source = r#"
+            type Counter = boxed mut int;
+            type Pair = (int, int);
+            newtype Meters = float;
+            newtype Point = Pair;
+
+            fn bump(c: Counter) {
+                *c += 1;
+            }
+
+            fn longer(a: Meters, b: Meters) -> Meters {
+                if a > b {
+                    return a;
+                }
+                return b + (0.5 as Meters);
+            }
+
+            fn main() -> int {
+                let c: Counter = box 1;
+                bump(c);
+                let p = (1, 2) as Point;
+                let (x, y) = p as Pair;
+                let m = longer(1.5 as Meters, -(2.5 as Meters));
+                return *c + x + y + (m as float) as int;
+            }
+        "#;
+        let generator = create_generator_with_ast(source).unwrap();
+        // a newtype is what it wraps at runtime, only `as int` converts
+        assert_eq!(count_instructions(&generator, |i| *i == Instruction::TO_INT), 1);
+        assert_eq!(count_instructions(&generator, |i| *i == Instruction::NEG), 1);
+        assert!(generator
+            .analysis_result
+            .type_table
+            .iter()
+            .all(|ty| !matches!(ty.ttype, RuntimeType::Base(crate::parser::types::BaseType::Custom(_)))));
+    }
+
+    #[test]
+    fn test_generate_overloads() {
+        let source = r#"
+            struct Point { x: int }
+
+            fn abs(x: int) -> int {
+                if x < 0 { return -x; }
+                return x;
+            }
+            fn abs(x: float) -> float {
+                if x < 0.0 { return -x; }
+                return x;
+            }
+
+            impl Point {
+                fn shift(&mut self, by: int) { self.x += by; }
+                fn shift(&mut self, by: float) { self.x += by as int; }
+            }
+
+            fn apply(f: fn(float) -> float, x: float) -> float {
+                return f(x);
+            }
+
+            fn main() -> float {
+                let p: mut Point = Point { x: 1 };
+                p.shift(2);
+                p.shift(0.5);
+                return abs(-1) as float + abs(-1.5) + apply(abs, 2.0);
+            }
+        "#;
+        let generator = create_generator_with_ast(source).unwrap();
+
+        // main, apply and both of each overload, every one of them called on its own
+        assert_eq!(generator.analysis_result.function_table.iter().count(), 6);
+        let calls: std::collections::HashSet<_> = generator
+            .code
+            .iter()
+            .filter_map(|node| match node {
+                BytecodeNode::Instruction(Instruction::CALL(f)) => Some(*f),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(calls.len(), 6);
+    }
 }
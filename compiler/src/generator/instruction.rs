@@ -1,5 +1,9 @@
 use crate::semantic_analyzer::{
-    const_pool::ConstIndex, function_table::FuncIndex, type_table::RuntimeTypeIndex,
+    const_pool::ConstIndex,
+    function_table::FuncIndex,
+    struct_table::{FieldIndex, VariantIndex},
+    trait_table::VirtualIndex,
+    type_table::RuntimeTypeIndex,
 };
 
 macro_rules! impl_extend {
@@ -24,13 +28,34 @@ pub struct CodeAddress(pub u64);
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Label(pub u64);
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Ord, Hash, Eq)]
+pub struct ElementIndex(pub u32);
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Ord, Hash, Eq)]
+pub struct JumpTableIndex(pub u32);
+// the type id of a primitive type, as in the type table
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Ord, Hash, Eq)]
+pub struct PrimitiveTypeId(pub u8);
+
+// where a dense switch goes for every value from `low` on, written out
+// right after its JMP_TABLE; anything past the targets takes `default`
+#[derive(Debug, Clone, PartialEq)]
+pub struct JumpTable {
+    pub low: i64,
+    pub default: Label,
+    pub targets: Vec<Label>,
+}
 
 impl_extend!(LocalAddress, 2);
 impl_extend!(GlobalAddress, 2);
 impl_extend!(FuncIndex, 4);
 impl_extend!(ConstIndex, 4);
 impl_extend!(RuntimeTypeIndex, 4);
+impl_extend!(FieldIndex, 4);
+impl_extend!(VariantIndex, 4);
+impl_extend!(ElementIndex, 4);
+impl_extend!(VirtualIndex, 4);
 impl_extend!(CodeAddress, 8);
+impl_extend!(PrimitiveTypeId, 1);
 
 macro_rules! define_instruction_enum {
     (
@@ -75,11 +100,27 @@ define_instruction_enum!(
 
         LOAD_GLOBAL(GlobalAddress) = 0x13,
         STORE_GLOBAL(GlobalAddress) = 0x14,
+        PUSH_ADDR_GLOBAL(GlobalAddress) = 0x15,
 
         LOAD = 0x31,
         STORE = 0x32,
+        FIELD(FieldIndex) = 0x33,
+        SET_FIELD(FieldIndex) = 0x34,
+        INDEX = 0x35,
+        SET_INDEX(ElementIndex) = 0x36,
+        FILL = 0x37,
+        IS_VARIANT(VariantIndex) = 0x38,
+        SET_VARIANT(VariantIndex) = 0x39,
 
         BOX_ALLOC(RuntimeTypeIndex) = 0x40,
+        NEW_AGGREGATE(RuntimeTypeIndex) = 0x41,
+        VEC_NEW(RuntimeTypeIndex) = 0x42,
+        VEC_PUSH = 0x43,
+        VEC_POP = 0x44,
+        VEC_LEN = 0x45,
+        STR_LEN = 0x46,
+        STR_SLICE = 0x47,
+        STR_NEXT = 0x48,
 
         ADD = 0x50,
         SUB = 0x51,
@@ -88,13 +129,35 @@ define_instruction_enum!(
         NEG = 0x54,
         INC = 0x55,
         MOD = 0x56,
+        TO_INT = 0x57,
+        TO_FLOAT = 0x58,
+        BIT_AND = 0x59,
+        BIT_OR = 0x5A,
+        BIT_XOR = 0x5B,
+        SHL = 0x5C,
+        SHR = 0x5D,
+        BIT_NOT = 0x5E,
+        TO_CHAR = 0x5F,
+
+        ADD_WRAP = 0xA0,
+        SUB_WRAP = 0xA1,
+        MUL_WRAP = 0xA2,
+        ADD_SAT = 0xA3,
+        SUB_SAT = 0xA4,
+        MUL_SAT = 0xA5,
+        TO_SIZED(PrimitiveTypeId) = 0xA6,
 
         JMP(Label) = 0x60,
         JMP_IF_TRUE(Label) = 0x61,
         JMP_IF_FALSE(Label) = 0x62,
+        JMP_TABLE(JumpTableIndex) = 0x63,
 
         CALL(FuncIndex) = 0x70,
         RET = 0x71,
+        FUNC_REF(FuncIndex) = 0x72,
+        CLOSURE(FuncIndex) = 0x73,
+        CALL_INDIRECT = 0x74,
+        CALL_VIRTUAL(VirtualIndex) = 0x75,
 
         EQ = 0x80,
         NEQ = 0x81,
@@ -108,6 +171,7 @@ define_instruction_enum!(
 
         POP = 0x90,
         DUP = 0x91,
+        SWAP = 0x92,
 
         HALT = 0xFF,
     }
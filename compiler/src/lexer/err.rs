@@ -13,6 +13,8 @@ pub enum LexerError {
     InvalidInt(ParseIntError),
     InvalidFloat(ParseFloatError),
     UnclosedStr,
+    UnclosedChar,
+    UnknownEscape,
     UnexpectedToken(&'static str, Token),
 }
 
@@ -23,6 +25,8 @@ impl Display for LexerError {
             Self::InvalidInt(_) => "Invalid integer",
             Self::InvalidFloat(_) => "Invalid float",
             Self::UnclosedStr => "Unclosed String",
+            Self::UnclosedChar => "Unclosed char",
+            Self::UnknownEscape => "Unknown escape sequence",
             Self::UnexpectedToken(expected, got) => &format!(
                 "expected {}{}{}, got {}\"{}\"{}",
                 Color::Green,
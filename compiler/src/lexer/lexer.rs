@@ -17,14 +17,23 @@ pub struct Lexer<'lexer> {
 
 impl<'lexer> Lexer<'lexer> {
     pub fn new(buffer: &'lexer SourceFile) -> Result<Self> {
+        Self::for_file(buffer, 0)
+    }
+
+    // `file` is the index of `buffer` in the `SourceMap`, stamped into every span
+    pub fn for_file(buffer: &'lexer SourceFile, file: usize) -> Result<Self> {
+        let position = Position {
+            file,
+            ..Position::default()
+        };
         let mut l = Self {
             chars: buffer.text.chars().peekable(),
-            position: Position::default(),
+            position,
             next: Token {
                 t: crate::lexer::tokens::TokenType::Eof,
-                span: Span::new(Position::default(), Position::default()),
+                span: Span::new(position, position),
             },
-            last_position: Position::default(),
+            last_position: position,
         };
 
         l.next_token()?;
@@ -50,6 +59,19 @@ impl<'lexer> Lexer<'lexer> {
         self.chars.peek().copied()
     }
 
+    pub(in crate::lexer) fn peek_second_char(&self) -> Option<char> {
+        let mut chars = self.chars.clone();
+        chars.next();
+        chars.next()
+    }
+
+    pub(in crate::lexer) fn peek_third_char(&self) -> Option<char> {
+        let mut chars = self.chars.clone();
+        chars.next();
+        chars.next();
+        chars.next()
+    }
+
     pub(in crate::lexer) fn position(&self) -> Position {
         self.position
     }
@@ -117,6 +139,11 @@ impl<'lexer> Lexer<'lexer> {
     pub fn peek(&self) -> Result<&TokenType> {
         Ok(&self.next.t)
     }
+
+    // while a token is being lexed, the one right before it
+    pub(in crate::lexer) fn previous(&self) -> &TokenType {
+        &self.next.t
+    }
 }
 
 #[cfg(test)]
@@ -348,4 +375,55 @@ mod tests {
             TokenType::Literal(Lit::LitBool(false))
         );
     }
+
+    #[test]
+    fn test_tuple_index_is_not_a_float() {
+        make_lexer!(lexer, "t.0.1 1.5");
+        assert_eq!(lexer.next_token().unwrap().t, TokenType::Ident("t".into()));
+        assert_eq!(lexer.next_token().unwrap().t, TokenType::Symbol(Sym::Dot));
+        assert_eq!(lexer.next_token().unwrap().t, TokenType::Literal(Lit::LitInt(0)));
+        assert_eq!(lexer.next_token().unwrap().t, TokenType::Symbol(Sym::Dot));
+        assert_eq!(lexer.next_token().unwrap().t, TokenType::Literal(Lit::LitInt(1)));
+        assert_eq!(
+            lexer.next_token().unwrap().t,
+            TokenType::Literal(Lit::LitFloat(1.5f64.to_bits()))
+        );
+    }
+
+    #[test]
+    fn test_range_after_int_is_not_a_float() {
+        make_lexer!(lexer, "0..10 1.5..=2");
+        assert_eq!(lexer.next_token().unwrap().t, TokenType::Literal(Lit::LitInt(0)));
+        assert_eq!(lexer.next_token().unwrap().t, TokenType::Symbol(Sym::Range));
+        assert_eq!(lexer.next_token().unwrap().t, TokenType::Literal(Lit::LitInt(10)));
+        assert_eq!(
+            lexer.next_token().unwrap().t,
+            TokenType::Literal(Lit::LitFloat(1.5f64.to_bits()))
+        );
+        assert_eq!(lexer.next_token().unwrap().t, TokenType::Symbol(Sym::Range));
+        assert_eq!(lexer.next_token().unwrap().t, TokenType::Symbol(Sym::Assign));
+    }
+
+    #[test]
+    fn test_char_literals() {
+        make_lexer!(lexer, r"'a' 'é' '\n' '\'' 'outer: char");
+        assert_eq!(lexer.next_token().unwrap().t, TokenType::Literal(Lit::LitChar('a')));
+        assert_eq!(lexer.next_token().unwrap().t, TokenType::Literal(Lit::LitChar('é')));
+        assert_eq!(lexer.next_token().unwrap().t, TokenType::Literal(Lit::LitChar('\n')));
+        assert_eq!(lexer.next_token().unwrap().t, TokenType::Literal(Lit::LitChar('\'')));
+        // a quote before a longer name is still a loop label
+        assert_eq!(lexer.next_token().unwrap().t, TokenType::Symbol(Sym::Quote));
+        assert_eq!(lexer.next_token().unwrap().t, TokenType::Ident("outer".into()));
+        assert_eq!(lexer.next_token().unwrap().t, TokenType::Symbol(Sym::Colon));
+        assert_eq!(lexer.next_token().unwrap().t, TokenType::Keyword(Key::Char));
+    }
+
+    #[test]
+    fn test_sized_int_keywords() {
+        make_lexer!(lexer, "i8 i16 i32 i64 u8 u16 u32 u64 int8");
+        for key in [Key::I8, Key::I16, Key::I32, Key::I64, Key::U8, Key::U16, Key::U32, Key::U64] {
+            assert_eq!(lexer.next_token().unwrap().t, TokenType::Keyword(key));
+        }
+        assert_eq!(lexer.next_token().unwrap().t, TokenType::Ident("int8".into()));
+    }
 }
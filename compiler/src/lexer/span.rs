@@ -3,6 +3,8 @@ pub struct Position {
     pub line: usize,
     pub column: usize,
     pub byte: usize,
+    // index of the file in the `SourceMap` the position points into
+    pub file: usize,
 }
 
 impl Default for Position {
@@ -11,6 +13,7 @@ impl Default for Position {
             line: 1,
             column: 1,
             byte: 0,
+            file: 0,
         }
     }
 }
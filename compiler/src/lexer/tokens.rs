@@ -155,6 +155,8 @@ macro_rules! define_tokens {
                 let start = l.position();
                 let mut result = String::with_capacity(4);
                 let mut is_float = false;
+                // `t.0.1` indexes a tuple twice rather than with the float `0.1`
+                let is_index = *l.previous() == TokenType::Symbol(Sym::Dot);
 
                 while let Some(c) = l.peek_char() {
                     match c {
@@ -163,14 +165,15 @@ macro_rules! define_tokens {
                             l.next_char();
                         }
                         '.' => {
-                            if is_float {
+                            // `0..10` is a range, not the float `0.`
+                            if is_float || is_index || l.peek_second_char() == Some('.') {
                                 break;
                             }
                             is_float = true;
                             result.push(c);
                             l.next_char();
                         }
-                        'f' | 'F' => {
+                        'f' | 'F' if !is_index => {
                             is_float = true;
                             l.next_char();
                             break;
@@ -229,6 +232,29 @@ macro_rules! define_tokens {
                 return Ok(TokenType::Literal(Lit::LitStr(result)))
             }
 
+            fn parse_char(l: &mut Lexer) -> Result<TokenType> {
+                let start = l.position();
+                l.next_char(); //discard first '
+                let c = match l.next_char() {
+                    Some('\\') => match l.next_char() {
+                        Some('n') => '\n',
+                        Some('t') => '\t',
+                        Some('r') => '\r',
+                        Some('0') => '\0',
+                        Some(c @ ('\\' | '\'' | '"')) => c,
+                        _ => return HarpyError::lexer(LexerError::UnknownEscape, Span::new(start, l.position())),
+                    },
+                    Some(c) => c,
+                    None => return HarpyError::lexer(LexerError::UnclosedChar, Span::new(start, l.position())),
+                };
+
+                if l.next_char() != Some('\'') {
+                    return HarpyError::lexer(LexerError::UnclosedChar, Span::new(start, l.position()));
+                }
+
+                Ok(TokenType::Literal(Lit::LitChar(c)))
+            }
+
 
             pub(super) fn parse(l: &mut Lexer) -> Result<Self> {
                 l.skip_whitespace();
@@ -243,6 +269,11 @@ macro_rules! define_tokens {
 
                 if c == '_' || c.is_alphabetic() {
                     Self::parse_ident(l)
+                } else
+
+                // `'a'` is a char, `'outer` a loop label
+                if c == '\'' && (l.peek_second_char() == Some('\\') || l.peek_third_char() == Some('\'')) {
+                    Self::parse_char(l)?
                 } else {
 
                 match c {
@@ -292,6 +323,7 @@ define_tokens!(
     [keywords] => {
         "let" => Let,
         "global" => Global,
+        "const" => Const,
         "mut" => Mut,
 
         "fn" => Fn,
@@ -301,11 +333,22 @@ define_tokens!(
         "float" => Float,
         "str" => Str,
         "bool" => Bool,
+        "char" => Char,
+        "i8" => I8,
+        "i16" => I16,
+        "i32" => I32,
+        "i64" => I64,
+        "u8" => U8,
+        "u16" => U16,
+        "u32" => U32,
+        "u64" => U64,
 
         "for" => For,
         "in" => In,
         "while" => While,
         "loop" => Loop,
+        "break" => Break,
+        "continue" => Continue,
 
         "if" => If,
         "else" => Else,
@@ -314,8 +357,27 @@ define_tokens!(
         "boxed" => Boxed,
         "box" => Box,
 
+        "vec" => Vector,
+
+        "some" => OptSome,
+        "none" => OptNone,
+        "ok" => ResOk,
+        "err" => ResErr,
+
         "borrowed" => Borrowed,
         "borrow" => Borrow,
+
+        "struct" => Struct,
+        "enum" => Enum,
+        "impl" => Impl,
+        "trait" => Trait,
+        "dyn" => Dyn,
+        "type" => TypeAlias,
+        "newtype" => Newtype,
+
+        "import" => Import,
+        "as" => As,
+        "pub" => Pub,
     }
 
     [symbols] => {
@@ -365,27 +427,41 @@ define_tokens!(
             '.' => Range,
         }
 
-        ':' => Colon {}
+        ':' => Colon {
+            ':' => PathSep,
+        }
 
         ';' => Semi {}
 
         '&' => And {
             '&' => LogAnd,
+            '=' => AndAssign,
         }
 
         '|' => Pipe {
             '|' => LogOr,
+            '=' => OrAssign,
+        }
+
+        '^' => Caret {
+            '=' => XorAssign,
         }
 
+        '~' => Tilde {}
+
         '!' => Neg {
             '=' => Neq,
         }
 
         ',' => Comma {}
 
+        '\'' => Quote {}
+
         '%' => Modulo {
             '=' => ModuloAssign,
         }
+
+        '?' => Question {}
     }
 
     [literals] => {
@@ -393,9 +469,18 @@ define_tokens!(
         LitFloat(u64)
         LitStr(String)
         LitBool(bool)
+        LitChar(char)
+        LitSized(crate::parser::types::SizedInt)
     }
 );
 
+impl Ident {
+    // names the compiler makes up for things the source leaves anonymous
+    pub(crate) fn new(value: String, span: Span) -> Self {
+        Self { span, value }
+    }
+}
+
 #[macro_export]
 macro_rules! t {
     (let) => {
@@ -404,6 +489,9 @@ macro_rules! t {
     (global) => {
         $crate::lexer::tokens::Global
     };
+    (const) => {
+        $crate::lexer::tokens::Const
+    };
     (mut) => {
         $crate::lexer::tokens::Mut
     };
@@ -425,6 +513,33 @@ macro_rules! t {
     (bool) => {
         $crate::lexer::tokens::Bool
     };
+    (char) => {
+        $crate::lexer::tokens::Char
+    };
+    (i8) => {
+        $crate::lexer::tokens::I8
+    };
+    (i16) => {
+        $crate::lexer::tokens::I16
+    };
+    (i32) => {
+        $crate::lexer::tokens::I32
+    };
+    (i64) => {
+        $crate::lexer::tokens::I64
+    };
+    (u8) => {
+        $crate::lexer::tokens::U8
+    };
+    (u16) => {
+        $crate::lexer::tokens::U16
+    };
+    (u32) => {
+        $crate::lexer::tokens::U32
+    };
+    (u64) => {
+        $crate::lexer::tokens::U64
+    };
     (for) => {
         $crate::lexer::tokens::For
     };
@@ -437,6 +552,12 @@ macro_rules! t {
     (loop) => {
         $crate::lexer::tokens::Loop
     };
+    (break) => {
+        $crate::lexer::tokens::Break
+    };
+    (continue) => {
+        $crate::lexer::tokens::Continue
+    };
     (if) => {
         $crate::lexer::tokens::If
     };
@@ -458,6 +579,54 @@ macro_rules! t {
     (borrowed) => {
         $crate::lexer::tokens::Borrowed
     };
+    (struct) => {
+        $crate::lexer::tokens::Struct
+    };
+    (enum) => {
+        $crate::lexer::tokens::Enum
+    };
+    (impl) => {
+        $crate::lexer::tokens::Impl
+    };
+    (trait) => {
+        $crate::lexer::tokens::Trait
+    };
+    (dyn) => {
+        $crate::lexer::tokens::Dyn
+    };
+    (type) => {
+        $crate::lexer::tokens::TypeAlias
+    };
+    (newtype) => {
+        $crate::lexer::tokens::Newtype
+    };
+    (import) => {
+        $crate::lexer::tokens::Import
+    };
+    (as) => {
+        $crate::lexer::tokens::As
+    };
+    (pub) => {
+        $crate::lexer::tokens::Pub
+    };
+    (vec) => {
+        $crate::lexer::tokens::Vector
+    };
+    (some) => {
+        $crate::lexer::tokens::OptSome
+    };
+    (none) => {
+        $crate::lexer::tokens::OptNone
+    };
+    (ok) => {
+        $crate::lexer::tokens::ResOk
+    };
+    (err) => {
+        $crate::lexer::tokens::ResErr
+    };
+    (?) => {
+        $crate::lexer::tokens::Question
+    };
     (ident) => {
         $crate::lexer::tokens::Ident
     };
@@ -541,6 +710,9 @@ macro_rules! t {
     ("[") => {
         $crate::lexer::tokens::LSquare
     };
+    ("'") => {
+        $crate::lexer::tokens::Quote
+    };
     ("]") => {
         $crate::lexer::tokens::RSquare
     };
@@ -556,6 +728,9 @@ macro_rules! t {
     (:) => {
         $crate::lexer::tokens::Colon
     };
+    (::) => {
+        $crate::lexer::tokens::PathSep
+    };
     (;) => {
         $crate::lexer::tokens::Semi
     };
@@ -580,6 +755,21 @@ macro_rules! t {
     (||) => {
         $crate::lexer::tokens::LogOr
     };
+    (&=) => {
+        $crate::lexer::tokens::AndAssign
+    };
+    (|=) => {
+        $crate::lexer::tokens::OrAssign
+    };
+    (^) => {
+        $crate::lexer::tokens::Caret
+    };
+    (^=) => {
+        $crate::lexer::tokens::XorAssign
+    };
+    (~) => {
+        $crate::lexer::tokens::Tilde
+    };
     (,) => {
         $crate::lexer::tokens::Comma
     };
@@ -596,6 +786,9 @@ macro_rules! tt {
     (global) => {
         $crate::lexer::tokens::TokenType::Keyword($crate::lexer::tokens::Key::Global)
     };
+    (const) => {
+        $crate::lexer::tokens::TokenType::Keyword($crate::lexer::tokens::Key::Const)
+    };
     (mut) => {
         $crate::lexer::tokens::TokenType::Keyword($crate::lexer::tokens::Key::Mut)
     };
@@ -617,6 +810,33 @@ macro_rules! tt {
     (bool) => {
         $crate::lexer::tokens::TokenType::Keyword($crate::lexer::tokens::Key::Bool)
     };
+    (char) => {
+        $crate::lexer::tokens::TokenType::Keyword($crate::lexer::tokens::Key::Char)
+    };
+    (i8) => {
+        $crate::lexer::tokens::TokenType::Keyword($crate::lexer::tokens::Key::I8)
+    };
+    (i16) => {
+        $crate::lexer::tokens::TokenType::Keyword($crate::lexer::tokens::Key::I16)
+    };
+    (i32) => {
+        $crate::lexer::tokens::TokenType::Keyword($crate::lexer::tokens::Key::I32)
+    };
+    (i64) => {
+        $crate::lexer::tokens::TokenType::Keyword($crate::lexer::tokens::Key::I64)
+    };
+    (u8) => {
+        $crate::lexer::tokens::TokenType::Keyword($crate::lexer::tokens::Key::U8)
+    };
+    (u16) => {
+        $crate::lexer::tokens::TokenType::Keyword($crate::lexer::tokens::Key::U16)
+    };
+    (u32) => {
+        $crate::lexer::tokens::TokenType::Keyword($crate::lexer::tokens::Key::U32)
+    };
+    (u64) => {
+        $crate::lexer::tokens::TokenType::Keyword($crate::lexer::tokens::Key::U64)
+    };
     (for) => {
         $crate::lexer::tokens::TokenType::Keyword($crate::lexer::tokens::Key::For)
     };
@@ -629,6 +849,12 @@ macro_rules! tt {
     (loop) => {
         $crate::lexer::tokens::TokenType::Keyword($crate::lexer::tokens::Key::Loop)
     };
+    (break) => {
+        $crate::lexer::tokens::TokenType::Keyword($crate::lexer::tokens::Key::Break)
+    };
+    (continue) => {
+        $crate::lexer::tokens::TokenType::Keyword($crate::lexer::tokens::Key::Continue)
+    };
     (if) => {
         $crate::lexer::tokens::TokenType::Keyword($crate::lexer::tokens::Key::If)
     };
@@ -650,6 +876,54 @@ macro_rules! tt {
     (borrowed) => {
         $crate::lexer::tokens::TokenType::Keyword($crate::lexer::tokens::Key::Borrowed)
     };
+    (struct) => {
+        $crate::lexer::tokens::TokenType::Keyword($crate::lexer::tokens::Key::Struct)
+    };
+    (enum) => {
+        $crate::lexer::tokens::TokenType::Keyword($crate::lexer::tokens::Key::Enum)
+    };
+    (impl) => {
+        $crate::lexer::tokens::TokenType::Keyword($crate::lexer::tokens::Key::Impl)
+    };
+    (trait) => {
+        $crate::lexer::tokens::TokenType::Keyword($crate::lexer::tokens::Key::Trait)
+    };
+    (dyn) => {
+        $crate::lexer::tokens::TokenType::Keyword($crate::lexer::tokens::Key::Dyn)
+    };
+    (type) => {
+        $crate::lexer::tokens::TokenType::Keyword($crate::lexer::tokens::Key::TypeAlias)
+    };
+    (newtype) => {
+        $crate::lexer::tokens::TokenType::Keyword($crate::lexer::tokens::Key::Newtype)
+    };
+    (import) => {
+        $crate::lexer::tokens::TokenType::Keyword($crate::lexer::tokens::Key::Import)
+    };
+    (as) => {
+        $crate::lexer::tokens::TokenType::Keyword($crate::lexer::tokens::Key::As)
+    };
+    (pub) => {
+        $crate::lexer::tokens::TokenType::Keyword($crate::lexer::tokens::Key::Pub)
+    };
+    (vec) => {
+        $crate::lexer::tokens::TokenType::Keyword($crate::lexer::tokens::Key::Vector)
+    };
+    (some) => {
+        $crate::lexer::tokens::TokenType::Keyword($crate::lexer::tokens::Key::OptSome)
+    };
+    (none) => {
+        $crate::lexer::tokens::TokenType::Keyword($crate::lexer::tokens::Key::OptNone)
+    };
+    (ok) => {
+        $crate::lexer::tokens::TokenType::Keyword($crate::lexer::tokens::Key::ResOk)
+    };
+    (err) => {
+        $crate::lexer::tokens::TokenType::Keyword($crate::lexer::tokens::Key::ResErr)
+    };
+    (?) => {
+        $crate::lexer::tokens::TokenType::Symbol($crate::lexer::tokens::Sym::Question)
+    };
 
     (=) => {
         $crate::lexer::tokens::TokenType::Symbol($crate::lexer::tokens::Sym::Assign)
@@ -722,6 +996,9 @@ macro_rules! tt {
     ("[") => {
         $crate::lexer::tokens::TokenType::Symbol($crate::lexer::tokens::Sym::LSquare)
     };
+    ("'") => {
+        $crate::lexer::tokens::TokenType::Symbol($crate::lexer::tokens::Sym::Quote)
+    };
     ("]") => {
         $crate::lexer::tokens::TokenType::Symbol($crate::lexer::tokens::Sym::RSquare)
     };
@@ -731,6 +1008,9 @@ macro_rules! tt {
     (:) => {
         $crate::lexer::tokens::TokenType::Symbol($crate::lexer::tokens::Sym::Colon)
     };
+    (::) => {
+        $crate::lexer::tokens::TokenType::Symbol($crate::lexer::tokens::Sym::PathSep)
+    };
     (;) => {
         $crate::lexer::tokens::TokenType::Symbol($crate::lexer::tokens::Sym::Semi)
     };
@@ -773,6 +1053,21 @@ macro_rules! tt {
     (||) => {
         $crate::lexer::tokens::TokenType::Symbol($crate::lexer::tokens::Sym::LogOr)
     };
+    (&=) => {
+        $crate::lexer::tokens::TokenType::Symbol($crate::lexer::tokens::Sym::AndAssign)
+    };
+    (|=) => {
+        $crate::lexer::tokens::TokenType::Symbol($crate::lexer::tokens::Sym::OrAssign)
+    };
+    (^) => {
+        $crate::lexer::tokens::TokenType::Symbol($crate::lexer::tokens::Sym::Caret)
+    };
+    (^=) => {
+        $crate::lexer::tokens::TokenType::Symbol($crate::lexer::tokens::Sym::XorAssign)
+    };
+    (~) => {
+        $crate::lexer::tokens::TokenType::Symbol($crate::lexer::tokens::Sym::Tilde)
+    };
     (,) => {
         $crate::lexer::tokens::TokenType::Symbol($crate::lexer::tokens::Sym::Comma)
     };
@@ -1,10 +1,7 @@
-use std::io::BufReader;
-
 use aliases::Result;
 use generator::generator::Generator;
-use parser::parser::Parser;
+use parser::{generics::Instantiations, module::ModuleGraph};
 use semantic_analyzer::analyzer::Analyzer;
-use source::SourceFile;
 
 pub mod aliases;
 pub mod color;
@@ -17,30 +14,33 @@ pub mod semantic_analyzer;
 pub mod source;
 
 pub fn compile_file(filename: &str) -> Result<Vec<u8>> {
-    let reader = BufReader::new(std::fs::File::open(filename)?);
-    let source = SourceFile::new(reader)?;
-
-    let lexer = lexer::Lexer::new(&source)?;
-    let parser = Parser::new(lexer);
+    let (modules, errors) = ModuleGraph::load(std::path::Path::new(filename));
+    let source = modules.sources();
+    if !errors.is_empty() {
+        for err in errors {
+            err.show(source);
+        }
+        return Err(std::io::Error::other("Import errors").into());
+    }
 
-    let ast = parser.build_ast().map_err(|errors| {
+    let mut ast = modules.parse(&Instantiations::new()).map_err(|errors| {
         for err in errors {
-            err.show(&source);
+            err.show(source);
         }
         std::io::Error::new(std::io::ErrorKind::Other, "Parse errors")
     })?;
 
-    let result = Analyzer::analyze(&ast)
+    let result = Analyzer::analyze(&mut ast, &modules)
         .map_err(|errors| {
             for err in errors {
-                err.show(&source);
+                err.show(source);
             }
             std::io::Error::new(std::io::ErrorKind::Other, "Analysis errors")
         })?
         .into_runtime()
         .map_err(|errors| {
             for err in errors {
-                err.show(&source);
+                err.show(source);
             }
             std::io::Error::new(std::io::ErrorKind::Other, "Runtime errors")
         })?;
@@ -1,11 +1,11 @@
-use std::io::{BufReader, Write};
+use std::io::Write;
 
 use aliases::Result;
 use err::HarpyError;
 use generator::generator::Generator;
-use parser::parser::Parser;
+use parser::{generics::Instantiations, module::ModuleGraph};
 use semantic_analyzer::analyzer::Analyzer;
-use source::SourceFile;
+use source::SourceMap;
 
 pub mod aliases;
 pub mod color;
@@ -17,7 +17,7 @@ pub mod parser;
 pub mod semantic_analyzer;
 pub mod source;
 
-fn print_errors(errors: Vec<HarpyError>, source: &SourceFile) {
+fn print_errors(errors: Vec<HarpyError>, source: &SourceMap) {
     for err in errors {
         err.show(source);
     }
@@ -31,24 +31,25 @@ fn main() -> Result<()> {
     }
 
     let filename = &args[1];
-    let reader = BufReader::new(std::fs::File::open(filename)?);
-    let source = SourceFile::new(reader)?;
-
-    let lexer = lexer::Lexer::new(&source)?;
-    let parser = Parser::new(lexer);
+    let (modules, errors) = ModuleGraph::load(std::path::Path::new(filename));
+    let source = modules.sources();
+    if !errors.is_empty() {
+        print_errors(errors, source);
+        return Ok(());
+    }
 
-    let ast = match parser.build_ast() {
+    let mut ast = match modules.parse(&Instantiations::new()) {
         Ok(ast) => ast,
         Err(errors) => {
-            print_errors(errors, &source);
+            print_errors(errors, source);
             return Ok(());
         }
     };
 
-    let result = match Analyzer::analyze(&ast) {
+    let result = match Analyzer::analyze(&mut ast, &modules) {
         Ok(result) => result.into_runtime(),
         Err(errors) => {
-            print_errors(errors, &source);
+            print_errors(errors, source);
             return Ok(());
         }
     };
@@ -56,7 +57,7 @@ fn main() -> Result<()> {
     let result = match result {
         Ok(rti) => rti,
         Err(errors) => {
-            print_errors(errors, &source);
+            print_errors(errors, source);
             return Ok(());
         }
     };
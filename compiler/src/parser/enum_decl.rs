@@ -0,0 +1,141 @@
+use super::{node::Node, parser::Parser, types::TypeSpanned, Parse};
+use crate::{
+    aliases::Result,
+    generator::compile_trait::Generate,
+    lexer::tokens::Ident,
+    semantic_analyzer::{analyze_trait::Analyze, return_status::ReturnStatus},
+    t, tt,
+};
+
+#[derive(Debug, Clone)]
+pub struct VariantDecl {
+    pub name: Node<Ident>,
+    pub fields: Vec<TypeSpanned>,
+}
+
+impl Parse for VariantDecl {
+    fn parse(parser: &mut Parser) -> Result<Self> {
+        let name = parser.parse_node()?;
+        let mut fields = vec![];
+
+        if let tt!("(") = parser.peek()? {
+            parser.consume::<t!("(")>()?;
+            loop {
+                if let tt!(")") = parser.peek()? {
+                    break;
+                }
+
+                fields.push(parser.parse()?);
+
+                if let tt!(,) = parser.peek()? {
+                    parser.consume::<t!(,)>()?;
+                } else {
+                    break;
+                }
+            }
+            parser.consume::<t!(")")>()?;
+        }
+
+        Ok(Self { name, fields })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct EnumDecl {
+    pub name: Node<Ident>,
+    pub variants: Vec<Node<VariantDecl>>,
+}
+
+impl Parse for EnumDecl {
+    fn parse(parser: &mut Parser) -> Result<Self> {
+        parser.consume::<t!(enum)>()?;
+        let name = parser.parse_node()?;
+        parser.consume::<t!("{")>()?;
+
+        let mut variants = vec![];
+        loop {
+            if let tt!("}") = parser.peek()? {
+                break;
+            }
+
+            variants.push(parser.parse_node::<VariantDecl>()?);
+
+            if let tt!(,) = parser.peek()? {
+                parser.consume::<t!(,)>()?;
+            } else {
+                break;
+            }
+        }
+
+        parser.consume::<t!("}")>()?;
+
+        Ok(Self { name, variants })
+    }
+}
+
+impl EnumDecl {
+    pub fn declare(&self, builder: &mut crate::semantic_analyzer::scope_builder::ScopeBuilder) {
+        builder.declare_enum(&self.name);
+    }
+}
+
+impl Analyze for EnumDecl {
+    fn build(&self, builder: &mut crate::semantic_analyzer::scope_builder::ScopeBuilder) {
+        let variants = self
+            .variants
+            .iter()
+            .map(|variant| {
+                let fields = variant
+                    .fields
+                    .iter()
+                    .map(|ty| builder.register_type(ty))
+                    .collect();
+                (variant.name.clone(), fields)
+            })
+            .collect();
+
+        builder.define_enum(&self.name, variants);
+    }
+
+    fn analyze_semantics(
+        &self,
+        analyzer: &mut crate::semantic_analyzer::analyzer::Analyzer,
+    ) -> ReturnStatus {
+        analyzer.check_struct_recursion(&self.name);
+        ReturnStatus::Never
+    }
+}
+
+impl Generate for EnumDecl {
+    fn generate(&self, _generator: &mut crate::generator::generator::Generator) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::EnumDecl;
+    use crate::{lexer::Lexer, parser::parser::Parser, parser::types::Type, source::SourceFile};
+    use std::io::Cursor;
+
+    fn parse_enum(input: &str) -> EnumDecl {
+        let source = SourceFile::new(Cursor::new(input)).unwrap();
+        let mut parser = Parser::new(Lexer::new(&source).unwrap());
+        parser.parse::<EnumDecl>().unwrap()
+    }
+
+    #[test]
+    fn test_simple_enum() {
+        let decl = parse_enum("enum Shape { Circle(float), Rect(float, float), Empty }");
+        assert_eq!(decl.name.value().as_str(), "Shape");
+        assert_eq!(decl.variants.len(), 3);
+        assert_eq!(decl.variants[0].fields[0].ty, Type::float());
+        assert_eq!(decl.variants[1].fields.len(), 2);
+        assert!(decl.variants[2].fields.is_empty());
+    }
+
+    #[test]
+    fn test_enum_trailing_comma() {
+        let decl = parse_enum("enum List { Cons(int, boxed List), Nil, }");
+        assert_eq!(decl.variants.len(), 2);
+        assert_eq!(decl.variants[0].fields[1].ty, Type::boxed(Type::custom("List")));
+    }
+}
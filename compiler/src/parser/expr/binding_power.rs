@@ -9,3 +9,7 @@ impl From<(u8, u8)> for Bp {
         Self { left, right }
     }
 }
+
+// `as` binds tighter than any infix operator, but a prefix operator is applied
+// before the cast: `-x as float` is `(-x) as float`
+pub const CAST_BP: u8 = 65;
@@ -0,0 +1,143 @@
+use std::fmt::Display;
+
+use crate::{
+    aliases::Result,
+    generator::{compile_trait::Generate, generator::Generator, instruction::Instruction},
+    lexer::tokens::Ident,
+    parser::{
+        func_decl::{FuncDelc, Param},
+        node::Node,
+        parser::Parser,
+        statements::BlockStmt,
+        types::{Type, TypeInner, TypeSpanned},
+        Parse,
+    },
+    semantic_analyzer::{
+        analyze_trait::Analyze, analyzer::Analyzer, err::SemanticError,
+        return_status::ReturnStatus, scope_builder::ScopeBuilder, struct_table::FieldIndex,
+    },
+    t, tt,
+};
+
+#[derive(Debug, Clone)]
+pub struct ClosureExpr {
+    // closures are compiled as functions under a name no source identifier can clash with
+    name: Node<Ident>,
+    params: Vec<Node<Param>>,
+    return_type: TypeSpanned,
+    block: BlockStmt,
+}
+
+impl Parse for ClosureExpr {
+    fn parse(parser: &mut Parser) -> Result<Self> {
+        let fn_token = parser.consume::<t!(fn)>()?;
+        let id = parser.next_id();
+        let name = Node::new(
+            id,
+            fn_token.span(),
+            Ident::new(format!("{{closure#{}}}", id.0), fn_token.span()),
+        );
+
+        parser.consume::<t!("(")>()?;
+        let mut params = vec![];
+
+        if let tt!(ident) = parser.peek()? {
+            match FuncDelc::parse_params(parser, &mut params) {
+                Ok(()) => (),
+                Err(e) => parser.report_error(e, &[tt!(")")])?,
+            }
+        }
+
+        parser.consume::<t!(")")>()?;
+
+        let mut return_type = TypeSpanned::dummy(Type::void());
+
+        if let tt!(->) = parser.peek()? {
+            parser.consume::<t!(->)>()?;
+            return_type = parser.parse::<TypeSpanned>()?;
+        }
+
+        let block = parser.parse::<BlockStmt>()?;
+
+        Ok(Self {
+            name,
+            params,
+            return_type,
+            block,
+        })
+    }
+}
+
+impl ClosureExpr {
+    pub fn signature(&self) -> Type {
+        let params = self.params.iter().map(|p| p.ttype.ty.clone()).collect();
+        Type::function(params, self.return_type.ty.clone())
+    }
+}
+
+impl Analyze for ClosureExpr {
+    fn build(&self, builder: &mut ScopeBuilder) {
+        let return_info = builder.register_type(&self.return_type);
+        builder.define_func(&self.name, return_info);
+        builder.push_closure_scope(&self.name);
+        for param in &self.params {
+            let param_info = builder.register_type(&param.ttype);
+            builder.define_param(&param.name, param_info);
+        }
+
+        self.block.build(builder);
+        builder.pop_scope();
+    }
+
+    // the body is checked where the closure appears, so it can see the
+    // variables it captures
+    fn analyze_semantics(&self, analyzer: &mut Analyzer) -> ReturnStatus {
+        analyzer.enter_closure(&self.name);
+        let block_status = self.block.analyze_semantics(analyzer);
+
+        if analyzer.expand_type(&self.return_type).inner != TypeInner::Void
+            && block_status != ReturnStatus::Always
+        {
+            analyzer.report_semantic_error(SemanticError::NotAllPathsReturn, self.name.span());
+        }
+
+        analyzer.exit_closure(&self.name);
+        ReturnStatus::Never
+    }
+}
+
+impl Generate for ClosureExpr {
+    fn generate(&self, generator: &mut Generator) {
+        // the body is emitted in place, so jump over it
+        let skip = generator.create_label();
+        generator.push_instruction(Instruction::JMP(skip));
+
+        let func_label = generator.create_label();
+        let id = generator.get_function_mapping(self.name.id());
+        generator.register_function(id, func_label);
+        generator.place_label(func_label);
+        self.block.generate(generator);
+        generator.place_ret();
+        generator.place_label(skip);
+
+        let (env, captures) = generator.get_closure_env(self.name.id());
+        let Some(env) = env else {
+            generator.push_instruction(Instruction::FUNC_REF(id));
+            return;
+        };
+
+        generator.push_instruction(Instruction::NEW_AGGREGATE(env));
+        for (i, local) in captures.into_iter().enumerate() {
+            generator.push_instruction(Instruction::LOAD_LOCAL(local));
+            generator.push_instruction(Instruction::SET_FIELD(FieldIndex(i as u32)));
+        }
+        generator.push_instruction(Instruction::BOX_ALLOC(env));
+        generator.push_instruction(Instruction::CLOSURE(id));
+    }
+}
+
+impl Display for ClosureExpr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.signature())
+    }
+}
@@ -2,13 +2,19 @@ use std::fmt::Display;
 use std::ops::Deref;
 
 use crate::lexer::span::Span;
-use crate::lexer::tokens::Ident;
+use crate::lexer::tokens::{Ident, Lit, LitInt, TokenType};
 use crate::parser::node::Node;
 use crate::parser::parser::Parser;
+use crate::parser::statements::{BlockStmt, IfStmt, SwitchStmt};
+use crate::parser::types::TypeSpanned;
+use crate::semantic_analyzer::analyze_trait::Analyze;
+use crate::semantic_analyzer::scope_builder::ScopeBuilder;
 use crate::t;
 use crate::tt;
 use crate::{aliases::Result, lexer::tokens::Literal, parser::Parse};
 
+use super::binding_power::CAST_BP;
+use super::closure::ClosureExpr;
 use super::infix::InfixOp;
 use super::prefix::PrefixOp;
 use super::prefix::PrefixOpKind;
@@ -51,26 +57,284 @@ pub struct CallExpr {
     pub args: Vec<Node<Expr>>,
 }
 
+// parses `(a, b, ...)`, returning the arguments and the span of the closing paren
+fn parse_args(parser: &mut Parser) -> Result<(Vec<Node<Expr>>, Span)> {
+    parser.consume::<t!("(")>()?;
+    let mut args = vec![];
+    loop {
+        if *parser.peek()? == tt!(")") {
+            break;
+        }
+
+        args.push(parser.parse_node::<Expr>()?);
+        if *parser.peek()? == tt!(,) {
+            parser.consume::<t!(,)>()?;
+        } else {
+            break;
+        }
+    }
+    let close = parser.consume::<t!(")")>()?;
+
+    Ok((args, close.span()))
+}
+
 impl Parse for CallExpr {
     fn parse(parser: &mut Parser) -> Result<Self> {
-        let ident = parser.consume()?;
-        parser.consume::<t!("(")>()?;
-        let mut args = vec![];
+        let ident = (*parser.parse_path(false)?).clone();
+        let (args, _) = parse_args(parser)?;
+
+        Ok(Self { ident, args })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MethodCallExpr {
+    pub receiver: Node<Expr>,
+    pub method: Node<Ident>,
+    pub args: Vec<Node<Expr>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct FieldInit {
+    pub name: Node<Ident>,
+    pub value: Node<Expr>,
+}
+
+impl Parse for FieldInit {
+    fn parse(parser: &mut Parser) -> Result<Self> {
+        let name = parser.parse_node()?;
+        parser.consume::<t!(:)>()?;
+        let value = parser.parse_node()?;
+        Ok(Self { name, value })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct StructExpr {
+    pub name: Node<Ident>,
+    pub fields: Vec<Node<FieldInit>>,
+}
+
+impl Parse for StructExpr {
+    fn parse(parser: &mut Parser) -> Result<Self> {
+        let name = parser.parse_path(true)?;
+        parser.consume::<t!("{")>()?;
+        let mut fields = vec![];
         loop {
-            if *parser.peek()? == tt!(")") {
+            if *parser.peek()? == tt!("}") {
                 break;
             }
 
-            args.push(parser.parse_node::<Expr>()?);
+            fields.push(parser.parse_node::<FieldInit>()?);
             if *parser.peek()? == tt!(,) {
                 parser.consume::<t!(,)>()?;
             } else {
                 break;
             }
         }
+        parser.consume::<t!("}")>()?;
+
+        Ok(Self { name, fields })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct VariantExpr {
+    pub enum_name: Node<Ident>,
+    pub variant: Node<Ident>,
+    pub args: Vec<Node<Expr>>,
+}
+
+impl Parse for VariantExpr {
+    fn parse(parser: &mut Parser) -> Result<Self> {
+        let enum_name = parser.parse_path(true)?;
+        parser.consume::<t!(::)>()?;
+        let variant = parser.parse_node()?;
+
+        let mut args = vec![];
+        if let tt!("(") = parser.peek()? {
+            (args, _) = parse_args(parser)?;
+        }
+
+        Ok(Self {
+            enum_name,
+            variant,
+            args,
+        })
+    }
+}
+
+// `some(value)`, or `none` when there is no value
+#[derive(Debug, Clone)]
+pub struct OptionalExpr {
+    pub wrapped: Option<Node<Expr>>,
+}
+
+impl Parse for OptionalExpr {
+    fn parse(parser: &mut Parser) -> Result<Self> {
+        if let tt!(none) = parser.peek()? {
+            parser.consume::<t!(none)>()?;
+            return Ok(Self { wrapped: None });
+        }
+
+        parser.consume::<t!(some)>()?;
+        parser.consume::<t!("(")>()?;
+        let wrapped = parser.parse_node()?;
         parser.consume::<t!(")")>()?;
 
-        Ok(Self { ident, args })
+        Ok(Self {
+            wrapped: Some(wrapped),
+        })
+    }
+}
+
+// `ok(value)`, or `err(error)` when something went wrong
+#[derive(Debug, Clone)]
+pub enum ResultExpr {
+    Ok(Node<Expr>),
+    Err(Node<Expr>),
+}
+
+impl ResultExpr {
+    pub fn wrapped(&self) -> &Node<Expr> {
+        match self {
+            Self::Ok(value) | Self::Err(value) => value,
+        }
+    }
+}
+
+impl Parse for ResultExpr {
+    fn parse(parser: &mut Parser) -> Result<Self> {
+        let is_ok = if let tt!(ok) = parser.peek()? {
+            parser.consume::<t!(ok)>()?;
+            true
+        } else {
+            parser.consume::<t!(err)>()?;
+            false
+        };
+
+        parser.consume::<t!("(")>()?;
+        let wrapped = parser.parse_node()?;
+        parser.consume::<t!(")")>()?;
+
+        Ok(if is_ok {
+            Self::Ok(wrapped)
+        } else {
+            Self::Err(wrapped)
+        })
+    }
+}
+
+// `expr?` - the value inside an optional or result, returning `none` or the
+// error from the enclosing function when there isn't one
+#[derive(Debug, Clone)]
+pub struct TryExpr {
+    pub expr: Node<Expr>,
+}
+
+impl Parse for TryExpr {
+    fn parse(parser: &mut Parser) -> Result<Self> {
+        let expr = parser.parse_null_den_node()?;
+        parser.consume::<t!(?)>()?;
+        Ok(Self { expr })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CastExpr {
+    pub expr: Node<Expr>,
+    pub ty: TypeSpanned,
+}
+
+#[derive(Debug, Clone)]
+pub enum ArrayExpr {
+    List(Vec<Node<Expr>>),
+    Repeat(Box<Node<Expr>>, usize),
+}
+
+impl Parse for ArrayExpr {
+    fn parse(parser: &mut Parser) -> Result<Self> {
+        parser.consume::<t!("[")>()?;
+        if *parser.peek()? == tt!("]") {
+            parser.consume::<t!("]")>()?;
+            return Ok(Self::List(vec![]));
+        }
+
+        let first = parser.parse_node::<Expr>()?;
+        if *parser.peek()? == tt!(;) {
+            parser.consume::<t!(;)>()?;
+            let len = parser.consume::<LitInt>()?;
+            parser.consume::<t!("]")>()?;
+            return Ok(Self::Repeat(Box::new(first), *len.value() as usize));
+        }
+
+        let mut elements = vec![first];
+        while *parser.peek()? == tt!(,) {
+            parser.consume::<t!(,)>()?;
+            if *parser.peek()? == tt!("]") {
+                break;
+            }
+            elements.push(parser.parse_node::<Expr>()?);
+        }
+        parser.consume::<t!("]")>()?;
+
+        Ok(Self::List(elements))
+    }
+}
+
+// `(a, b, ...)`, parsed along with parenthesized expressions
+#[derive(Debug, Clone)]
+pub struct TupleExpr {
+    pub elements: Vec<Node<Expr>>,
+}
+
+impl Parse for TupleExpr {
+    fn parse(parser: &mut Parser) -> Result<Self> {
+        parser.consume::<t!("(")>()?;
+        let mut elements = vec![parser.parse_node::<Expr>()?];
+        while *parser.peek()? == tt!(,) {
+            parser.consume::<t!(,)>()?;
+            elements.push(parser.parse_node::<Expr>()?);
+        }
+        parser.consume::<t!(")")>()?;
+
+        Ok(Self { elements })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct VecExpr {
+    pub element: Option<TypeSpanned>,
+    pub elements: Vec<Node<Expr>>,
+}
+
+impl Parse for VecExpr {
+    fn parse(parser: &mut Parser) -> Result<Self> {
+        parser.consume::<t!(vec)>()?;
+        let mut element = None;
+        if *parser.peek()? == tt!(<) {
+            parser.consume::<t!(<)>()?;
+            element = Some(parser.parse()?);
+            parser.consume::<t!(>)>()?;
+        }
+
+        parser.consume::<t!("[")>()?;
+        let mut elements = vec![];
+        loop {
+            if *parser.peek()? == tt!("]") {
+                break;
+            }
+
+            elements.push(parser.parse_node::<Expr>()?);
+            if *parser.peek()? == tt!(,) {
+                parser.consume::<t!(,)>()?;
+            } else {
+                break;
+            }
+        }
+        parser.consume::<t!("]")>()?;
+
+        Ok(Self { element, elements })
     }
 }
 
@@ -83,6 +347,22 @@ pub enum Expr {
     Call(Node<CallExpr>),
     Borrow(Box<SpannedExpr>, bool),
     Box(Box<Node<Expr>>),
+    Field(Box<Node<Expr>>, Node<Ident>),
+    Struct(Box<Node<StructExpr>>),
+    Index(Box<Node<Expr>>, Box<Node<Expr>>),
+    Array(Node<ArrayExpr>),
+    Vec(Box<Node<VecExpr>>),
+    MethodCall(Box<MethodCallExpr>),
+    Variant(Box<Node<VariantExpr>>),
+    Closure(Box<Node<ClosureExpr>>),
+    Cast(Box<CastExpr>),
+    Optional(Box<Node<OptionalExpr>>),
+    Result(Box<Node<ResultExpr>>),
+    Try(Box<Node<TryExpr>>),
+    Tuple(Node<TupleExpr>),
+    If(Box<Node<IfStmt>>),
+    Switch(Box<Node<SwitchStmt>>),
+    Block(Box<Node<BlockStmt>>),
 }
 
 impl Expr {
@@ -90,6 +370,68 @@ impl Expr {
         let mut lhs = parser.parse_null_den_node()?;
 
         loop {
+            // field access, method calls and indexing bind tighter than any prefix or infix operator
+            if let tt!(.) = parser.peek()? {
+                parser.consume::<t!(.)>()?;
+                let field = Self::parse_field_name(parser)?;
+
+                if let tt!("(") = parser.peek()? {
+                    let (args, close) = parse_args(parser)?;
+                    lhs = Node::new(
+                        parser.next_id(),
+                        Span::new(lhs.span().start, close.end),
+                        Expr::MethodCall(Box::new(MethodCallExpr {
+                            receiver: lhs,
+                            method: field,
+                            args,
+                        })),
+                    );
+                    continue;
+                }
+
+                lhs = Node::new(
+                    parser.next_id(),
+                    Span::new(lhs.span().start, field.span().end),
+                    Expr::Field(Box::new(lhs), field),
+                );
+                continue;
+            }
+
+            if let tt!("[") = parser.peek()? {
+                parser.consume::<t!("[")>()?;
+                let index = parser.parse_node::<Expr>()?;
+                let end = parser.consume::<t!("]")>()?.span().end;
+                lhs = Node::new(
+                    parser.next_id(),
+                    Span::new(lhs.span().start, end),
+                    Expr::Index(Box::new(lhs), Box::new(index)),
+                );
+                continue;
+            }
+
+            if let tt!(?) = parser.peek()? {
+                let end = parser.consume::<t!(?)>()?.span().end;
+                let span = Span::new(lhs.span().start, end);
+                let try_expr = Node::new(parser.next_id(), span, TryExpr { expr: lhs });
+                lhs = Node::new(parser.next_id(), span, Expr::Try(Box::new(try_expr)));
+                continue;
+            }
+
+            if let tt!(as) = parser.peek()? {
+                if CAST_BP < min_bp {
+                    break;
+                }
+
+                parser.consume::<t!(as)>()?;
+                let ty = parser.parse::<TypeSpanned>()?;
+                lhs = Node::new(
+                    parser.next_id(),
+                    Span::new(lhs.span().start, ty.span().end),
+                    Expr::Cast(Box::new(CastExpr { expr: lhs, ty })),
+                );
+                continue;
+            }
+
             let mut fork = parser.fork();
             let Ok(op) = fork.parse::<InfixOp>() else {
                 break;
@@ -113,6 +455,17 @@ impl Expr {
         Ok((*lhs).clone())
     }
 
+    // a struct field, or `0`, `1`, ... naming an element of a tuple
+    fn parse_field_name(parser: &mut Parser) -> Result<Node<Ident>> {
+        if let TokenType::Literal(Lit::LitInt(_)) = parser.peek()? {
+            let index = parser.consume::<LitInt>()?;
+            let name = Ident::new(index.value().to_string(), index.span());
+            return Ok(Node::new(parser.next_id(), index.span(), name));
+        }
+
+        parser.parse_node::<Ident>()
+    }
+
     pub(in crate::parser) fn parse_null_den(parser: &mut Parser) -> Result<Self> {
         match parser.peek()? {
             tt!(lit) => {
@@ -121,14 +474,31 @@ impl Expr {
             }
             tt!(ident) => {
                 let mut fork = parser.fork();
-                fork.consume::<t!(ident)>()?;
+                fork.parse_path(false)?;
 
                 if let tt!("(") = *fork.peek()? {
                     let call = parser.parse_node::<CallExpr>()?;
                     return Ok(Expr::Call(call));
                 }
 
-                let ident = parser.parse_node::<t!(ident)>()?;
+                if let tt!(::) = *fork.peek()? {
+                    let v = parser.parse_node::<VariantExpr>()?;
+                    return Ok(Expr::Variant(Box::new(v)));
+                }
+
+                // `Name { field: ...` - plain `ident {` is left alone so `if x {` keeps working
+                if let tt!("{") = *fork.peek()? {
+                    fork.consume::<t!("{")>()?;
+                    if let tt!(ident) = *fork.peek()? {
+                        fork.consume::<t!(ident)>()?;
+                        if let tt!(:) = *fork.peek()? {
+                            let s = parser.parse_node::<StructExpr>()?;
+                            return Ok(Expr::Struct(Box::new(s)));
+                        }
+                    }
+                }
+
+                let ident = parser.parse_path(false)?;
                 return Ok(Expr::Ident(ident));
             }
             tt!(&) => {
@@ -141,12 +511,53 @@ impl Expr {
                 let expr = parser.parse()?;
                 return Ok(Expr::Borrow(Box::new(expr), mutable));
             }
+            tt!("[") => {
+                let array = parser.parse_node()?;
+                return Ok(Expr::Array(array));
+            }
+            tt!(vec) => {
+                let v = parser.parse_node()?;
+                return Ok(Expr::Vec(Box::new(v)));
+            }
+            tt!(fn) => {
+                let closure = parser.parse_node()?;
+                return Ok(Expr::Closure(Box::new(closure)));
+            }
+            tt!(some) | tt!(none) => {
+                let optional = parser.parse_node()?;
+                return Ok(Expr::Optional(Box::new(optional)));
+            }
+            tt!(ok) | tt!(err) => {
+                let result = parser.parse_node()?;
+                return Ok(Expr::Result(Box::new(result)));
+            }
             tt!(box) => {
                 parser.consume::<t!(box)>()?;
                 let expr = parser.parse_node()?;
                 return Ok(Expr::Box(Box::new(expr)));
             }
+            tt!(if) => {
+                let if_expr = parser.parse_node()?;
+                return Ok(Expr::If(Box::new(if_expr)));
+            }
+            tt!(switch) => {
+                let switch = parser.parse_node()?;
+                return Ok(Expr::Switch(Box::new(switch)));
+            }
+            tt!("{") => {
+                let block = parser.parse_node()?;
+                return Ok(Expr::Block(Box::new(block)));
+            }
             tt!("(") => {
+                // `(a, b, ...)` is a tuple, `(a)` just groups
+                let mut fork = parser.fork();
+                fork.consume::<t!("(")>()?;
+                fork.parse::<Expr>()?;
+                if let tt!(,) = *fork.peek()? {
+                    let tuple = parser.parse_node()?;
+                    return Ok(Expr::Tuple(tuple));
+                }
+
                 parser.consume::<t!("(")>()?;
                 let expr = Expr::parse_expr(parser, 0)?;
                 parser.consume::<t!(")")>()?;
@@ -180,6 +591,25 @@ impl Expr {
             Expr::Borrow(expr, _) => expr.calc_span(),
             Expr::Call(expr) => expr.span(),
             Expr::Box(expr) => expr.span(),
+            Expr::Field(base, field) => Span::new(base.span().start, field.span().end),
+            Expr::Struct(s) => s.span(),
+            Expr::Index(base, index) => Span::new(base.span().start, index.span().end),
+            Expr::Array(a) => a.span(),
+            Expr::Vec(v) => v.span(),
+            Expr::MethodCall(call) => {
+                let end = call.args.last().map_or(call.method.span().end, |a| a.span().end);
+                Span::new(call.receiver.span().start, end)
+            }
+            Expr::Variant(v) => v.span(),
+            Expr::Closure(c) => c.span(),
+            Expr::Cast(cast) => Span::new(cast.expr.span().start, cast.ty.span().end),
+            Expr::Optional(o) => o.span(),
+            Expr::Result(r) => r.span(),
+            Expr::Try(t) => t.span(),
+            Expr::Tuple(t) => t.span(),
+            Expr::If(i) => i.span(),
+            Expr::Switch(s) => s.span(),
+            Expr::Block(b) => b.span(),
         }
     }
 
@@ -188,12 +618,66 @@ impl Expr {
             Expr::Ident(i) => Some(i),
             Expr::Prefix(PrefixOp { op, .. }, expr) if *op == PrefixOpKind::Star => expr.lvalue(),
             Expr::Borrow(expr, _) => expr.lvalue(),
+            Expr::Field(base, _) => base.lvalue(),
+            Expr::Index(base, _) => base.lvalue(),
 
             Expr::Literal(_) => None,
             Expr::Call(_) => None,
             Expr::Infix(_, _, _) => None,
             Expr::Prefix(_, _) => None,
             Expr::Box(_) => None,
+            Expr::Struct(_) => None,
+            Expr::Array(_) => None,
+            Expr::Vec(_) => None,
+            Expr::MethodCall(_) => None,
+            Expr::Variant(_) => None,
+            Expr::Closure(_) => None,
+            Expr::Cast(_) => None,
+            Expr::Optional(_) => None,
+            Expr::Result(_) => None,
+            Expr::Try(_) => None,
+            Expr::Tuple(_) => None,
+            Expr::If(_) => None,
+            Expr::Switch(_) => None,
+            Expr::Block(_) => None,
+        }
+    }
+
+    // closures define functions of their own, so the scope builder has to
+    // visit every one of them wherever it is nested
+    pub fn build(&self, builder: &mut ScopeBuilder) {
+        match self {
+            Expr::Closure(c) => c.build(builder),
+            Expr::Infix(lhs, _, rhs) | Expr::Index(lhs, rhs) => {
+                lhs.build(builder);
+                rhs.build(builder);
+            }
+            Expr::Prefix(_, expr) | Expr::Box(expr) | Expr::Field(expr, _) => expr.build(builder),
+            Expr::Cast(cast) => cast.expr.build(builder),
+            Expr::Borrow(expr, _) => expr.build(builder),
+            Expr::Call(call) => call.args.iter().for_each(|a| a.build(builder)),
+            Expr::Struct(s) => s.fields.iter().for_each(|f| {
+                let FieldInit { value, .. } = &**f;
+                value.build(builder)
+            }),
+            Expr::Array(a) => match &**a {
+                ArrayExpr::List(elements) => elements.iter().for_each(|e| e.build(builder)),
+                ArrayExpr::Repeat(value, _) => value.build(builder),
+            },
+            Expr::Vec(v) => v.elements.iter().for_each(|e| e.build(builder)),
+            Expr::Tuple(t) => t.elements.iter().for_each(|e| e.build(builder)),
+            Expr::MethodCall(call) => {
+                call.receiver.build(builder);
+                call.args.iter().for_each(|a| a.build(builder));
+            }
+            Expr::Variant(v) => v.args.iter().for_each(|a| a.build(builder)),
+            Expr::Optional(o) => o.wrapped.iter().for_each(|v| v.build(builder)),
+            Expr::Result(r) => r.wrapped().build(builder),
+            Expr::Try(t) => t.expr.build(builder),
+            Expr::If(i) => i.build(builder),
+            Expr::Switch(s) => s.build(builder),
+            Expr::Block(b) => b.build(builder),
+            Expr::Literal(_) | Expr::Ident(_) => (),
         }
     }
 }
@@ -215,6 +699,61 @@ impl Display for Expr {
             Expr::Infix(lhs, op, rhs) => format!("{lhs} {op} {rhs}"),
             Expr::Borrow(rhs, mutable) => format!("&{}{rhs}", if *mutable { "mut " } else { "" }),
             Expr::Box(expr) => format!("box {expr}"),
+            Expr::Field(base, field) => format!("{base}.{}", field.value()),
+            Expr::Struct(s) => {
+                let mut r = format!("{} {{ ", s.name.value());
+                for field in &s.fields {
+                    let FieldInit { name, value } = &**field;
+                    r.push_str(&format!("{}: {}, ", name.value(), value));
+                }
+                r.push('}');
+                r
+            }
+            Expr::Index(base, index) => format!("{base}[{index}]"),
+            Expr::Array(a) => match &**a {
+                ArrayExpr::List(elements) => {
+                    let elements: Vec<_> = elements.iter().map(|e| e.to_string()).collect();
+                    format!("[{}]", elements.join(", "))
+                }
+                ArrayExpr::Repeat(value, len) => format!("[{value}; {len}]"),
+            },
+            Expr::Vec(v) => {
+                let elements: Vec<_> = v.elements.iter().map(|e| e.to_string()).collect();
+                match &v.element {
+                    Some(ty) => format!("vec<{}>[{}]", ty.ty, elements.join(", ")),
+                    None => format!("vec[{}]", elements.join(", ")),
+                }
+            }
+            Expr::MethodCall(call) => {
+                let args: Vec<_> = call.args.iter().map(|a| a.to_string()).collect();
+                format!("{}.{}({})", call.receiver, call.method.value(), args.join(", "))
+            }
+            Expr::Variant(v) => {
+                let mut s = format!("{}::{}", v.enum_name.value(), v.variant.value());
+                if !v.args.is_empty() {
+                    let args: Vec<_> = v.args.iter().map(|a| a.to_string()).collect();
+                    s.push_str(&format!("({})", args.join(", ")));
+                }
+                s
+            }
+            Expr::Closure(c) => c.to_string(),
+            Expr::Cast(cast) => format!("{} as {}", cast.expr, cast.ty.ty),
+            Expr::Optional(o) => match &o.wrapped {
+                Some(value) => format!("some({value})"),
+                None => "none".to_owned(),
+            },
+            Expr::Result(r) => match &***r {
+                ResultExpr::Ok(value) => format!("ok({value})"),
+                ResultExpr::Err(error) => format!("err({error})"),
+            },
+            Expr::Try(t) => format!("{}?", t.expr),
+            Expr::Tuple(t) => {
+                let elements: Vec<_> = t.elements.iter().map(|e| e.to_string()).collect();
+                format!("({})", elements.join(", "))
+            }
+            Expr::If(i) => format!("if {} {{ .. }}", i.condition()),
+            Expr::Switch(s) => format!("switch {} {{ .. }}", s.matched()),
+            Expr::Block(_) => "{ .. }".to_owned(),
         };
 
         write!(f, "{s}")
@@ -225,7 +764,9 @@ impl Display for Expr {
 mod tests {
     use std::io::Cursor;
     use crate::{lexer::Lexer, parser::parser::Parser, source::SourceFile};
-    use super::Expr;
+    use super::{ArrayExpr, Expr};
+    use crate::parser::expr::infix::{InfixOp, InfixOpKind};
+    use crate::parser::types::Type;
 
     #[test]
     fn test_literal_expr() {
@@ -235,6 +776,21 @@ mod tests {
         assert!(matches!(expr, Expr::Literal(_)));
     }
 
+    #[test]
+    fn test_closure_expr() {
+        let source =
+            SourceFile::new(Cursor::new("fn(a: int, b: float) -> int { return a; }")).unwrap();
+        let mut parser = Parser::new(Lexer::new(&source).unwrap());
+        let expr = parser.parse::<Expr>().unwrap();
+        let Expr::Closure(closure) = expr else {
+            panic!("expected a closure");
+        };
+        assert_eq!(
+            closure.signature(),
+            Type::function(vec![Type::int(), Type::float()], Type::int())
+        );
+    }
+
     #[test]
     fn test_ident_expr() {
         let source = SourceFile::new(Cursor::new("variable")).unwrap();
@@ -267,6 +823,138 @@ mod tests {
         assert!(matches!(expr, Expr::Borrow(_, true)));
     }
 
+    #[test]
+    fn test_field_expr() {
+        let source = SourceFile::new(Cursor::new("a.b.c + 1")).unwrap();
+        let mut parser = Parser::new(Lexer::new(&source).unwrap());
+        let expr = parser.parse::<Expr>().unwrap();
+        let Expr::Infix(lhs, _, _) = expr else {
+            panic!("expected infix expression");
+        };
+        let Expr::Field(ref base, ref field) = **lhs else {
+            panic!("expected field access");
+        };
+        assert_eq!(field.value().as_str(), "c");
+        assert!(matches!(***base, Expr::Field(_, _)));
+    }
+
+    #[test]
+    fn test_deref_field_expr() {
+        let source = SourceFile::new(Cursor::new("*p.x")).unwrap();
+        let mut parser = Parser::new(Lexer::new(&source).unwrap());
+        let expr = parser.parse::<Expr>().unwrap();
+        let Expr::Prefix(_, rhs) = expr else {
+            panic!("expected prefix expression");
+        };
+        assert!(matches!(**rhs, Expr::Field(_, _)));
+    }
+
+    #[test]
+    fn test_struct_expr() {
+        let source = SourceFile::new(Cursor::new("Point { x: 1, y: 2 + 3, }")).unwrap();
+        let mut parser = Parser::new(Lexer::new(&source).unwrap());
+        let expr = parser.parse::<Expr>().unwrap();
+        let Expr::Struct(s) = expr else {
+            panic!("expected struct literal");
+        };
+        assert_eq!(s.name.value().as_str(), "Point");
+        assert_eq!(s.fields.len(), 2);
+        assert_eq!(s.fields[1].name.value().as_str(), "y");
+    }
+
+    #[test]
+    fn test_ident_before_block_is_not_struct() {
+        let source = SourceFile::new(Cursor::new("x { return 1; }")).unwrap();
+        let mut parser = Parser::new(Lexer::new(&source).unwrap());
+        let expr = parser.parse::<Expr>().unwrap();
+        assert!(matches!(expr, Expr::Ident(_)));
+    }
+
+    #[test]
+    fn test_index_expr() {
+        let source = SourceFile::new(Cursor::new("-grid[i][j + 1].x")).unwrap();
+        let mut parser = Parser::new(Lexer::new(&source).unwrap());
+        let expr = parser.parse::<Expr>().unwrap();
+        let Expr::Prefix(_, rhs) = expr else {
+            panic!("expected prefix expression");
+        };
+        let Expr::Field(ref base, _) = **rhs else {
+            panic!("expected field access");
+        };
+        let Expr::Index(ref inner, ref index) = ***base else {
+            panic!("expected index expression");
+        };
+        assert!(matches!(***inner, Expr::Index(_, _)));
+        assert!(matches!(***index, Expr::Infix(_, _, _)));
+    }
+
+    #[test]
+    fn test_array_exprs() {
+        let source = SourceFile::new(Cursor::new("[1, 2, 3,]")).unwrap();
+        let mut parser = Parser::new(Lexer::new(&source).unwrap());
+        let Expr::Array(array) = parser.parse::<Expr>().unwrap() else {
+            panic!("expected array literal");
+        };
+        assert!(matches!(&*array, ArrayExpr::List(elements) if elements.len() == 3));
+
+        let source = SourceFile::new(Cursor::new("[0f; 16]")).unwrap();
+        let mut parser = Parser::new(Lexer::new(&source).unwrap());
+        let Expr::Array(array) = parser.parse::<Expr>().unwrap() else {
+            panic!("expected array literal");
+        };
+        assert!(matches!(&*array, ArrayExpr::Repeat(_, 16)));
+    }
+
+    #[test]
+    fn test_vec_exprs() {
+        let source = SourceFile::new(Cursor::new("vec<vec<int>>[vec[1, 2], vec[]]")).unwrap();
+        let mut parser = Parser::new(Lexer::new(&source).unwrap());
+        let Expr::Vec(v) = parser.parse::<Expr>().unwrap() else {
+            panic!("expected vec literal");
+        };
+        assert_eq!(v.element.as_ref().unwrap().ty, Type::vector(Type::int()));
+        assert_eq!(v.elements.len(), 2);
+        assert!(matches!(&*v.elements[1], Expr::Vec(inner) if inner.elements.is_empty()));
+    }
+
+    #[test]
+    fn test_method_call_expr() {
+        let source = SourceFile::new(Cursor::new("xs.get(i + 1).len() * 2")).unwrap();
+        let mut parser = Parser::new(Lexer::new(&source).unwrap());
+        let Expr::Infix(lhs, _, _) = parser.parse::<Expr>().unwrap() else {
+            panic!("expected infix expression");
+        };
+        let Expr::MethodCall(ref len) = **lhs else {
+            panic!("expected method call");
+        };
+        assert_eq!(len.method.value().as_str(), "len");
+        assert!(len.args.is_empty());
+        let Expr::MethodCall(ref get) = *len.receiver else {
+            panic!("expected method call");
+        };
+        assert_eq!(get.method.value().as_str(), "get");
+        assert_eq!(get.args.len(), 1);
+    }
+
+    #[test]
+    fn test_variant_expr() {
+        let source = SourceFile::new(Cursor::new("Shape::Rect(w, 2f)")).unwrap();
+        let mut parser = Parser::new(Lexer::new(&source).unwrap());
+        let Expr::Variant(v) = parser.parse::<Expr>().unwrap() else {
+            panic!("expected variant expression");
+        };
+        assert_eq!(v.enum_name.value().as_str(), "Shape");
+        assert_eq!(v.variant.value().as_str(), "Rect");
+        assert_eq!(v.args.len(), 2);
+
+        let source = SourceFile::new(Cursor::new("Shape::Empty")).unwrap();
+        let mut parser = Parser::new(Lexer::new(&source).unwrap());
+        let Expr::Variant(v) = parser.parse::<Expr>().unwrap() else {
+            panic!("expected variant expression");
+        };
+        assert!(v.args.is_empty());
+    }
+
     #[test]
     fn test_box_expr() {
         let source = SourceFile::new(Cursor::new("box value")).unwrap();
@@ -274,4 +962,145 @@ mod tests {
         let expr = parser.parse::<Expr>().unwrap();
         assert!(matches!(expr, Expr::Box(_)));
     }
+
+    #[test]
+    fn test_cast_expr() {
+        let source = SourceFile::new(Cursor::new("a + -b as float * c")).unwrap();
+        let mut parser = Parser::new(Lexer::new(&source).unwrap());
+        let expr = parser.parse::<Expr>().unwrap();
+        assert_eq!(expr.to_string(), "a add -b as float multiply c");
+        let Expr::Infix(_, _, rhs) = expr else {
+            panic!("expected an addition");
+        };
+        let Expr::Infix(lhs, _, _) = &**rhs else {
+            panic!("expected a multiplication");
+        };
+        let Expr::Cast(cast) = &***lhs else {
+            panic!("expected a cast");
+        };
+        assert!(matches!(*cast.expr, Expr::Prefix(..)));
+        assert_eq!(cast.ty.ty, Type::float());
+    }
+
+    #[test]
+    fn test_bitwise_precedence() {
+        let source = SourceFile::new(Cursor::new("a | b ^ c & d << 1 + e")).unwrap();
+        let mut parser = Parser::new(Lexer::new(&source).unwrap());
+        let expr = parser.parse::<Expr>().unwrap();
+        assert_eq!(
+            expr.to_string(),
+            "a bitwise or b bitwise xor c bitwise and d shift left 1 add e"
+        );
+        let Expr::Infix(_, op, rhs) = expr else {
+            panic!("expected an or");
+        };
+        assert_eq!(op.op, InfixOpKind::BitOr);
+        let Expr::Infix(_, op, _) = &**rhs else {
+            panic!("expected a xor");
+        };
+        assert_eq!(op.op, InfixOpKind::BitXor);
+    }
+
+    #[test]
+    fn test_shift_needs_touching_brackets() {
+        let source = SourceFile::new(Cursor::new("a >> b > c")).unwrap();
+        let mut parser = Parser::new(Lexer::new(&source).unwrap());
+        let Expr::Infix(lhs, op, _) = parser.parse::<Expr>().unwrap() else {
+            panic!("expected a comparison");
+        };
+        assert_eq!(op.op, InfixOpKind::Gt);
+        assert!(matches!(&**lhs, Expr::Infix(_, InfixOp { op: InfixOpKind::Shr, .. }, _)));
+
+        let source = SourceFile::new(Cursor::new("a > > b")).unwrap();
+        let mut parser = Parser::new(Lexer::new(&source).unwrap());
+        assert!(parser.parse::<Expr>().is_err());
+    }
+
+    #[test]
+    fn test_overflow_ops() {
+        let source = SourceFile::new(Cursor::new("a +% b *| c - d")).unwrap();
+        let mut parser = Parser::new(Lexer::new(&source).unwrap());
+        let Expr::Infix(lhs, op, _) = parser.parse::<Expr>().unwrap() else {
+            panic!("expected a subtraction");
+        };
+        assert_eq!(op.op, InfixOpKind::Minus);
+        let Expr::Infix(_, op, rhs) = &**lhs else {
+            panic!("expected a wrapping add");
+        };
+        assert_eq!(op.op, InfixOpKind::WrapPlus);
+        assert!(matches!(&***rhs, Expr::Infix(_, InfixOp { op: InfixOpKind::SatMult, .. }, _)));
+
+        // the suffix has to touch the operator
+        let source = SourceFile::new(Cursor::new("a + % b")).unwrap();
+        let mut parser = Parser::new(Lexer::new(&source).unwrap());
+        assert!(parser.parse::<Expr>().is_err());
+    }
+
+    #[test]
+    fn test_optional_expr() {
+        let source = SourceFile::new(Cursor::new("some(a + 1)")).unwrap();
+        let mut parser = Parser::new(Lexer::new(&source).unwrap());
+        let Expr::Optional(some) = parser.parse::<Expr>().unwrap() else {
+            panic!("expected an optional");
+        };
+        assert!(matches!(some.wrapped.as_deref(), Some(Expr::Infix(..))));
+
+        let source = SourceFile::new(Cursor::new("none")).unwrap();
+        let mut parser = Parser::new(Lexer::new(&source).unwrap());
+        let expr = parser.parse::<Expr>().unwrap();
+        assert!(matches!(&expr, Expr::Optional(none) if none.wrapped.is_none()));
+        assert_eq!(expr.to_string(), "none");
+    }
+
+    #[test]
+    fn test_try_expr() {
+        let source = SourceFile::new(Cursor::new("1 + f(x)?.len()? as int")).unwrap();
+        let mut parser = Parser::new(Lexer::new(&source).unwrap());
+        let Expr::Infix(_, _, rhs) = parser.parse::<Expr>().unwrap() else {
+            panic!("expected an addition");
+        };
+        let Expr::Cast(cast) = &**rhs else {
+            panic!("expected a cast");
+        };
+        let Expr::Try(outer) = &*cast.expr else {
+            panic!("expected a try");
+        };
+        assert_eq!(outer.expr.to_string(), "f(x,)?.len()");
+
+        let source = SourceFile::new(Cursor::new("err(\"bad\")")).unwrap();
+        let mut parser = Parser::new(Lexer::new(&source).unwrap());
+        let expr = parser.parse::<Expr>().unwrap();
+        assert!(matches!(&expr, Expr::Result(r) if matches!(***r, super::ResultExpr::Err(_))));
+    }
+
+    #[test]
+    fn test_tuple_expr() {
+        let source = SourceFile::new(Cursor::new("(a, (b + 1) * 2, c.0.1)")).unwrap();
+        let mut parser = Parser::new(Lexer::new(&source).unwrap());
+        let Expr::Tuple(tuple) = parser.parse::<Expr>().unwrap() else {
+            panic!("expected a tuple");
+        };
+        assert_eq!(tuple.elements.len(), 3);
+        assert!(matches!(*tuple.elements[1], Expr::Infix(..)));
+        assert_eq!(tuple.elements[2].to_string(), "c.0.1");
+
+        let source = SourceFile::new(Cursor::new("(a)")).unwrap();
+        let mut parser = Parser::new(Lexer::new(&source).unwrap());
+        assert!(matches!(parser.parse::<Expr>().unwrap(), Expr::Ident(_)));
+    }
+
+    #[test]
+    fn test_branching_exprs() {
+        let source = SourceFile::new(Cursor::new("if a { 1 } else { 2 } + { b }")).unwrap();
+        let mut parser = Parser::new(Lexer::new(&source).unwrap());
+        let Expr::Infix(lhs, _, rhs) = parser.parse::<Expr>().unwrap() else {
+            panic!("expected an infix expression");
+        };
+        assert!(matches!(**lhs, Expr::If(_)));
+        assert!(matches!(**rhs, Expr::Block(_)));
+
+        let source = SourceFile::new(Cursor::new("switch x { 1 -> 2; . -> 3; }")).unwrap();
+        let mut parser = Parser::new(Lexer::new(&source).unwrap());
+        assert!(matches!(parser.parse::<Expr>().unwrap(), Expr::Switch(_)));
+    }
 }
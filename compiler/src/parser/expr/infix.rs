@@ -3,6 +3,8 @@ use std::fmt::Display;
 use super::binding_power::Bp;
 use crate::generator::compile_trait::Generate;
 use crate::generator::instruction::Instruction;
+use crate::err::HarpyError;
+use crate::lexer::err::LexerError;
 use crate::lexer::span::Span;
 use crate::parser::parser::Parser;
 use crate::parser::Parse;
@@ -15,6 +17,19 @@ pub enum InfixOpKind {
     Mult,
     Div,
     Mod,
+    // `+%`, `-%` and `*%` wrap around on overflow, `+|`, `-|` and `*|` stop at
+    // the ends of the type; the plain operators fail instead
+    WrapPlus,
+    WrapMinus,
+    WrapMult,
+    SatPlus,
+    SatMinus,
+    SatMult,
+    BitAnd,
+    BitOr,
+    BitXor,
+    Shl,
+    Shr,
     And,
     Or,
     Gt,
@@ -32,10 +47,27 @@ pub struct InfixOp {
 }
 
 impl InfixOp {
+    pub(crate) fn new(op: InfixOpKind, span: Span) -> Self {
+        Self { op, span }
+    }
+
     pub fn bp(&self) -> Bp {
         match self.op {
-            InfixOpKind::Mult | InfixOpKind::Div | InfixOpKind::Mod => (60, 61),
-            InfixOpKind::Plus | InfixOpKind::Minus => (50, 51),
+            InfixOpKind::Mult
+            | InfixOpKind::Div
+            | InfixOpKind::Mod
+            | InfixOpKind::WrapMult
+            | InfixOpKind::SatMult => (60, 61),
+            InfixOpKind::Plus
+            | InfixOpKind::Minus
+            | InfixOpKind::WrapPlus
+            | InfixOpKind::WrapMinus
+            | InfixOpKind::SatPlus
+            | InfixOpKind::SatMinus => (50, 51),
+            InfixOpKind::Shl | InfixOpKind::Shr => (48, 49),
+            InfixOpKind::BitAnd => (46, 47),
+            InfixOpKind::BitXor => (44, 45),
+            InfixOpKind::BitOr => (42, 43),
             InfixOpKind::GtEq
             | InfixOpKind::LtEq
             | InfixOpKind::Eq
@@ -51,6 +83,72 @@ impl InfixOp {
     pub fn span(&self) -> Span {
         self.span
     }
+
+    // `<<` and `>>` are not tokens of their own, so that `vec<vec<int>>` still
+    // closes one type argument at a time. a shift is two touching angle brackets
+    fn parse_angle(parser: &mut Parser) -> crate::aliases::Result<Self> {
+        let first = parser.discard_next()?;
+        let is_lt = *first.kind() == tt!(<);
+
+        let checkpoint = parser.checkpoint();
+        let second = parser.discard_next()?;
+        let touching = second.span().start == first.span().end;
+
+        let op = match (second.kind(), is_lt) {
+            (tt!(<), true) if touching => InfixOpKind::Shl,
+            (tt!(>), false) if touching => InfixOpKind::Shr,
+            // `<<=` and `>>=` are compound assignments
+            (tt!(<=), true) | (tt!(>=), false) if touching => {
+                return HarpyError::lexer(
+                    LexerError::UnexpectedToken("infix operator", second.clone()),
+                    second.span(),
+                );
+            }
+            _ => {
+                parser.rewind(checkpoint);
+                let op = if is_lt { InfixOpKind::Lt } else { InfixOpKind::Gt };
+                return Ok(Self {
+                    op,
+                    span: first.span(),
+                });
+            }
+        };
+
+        Ok(Self {
+            op,
+            span: Span::new(first.span().start, second.span().end),
+        })
+    }
+
+    // `+%` and `+|` are an operator followed by a touching `%` or `|`
+    fn parse_overflow(op: InfixOpKind, parser: &mut Parser) -> crate::aliases::Result<Self> {
+        let first = parser.discard_next()?;
+
+        let checkpoint = parser.checkpoint();
+        let second = parser.discard_next()?;
+        let touching = second.span().start == first.span().end;
+
+        let op = match (second.kind(), &op) {
+            (tt!(%), InfixOpKind::Plus) if touching => InfixOpKind::WrapPlus,
+            (tt!(%), InfixOpKind::Minus) if touching => InfixOpKind::WrapMinus,
+            (tt!(%), InfixOpKind::Mult) if touching => InfixOpKind::WrapMult,
+            (tt!(|), InfixOpKind::Plus) if touching => InfixOpKind::SatPlus,
+            (tt!(|), InfixOpKind::Minus) if touching => InfixOpKind::SatMinus,
+            (tt!(|), InfixOpKind::Mult) if touching => InfixOpKind::SatMult,
+            _ => {
+                parser.rewind(checkpoint);
+                return Ok(Self {
+                    op,
+                    span: first.span(),
+                });
+            }
+        };
+
+        Ok(Self {
+            op,
+            span: Span::new(first.span().start, second.span().end),
+        })
+    }
 }
 
 impl Display for InfixOp {
@@ -62,6 +160,17 @@ impl Display for InfixOp {
             Mult => "multiply",
             Div => "divide",
             Mod => "modulo",
+            WrapPlus => "wrapping add",
+            WrapMinus => "wrapping subtract",
+            WrapMult => "wrapping multiply",
+            SatPlus => "saturating add",
+            SatMinus => "saturating subtract",
+            SatMult => "saturating multiply",
+            BitAnd => "bitwise and",
+            BitOr => "bitwise or",
+            BitXor => "bitwise xor",
+            Shl => "shift left",
+            Shr => "shift right",
             And => "&&",
             Or => "||",
             Gt => ">",
@@ -79,15 +188,17 @@ impl Display for InfixOp {
 impl Parse for InfixOp {
     fn parse(parser: &mut Parser) -> crate::aliases::Result<Self> {
         let op = match parser.peek()? {
-            tt!(+) => InfixOpKind::Plus,
-            tt!(-) => InfixOpKind::Minus,
-            tt!(*) => InfixOpKind::Mult,
+            tt!(+) => return Self::parse_overflow(InfixOpKind::Plus, parser),
+            tt!(-) => return Self::parse_overflow(InfixOpKind::Minus, parser),
+            tt!(*) => return Self::parse_overflow(InfixOpKind::Mult, parser),
             tt!(/) => InfixOpKind::Div,
             tt!(%) => InfixOpKind::Mod,
+            tt!(&) => InfixOpKind::BitAnd,
+            tt!(|) => InfixOpKind::BitOr,
+            tt!(^) => InfixOpKind::BitXor,
             tt!(&&) => InfixOpKind::And,
             tt!(||) => InfixOpKind::Or,
-            tt!(>) => InfixOpKind::Gt,
-            tt!(<) => InfixOpKind::Lt,
+            tt!(>) | tt!(<) => return Self::parse_angle(parser),
             tt!(==) => InfixOpKind::Eq,
             tt!(>=) => InfixOpKind::GtEq,
             tt!(<=) => InfixOpKind::LtEq,
@@ -112,6 +223,17 @@ impl Generate for InfixOp {
             Mult => generator.push_instruction(Instruction::MUL),
             Div => generator.push_instruction(Instruction::DIV),
             Mod => generator.push_instruction(Instruction::MOD),
+            WrapPlus => generator.push_instruction(Instruction::ADD_WRAP),
+            WrapMinus => generator.push_instruction(Instruction::SUB_WRAP),
+            WrapMult => generator.push_instruction(Instruction::MUL_WRAP),
+            SatPlus => generator.push_instruction(Instruction::ADD_SAT),
+            SatMinus => generator.push_instruction(Instruction::SUB_SAT),
+            SatMult => generator.push_instruction(Instruction::MUL_SAT),
+            BitAnd => generator.push_instruction(Instruction::BIT_AND),
+            BitOr => generator.push_instruction(Instruction::BIT_OR),
+            BitXor => generator.push_instruction(Instruction::BIT_XOR),
+            Shl => generator.push_instruction(Instruction::SHL),
+            Shr => generator.push_instruction(Instruction::SHR),
             And => generator.push_instruction(Instruction::AND),
             Or => generator.push_instruction(Instruction::OR),
             Gt => generator.push_instruction(Instruction::GT),
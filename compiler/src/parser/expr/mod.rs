@@ -1,4 +1,5 @@
 mod binding_power;
+pub mod closure;
 pub mod expr;
 pub mod infix;
 pub mod prefix;
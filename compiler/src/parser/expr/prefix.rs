@@ -14,6 +14,7 @@ pub enum PrefixOpKind {
     Plus,
     Neg,
     Star,
+    BitNot,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -40,6 +41,7 @@ impl Display for PrefixOp {
             Plus => "+",
             Neg => "!",
             Star => "*",
+            BitNot => "~",
         };
 
         write!(f, "{s}")
@@ -53,6 +55,7 @@ impl Parse for PrefixOp {
             tt!(-) => PrefixOpKind::Minus,
             tt!(!) => PrefixOpKind::Neg,
             tt!(*) => PrefixOpKind::Star,
+            tt!(~) => PrefixOpKind::BitNot,
             _ => {
                 return parser.unexpected("prefix operator");
             }
@@ -72,6 +75,7 @@ impl Generate for PrefixOp {
             Plus => {}
             Neg => generator.push_instruction(crate::generator::instruction::Instruction::NOT),
             Star => generator.push_instruction(crate::generator::instruction::Instruction::LOAD),
+            BitNot => generator.push_instruction(crate::generator::instruction::Instruction::BIT_NOT),
         }
     }
 }
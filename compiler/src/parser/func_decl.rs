@@ -1,4 +1,5 @@
 use super::{
+    generics::Instantiations,
     node::Node,
     parser::Parser,
     statements::BlockStmt,
@@ -8,21 +9,73 @@ use super::{
 use crate::{
     aliases::Result,
     generator::compile_trait::Generate,
-    lexer::tokens::Ident,
+    lexer::{tokens::Ident, Lexer},
     semantic_analyzer::{
         analyze_trait::Analyze, err::SemanticError, return_status::ReturnStatus, scope::ScopeKind,
+        symbol_info::GenericInfo,
     },
     t, tt,
 };
 
 #[derive(Debug, Clone)]
 pub struct Param {
-    name: Node<Ident>,
-    ttype: TypeSpanned,
+    pub(in crate::parser) name: Node<Ident>,
+    pub(in crate::parser) ttype: TypeSpanned,
+}
+
+impl Param {
+    pub(in crate::parser) fn is_self(&self) -> bool {
+        self.name.value() == "self"
+    }
+
+    // `self`, `&self` or `&mut self` in a method, typed after the impl block's `Self`
+    fn parse_receiver(parser: &mut Parser, self_type: &Type) -> Result<Self> {
+        let mut reference = None;
+        if let tt!(&) = parser.peek()? {
+            parser.consume::<t!(&)>()?;
+            let mutable = matches!(parser.peek()?, tt!(mut));
+            if mutable {
+                parser.consume::<t!(mut)>()?;
+            }
+            reference = Some(mutable);
+        }
+
+        if !Self::at_self(parser) {
+            return parser.unexpected("self");
+        }
+        let name: Node<Ident> = parser.parse_node()?;
+
+        let ty = match reference {
+            None => self_type.clone(),
+            Some(mutable) => Type {
+                mutable: false,
+                inner: TypeInner::Ref(Box::new(Type {
+                    mutable,
+                    inner: self_type.inner.clone(),
+                })),
+            },
+        };
+        let span = name.span();
+
+        Ok(Self {
+            name,
+            ttype: TypeSpanned { ty, span },
+        })
+    }
+
+    fn at_self(parser: &Parser) -> bool {
+        parser.fork().parse::<Ident>().is_ok_and(|i| i.value() == "self")
+    }
 }
 
 impl Parse for Param {
     fn parse(parser: &mut Parser) -> crate::aliases::Result<Self> {
+        if let Some(self_type) = parser.type_arg("Self").cloned() {
+            if matches!(parser.peek()?, tt!(&)) || Self::at_self(parser) {
+                return Self::parse_receiver(parser, &self_type);
+            }
+        }
+
         let name = parser.parse_node()?;
         parser.consume::<t!(:)>()?;
         let ttype = parser.parse()?;
@@ -33,13 +86,18 @@ impl Parse for Param {
 #[derive(Debug, Clone)]
 pub struct FuncDelc {
     name: Node<Ident>,
+    // empty for ordinary functions and for specializations of generic ones
+    type_params: Vec<Node<Ident>>,
     params: Vec<Node<Param>>,
     return_type: TypeSpanned,
     block: BlockStmt,
 }
 
 impl FuncDelc {
-    fn parse_params(parser: &mut Parser, params: &mut Vec<Node<Param>>) -> Result<()> {
+    pub(in crate::parser) fn parse_params(
+        parser: &mut Parser,
+        params: &mut Vec<Node<Param>>,
+    ) -> Result<()> {
         let first = parser.parse_node::<Param>()?;
         params.push(first);
         loop {
@@ -52,16 +110,94 @@ impl FuncDelc {
 
         Ok(())
     }
+
+    fn parse_type_params(parser: &mut Parser) -> Result<Vec<Node<Ident>>> {
+        let mut type_params = vec![];
+        if !matches!(parser.peek()?, tt!(<)) {
+            return Ok(type_params);
+        }
+
+        parser.consume::<t!(<)>()?;
+        type_params.push(parser.parse_node::<Ident>()?);
+        while let tt!(,) = parser.peek()? {
+            parser.consume::<t!(,)>()?;
+            type_params.push(parser.parse_node::<Ident>()?);
+        }
+        parser.consume::<t!(>)>()?;
+
+        Ok(type_params)
+    }
+
+    pub fn name(&self) -> &Node<Ident> {
+        &self.name
+    }
+
+    pub(in crate::parser) fn params(&self) -> &[Node<Param>] {
+        &self.params
+    }
+
+    pub(in crate::parser) fn return_type(&self) -> &TypeSpanned {
+        &self.return_type
+    }
+
+    pub(in crate::parser) fn name_mut(&mut self) -> &mut Node<Ident> {
+        &mut self.name
+    }
+
+    pub fn is_generic(&self) -> bool {
+        !self.type_params.is_empty()
+    }
+
+    // parses the generic function starting at `checkpoint` again for each set of
+    // type arguments it was instantiated with, substituting them for its type
+    // parameters; the copies get fresh node ids, so they are analyzed and
+    // generated like any other function
+    pub(in crate::parser) fn parse_instances<'a>(
+        parser: &mut Parser<'a>,
+        checkpoint: Lexer<'a>,
+        template: &FuncDelc,
+    ) -> Result<Vec<Node<FuncDelc>>> {
+        let mut instances = vec![];
+        let end = parser.checkpoint();
+
+        for args in parser.instances_of(template.name.value()) {
+            if args.len() != template.type_params.len() {
+                continue;
+            }
+
+            let type_args = template
+                .type_params
+                .iter()
+                .map(|p| p.value().clone())
+                .zip(args.iter().cloned())
+                .collect();
+            parser.rewind(checkpoint.clone());
+            parser.set_type_args(type_args);
+            let instance = parser.parse_node::<FuncDelc>();
+            parser.set_type_args(Default::default());
+            let mut instance = instance?;
+
+            let span = instance.name.span();
+            let name = Instantiations::mangle(template.name.value(), &args);
+            instance.value.name.value = Ident::new(name, span);
+            instance.value.type_params.clear();
+            instances.push(instance);
+        }
+
+        parser.rewind(end);
+        Ok(instances)
+    }
 }
 
 impl Parse for FuncDelc {
     fn parse(parser: &mut Parser) -> Result<Self> {
         parser.consume::<t!(fn)>()?;
         let name = parser.parse_node()?;
+        let type_params = Self::parse_type_params(parser)?;
         parser.consume::<t!("(")>()?;
         let mut params = vec![];
 
-        if let tt!(ident) = parser.peek()? {
+        if let tt!(ident) | tt!(&) = parser.peek()? {
             match Self::parse_params(parser, &mut params) {
                 Ok(()) => (),
                 Err(e) => parser.report_error(e, &[tt!(")")])?,
@@ -81,6 +217,7 @@ impl Parse for FuncDelc {
 
         Ok(Self {
             name,
+            type_params,
             params,
             return_type,
             block,
@@ -90,11 +227,30 @@ impl Parse for FuncDelc {
 
 impl Analyze for FuncDelc {
     fn build(&self, builder: &mut crate::semantic_analyzer::scope_builder::ScopeBuilder) {
+        // the body of a generic function is only checked in its specializations
+        if self.is_generic() {
+            builder.define_generic(
+                &self.name,
+                &self.type_params,
+                GenericInfo {
+                    type_params: self.type_params.iter().map(|p| p.value().clone()).collect(),
+                    params: self.params.iter().map(|p| p.ttype.ty.clone()).collect(),
+                    return_type: self.return_type.ty.clone(),
+                },
+            );
+            return;
+        }
+
         let return_info = builder.register_type(&self.return_type);
-        builder.define_func(&self.name, return_info);
-        builder.push_scope(ScopeKind::Function(self.name.value().clone()));
-        for param in &self.params {
-            let param_info = builder.register_type(&param.ttype);
+        let params: Vec<_> = self.params.iter().map(|p| builder.register_type(&p.ttype)).collect();
+
+        // a function sharing its name with others is defined under a name of its own
+        let span = self.name.span();
+        let name = builder.overload_name(&self.name, &params);
+        let defined_as = Node::new(self.name.id(), span, Ident::new(name.clone(), span));
+        builder.define_func(&defined_as, return_info);
+        builder.push_scope(ScopeKind::Function(name));
+        for (param, param_info) in self.params.iter().zip(params) {
             builder.define_param(&param.name, param_info);
         }
 
@@ -106,10 +262,16 @@ impl Analyze for FuncDelc {
         &self,
         analyzer: &mut crate::semantic_analyzer::analyzer::Analyzer,
     ) -> ReturnStatus {
+        if self.is_generic() {
+            return ReturnStatus::Never;
+        }
+
         analyzer.enter_scope();
         let block_status = self.block.analyze_semantics(analyzer);
 
-        if self.return_type.inner != TypeInner::Void && block_status != ReturnStatus::Always {
+        if analyzer.expand_type(&self.return_type).inner != TypeInner::Void
+            && block_status != ReturnStatus::Always
+        {
             analyzer.report_semantic_error(SemanticError::NotAllPathsReturn, self.name.span());
         }
 
@@ -120,6 +282,10 @@ impl Analyze for FuncDelc {
 
 impl Generate for FuncDelc {
     fn generate(&self, generator: &mut crate::generator::generator::Generator) {
+        if self.is_generic() {
+            return;
+        }
+
         let func_label = generator.create_label();
         let id = generator.get_function_mapping(self.name.id());
         generator.register_function(id, func_label);
@@ -144,6 +310,17 @@ mod tests {
         assert!(func.params.is_empty());
     }
 
+    #[test]
+    fn test_generic_function() {
+        let source =
+            SourceFile::new(Cursor::new("fn max<T, U>(a: T, b: U) -> T { return a; }")).unwrap();
+        let mut parser = Parser::new(Lexer::new(&source).unwrap());
+        let func = parser.parse::<FuncDelc>().unwrap();
+        assert!(func.is_generic());
+        let type_params: Vec<_> = func.type_params.iter().map(|p| p.value().as_str()).collect();
+        assert_eq!(type_params, ["T", "U"]);
+    }
+
     #[test]
     fn test_function_with_params() {
         let source = SourceFile::new(Cursor::new("fn add(a: int, b: int) -> int { return a + b; }")).unwrap();
@@ -0,0 +1,208 @@
+use std::collections::HashMap;
+
+use super::types::{BaseType, Type, TypeInner};
+
+// type parameter name -> the type it stands for in one instantiation
+pub type TypeArgs = HashMap<String, Type>;
+
+// the type arguments each generic function has been instantiated with, by
+// function name; the parser emits one specialized copy of the function per entry
+#[derive(Debug, Clone, Default)]
+pub struct Instantiations {
+    by_name: HashMap<String, Vec<Vec<Type>>>,
+}
+
+impl Instantiations {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, name: &str) -> &[Vec<Type>] {
+        self.by_name.get(name).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    // false if the instantiation was already known
+    pub fn insert(&mut self, name: &str, args: Vec<Type>) -> bool {
+        let known = self.by_name.entry(name.to_owned()).or_default();
+        if known.contains(&args) {
+            return false;
+        }
+
+        known.push(args);
+        true
+    }
+
+    // the name the specialization of `name` is compiled under; `<` can't appear
+    // in an identifier, so it never clashes with a user defined function
+    pub fn mangle(name: &str, args: &[Type]) -> String {
+        let args = args.iter().map(Type::to_string).collect::<Vec<_>>();
+        format!("{name}<{}>", args.join(", "))
+    }
+}
+
+fn type_param<'a>(ty: &Type, params: &'a [String]) -> Option<&'a String> {
+    match &ty.inner {
+        TypeInner::Base(BaseType::Custom(c)) => params.iter().find(|p| *p == c.name()),
+        _ => None,
+    }
+}
+
+// matches the declared parameter type `pattern` against the type of an argument,
+// recording what each type parameter in it stands for; on a conflict returns
+// the parameter with the type it was bound to before
+pub fn infer_type_args(
+    pattern: &Type,
+    actual: &Type,
+    params: &[String],
+    args: &mut TypeArgs,
+) -> std::result::Result<(), (String, Type)> {
+    if let Some(name) = type_param(pattern, params) {
+        // mutability belongs to the binding, not to the type
+        let bound = Type {
+            mutable: false,
+            inner: actual.inner.clone(),
+        };
+
+        return match args.get(name) {
+            Some(previous) if *previous != bound => Err((name.clone(), previous.clone())),
+            Some(_) => Ok(()),
+            None => {
+                args.insert(name.clone(), bound);
+                Ok(())
+            }
+        };
+    }
+
+    match (&pattern.inner, &actual.inner) {
+        (TypeInner::Boxed(p), TypeInner::Boxed(a))
+        | (TypeInner::Ref(p), TypeInner::Ref(a))
+        | (TypeInner::Vector(p), TypeInner::Vector(a))
+        | (TypeInner::Optional(p), TypeInner::Optional(a))
+        | (TypeInner::Array(p, _), TypeInner::Array(a, _)) => infer_type_args(p, a, params, args),
+        (TypeInner::Result(pv, pe), TypeInner::Result(av, ae)) => {
+            infer_type_args(pv, av, params, args)?;
+            infer_type_args(pe, ae, params, args)
+        }
+        (TypeInner::Function(pp, pr), TypeInner::Function(ap, ar)) if pp.len() == ap.len() => {
+            for (p, a) in pp.iter().zip(ap) {
+                infer_type_args(p, a, params, args)?;
+            }
+            infer_type_args(pr, ar, params, args)
+        }
+        (TypeInner::Tuple(pe), TypeInner::Tuple(ae)) if pe.len() == ae.len() => {
+            for (p, a) in pe.iter().zip(ae) {
+                infer_type_args(p, a, params, args)?;
+            }
+            Ok(())
+        }
+        // a shape mismatch shows up when the argument is checked against the
+        // substituted parameter type
+        _ => Ok(()),
+    }
+}
+
+// how many types `ty` is built from, itself included
+pub fn type_size(ty: &Type) -> usize {
+    1 + match &ty.inner {
+        TypeInner::Boxed(t)
+        | TypeInner::Ref(t)
+        | TypeInner::Vector(t)
+        | TypeInner::Optional(t)
+        | TypeInner::Array(t, _) => type_size(t),
+        TypeInner::Result(value, error) => type_size(value) + type_size(error),
+        TypeInner::Function(params, ret) => params.iter().map(type_size).sum::<usize>() + type_size(ret),
+        TypeInner::Tuple(elements) => elements.iter().map(type_size).sum(),
+        TypeInner::Base(_) | TypeInner::Dyn(_) | TypeInner::Void | TypeInner::Unknown => 0,
+    }
+}
+
+pub fn substitute(ty: &Type, args: &TypeArgs) -> Type {
+    let inner = match &ty.inner {
+        TypeInner::Base(BaseType::Custom(c)) => match args.get(c.name()) {
+            Some(arg) => {
+                return Type {
+                    mutable: ty.mutable || arg.mutable,
+                    inner: arg.inner.clone(),
+                }
+            }
+            None => ty.inner.clone(),
+        },
+        TypeInner::Boxed(t) => TypeInner::Boxed(Box::new(substitute(t, args))),
+        TypeInner::Ref(t) => TypeInner::Ref(Box::new(substitute(t, args))),
+        TypeInner::Vector(t) => TypeInner::Vector(Box::new(substitute(t, args))),
+        TypeInner::Optional(t) => TypeInner::Optional(Box::new(substitute(t, args))),
+        TypeInner::Result(value, error) => TypeInner::Result(
+            Box::new(substitute(value, args)),
+            Box::new(substitute(error, args)),
+        ),
+        TypeInner::Array(t, len) => TypeInner::Array(Box::new(substitute(t, args)), *len),
+        TypeInner::Function(params, ret) => TypeInner::Function(
+            params.iter().map(|p| substitute(p, args)).collect(),
+            Box::new(substitute(ret, args)),
+        ),
+        TypeInner::Tuple(elements) => {
+            TypeInner::Tuple(elements.iter().map(|e| substitute(e, args)).collect())
+        }
+        TypeInner::Base(_) | TypeInner::Dyn(_) | TypeInner::Void | TypeInner::Unknown => {
+            ty.inner.clone()
+        }
+    };
+
+    Type {
+        mutable: ty.mutable,
+        inner,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params() -> Vec<String> {
+        vec!["T".to_owned()]
+    }
+
+    #[test]
+    fn test_infer_and_substitute() {
+        let pattern = Type::vector(Type::custom("T"));
+        let mut args = TypeArgs::new();
+        infer_type_args(&pattern, &Type::vector(Type::float()), &params(), &mut args).unwrap();
+        assert_eq!(args["T"], Type::float());
+
+        let ret = Type::function(vec![Type::custom("T")], Type::custom("T"));
+        assert_eq!(
+            substitute(&ret, &args),
+            Type::function(vec![Type::float()], Type::float())
+        );
+    }
+
+    #[test]
+    fn test_conflicting_type_args() {
+        let mut args = TypeArgs::new();
+        let t = Type::custom("T");
+        infer_type_args(&t, &Type::int(), &params(), &mut args).unwrap();
+        let (name, previous) =
+            infer_type_args(&t, &Type::float(), &params(), &mut args).unwrap_err();
+        assert_eq!(name, "T");
+        assert_eq!(previous, Type::int());
+    }
+
+    #[test]
+    fn test_instantiations() {
+        let mut instances = Instantiations::new();
+        assert!(instances.insert("max", vec![Type::int()]));
+        assert!(!instances.insert("max", vec![Type::int()]));
+        assert!(instances.insert("max", vec![Type::float()]));
+        assert_eq!(instances.get("max").len(), 2);
+        assert!(instances.get("min").is_empty());
+        assert_eq!(Instantiations::mangle("max", &[Type::int()]), "max<int>");
+    }
+
+    #[test]
+    fn test_type_size() {
+        assert_eq!(type_size(&Type::int()), 1);
+        let pair = Type::tuple(vec![Type::int(), Type::int()]);
+        assert_eq!(type_size(&Type::vector(pair.clone())), 4);
+        assert_eq!(type_size(&Type::tuple(vec![pair.clone(), pair])), 7);
+    }
+}
@@ -0,0 +1,218 @@
+use super::{
+    func_decl::FuncDelc,
+    generics::TypeArgs,
+    node::Node,
+    parser::Parser,
+    types::{Type, TypeInner, TypeSpanned},
+    Parse,
+};
+use crate::{
+    aliases::Result,
+    err::HarpyError,
+    generator::compile_trait::Generate,
+    lexer::tokens::Ident,
+    semantic_analyzer::{analyze_trait::Analyze, err::SemanticError, return_status::ReturnStatus},
+    t, tt,
+};
+
+// `impl Type { fn method(&self, ...) { ... } }`. the methods are ordinary
+// functions named `Type::method` that take the receiver as their first param.
+// `impl Trait for Type { ... }` defines the methods of the trait for the type
+#[derive(Debug, Clone)]
+pub struct ImplDecl {
+    pub ty: Node<Ident>,
+    pub tr: Option<Node<Ident>>,
+    pub methods: Vec<Node<FuncDelc>>,
+}
+
+impl ImplDecl {
+    pub fn method_name(ty: &str, method: &str) -> String {
+        format!("{ty}::{method}")
+    }
+
+    fn parse_methods(parser: &mut Parser) -> Result<Vec<Node<FuncDelc>>> {
+        let mut methods = vec![];
+        while !matches!(parser.peek()?, tt!("}")) {
+            methods.push(parser.parse_node::<FuncDelc>()?);
+        }
+        Ok(methods)
+    }
+
+    fn check_trait(&self, tr: &Node<Ident>, analyzer: &mut crate::semantic_analyzer::analyzer::Analyzer) {
+        let Some(info) = analyzer.get_trait(tr.value()) else {
+            analyzer.report_semantic_error(SemanticError::UndefinedTrait(tr.value().clone()), tr.span());
+            return;
+        };
+
+        // the vtable is looked up by the type of the boxed value
+        analyzer.register_type(&TypeSpanned::dummy(Type::custom(self.ty.value())));
+
+        for method in &self.methods {
+            let name = method.name();
+            let bare = name.value().rsplit_once("::").map_or(name.value().as_str(), |(_, m)| m);
+            let Some(expected) = info.method(bare) else {
+                analyzer.report_semantic_error(
+                    SemanticError::NotATraitMethod(name.clone(), tr.value().clone()),
+                    name.span(),
+                );
+                continue;
+            };
+
+            let (receiver, params) = method.params().split_first().unwrap();
+            let receiver_matches = matches!(
+                &receiver.ttype.inner,
+                TypeInner::Ref(pointee) if pointee.mutable == expected.mutable_self
+            );
+            let params_match = params.len() == expected.params.len()
+                && params
+                    .iter()
+                    .zip(&expected.params)
+                    .all(|(p, e)| analyzer.expand_type(&p.ttype).inner == e.ttype.inner);
+            let return_type = analyzer.expand_type(method.return_type());
+
+            if !receiver_matches || !params_match || return_type.inner != expected.return_type.ttype.inner {
+                analyzer.report_semantic_error(
+                    SemanticError::TraitMethodMismatch(name.clone(), tr.value().clone()),
+                    name.span(),
+                );
+            }
+        }
+
+        for expected in &info.methods {
+            let defined = self.methods.iter().any(|m| {
+                m.name().value().rsplit_once("::").is_some_and(|(_, m)| *m == expected.name)
+            });
+            if !defined {
+                analyzer.report_semantic_error(
+                    SemanticError::MissingTraitMethod(expected.name.clone(), tr.value().clone()),
+                    tr.span(),
+                );
+            }
+        }
+    }
+}
+
+impl Parse for ImplDecl {
+    fn parse(parser: &mut Parser) -> Result<Self> {
+        parser.consume::<t!(impl)>()?;
+        let mut ty = parser.parse_path(true)?;
+        let mut tr = None;
+        if let tt!(for) = parser.peek()? {
+            parser.consume::<t!(for)>()?;
+            tr = Some(std::mem::replace(&mut ty, parser.parse_path(true)?));
+        }
+        parser.consume::<t!("{")>()?;
+
+        // `Self` stands for the type in the methods' signatures and bodies
+        parser.set_type_args(TypeArgs::from([("Self".to_owned(), Type::custom(ty.value()))]));
+        let methods = Self::parse_methods(parser);
+        parser.set_type_args(Default::default());
+        let mut methods = methods?;
+        parser.consume::<t!("}")>()?;
+
+        for method in &mut methods {
+            if !method.params().first().is_some_and(|p| p.is_self()) {
+                return HarpyError::semantic(
+                    SemanticError::MissingSelf(method.name().clone()),
+                    method.name().span(),
+                );
+            }
+
+            let span = method.name().span();
+            let name = Self::method_name(ty.value(), method.name().value());
+            method.value.name_mut().value = Ident::new(name, span);
+        }
+
+        Ok(Self { ty, tr, methods })
+    }
+}
+
+impl Analyze for ImplDecl {
+    fn build(&self, builder: &mut crate::semantic_analyzer::scope_builder::ScopeBuilder) {
+        if let Some(tr) = &self.tr {
+            builder.implement_trait(&self.ty, tr);
+        }
+
+        for method in &self.methods {
+            method.build(builder);
+        }
+    }
+
+    fn analyze_semantics(
+        &self,
+        analyzer: &mut crate::semantic_analyzer::analyzer::Analyzer,
+    ) -> ReturnStatus {
+        if !analyzer.is_type_defined(self.ty.value()) {
+            analyzer.report_semantic_error(
+                SemanticError::UndefinedType(self.ty.value().clone()),
+                self.ty.span(),
+            );
+        } else if let Some(tr) = &self.tr {
+            self.check_trait(tr, analyzer);
+        }
+
+        for method in &self.methods {
+            method.analyze_semantics(analyzer);
+        }
+        ReturnStatus::Never
+    }
+}
+
+impl Generate for ImplDecl {
+    fn generate(&self, generator: &mut crate::generator::generator::Generator) {
+        for method in &self.methods {
+            method.generate(generator);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ImplDecl;
+    use crate::{
+        lexer::Lexer,
+        parser::parser::Parser,
+        source::SourceFile,
+    };
+    use std::io::Cursor;
+
+    fn parse_impl(input: &str) -> crate::aliases::Result<ImplDecl> {
+        let source = SourceFile::new(Cursor::new(input)).unwrap();
+        let mut parser = Parser::new(Lexer::new(&source).unwrap());
+        parser.parse::<ImplDecl>()
+    }
+
+    #[test]
+    fn test_impl_block() {
+        let decl = parse_impl(
+            "impl Point {
+                fn len(&self) -> int { return self.x; }
+                fn scale(&mut self, by: int) { self.x *= by; }
+                fn sum(self, other: &Self) -> int { return self.x + other.x; }
+            }",
+        )
+        .unwrap();
+
+        let names: Vec<_> = decl.methods.iter().map(|m| m.name().value().as_str()).collect();
+        assert_eq!(names, ["Point::len", "Point::scale", "Point::sum"]);
+
+        let receivers: Vec<_> = decl.methods.iter().map(|m| m.params()[0].ttype.ty.to_string()).collect();
+        assert_eq!(receivers, ["&Point", "&mut Point", "Point"]);
+        assert_eq!(decl.methods[2].params()[1].ttype.ty.to_string(), "&Point");
+    }
+
+    #[test]
+    fn test_trait_impl() {
+        let decl = parse_impl("impl Shape for Circle { fn area(&self) -> float { return 3.0; } }").unwrap();
+        assert_eq!(decl.ty.value().as_str(), "Circle");
+        assert_eq!(decl.tr.unwrap().value().as_str(), "Shape");
+        assert_eq!(decl.methods[0].name().value().as_str(), "Circle::area");
+    }
+
+    #[test]
+    fn test_method_needs_self() {
+        assert!(parse_impl("impl Point { fn origin() -> int { return 0; } }").is_err());
+        assert!(parse_impl("impl Point { fn f(x: int) { } }").is_err());
+        assert!(parse_impl("impl Point { fn f(&other) { } }").is_err());
+    }
+}
@@ -1,10 +1,17 @@
+pub mod enum_decl;
 pub mod expr;
 pub mod func_decl;
+pub mod generics;
+pub mod impl_decl;
+pub mod module;
 pub mod node;
 pub mod parse_trait;
 pub mod parser;
 pub mod program;
 pub mod statements;
+pub mod struct_decl;
+pub mod trait_decl;
+pub mod type_decl;
 pub mod types;
 
 pub use parse_trait::Parse;
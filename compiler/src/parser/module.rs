@@ -0,0 +1,491 @@
+use std::{
+    collections::{HashMap, HashSet},
+    io::Cursor,
+    path::{Component, Path, PathBuf},
+    rc::Rc,
+};
+
+use crate::{
+    aliases::Result,
+    err::{HarpyError, HarpyErrorKind},
+    lexer::{
+        span::Span,
+        tokens::{Ident, LitStr},
+        Lexer,
+    },
+    semantic_analyzer::err::SemanticError,
+    source::{SourceFile, SourceMap},
+    t, tt,
+};
+
+use super::{
+    generics::Instantiations,
+    node::{Node, NodeId},
+    parser::Parser,
+    program::Program,
+    Parse,
+};
+
+// `import "path/to/file.hrpy" as name;`, the path is relative to the importing
+// file and the name defaults to the file name without its extension
+#[derive(Debug, Clone)]
+pub struct Import {
+    path: String,
+    alias: Option<Node<Ident>>,
+}
+
+impl Import {
+    pub fn alias(&self) -> String {
+        match &self.alias {
+            Some(alias) => alias.value().clone(),
+            None => Path::new(&self.path)
+                .file_stem()
+                .map(|s| s.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+impl Parse for Import {
+    fn parse(parser: &mut Parser) -> Result<Self> {
+        parser.consume::<t!(import)>()?;
+        let path = parser.consume::<LitStr>()?.value().clone();
+
+        let mut alias = None;
+        if let tt!(as) = parser.peek()? {
+            parser.consume::<t!(as)>()?;
+            alias = Some(parser.parse_node()?);
+        }
+        parser.consume::<t!(;)>()?;
+
+        Ok(Self { path, alias })
+    }
+}
+
+// what an importing module can see of a module: its items, and which of them are `pub`
+#[derive(Debug, Default)]
+pub struct Exports {
+    name: String,
+    items: HashMap<String, bool>,
+}
+
+// a private or unknown item of an imported module; parsing goes on with its
+// qualified name and the analyzer reports it
+#[derive(Debug, Clone)]
+pub struct InaccessibleItem {
+    module: String,
+    item: String,
+    private: bool,
+    span: Span,
+}
+
+impl InaccessibleItem {
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    pub fn error(&self) -> HarpyError {
+        let (module, item) = (self.module.clone(), self.item.clone());
+        let error = match self.private {
+            true => SemanticError::PrivateItem(module, item),
+            false => SemanticError::UnknownModuleItem(module, item),
+        };
+        HarpyError::new(HarpyErrorKind::SemanticError(error), self.span)
+    }
+}
+
+// what the parser knows about the module it is parsing
+#[derive(Debug, Clone, Default)]
+pub struct ModuleContext {
+    // prefix of the module's items, empty for the root module
+    name: String,
+    // the module's own structs and enums
+    types: HashSet<String>,
+    imports: HashMap<String, Rc<Exports>>,
+}
+
+impl ModuleContext {
+    pub(in crate::parser) fn qualify(&self, name: &str) -> String {
+        if self.name.is_empty() {
+            return name.to_owned();
+        }
+
+        format!("{}::{name}", self.name)
+    }
+
+    // `alias::item` for an item of an imported module, `None` if `alias` isn't imported;
+    // the item's qualified name comes with what's wrong with using it, if anything
+    pub(in crate::parser) fn resolve_import(
+        &self,
+        alias: &str,
+        item: &Node<Ident>,
+    ) -> Option<(String, Option<InaccessibleItem>)> {
+        let module = self.imports.get(alias)?;
+        let name = format!("{}::{}", module.name, item.value());
+        let error = match module.items.get(item.value()) {
+            Some(true) => None,
+            known => Some(InaccessibleItem {
+                module: module.name.clone(),
+                item: item.value().clone(),
+                private: known.is_some(),
+                span: item.span(),
+            }),
+        };
+
+        Some((name, error))
+    }
+
+    pub(in crate::parser) fn is_import(&self, alias: &str) -> bool {
+        self.imports.contains_key(alias)
+    }
+
+    pub(in crate::parser) fn qualify_type(&self, name: &str) -> Option<String> {
+        self.types.contains(name).then(|| self.qualify(name))
+    }
+}
+
+#[derive(Debug)]
+struct Module {
+    name: String,
+    file: usize,
+    // import alias -> index of the imported module
+    imports: Vec<(String, usize)>,
+}
+
+// the root file and every file it imports, directly or not
+#[derive(Debug)]
+pub struct ModuleGraph {
+    sources: SourceMap,
+    // every module comes after the modules it imports, the root module is last
+    modules: Vec<Module>,
+}
+
+struct Loader<R> {
+    read: R,
+    sources: SourceMap,
+    modules: Vec<Module>,
+    // `None` while the module's imports are being loaded
+    loaded: HashMap<PathBuf, Option<usize>>,
+    stack: Vec<PathBuf>,
+    errors: Vec<HarpyError>,
+}
+
+// `a/./b/../c` -> `a/c`, so a file reached through different paths is loaded once
+fn normalize(path: &Path) -> PathBuf {
+    let mut result = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => (),
+            Component::ParentDir
+                if matches!(result.components().next_back(), Some(Component::Normal(_))) =>
+            {
+                result.pop();
+            }
+            c => result.push(c),
+        }
+    }
+    result
+}
+
+impl<R: FnMut(&Path) -> std::io::Result<String>> Loader<R> {
+    fn error(&mut self, error: SemanticError, span: Span) {
+        self.errors
+            .push(HarpyError::new(HarpyErrorKind::SemanticError(error), span));
+    }
+
+    // syntax errors are left for the full parse of the file to report
+    fn scan_imports(&self, file: usize) -> Vec<Node<Import>> {
+        let source = self.sources.get(file).unwrap();
+        let Ok(lexer) = Lexer::for_file(source, file) else {
+            return vec![];
+        };
+
+        Parser::new(lexer).parse_imports().unwrap_or_default()
+    }
+
+    fn module_name(&self, path: &Path) -> String {
+        let stem = path
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default();
+
+        // two files with the same name in different directories
+        let mut name = stem.clone();
+        let mut n = 1;
+        while self.modules.iter().any(|m| m.name == name) {
+            n += 1;
+            name = format!("{stem}{n}");
+        }
+        name
+    }
+
+    fn load(&mut self, path: PathBuf, span: Span) -> Option<usize> {
+        match self.loaded.get(&path) {
+            Some(Some(module)) => return Some(*module),
+            Some(None) => {
+                let start = self.stack.iter().position(|p| *p == path).unwrap();
+                let cycle = self.stack[start..]
+                    .iter()
+                    .chain([&path])
+                    .map(|p| p.display().to_string())
+                    .collect();
+                self.error(SemanticError::ImportCycle(cycle), span);
+                return None;
+            }
+            None => (),
+        }
+
+        let text = match (self.read)(&path) {
+            Ok(text) => text,
+            Err(e) => {
+                self.error(SemanticError::ImportFailed(path.display().to_string(), e), span);
+                return None;
+            }
+        };
+        // reading from memory can't fail
+        let source = SourceFile::new(Cursor::new(text)).unwrap();
+        let file = self.sources.add(path.clone(), source);
+
+        self.loaded.insert(path.clone(), None);
+        self.stack.push(path.clone());
+
+        let dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+        let mut imports: Vec<(String, usize)> = vec![];
+        for import in self.scan_imports(file) {
+            let alias = import.alias();
+            if imports.iter().any(|(a, _)| *a == alias) {
+                self.error(SemanticError::DuplicateImport(alias), import.span());
+                continue;
+            }
+
+            if let Some(module) = self.load(normalize(&dir.join(&import.path)), import.span()) {
+                imports.push((alias, module));
+            }
+        }
+
+        self.stack.pop();
+        let name = match self.stack.is_empty() {
+            true => String::new(),
+            false => self.module_name(&path),
+        };
+        self.modules.push(Module {
+            name,
+            file,
+            imports,
+        });
+
+        let module = self.modules.len() - 1;
+        self.loaded.insert(path, Some(module));
+        Some(module)
+    }
+}
+
+impl ModuleGraph {
+    // the graph is returned even if some files couldn't be loaded, so the errors
+    // can be shown against the files that were
+    pub fn load(root: &Path) -> (Self, Vec<HarpyError>) {
+        Self::load_with(root, |path| std::fs::read_to_string(path))
+    }
+
+    // like `load`, but the files are read with `read`
+    pub fn load_with<R: FnMut(&Path) -> std::io::Result<String>>(
+        root: &Path,
+        read: R,
+    ) -> (Self, Vec<HarpyError>) {
+        let mut loader = Loader {
+            read,
+            sources: SourceMap::new(),
+            modules: vec![],
+            loaded: HashMap::new(),
+            stack: vec![],
+            errors: vec![],
+        };
+        loader.load(normalize(root), Span::default());
+
+        let graph = Self {
+            sources: loader.sources,
+            modules: loader.modules,
+        };
+        (graph, loader.errors)
+    }
+
+    pub fn sources(&self) -> &SourceMap {
+        &self.sources
+    }
+
+    fn parse_module(
+        &self,
+        module: &Module,
+        context: ModuleContext,
+        first_id: NodeId,
+        instances: &Instantiations,
+    ) -> std::result::Result<(Program, NodeId), Vec<HarpyError>> {
+        let source = self.sources.get(module.file).unwrap();
+        let lexer = Lexer::for_file(source, module.file).map_err(|e| vec![e])?;
+        Parser::new(lexer)
+            .with_instances(instances.clone())
+            .with_module(context, first_id)
+            .build_module()
+    }
+
+    // parses every module into a single program, the items of all but the root
+    // module named `module::item`
+    pub fn parse(
+        &self,
+        instances: &Instantiations,
+    ) -> std::result::Result<Program, Vec<HarpyError>> {
+        let mut exports: Vec<Rc<Exports>> = Vec::with_capacity(self.modules.len());
+        let mut next_id = NodeId(0);
+        let mut parts = vec![];
+        let mut import_errors = vec![];
+
+        for module in &self.modules {
+            let mut context = ModuleContext {
+                name: module.name.clone(),
+                types: HashSet::new(),
+                imports: module
+                    .imports
+                    .iter()
+                    .map(|(alias, m)| (alias.clone(), exports[*m].clone()))
+                    .collect(),
+            };
+
+            // the module's structs and enums can be used before they are declared, so
+            // a first pass finds their names
+            if !module.name.is_empty() {
+                if let Ok((first, _)) =
+                    self.parse_module(module, context.clone(), next_id, instances)
+                {
+                    context.types = first.type_names().map(str::to_owned).collect();
+                }
+            }
+
+            let (program, id) = self.parse_module(module, context, next_id, instances)?;
+            next_id = id;
+            exports.push(Rc::new(Exports {
+                name: module.name.clone(),
+                items: program.items.clone(),
+            }));
+            parts.extend(program.parts);
+            import_errors.extend(program.import_errors);
+        }
+
+        let mut modules = vec![String::new(); self.sources.len()];
+        for module in &self.modules {
+            modules[module.file] = module.name.clone();
+        }
+
+        Ok(Program {
+            parts,
+            items: HashMap::new(),
+            modules,
+            import_errors,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn load(files: &[(&str, &str)]) -> std::result::Result<ModuleGraph, Vec<HarpyError>> {
+        let files: HashMap<PathBuf, String> = files
+            .iter()
+            .map(|(path, text)| (PathBuf::from(path), text.to_string()))
+            .collect();
+        let (graph, errors) = ModuleGraph::load_with(Path::new("main.hrpy"), |path| {
+            files
+                .get(path)
+                .cloned()
+                .ok_or_else(|| std::io::ErrorKind::NotFound.into())
+        });
+
+        match errors.is_empty() {
+            true => Ok(graph),
+            false => Err(errors),
+        }
+    }
+
+    fn semantic_error(errors: &[HarpyError]) -> &SemanticError {
+        match errors[0].kind() {
+            HarpyErrorKind::SemanticError(e) => e,
+            e => panic!("unexpected error {e:?}"),
+        }
+    }
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize(Path::new("a/./b/../c.hrpy")), PathBuf::from("a/c.hrpy"));
+        assert_eq!(normalize(Path::new("../a.hrpy")), PathBuf::from("../a.hrpy"));
+    }
+
+    #[test]
+    fn test_load_in_dependency_order() {
+        let graph = load(&[
+            ("main.hrpy", "import \"lib/geo.hrpy\"; import \"util.hrpy\" as u;"),
+            ("lib/geo.hrpy", "import \"../util.hrpy\";"),
+            ("util.hrpy", ""),
+        ])
+        .unwrap();
+
+        let names = graph.modules.iter().map(|m| m.name.as_str()).collect::<Vec<_>>();
+        assert_eq!(names, ["util", "geo", ""]);
+        assert_eq!(graph.sources().len(), 3);
+        assert_eq!(graph.modules[2].imports, [("geo".to_owned(), 1), ("u".to_owned(), 0)]);
+    }
+
+    #[test]
+    fn test_import_cycle() {
+        let errors = load(&[
+            ("main.hrpy", "import \"a.hrpy\";"),
+            ("a.hrpy", "import \"b.hrpy\";"),
+            ("b.hrpy", "import \"a.hrpy\";"),
+        ])
+        .unwrap_err();
+
+        let SemanticError::ImportCycle(cycle) = semantic_error(&errors) else {
+            panic!("expected an import cycle");
+        };
+        assert_eq!(cycle, &["a.hrpy", "b.hrpy", "a.hrpy"]);
+        assert_eq!(errors[0].span().start.file, 2);
+    }
+
+    #[test]
+    fn test_missing_import() {
+        let errors = load(&[("main.hrpy", "import \"nope.hrpy\";")]).unwrap_err();
+        assert!(matches!(semantic_error(&errors), SemanticError::ImportFailed(..)));
+    }
+
+    #[test]
+    fn test_private_item() {
+        let graph = load(&[
+            ("main.hrpy", "import \"geo.hrpy\";\nfn main() -> int { return geo::secret(); }"),
+            ("geo.hrpy", "fn secret() -> int { return 1; }"),
+        ])
+        .unwrap();
+
+        // it's left for the analyzer, so parsing still succeeds
+        let program = graph.parse(&Instantiations::new()).unwrap();
+        let errors = program.import_errors().iter().map(InaccessibleItem::error).collect::<Vec<_>>();
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(semantic_error(&errors), SemanticError::PrivateItem(..)));
+        assert_eq!(errors[0].span().start.file, 0);
+    }
+
+    #[test]
+    fn test_qualified_items() {
+        let graph = load(&[
+            ("main.hrpy", "import \"geo.hrpy\" as g;\nfn main() -> g::Point { return g::origin(); }"),
+            (
+                "geo.hrpy",
+                "pub fn origin() -> Point { return Point { x: 0 }; }\npub struct Point { x: int }",
+            ),
+        ])
+        .unwrap();
+
+        let program = graph.parse(&Instantiations::new()).unwrap();
+        let names = program.parts.iter().map(|p| p.name().value().as_str()).collect::<Vec<_>>();
+        assert_eq!(names, ["geo::origin", "geo::Point", "main"]);
+        assert_eq!(program.modules, ["", "geo"]);
+    }
+}
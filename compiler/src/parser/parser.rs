@@ -3,15 +3,20 @@ use crate::{
     err::HarpyError,
     lexer::{
         span::Span,
-        tokens::{Token, TokenType, Tokenize},
+        tokens::{Ident, Token, TokenType, Tokenize},
         Lexer,
     },
-    tt,
+    t, tt,
 };
 
+use std::rc::Rc;
+
 use super::{
+    generics::{Instantiations, TypeArgs},
+    module::{Import, InaccessibleItem, ModuleContext},
     node::{Node, NodeId},
     program::Program,
+    types::Type,
     Parse,
 };
 
@@ -19,7 +24,14 @@ use super::{
 pub struct Parser<'parser> {
     lexer: Lexer<'parser>,
     errors: Vec<HarpyError>,
+    // uses of private or unknown items of imported modules, which the analyzer
+    // reports with its own errors
+    import_errors: Vec<InaccessibleItem>,
     next_id: NodeId,
+    instances: Instantiations,
+    // set while parsing a specialized copy of a generic function
+    type_args: TypeArgs,
+    module: Rc<ModuleContext>,
 }
 
 impl<'parser> Parser<'parser> {
@@ -27,10 +39,50 @@ impl<'parser> Parser<'parser> {
         Self {
             lexer,
             errors: vec![],
+            import_errors: vec![],
             next_id: NodeId(0),
+            instances: Instantiations::new(),
+            type_args: TypeArgs::new(),
+            module: Rc::default(),
         }
     }
 
+    // node ids start at `first_id`, after those of the modules parsed before
+    pub(in crate::parser) fn with_module(mut self, module: ModuleContext, first_id: NodeId) -> Self {
+        self.module = Rc::new(module);
+        self.next_id = first_id;
+        self
+    }
+
+    pub(in crate::parser) fn module(&self) -> &ModuleContext {
+        &self.module
+    }
+
+    pub fn with_instances(mut self, instances: Instantiations) -> Self {
+        self.instances = instances;
+        self
+    }
+
+    pub(in crate::parser) fn instances_of(&self, name: &str) -> Vec<Vec<Type>> {
+        self.instances.get(name).to_vec()
+    }
+
+    pub(in crate::parser) fn type_arg(&self, name: &str) -> Option<&Type> {
+        self.type_args.get(name)
+    }
+
+    pub(in crate::parser) fn set_type_args(&mut self, args: TypeArgs) {
+        self.type_args = args;
+    }
+
+    pub(in crate::parser) fn checkpoint(&self) -> Lexer<'parser> {
+        self.lexer.clone()
+    }
+
+    pub(in crate::parser) fn rewind(&mut self, checkpoint: Lexer<'parser>) {
+        self.lexer = checkpoint;
+    }
+
     pub(in crate::parser) fn next_id(&mut self) -> NodeId {
         let i = self.next_id;
         self.next_id.0 += 1;
@@ -88,6 +140,45 @@ impl<'parser> Parser<'parser> {
         Ok((value, Span::new(start, end)))
     }
 
+    // the name of an item, `alias::item` naming one of an imported module; in a
+    // module other than the root, `is_type` names of its structs and enums get
+    // qualified with the module name
+    pub(in crate::parser) fn parse_path(&mut self, is_type: bool) -> Result<Node<Ident>> {
+        let first = self.parse_node::<Ident>()?;
+
+        if self.module.is_import(first.value()) && *self.peek()? == tt!(::) {
+            self.consume::<t!(::)>()?;
+            let item = self.parse_node::<Ident>()?;
+            let span = Span::new(first.span().start, item.span().end);
+            let (name, error) = self.module.resolve_import(first.value(), &item).unwrap();
+            // noted once, though a generic function's body is parsed again for each instance
+            if let Some(error) = error.filter(|e| !self.import_errors.iter().any(|o| o.span() == e.span())) {
+                self.import_errors.push(error);
+            }
+            return Ok(Node::new(first.id(), span, Ident::new(name, span)));
+        }
+
+        if is_type {
+            if let Some(name) = self.module.qualify_type(first.value()) {
+                return Ok(Node::new(first.id(), first.span(), Ident::new(name, first.span())));
+            }
+        }
+
+        Ok(first)
+    }
+
+    pub(in crate::parser) fn parse_imports(&mut self) -> Result<Vec<Node<Import>>> {
+        let mut imports = vec![];
+        while let tt!(import) = self.peek()? {
+            imports.push(self.parse_node()?);
+        }
+        Ok(imports)
+    }
+
+    pub(in crate::parser) fn take_import_errors(&mut self) -> Vec<InaccessibleItem> {
+        std::mem::take(&mut self.import_errors)
+    }
+
     pub(in crate::parser) fn try_parse<T: Parse>(&mut self) -> Option<T> {
         let old = self.lexer.clone();
         if let Ok(parsed) = T::parse(self) {
@@ -102,7 +193,11 @@ impl<'parser> Parser<'parser> {
         Self {
             lexer: self.lexer.clone(),
             errors: vec![],
+            import_errors: vec![],
             next_id: self.next_id,
+            instances: self.instances.clone(),
+            type_args: self.type_args.clone(),
+            module: self.module.clone(),
         }
     }
 
@@ -138,11 +233,18 @@ impl<'parser> Parser<'parser> {
         Ok(())
     }
 
-    pub fn build_ast(mut self) -> std::result::Result<Program, Vec<HarpyError>> {
+    pub fn build_ast(self) -> std::result::Result<Program, Vec<HarpyError>> {
+        self.build_module().map(|(program, _)| program)
+    }
+
+    // also returns the next unused node id
+    pub(in crate::parser) fn build_module(
+        mut self,
+    ) -> std::result::Result<(Program, NodeId), Vec<HarpyError>> {
         match self.parse::<Program>() {
             Ok(p) => {
                 if self.errors.is_empty() {
-                    return Ok(p);
+                    return Ok((p, self.next_id));
                 }
 
                 Err(self.errors)
@@ -1,72 +1,218 @@
-use super::{func_decl::FuncDelc, node::Node, statements::{GlobalStmt}, Parse};
+use std::collections::HashMap;
+
+use super::{enum_decl::EnumDecl, func_decl::FuncDelc, impl_decl::ImplDecl, module::InaccessibleItem, node::Node, statements::{ConstStmt, GlobalStmt}, struct_decl::StructDecl, trait_decl::TraitDecl, type_decl::TypeDecl, Parse};
 use crate::{
     generator::compile_trait::Generate,
+    lexer::tokens::Ident,
     semantic_analyzer::{analyze_trait::Analyze, return_status::ReturnStatus},
-    tt,
+    t, tt,
 };
 
 #[derive(Debug, Clone)]
 pub enum SubProgram {
     Global(Node<GlobalStmt>),
+    Const(Node<ConstStmt>),
     FuncDecl(Node<FuncDelc>),
+    StructDecl(Node<StructDecl>),
+    EnumDecl(Node<EnumDecl>),
+    ImplDecl(Node<ImplDecl>),
+    TraitDecl(Node<TraitDecl>),
+    TypeDecl(Node<TypeDecl>),
 }
 
 impl Parse for SubProgram {
     fn parse(parser: &mut super::parser::Parser) -> crate::aliases::Result<Self> {
         let s = match parser.peek()? {
             tt!(global) => Self::Global(parser.parse_node::<GlobalStmt>()?),
+            tt!(const) => Self::Const(parser.parse_node::<ConstStmt>()?),
             tt!(fn) => Self::FuncDecl(parser.parse_node::<FuncDelc>()?),
-            _ => return parser.unexpected("global statement or a function declaration"),
+            tt!(struct) => Self::StructDecl(parser.parse_node::<StructDecl>()?),
+            tt!(enum) => Self::EnumDecl(parser.parse_node::<EnumDecl>()?),
+            tt!(trait) => Self::TraitDecl(parser.parse_node::<TraitDecl>()?),
+            tt!(type) | tt!(newtype) => Self::TypeDecl(parser.parse_node::<TypeDecl>()?),
+            _ => {
+                return parser.unexpected("global statement, constant, function, struct, enum, trait or type declaration")
+            }
         };
 
         Ok(s)
     }
 }
 
+impl SubProgram {
+    // an impl block goes by the type it is for
+    pub fn name(&self) -> &Node<Ident> {
+        match self {
+            Self::Global(g) => &g.var,
+            Self::Const(c) => &c.var,
+            Self::FuncDecl(f) => f.name(),
+            Self::StructDecl(s) => &s.name,
+            Self::EnumDecl(e) => &e.name,
+            Self::ImplDecl(i) => &i.ty,
+            Self::TraitDecl(t) => &t.name,
+            Self::TypeDecl(t) => &t.name,
+        }
+    }
+
+    fn name_mut(&mut self) -> &mut Node<Ident> {
+        match self {
+            Self::Global(g) => &mut g.value.var,
+            Self::Const(c) => &mut c.value.var,
+            Self::FuncDecl(f) => f.value.name_mut(),
+            Self::StructDecl(s) => &mut s.value.name,
+            Self::EnumDecl(e) => &mut e.value.name,
+            Self::ImplDecl(i) => &mut i.value.ty,
+            Self::TraitDecl(t) => &mut t.value.name,
+            Self::TypeDecl(t) => &mut t.value.name,
+        }
+    }
+}
+
 impl Generate for SubProgram {
     fn generate(&self, generator: &mut crate::generator::generator::Generator) {
         match self {
             Self::Global(g) => g.generate(generator),
+            Self::Const(c) => c.generate(generator),
             Self::FuncDecl(f) => f.generate(generator),
+            Self::StructDecl(s) => s.generate(generator),
+            Self::EnumDecl(e) => e.generate(generator),
+            Self::ImplDecl(i) => i.generate(generator),
+            Self::TraitDecl(t) => t.generate(generator),
+            Self::TypeDecl(t) => t.generate(generator),
         }
     }
 }
 
 #[derive(Debug, Clone)]
 pub struct Program {
-    parts: Vec<SubProgram>,
+    pub(in crate::parser) parts: Vec<SubProgram>,
+    // unqualified name -> whether the item is `pub`
+    pub(in crate::parser) items: HashMap<String, bool>,
+    // file -> name of the module parsed from it
+    pub(in crate::parser) modules: Vec<String>,
+    // private or unknown items used from imported modules, left for the analyzer to report
+    pub(in crate::parser) import_errors: Vec<InaccessibleItem>,
+}
+
+impl Program {
+    // without the module name
+    pub(in crate::parser) fn type_names(&self) -> impl Iterator<Item = &str> {
+        self.parts
+            .iter()
+            .filter_map(|part| match part {
+                SubProgram::StructDecl(s) => Some(s.name.value().as_str()),
+                SubProgram::EnumDecl(e) => Some(e.name.value().as_str()),
+                SubProgram::TraitDecl(t) => Some(t.name.value().as_str()),
+                SubProgram::TypeDecl(t) => Some(t.name.value().as_str()),
+                _ => None,
+            })
+            .map(|name| name.rsplit_once("::").map_or(name, |(_, name)| name))
+    }
+
+    pub fn module_names(&self) -> &[String] {
+        &self.modules
+    }
+
+    pub fn import_errors(&self) -> &[InaccessibleItem] {
+        &self.import_errors
+    }
 }
 
 impl Parse for Program {
     fn parse(parser: &mut super::parser::Parser) -> crate::aliases::Result<Self> {
+        // the loader has already resolved them
+        parser.parse_imports()?;
+
         let mut parts = vec![];
+        let mut items = HashMap::new();
         loop {
             if let tt!(eof) = parser.peek()? {
                 break;
             }
 
-            parts.push(parser.parse::<SubProgram>()?);
+            // the methods are named after their type, which is qualified already
+            if let tt!(impl) = parser.peek()? {
+                parts.push(SubProgram::ImplDecl(parser.parse_node()?));
+                continue;
+            }
+
+            let public = matches!(parser.peek()?, tt!(pub));
+            if public {
+                parser.consume::<t!(pub)>()?;
+            }
+
+            let checkpoint = parser.checkpoint();
+            let mut part = parser.parse::<SubProgram>()?;
+            let name = part.name_mut();
+            items.insert(name.value().clone(), public);
+            name.value = Ident::new(parser.module().qualify(name.value()), name.span());
+
+            let instances = match &part {
+                SubProgram::FuncDecl(decl) if decl.is_generic() => {
+                    FuncDelc::parse_instances(parser, checkpoint, decl)?
+                }
+                _ => vec![],
+            };
+
+            parts.push(part);
+            parts.extend(instances.into_iter().map(SubProgram::FuncDecl));
         }
 
-        Ok(Self { parts })
+        Ok(Self {
+            parts,
+            items,
+            modules: vec![],
+            import_errors: parser.take_import_errors(),
+        })
     }
 }
 
 impl Analyze for Program {
     fn build(&self, builder: &mut crate::semantic_analyzer::scope_builder::ScopeBuilder) {
+        // struct, enum, trait and type names have to be known before any type referring to them is registered
+        for sub in &self.parts {
+            match sub {
+                SubProgram::StructDecl(decl) => decl.declare(builder),
+                SubProgram::EnumDecl(decl) => decl.declare(builder),
+                SubProgram::TraitDecl(decl) => decl.declare(builder),
+                SubProgram::TypeDecl(decl) => decl.declare(builder),
+                _ => (),
+            }
+        }
+
         for sub in &self.parts {
             match sub {
                 SubProgram::Global(global) => global.build(builder),
+                SubProgram::Const(constant) => constant.build(builder),
                 SubProgram::FuncDecl(decl) => decl.build(builder),
+                SubProgram::StructDecl(decl) => decl.build(builder),
+                SubProgram::EnumDecl(decl) => decl.build(builder),
+                SubProgram::ImplDecl(decl) => decl.build(builder),
+                SubProgram::TraitDecl(decl) => decl.build(builder),
+                SubProgram::TypeDecl(decl) => decl.build(builder),
             }
         }
     }
 
     fn analyze_semantics(&self, analyzer: &mut crate::semantic_analyzer::analyzer::Analyzer) -> ReturnStatus {
+        // constants are evaluated first so everything else can use their values,
+        // but only the ones declared before them
+        for sub in &self.parts {
+            if let SubProgram::Const(constant) = sub {
+                constant.analyze_semantics(analyzer);
+            }
+        }
+
         for sub in &self.parts {
             match sub {
                 SubProgram::Global(global) => { global.analyze_semantics(analyzer); },
+                SubProgram::Const(_) => (),
                 SubProgram::FuncDecl(decl) => { decl.analyze_semantics(analyzer); },
+                SubProgram::StructDecl(decl) => { decl.analyze_semantics(analyzer); },
+                SubProgram::EnumDecl(decl) => { decl.analyze_semantics(analyzer); },
+                SubProgram::ImplDecl(decl) => { decl.analyze_semantics(analyzer); },
+                SubProgram::TraitDecl(decl) => { decl.analyze_semantics(analyzer); },
+                SubProgram::TypeDecl(decl) => { decl.analyze_semantics(analyzer); },
             }
         }
         
@@ -86,8 +232,10 @@ impl Generate for Program {
         generator.push_instruction(crate::generator::instruction::Instruction::HALT);
         
         for sub in &self.parts {
-            if let SubProgram::FuncDecl(func) = sub {
-                func.generate(generator);
+            match sub {
+                SubProgram::FuncDecl(func) => func.generate(generator),
+                SubProgram::ImplDecl(decl) => decl.generate(generator),
+                _ => (),
             }
         }
     }
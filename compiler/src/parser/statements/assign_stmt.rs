@@ -1,3 +1,7 @@
+use crate::err::HarpyError;
+use crate::lexer::err::LexerError;
+use crate::lexer::span::Span;
+use crate::parser::expr::infix::{InfixOp, InfixOpKind};
 use crate::parser::parse_trait::Parse;
 use crate::parser::parser::Parser;
 use crate::tt;
@@ -10,6 +14,11 @@ pub enum AssignOp {
     Mult,
     Div,
     Mod,
+    BitAnd,
+    BitOr,
+    BitXor,
+    Shl,
+    Shr,
 }
 
 impl Parse for AssignOp {
@@ -21,6 +30,10 @@ impl Parse for AssignOp {
             tt!(*=) => Self::Mult,
             tt!(/=) => Self::Div,
             tt!(%=) => Self::Mod,
+            tt!(&=) => Self::BitAnd,
+            tt!(|=) => Self::BitOr,
+            tt!(^=) => Self::BitXor,
+            tt!(<) | tt!(>) => return Self::parse_shift(parser),
             _ => {
                 return parser.unexpected("assignment operator");
             }
@@ -32,6 +45,44 @@ impl Parse for AssignOp {
     }
 }
 
+impl AssignOp {
+    // the operator a compound assignment applies before storing
+    pub fn infix(&self, span: Span) -> Option<InfixOp> {
+        let op = match self {
+            Self::Normal => return None,
+            Self::Add => InfixOpKind::Plus,
+            Self::Sub => InfixOpKind::Minus,
+            Self::Mult => InfixOpKind::Mult,
+            Self::Div => InfixOpKind::Div,
+            Self::Mod => InfixOpKind::Mod,
+            Self::BitAnd => InfixOpKind::BitAnd,
+            Self::BitOr => InfixOpKind::BitOr,
+            Self::BitXor => InfixOpKind::BitXor,
+            Self::Shl => InfixOpKind::Shl,
+            Self::Shr => InfixOpKind::Shr,
+        };
+
+        Some(InfixOp::new(op, span))
+    }
+
+    // `<<=` and `>>=` are lexed as an angle bracket touching a `<=` or `>=`,
+    // see `InfixOp::parse_angle`
+    fn parse_shift(parser: &mut Parser) -> crate::aliases::Result<Self> {
+        let first = parser.discard_next()?;
+        let second = parser.discard_next()?;
+        let touching = second.span().start == first.span().end;
+
+        match (first.kind(), second.kind()) {
+            (tt!(<), tt!(<=)) if touching => Ok(Self::Shl),
+            (tt!(>), tt!(>=)) if touching => Ok(Self::Shr),
+            _ => HarpyError::lexer(
+                LexerError::UnexpectedToken("assignment operator", second.clone()),
+                second.span(),
+            ),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::AssignOp;
@@ -80,4 +131,22 @@ mod tests {
         let stmt = parse_stmt("var %= 5;");
         assert!(matches!(stmt, Stmt::AssignStmt(_, AssignOp::Mod, _)));
     }
+
+    #[test]
+    fn test_assign_op_bitwise() {
+        let stmt = parse_stmt("var &= 5;");
+        assert!(matches!(stmt, Stmt::AssignStmt(_, AssignOp::BitAnd, _)));
+        let stmt = parse_stmt("var |= 5;");
+        assert!(matches!(stmt, Stmt::AssignStmt(_, AssignOp::BitOr, _)));
+        let stmt = parse_stmt("var ^= 5;");
+        assert!(matches!(stmt, Stmt::AssignStmt(_, AssignOp::BitXor, _)));
+    }
+
+    #[test]
+    fn test_assign_op_shift() {
+        let stmt = parse_stmt("var <<= 2;");
+        assert!(matches!(stmt, Stmt::AssignStmt(_, AssignOp::Shl, _)));
+        let stmt = parse_stmt("var >>= 2;");
+        assert!(matches!(stmt, Stmt::AssignStmt(_, AssignOp::Shr, _)));
+    }
 }
@@ -1,7 +1,13 @@
 use crate::generator::compile_trait::Generate;
+use crate::generator::generator::Generator;
+use crate::lexer::span::Span;
+use crate::parser::expr::Expr;
+use crate::parser::node::Node;
 use crate::parser::parser::Parser;
 use crate::parser::{parse_trait::Parse, statements::Stmt};
 use crate::semantic_analyzer::analyze_trait::Analyze;
+use crate::semantic_analyzer::analyzer::Analyzer;
+use crate::semantic_analyzer::branch_unifier::BranchUnifier;
 use crate::semantic_analyzer::return_status::ReturnStatus;
 use crate::semantic_analyzer::scope::ScopeKind;
 use crate::{t, tt};
@@ -9,11 +15,15 @@ use crate::{t, tt};
 #[derive(Debug, Clone)]
 pub struct BlockStmt {
     stmts: Vec<Stmt>,
+    // an expression without a `;` before the closing brace, the value of the
+    // block when it is used as one
+    tail: Option<Box<Node<Expr>>>,
 }
 
 impl Parse for BlockStmt {
     fn parse(parser: &mut Parser) -> crate::aliases::Result<Self> {
         let mut stmts = vec![];
+        let mut tail = None;
         parser.consume::<t!("{")>()?;
 
         loop {
@@ -21,14 +31,80 @@ impl Parse for BlockStmt {
                 break;
             }
 
-            match parser.parse::<Stmt>() {
-                Ok(s) => stmts.push(s),
+            match Stmt::parse_in_block(parser, &mut tail) {
+                Ok(Some(s)) => stmts.push(s),
+                Ok(None) => (),
                 Err(e) => parser.report_error(e, &[])?,
             }
         }
 
         parser.consume::<t!("}")>()?;
-        Ok(Self { stmts })
+        Ok(Self { stmts, tail })
+    }
+}
+
+impl BlockStmt {
+    // without a tail, a block can still end in an if, switch or block that
+    // has a value
+    pub fn has_value(&self) -> bool {
+        self.tail.is_some() || self.stmts.last().is_some_and(Stmt::has_value)
+    }
+
+    fn analyze_stmts(stmts: &[Stmt], analyzer: &mut Analyzer) -> ReturnStatus {
+        let mut status = ReturnStatus::Never;
+        for stmt in stmts {
+            let stmt_status = stmt.analyze_semantics(analyzer);
+            status = status.then(stmt_status);
+        }
+        status
+    }
+
+    pub fn analyze_value(
+        &self,
+        analyzer: &mut Analyzer,
+        unifier: &mut BranchUnifier,
+        span: Span,
+    ) -> ReturnStatus {
+        analyzer.enter_scope();
+
+        let status = match (&self.tail, self.stmts.split_last()) {
+            (Some(tail), _) => {
+                let status = Self::analyze_stmts(&self.stmts, analyzer);
+                if let Some(expected) = unifier.expected() {
+                    analyzer.expect(expected);
+                }
+                let value = analyzer.resolve_expr(tail);
+                unifier.add(true, status, value, tail.span(), analyzer);
+                status
+            }
+            (None, Some((last, rest))) if last.has_value() => {
+                let status = Self::analyze_stmts(rest, analyzer);
+                status.then(last.analyze_value(analyzer, unifier))
+            }
+            _ => {
+                let status = Self::analyze_stmts(&self.stmts, analyzer);
+                unifier.add(false, status, None, span, analyzer);
+                status
+            }
+        };
+
+        analyzer.exit_scope();
+        status
+    }
+
+    // leaves the value of the block on the operand stack
+    pub fn generate_value(&self, generator: &mut Generator) {
+        match (&self.tail, self.stmts.split_last()) {
+            (Some(tail), _) => {
+                self.stmts.iter().for_each(|stmt| stmt.generate(generator));
+                generator.gen_expr(tail);
+            }
+            (None, Some((last, rest))) if last.has_value() => {
+                rest.iter().for_each(|stmt| stmt.generate(generator));
+                last.generate_value(generator);
+            }
+            _ => self.generate(generator),
+        }
     }
 }
 
@@ -38,28 +114,33 @@ impl Analyze for BlockStmt {
         for stmt in &self.stmts {
             stmt.build(builder)
         }
+        if let Some(tail) = &self.tail {
+            tail.build(builder);
+        }
         builder.pop_scope();
     }
 
-    fn analyze_semantics(&self, analyzer: &mut crate::semantic_analyzer::analyzer::Analyzer) -> ReturnStatus {
+    fn analyze_semantics(&self, analyzer: &mut Analyzer) -> ReturnStatus {
         analyzer.enter_scope();
-        
-        let mut status = ReturnStatus::Never;
-        for stmt in &self.stmts {
-            let stmt_status = stmt.analyze_semantics(analyzer);
-            status = status.then(stmt_status);
+
+        let status = Self::analyze_stmts(&self.stmts, analyzer);
+        if let Some(tail) = &self.tail {
+            analyzer.resolve_expr(tail);
         }
-        
+
         analyzer.exit_scope();
         status
     }
 }
 
 impl Generate for BlockStmt {
-    fn generate(&self, generator: &mut crate::generator::generator::Generator) {
+    fn generate(&self, generator: &mut Generator) {
         for stmt in &self.stmts {
             stmt.generate(generator);
         }
+        if let Some(tail) = &self.tail {
+            Stmt::generate_discarded(tail, generator);
+        }
     }
 }
 
@@ -98,4 +179,16 @@ mod tests {
         let block = parse_block("{ { let x = 1; let y = 2; } }");
         assert_eq!(block.stmts.len(), 1);
     }
+
+    #[test]
+    fn test_block_stmt_tail() {
+        let block = parse_block("{ let x = 5; x + 1 }");
+        assert_eq!(block.stmts.len(), 1);
+        assert!(block.tail.is_some());
+
+        let block = parse_block("{ let x = 5; x + 1; }");
+        assert_eq!(block.stmts.len(), 2);
+        assert!(block.tail.is_none());
+        assert!(!block.has_value());
+    }
 }
@@ -0,0 +1,81 @@
+use crate::{
+    generator::compile_trait::Generate,
+    get_symbol_mut,
+    lexer::tokens::Ident,
+    parser::{expr::Expr, node::Node, parse_trait::Parse, parser::Parser, types::TypeSpanned},
+    semantic_analyzer::{
+        analyze_trait::Analyze, const_eval::ConstEvaluator, err::SemanticError,
+        return_status::ReturnStatus, symbol_info::SymbolInfoKind,
+    },
+    t,
+};
+
+#[derive(Debug, Clone)]
+pub struct ConstStmt {
+    pub var: Node<Ident>,
+    pub ttype: TypeSpanned,
+    pub rhs: Node<Expr>,
+}
+
+impl Parse for ConstStmt {
+    fn parse(parser: &mut Parser) -> crate::aliases::Result<Self> {
+        parser.consume::<t!(const)>()?;
+        let var = parser.parse_node::<Ident>()?;
+
+        parser.consume::<t!(:)>()?;
+        let ttype = parser.parse()?;
+
+        parser.consume::<t!(=)>()?;
+        let rhs = parser.parse_node::<Expr>()?;
+
+        parser.consume::<t!(;)>()?;
+
+        Ok(Self { var, ttype, rhs })
+    }
+}
+
+impl Analyze for ConstStmt {
+    fn build(&self, builder: &mut crate::semantic_analyzer::scope_builder::ScopeBuilder) {
+        self.rhs.build(builder);
+        let type_info = builder.register_type(&self.ttype);
+        builder.define_const(&self.var, type_info);
+    }
+
+    // the value is computed here, so uses of the constant see it no matter
+    // where they are
+    fn analyze_semantics(
+        &self,
+        analyzer: &mut crate::semantic_analyzer::analyzer::Analyzer,
+    ) -> ReturnStatus {
+        analyzer.expect(&self.ttype);
+        let Some(expr_type) = analyzer.resolve_expr(&self.rhs) else {
+            return ReturnStatus::Never;
+        };
+
+        let value = match ConstEvaluator::eval(&self.rhs, analyzer) {
+            Ok(value) => Some(value),
+            Err(e) => {
+                analyzer.report_error(e);
+                None
+            }
+        };
+
+        get_symbol_mut!((analyzer, self.var) info {
+            if !info.ty.assign_compatible(&expr_type.ttype) {
+                analyzer.report_semantic_error(
+                    SemanticError::LetTypeMismatch(info.ty.written().clone(), expr_type.clone()),
+                    self.rhs.span(),
+                );
+            } else if let SymbolInfoKind::Constant(ref mut c) = &mut info.kind {
+                c.value = value;
+            }
+        });
+
+        ReturnStatus::Never
+    }
+}
+
+// every use of the constant loads its value from the const pool
+impl Generate for ConstStmt {
+    fn generate(&self, _generator: &mut crate::generator::generator::Generator) {}
+}
@@ -1,86 +1,206 @@
 use crate::generator::compile_trait::Generate;
-use crate::generator::instruction::Instruction;
-use crate::lexer::tokens::Ident;
-use crate::parser::node::Node;
+use crate::generator::generator::Generator;
+use crate::generator::instruction::{Instruction, Label, LocalAddress};
+use crate::lexer::tokens::{Ident, Lit, TokenType};
+use crate::parser::node::{Node, NodeId};
 use crate::parser::parser::Parser;
-use crate::parser::types::Type;
+use crate::parser::types::{Type, TypeSpanned};
 use crate::parser::{expr::Expr, parse_trait::Parse};
 use crate::semantic_analyzer::analyze_trait::Analyze;
+use crate::semantic_analyzer::analyzer::Analyzer;
+use crate::semantic_analyzer::const_eval::ConstEvaluator;
 use crate::semantic_analyzer::return_status::ReturnStatus;
+use crate::semantic_analyzer::scope_builder::ScopeBuilder;
 use crate::semantic_analyzer::err::SemanticError;
-use crate::semantic_analyzer::scope::ScopeKind;
 use crate::semantic_analyzer::symbol_info::SymbolInfoKind;
-use crate::{get_symbol_mut, t};
+use crate::{get_symbol_mut, t, tt};
 
+use super::jump_stmt::parse_loop_label;
 use super::BlockStmt;
 
+// `a => b` and `a..b` leave out the end, `a..=b` includes it; a negative step counts down
 #[derive(Debug, Clone)]
 pub struct IterExpr {
     from: Node<Expr>,
     to: Node<Expr>,
+    inclusive: bool,
+    step: Option<Node<Expr>>,
+    nodes: RangeNodes,
 }
 
-impl Parse for IterExpr {
-    fn parse(parser: &mut Parser) -> crate::aliases::Result<Self> {
-        let from = parser.parse_node::<Expr>()?;
-        parser.consume::<t!(=>)>()?;
+impl IterExpr {
+    fn parse_from(
+        from: Node<Expr>,
+        span: crate::lexer::span::Span,
+        parser: &mut Parser,
+    ) -> crate::aliases::Result<Self> {
+        // the caller has already seen `=>` or `..`
+        let inclusive = match parser.peek()? {
+            tt!(=>) => {
+                parser.consume::<t!(=>)>()?;
+                false
+            }
+            _ => {
+                parser.consume::<t!(..)>()?;
+                let inclusive = *parser.peek()? == tt!(=);
+                if inclusive {
+                    parser.consume::<t!(=)>()?;
+                }
+                inclusive
+            }
+        };
         let to = parser.parse_node::<Expr>()?;
 
-        Ok(Self { from, to })
+        // not a keyword, so it can still name things everywhere else
+        let mut step = None;
+        if let TokenType::Ident(ident) = parser.peek()? {
+            if ident == "step" {
+                parser.consume::<t!(ident)>()?;
+                step = Some(parser.parse_node::<Expr>()?);
+            }
+        }
+
+        Ok(Self {
+            from,
+            to,
+            inclusive,
+            step,
+            nodes: RangeNodes::new(parser, span),
+        })
+    }
+}
+
+// the loop's hidden state: locals holding the end and a step only known at
+// runtime, and the constants the counter is compared against
+#[derive(Debug, Clone)]
+struct RangeNodes {
+    end: Node<Ident>,
+    step: Node<Ident>,
+    step_value: NodeId,
+    zero: NodeId,
+    // the counter can't be stepped past these without overflowing
+    max: NodeId,
+    min: NodeId,
+}
+
+impl RangeNodes {
+    fn new(parser: &mut Parser, span: crate::lexer::span::Span) -> Self {
+        let mut local = |name: &str| {
+            let id = parser.next_id();
+            Node::new(id, span, Ident::new(format!("{{{name}#{}}}", id.0), span))
+        };
+        let end = local("end");
+        let step = local("step");
+
+        Self {
+            end,
+            step,
+            step_value: parser.next_id(),
+            zero: parser.next_id(),
+            max: parser.next_id(),
+            min: parser.next_id(),
+        }
+    }
+}
+
+// walking a string keeps it and the byte offset of its next char in locals
+#[derive(Debug, Clone)]
+struct CharsExpr {
+    string: Node<Expr>,
+    local: Node<Ident>,
+    offset: Node<Ident>,
+    zero: NodeId,
+}
+
+impl CharsExpr {
+    fn new(string: Node<Expr>, span: crate::lexer::span::Span, parser: &mut Parser) -> Self {
+        let mut local = |name: &str| {
+            let id = parser.next_id();
+            Node::new(id, span, Ident::new(format!("{{{name}#{}}}", id.0), span))
+        };
+        let local_string = local("string");
+        let offset = local("offset");
+
+        Self {
+            string,
+            local: local_string,
+            offset,
+            zero: parser.next_id(),
+        }
     }
 }
 
+// a range of ints, or a string whose chars are gone over one by one
+#[derive(Debug, Clone)]
+enum ForIter {
+    Range(Box<IterExpr>),
+    Chars(Box<CharsExpr>),
+}
+
 #[derive(Debug, Clone)]
 pub struct ForStmt {
+    label: Option<Node<Ident>>,
     var: Node<Ident>,
-    iter: IterExpr,
+    iter: ForIter,
     block: BlockStmt,
 }
 
 impl Parse for ForStmt {
     fn parse(parser: &mut Parser) -> crate::aliases::Result<Self> {
+        let label = parse_loop_label(parser)?;
         parser.consume::<t!(for)>()?;
-        let var = parser.parse_node()?;
+        let var = parser.parse_node::<Ident>()?;
         parser.consume::<t!(in)>()?;
-        let iter = parser.parse()?;
+        let from = parser.parse_node::<Expr>()?;
+        let iter = match parser.peek()? {
+            tt!(=>) | tt!(..) => ForIter::Range(Box::new(IterExpr::parse_from(from, var.span(), parser)?)),
+            _ => ForIter::Chars(Box::new(CharsExpr::new(from, var.span(), parser))),
+        };
         let block = parser.parse()?;
-        Ok(Self { var, iter, block })
+        Ok(Self {
+            label,
+            var,
+            iter,
+            block,
+        })
     }
 }
 
-impl Analyze for ForStmt {
-    fn build(&self, builder: &mut crate::semantic_analyzer::scope_builder::ScopeBuilder) {
-        builder.push_scope(ScopeKind::Loop);
+impl IterExpr {
+    fn build(&self, var: &Node<Ident>, builder: &mut ScopeBuilder) {
+        self.from.build(builder);
+        self.to.build(builder);
+        if let Some(step) = &self.step {
+            step.build(builder);
+        }
 
-        let type_info =
-            builder.register_type(&crate::parser::types::TypeSpanned::dummy(Type::unknown()));
+        let int = builder.register_type(&TypeSpanned::dummy(Type::int()));
+        builder.define_var(&self.nodes.end, int.clone());
+        builder.define_var(&self.nodes.step, int);
 
-        builder.define_var(&self.var, type_info);
+        let type_info = builder.register_type(&TypeSpanned::dummy(Type::unknown()));
 
-        self.block.build(builder);
-        builder.pop_scope();
+        builder.define_var(var, type_info);
     }
 
-    fn analyze_semantics(&self, analyzer: &mut crate::semantic_analyzer::analyzer::Analyzer) -> ReturnStatus {
-        analyzer.enter_scope();
-
-        if let Some(from_type) = analyzer.resolve_expr(&self.iter.from) {
+    fn analyze_semantics(&self, var: &Node<Ident>, analyzer: &mut Analyzer) {
+        if let Some(from_type) = analyzer.resolve_expr(&self.from) {
             if !from_type.compatible(&Type::int()) {
                 analyzer.report_semantic_error(
                     SemanticError::ForTypeMismatch(from_type.clone(), Type::int()),
-                    self.iter.to.span(),
+                    self.to.span(),
                 );
             }
 
-            if let Some(to_type) = analyzer.resolve_expr(&self.iter.to) {
+            if let Some(to_type) = analyzer.resolve_expr(&self.to) {
                 if !to_type.compatible(&from_type.ttype) {
                     analyzer.report_semantic_error(
                         SemanticError::ForTypeMismatch(from_type.clone(), to_type.ttype.clone()),
-                        self.iter.to.span(),
+                        self.to.span(),
                     );
                 }
 
-                get_symbol_mut!((analyzer, self.var) var {
+                get_symbol_mut!((analyzer, var) var {
                     var.infer_type(&from_type);
                     if let SymbolInfoKind::Variable(ref mut v) = var.kind {
                         v.initialized = true;
@@ -89,6 +209,106 @@ impl Analyze for ForStmt {
             }
         }
 
+        if let Some(step) = &self.step {
+            if let Some(step_type) = analyzer.resolve_expr(step) {
+                if !step_type.compatible(&Type::int()) {
+                    analyzer.report_semantic_error(
+                        SemanticError::ForTypeMismatch(step_type.clone(), Type::int()),
+                        step.span(),
+                    );
+                }
+            }
+        }
+        self.register_step(var, analyzer);
+    }
+
+    // a step known at compile time decides the direction of the loop up front
+    fn register_step(&self, var: &Node<Ident>, analyzer: &mut Analyzer) {
+        let step = match &self.step {
+            None => Some(1),
+            Some(step) => match ConstEvaluator::eval(step, analyzer) {
+                Ok(Lit::LitInt(s)) => Some(s as i64),
+                _ => None,
+            },
+        };
+
+        let int = Type::int();
+        let nodes = &self.nodes;
+        let value = |v: i64| Lit::LitInt(v as u64);
+        match step {
+            Some(0) => {
+                let span = self.step.as_ref().map_or(var.span(), |s| s.span());
+                analyzer.report_semantic_error(SemanticError::ZeroStep, span);
+            }
+            Some(s) => {
+                analyzer.register_constant_value(nodes.step_value, &value(s), &int);
+                if s > 0 {
+                    analyzer.register_constant_value(nodes.max, &value(i64::MAX - s), &int);
+                } else {
+                    analyzer.register_constant_value(nodes.min, &value(i64::MIN - s), &int);
+                }
+            }
+            None => {
+                analyzer.register_constant_value(nodes.zero, &value(0), &int);
+                analyzer.register_constant_value(nodes.max, &value(i64::MAX), &int);
+                analyzer.register_constant_value(nodes.min, &value(i64::MIN), &int);
+            }
+        }
+    }
+}
+
+impl CharsExpr {
+    fn build(&self, var: &Node<Ident>, builder: &mut ScopeBuilder) {
+        self.string.build(builder);
+
+        let str = builder.register_type(&TypeSpanned::dummy(Type::str()));
+        let int = builder.register_type(&TypeSpanned::dummy(Type::int()));
+        builder.define_var(&self.local, str);
+        builder.define_var(&self.offset, int);
+
+        let char = builder.register_type(&TypeSpanned::dummy(Type::char()));
+        builder.define_var(var, char);
+    }
+
+    fn analyze_semantics(&self, var: &Node<Ident>, analyzer: &mut Analyzer) {
+        if let Some(string_type) = analyzer.resolve_expr(&self.string) {
+            if !string_type.compatible(&Type::str()) {
+                analyzer.report_semantic_error(
+                    SemanticError::ForTypeMismatch(string_type.clone(), Type::str()),
+                    self.string.span(),
+                );
+            }
+        }
+
+        get_symbol_mut!((analyzer, var) var {
+            if let SymbolInfoKind::Variable(ref mut v) = var.kind {
+                v.initialized = true;
+            }
+        });
+        analyzer.register_constant_value(self.zero, &Lit::LitInt(0), &Type::int());
+    }
+}
+
+impl Analyze for ForStmt {
+    fn build(&self, builder: &mut ScopeBuilder) {
+        builder.push_loop_scope(self.label.as_ref());
+        match &self.iter {
+            ForIter::Range(range) => range.build(&self.var, builder),
+            ForIter::Chars(chars) => chars.build(&self.var, builder),
+        }
+
+        self.block.build(builder);
+        builder.pop_scope();
+    }
+
+    fn analyze_semantics(&self, analyzer: &mut Analyzer) -> ReturnStatus {
+        analyzer.enter_scope();
+
+        match &self.iter {
+            ForIter::Range(range) => range.analyze_semantics(&self.var, analyzer),
+            ForIter::Chars(chars) => chars.analyze_semantics(&self.var, analyzer),
+        }
+
         let block_status = self.block.analyze_semantics(analyzer);
         analyzer.exit_scope();
         match block_status {
@@ -98,27 +318,182 @@ impl Analyze for ForStmt {
     }
 }
 
-impl Generate for ForStmt {
-    fn generate(&self, generator: &mut crate::generator::generator::Generator) {
-        generator.gen_expr(&self.iter.from);
-        let iter_var = generator.get_local_mapping(self.var.id());
-        generator.push_instruction(Instruction::STORE_LOCAL(iter_var));
+// where the step comes from decides whether the direction is known up front
+#[derive(Clone, Copy)]
+enum Step {
+    Const { up: bool },
+    Local(LocalAddress),
+}
+
+// the counter is only stepped after checking that doing so can't overflow, so
+// a range ending at the edge of `int` still stops
+impl IterExpr {
+    // leaves whether the counter is still in range on the stack
+    fn gen_condition(&self, counter: LocalAddress, up: bool, generator: &mut Generator) {
+        let end = generator.get_local_mapping(self.nodes.end.id());
+        generator.push_instruction(Instruction::LOAD_LOCAL(counter));
+        generator.push_instruction(Instruction::LOAD_LOCAL(end));
+        generator.push_instruction(match (up, self.inclusive) {
+            (true, false) => Instruction::LT,
+            (true, true) => Instruction::LTE,
+            (false, false) => Instruction::GT,
+            (false, true) => Instruction::GTE,
+        });
+    }
+
+    // leaves whether stepping the counter would overflow on the stack
+    fn gen_overflow_check(
+        &self,
+        counter: LocalAddress,
+        up: bool,
+        step: Option<LocalAddress>,
+        generator: &mut Generator,
+    ) {
+        let (bound, cmp) = match up {
+            true => (self.nodes.max, Instruction::GT),
+            false => (self.nodes.min, Instruction::LT),
+        };
+
+        generator.push_instruction(Instruction::LOAD_LOCAL(counter));
+        generator.push_instruction(Instruction::LOAD_CONST(generator.get_const_mapping(bound)));
+        // only constant steps have the bound worked out already
+        if let Some(step) = step {
+            generator.push_instruction(Instruction::LOAD_LOCAL(step));
+            generator.push_instruction(Instruction::SUB);
+        }
+        generator.push_instruction(cmp);
+    }
+
+    // runs `gen` for the direction the step held in `step` goes in; a step of 0
+    // jumps to `on_zero` if there is one, and counts as going down otherwise
+    fn gen_branches(
+        &self,
+        step: LocalAddress,
+        on_zero: Option<Label>,
+        generator: &mut Generator,
+        gen: impl Fn(&Self, bool, &mut Generator),
+    ) {
+        let zero = generator.get_const_mapping(self.nodes.zero);
+        let down = generator.create_label();
+        let done = generator.create_label();
+
+        generator.push_instruction(Instruction::LOAD_LOCAL(step));
+        generator.push_instruction(Instruction::LOAD_CONST(zero));
+        generator.push_instruction(Instruction::GT);
+        generator.push_instruction(Instruction::JMP_IF_FALSE(down));
+        gen(self, true, generator);
+        generator.push_instruction(Instruction::JMP(done));
+
+        generator.place_label(down);
+        if let Some(on_zero) = on_zero {
+            generator.push_instruction(Instruction::LOAD_LOCAL(step));
+            generator.push_instruction(Instruction::LOAD_CONST(zero));
+            generator.push_instruction(Instruction::EQ);
+            generator.push_instruction(Instruction::JMP_IF_TRUE(on_zero));
+        }
+        gen(self, false, generator);
+        generator.place_label(done);
+    }
+}
+
+impl ForStmt {
+    fn generate_range(&self, range: &IterExpr, generator: &mut Generator) {
+        let counter = generator.get_local_mapping(self.var.id());
+        let end = generator.get_local_mapping(range.nodes.end.id());
+
+        generator.gen_expr(&range.from);
+        generator.push_instruction(Instruction::STORE_LOCAL(counter));
+        generator.gen_expr(&range.to);
+        generator.push_instruction(Instruction::STORE_LOCAL(end));
+
+        let step = if generator.is_const(range.nodes.step_value) {
+            let Lit::LitInt(step) = *generator.get_const_value(range.nodes.step_value) else {
+                unreachable!()
+            };
+            Step::Const {
+                up: (step as i64) > 0,
+            }
+        } else {
+            let local = generator.get_local_mapping(range.nodes.step.id());
+            generator.gen_expr(range.step.as_ref().unwrap());
+            generator.push_instruction(Instruction::STORE_LOCAL(local));
+            Step::Local(local)
+        };
 
         let loop_start = generator.create_label();
+        let loop_end = generator.create_label();
+        let loop_continue = generator.create_label();
         generator.place_label(loop_start);
 
-        generator.push_instruction(Instruction::LOAD_LOCAL(iter_var));
-        generator.gen_expr(&self.iter.to);
-        generator.push_instruction(Instruction::LT);
+        // a step of 0 doesn't run the body at all
+        match step {
+            Step::Const { up } => range.gen_condition(counter, up, generator),
+            Step::Local(local) => range.gen_branches(local, Some(loop_end), generator, |s, up, g| {
+                s.gen_condition(counter, up, g)
+            }),
+        }
+        generator.push_instruction(Instruction::JMP_IF_FALSE(loop_end));
+
+        generator.push_loop(self.label.as_ref(), loop_end, loop_continue);
+        self.block.generate(generator);
+        generator.pop_loop();
 
+        generator.place_label(loop_continue);
+        match step {
+            Step::Const { up } => range.gen_overflow_check(counter, up, None, generator),
+            Step::Local(local) => range.gen_branches(local, None, generator, |s, up, g| {
+                s.gen_overflow_check(counter, up, Some(local), g)
+            }),
+        }
+        generator.push_instruction(Instruction::JMP_IF_TRUE(loop_end));
+
+        generator.push_instruction(Instruction::LOAD_LOCAL(counter));
+        match step {
+            Step::Const { .. } => {
+                let step = generator.get_const_mapping(range.nodes.step_value);
+                generator.push_instruction(Instruction::LOAD_CONST(step));
+            }
+            Step::Local(local) => generator.push_instruction(Instruction::LOAD_LOCAL(local)),
+        }
+        generator.push_instruction(Instruction::ADD);
+        generator.push_instruction(Instruction::STORE_LOCAL(counter));
+
+        generator.push_instruction(Instruction::JMP(loop_start));
+
+        generator.place_label(loop_end);
+    }
+
+    // the offset moves on by however many bytes each char takes up
+    fn generate_chars(&self, chars: &CharsExpr, generator: &mut Generator) {
+        let var = generator.get_local_mapping(self.var.id());
+        let string = generator.get_local_mapping(chars.local.id());
+        let offset = generator.get_local_mapping(chars.offset.id());
+
+        generator.gen_expr(&chars.string);
+        generator.push_instruction(Instruction::STORE_LOCAL(string));
+        let zero = generator.get_const_mapping(chars.zero);
+        generator.push_instruction(Instruction::LOAD_CONST(zero));
+        generator.push_instruction(Instruction::STORE_LOCAL(offset));
+
+        let loop_start = generator.create_label();
         let loop_end = generator.create_label();
+        generator.place_label(loop_start);
+
+        generator.push_instruction(Instruction::LOAD_LOCAL(offset));
+        generator.push_instruction(Instruction::LOAD_LOCAL(string));
+        generator.push_instruction(Instruction::STR_LEN);
+        generator.push_instruction(Instruction::LT);
         generator.push_instruction(Instruction::JMP_IF_FALSE(loop_end));
 
-        self.block.generate(generator);
+        generator.push_instruction(Instruction::LOAD_LOCAL(string));
+        generator.push_instruction(Instruction::LOAD_LOCAL(offset));
+        generator.push_instruction(Instruction::STR_NEXT);
+        generator.push_instruction(Instruction::STORE_LOCAL(offset));
+        generator.push_instruction(Instruction::STORE_LOCAL(var));
 
-        generator.push_instruction(Instruction::LOAD_LOCAL(iter_var));
-        generator.push_instruction(Instruction::INC);
-        generator.push_instruction(Instruction::STORE_LOCAL(iter_var));
+        generator.push_loop(self.label.as_ref(), loop_end, loop_start);
+        self.block.generate(generator);
+        generator.pop_loop();
 
         generator.push_instruction(Instruction::JMP(loop_start));
 
@@ -126,9 +501,18 @@ impl Generate for ForStmt {
     }
 }
 
+impl Generate for ForStmt {
+    fn generate(&self, generator: &mut Generator) {
+        match &self.iter {
+            ForIter::Range(range) => self.generate_range(range, generator),
+            ForIter::Chars(chars) => self.generate_chars(chars, generator),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::ForStmt;
+    use super::{ForIter, ForStmt, IterExpr};
     use crate::{lexer::Lexer, parser::{parser::Parser, Parse}, source::SourceFile};
     use std::io::Cursor;
 
@@ -138,6 +522,13 @@ mod tests {
         ForStmt::parse(&mut parser).unwrap()
     }
 
+    fn range(for_stmt: &ForStmt) -> &IterExpr {
+        match &for_stmt.iter {
+            ForIter::Range(range) => range,
+            ForIter::Chars(_) => panic!("expected a range"),
+        }
+    }
+
     #[test]
     fn test_for_stmt_basic() {
         let for_stmt = parse_for("for i in 0 => 10 { }");
@@ -161,4 +552,32 @@ mod tests {
         let for_stmt = parse_for("for j in start => end { return j; }");
         assert_eq!(for_stmt.var.value(), "j");
     }
+
+    #[test]
+    fn test_for_stmt_ranges() {
+        let exclusive = parse_for("for i in 0..10 { }");
+        assert!(!range(&exclusive).inclusive);
+        assert!(range(&exclusive).step.is_none());
+
+        let inclusive = parse_for("for i in 10..=0 step -2 { }");
+        assert!(range(&inclusive).inclusive);
+        assert!(range(&inclusive).step.is_some());
+    }
+
+    #[test]
+    fn test_for_stmt_step_is_not_a_keyword() {
+        let for_stmt = parse_for("for step in 0..step step step { }");
+        assert_eq!(for_stmt.var.value(), "step");
+        assert!(range(&for_stmt).step.is_some());
+    }
+
+    #[test]
+    fn test_for_stmt_chars() {
+        let for_stmt = parse_for("for c in \"héllo\" { }");
+        assert_eq!(for_stmt.var.value(), "c");
+        assert!(matches!(for_stmt.iter, ForIter::Chars(_)));
+
+        let for_stmt = parse_for("for c in line { }");
+        assert!(matches!(for_stmt.iter, ForIter::Chars(_)));
+    }
 }
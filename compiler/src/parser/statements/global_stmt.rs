@@ -4,8 +4,8 @@ use crate::{
     lexer::tokens::Ident,
     parser::{expr::Expr, node::Node, parse_trait::Parse, parser::Parser, types::TypeSpanned},
     semantic_analyzer::{
-        analyze_trait::Analyze, err::SemanticError, return_status::ReturnStatus,
-        symbol_info::SymbolInfoKind,
+        analyze_trait::Analyze, const_eval::ConstEvaluator, err::SemanticError,
+        return_status::ReturnStatus, symbol_info::SymbolInfoKind,
     },
     t,
 };
@@ -36,6 +36,7 @@ impl Parse for GlobalStmt {
 
 impl Analyze for GlobalStmt {
     fn build(&self, builder: &mut crate::semantic_analyzer::scope_builder::ScopeBuilder) {
+        self.rhs.build(builder);
         let type_info = builder.register_type(&self.ttype);
         builder.define_global(&self.var, type_info);
     }
@@ -44,6 +45,7 @@ impl Analyze for GlobalStmt {
         &self,
         analyzer: &mut crate::semantic_analyzer::analyzer::Analyzer,
     ) -> ReturnStatus {
+        analyzer.expect(&self.ttype);
         let Some(expr_type) = analyzer.resolve_expr(&self.rhs) else {
             return ReturnStatus::Never;
         };
@@ -51,7 +53,7 @@ impl Analyze for GlobalStmt {
         get_symbol_mut!((analyzer, self.var) info {
             if !info.ty.assign_compatible(&expr_type.ttype) {
                 analyzer.report_semantic_error(
-                    SemanticError::LetTypeMismatch(info.ty.ttype.clone(), expr_type.clone()),
+                    SemanticError::LetTypeMismatch(info.ty.written().clone(), expr_type.clone()),
                     self.rhs.span(),
                 );
             }
@@ -61,13 +63,23 @@ impl Analyze for GlobalStmt {
             }
         });
 
+        // initializers that don't need the program to run are stored ready-made
+        if let Ok(value) = ConstEvaluator::eval(&self.rhs, analyzer) {
+            analyzer.register_constant_value(self.rhs.id(), &value, &expr_type.ttype);
+        }
+
         ReturnStatus::Never
     }
 }
 
 impl Generate for GlobalStmt {
     fn generate(&self, generator: &mut crate::generator::generator::Generator) {
-        generator.gen_expr(&self.rhs);
+        if generator.is_const(self.rhs.id()) {
+            let value = generator.get_const_mapping(self.rhs.id());
+            generator.push_instruction(Instruction::LOAD_CONST(value));
+        } else {
+            generator.gen_expr(&self.rhs);
+        }
         let id = generator.get_global_mapping(self.var.id());
         generator.push_instruction(Instruction::STORE_GLOBAL(id));
     }
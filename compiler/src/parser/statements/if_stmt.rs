@@ -1,19 +1,25 @@
 use crate::generator::compile_trait::Generate;
+use crate::generator::generator::Generator;
 use crate::generator::instruction::Instruction;
+use crate::lexer::span::Span;
 use crate::parser::node::Node;
 use crate::parser::parser::Parser;
-use crate::parser::types::Type;
+use crate::parser::types::{Type, TypeSpanned};
 use crate::parser::{expr::Expr, parse_trait::Parse};
 use crate::semantic_analyzer::analyze_trait::Analyze;
+use crate::semantic_analyzer::analyzer::Analyzer;
+use crate::semantic_analyzer::branch_unifier::BranchUnifier;
 use crate::semantic_analyzer::err::SemanticError;
 use crate::semantic_analyzer::return_status::ReturnStatus;
+use crate::semantic_analyzer::scope::ScopeKind;
 use crate::{t, tt};
 
+use super::switch_stmt::WrapperPattern;
 use super::BlockStmt;
 
 #[derive(Debug, Clone)]
 pub enum ElseStmt {
-    Block(BlockStmt),
+    Block(Node<BlockStmt>),
     If(Box<Node<IfStmt>>),
 }
 
@@ -24,7 +30,34 @@ impl Parse for ElseStmt {
             return Ok(Self::If(Box::new(parser.parse_node::<IfStmt>()?)));
         }
 
-        Ok(Self::Block(parser.parse::<BlockStmt>()?))
+        Ok(Self::Block(parser.parse_node::<BlockStmt>()?))
+    }
+}
+
+impl ElseStmt {
+    fn has_value(&self) -> bool {
+        match self {
+            ElseStmt::Block(b) => b.has_value(),
+            ElseStmt::If(if_stmt) => if_stmt.has_value(),
+        }
+    }
+
+    fn analyze_branch(&self, analyzer: &mut Analyzer, unifier: Option<&mut BranchUnifier>) -> ReturnStatus {
+        match (self, unifier) {
+            (ElseStmt::Block(b), Some(unifier)) => b.analyze_value(analyzer, unifier, b.span()),
+            (ElseStmt::If(if_stmt), Some(unifier)) => {
+                if_stmt.analyze_value(analyzer, unifier, if_stmt.span())
+            }
+            (ElseStmt::Block(b), None) => b.analyze_semantics(analyzer),
+            (ElseStmt::If(if_stmt), None) => if_stmt.analyze_semantics(analyzer),
+        }
+    }
+
+    fn generate_branch(&self, generator: &mut Generator, value: bool) {
+        match self {
+            ElseStmt::Block(b) => IfStmt::generate_block(b, generator, value),
+            ElseStmt::If(if_stmt) => if_stmt.generate_branches(generator, value),
+        }
     }
 }
 
@@ -36,35 +69,41 @@ impl Analyze for ElseStmt {
         }
     }
 
-    fn analyze_semantics(&self, analyzer: &mut crate::semantic_analyzer::analyzer::Analyzer) -> ReturnStatus {
-        match self {
-            ElseStmt::Block(b) => b.analyze_semantics(analyzer),
-            ElseStmt::If(if_stmt) => if_stmt.analyze_semantics(analyzer),
-        }
+    fn analyze_semantics(&self, analyzer: &mut Analyzer) -> ReturnStatus {
+        self.analyze_branch(analyzer, None)
     }
 }
 
 impl Generate for ElseStmt {
-    fn generate(&self, generator: &mut crate::generator::generator::Generator) {
-        match self {
-            ElseStmt::Block(b) => b.generate(generator),
-            ElseStmt::If(if_stmt) => if_stmt.generate(generator),
-        }
+    fn generate(&self, generator: &mut Generator) {
+        self.generate_branch(generator, false);
     }
 }
 
+// `if let some(x) = expr` runs the block only when the pattern matches, with
+// `x` bound to the unwrapped value. `ok(x)` and `err(e)` work on results.
+// used as a value, every branch has to evaluate to one, so it needs an `else`
 #[derive(Debug, Clone)]
 pub struct IfStmt {
+    pattern: Option<Node<WrapperPattern>>,
     expr: Node<Expr>,
-    block: BlockStmt,
+    block: Node<BlockStmt>,
     else_stmt: Option<ElseStmt>,
 }
 
 impl Parse for IfStmt {
     fn parse(parser: &mut Parser) -> crate::aliases::Result<Self> {
         parser.consume::<t!(if)>()?;
+
+        let mut pattern = None;
+        if let tt!(let) = parser.peek()? {
+            parser.consume::<t!(let)>()?;
+            pattern = Some(parser.parse_node()?);
+            parser.consume::<t!(=)>()?;
+        }
+
         let expr = parser.parse_node::<Expr>()?;
-        let block = parser.parse::<BlockStmt>()?;
+        let block = parser.parse_node::<BlockStmt>()?;
 
         let else_stmt = if let tt!(else) = parser.peek()? {
             Some(parser.parse::<ElseStmt>()?)
@@ -73,6 +112,7 @@ impl Parse for IfStmt {
         };
 
         Ok(Self {
+            pattern,
             expr,
             block,
             else_stmt,
@@ -80,33 +120,68 @@ impl Parse for IfStmt {
     }
 }
 
-impl Analyze for IfStmt {
-    fn build(&self, builder: &mut crate::semantic_analyzer::scope_builder::ScopeBuilder) {
-        builder.push_scope(crate::semantic_analyzer::scope::ScopeKind::Block);
-        self.block.build(builder);
-        if let Some(else_stmt) = &self.else_stmt {
-            match else_stmt {
-                ElseStmt::If(ifstmt) => ifstmt.build(builder),
-                ElseStmt::Block(block) => block.build(builder),
-            }
+impl IfStmt {
+    pub fn condition(&self) -> &Node<Expr> {
+        &self.expr
+    }
+
+    pub fn has_value(&self) -> bool {
+        self.block.has_value() && self.else_stmt.as_ref().is_some_and(ElseStmt::has_value)
+    }
+
+    pub fn analyze_value(
+        &self,
+        analyzer: &mut Analyzer,
+        unifier: &mut BranchUnifier,
+        span: Span,
+    ) -> ReturnStatus {
+        if self.else_stmt.is_none() {
+            analyzer.report_semantic_error(SemanticError::MissingElse, span);
         }
-        builder.pop_scope();
+        self.analyze_branches(analyzer, Some(unifier))
     }
 
-    fn analyze_semantics(&self, analyzer: &mut crate::semantic_analyzer::analyzer::Analyzer) -> ReturnStatus {
-        analyzer.enter_scope();
-        if let Some(expr_type) = analyzer.resolve_expr(&self.expr) {
-            if !expr_type.compatible(&Type::bool()) {
-                analyzer.report_semantic_error(
-                    SemanticError::IfTypeMismatch(expr_type),
-                    self.expr.span(),
-                );
-            }
+    fn analyze_block(
+        block: &Node<BlockStmt>,
+        analyzer: &mut Analyzer,
+        unifier: Option<&mut BranchUnifier>,
+    ) -> ReturnStatus {
+        match unifier {
+            Some(unifier) => block.analyze_value(analyzer, unifier, block.span()),
+            None => block.analyze_semantics(analyzer),
         }
+    }
+
+    // with a unifier, the branches are analyzed as values of it
+    fn analyze_branches(
+        &self,
+        analyzer: &mut Analyzer,
+        mut unifier: Option<&mut BranchUnifier>,
+    ) -> ReturnStatus {
+        analyzer.enter_scope();
+        let expr_type = analyzer.resolve_expr(&self.expr);
 
-        let then_status = self.block.analyze_semantics(analyzer);
+        let then_status = if let Some(pattern) = &self.pattern {
+            analyzer.enter_scope();
+            if let Some(expr_type) = expr_type {
+                pattern.analyze(&expr_type, self.expr.span(), analyzer);
+            }
+            let status = Self::analyze_block(&self.block, analyzer, unifier.as_deref_mut());
+            analyzer.exit_scope();
+            status
+        } else {
+            if let Some(expr_type) = expr_type {
+                if !expr_type.compatible(&Type::bool()) {
+                    analyzer.report_semantic_error(
+                        SemanticError::IfTypeMismatch(expr_type),
+                        self.expr.span(),
+                    );
+                }
+            }
+            Self::analyze_block(&self.block, analyzer, unifier.as_deref_mut())
+        };
         let else_status = if let Some(else_stmt) = &self.else_stmt {
-            else_stmt.analyze_semantics(analyzer)
+            else_stmt.analyze_branch(analyzer, unifier)
         } else {
             ReturnStatus::Never
         };
@@ -114,32 +189,110 @@ impl Analyze for IfStmt {
         analyzer.exit_scope();
         then_status.intersect(else_status)
     }
-}
 
-impl Generate for IfStmt {
-    fn generate(&self, generator: &mut crate::generator::generator::Generator) {
+    // leaves the value of the taken branch on the operand stack
+    pub fn generate_value(&self, generator: &mut Generator) {
+        self.generate_branches(generator, true);
+    }
+
+    fn generate_block(block: &BlockStmt, generator: &mut Generator, value: bool) {
+        if value {
+            block.generate_value(generator);
+        } else {
+            block.generate(generator);
+        }
+    }
+
+    fn generate_branches(&self, generator: &mut Generator, value: bool) {
         generator.gen_expr(&self.expr);
 
+        if let Some(pattern) = &self.pattern {
+            self.generate_if_let(pattern, generator, value);
+            return;
+        }
+
         if let Some(else_stmt) = &self.else_stmt {
             let else_label = generator.create_label();
             let end_label = generator.create_label();
 
             generator.push_instruction(Instruction::JMP_IF_FALSE(else_label));
-            self.block.generate(generator);
+            Self::generate_block(&self.block, generator, value);
 
             generator.push_instruction(Instruction::JMP(end_label));
             generator.place_label(else_label);
 
-            else_stmt.generate(generator);
+            else_stmt.generate_branch(generator, value);
             generator.place_label(end_label);
         } else {
             let end_label = generator.create_label();
             generator.push_instruction(Instruction::JMP_IF_FALSE(end_label));
-            self.block.generate(generator);
+            Self::generate_block(&self.block, generator, value);
 
             generator.place_label(end_label);
         }
     }
+
+    fn generate_if_let(&self, pattern: &WrapperPattern, generator: &mut Generator, value: bool) {
+        let none_label = generator.create_label();
+        let end_label = generator.create_label();
+
+        generator.push_instruction(Instruction::DUP);
+        generator.push_instruction(Instruction::IS_VARIANT(pattern.variant()));
+        generator.push_instruction(Instruction::JMP_IF_FALSE(none_label));
+
+        if let Some(binding) = pattern.bound() {
+            generator.push_instruction(Instruction::FIELD(pattern.field()));
+            let local = generator.get_local_mapping(binding.id());
+            generator.push_instruction(Instruction::STORE_LOCAL(local));
+        } else {
+            generator.push_instruction(Instruction::POP);
+        }
+        Self::generate_block(&self.block, generator, value);
+        generator.push_instruction(Instruction::JMP(end_label));
+
+        generator.place_label(none_label);
+        generator.push_instruction(Instruction::POP);
+        if let Some(else_stmt) = &self.else_stmt {
+            else_stmt.generate_branch(generator, value);
+        }
+        generator.place_label(end_label);
+    }
+}
+
+impl Analyze for IfStmt {
+    fn build(&self, builder: &mut crate::semantic_analyzer::scope_builder::ScopeBuilder) {
+        builder.push_scope(ScopeKind::Block);
+        self.expr.build(builder);
+        if let Some(pattern) = &self.pattern {
+            // the binding is only visible in the then block
+            builder.push_scope(ScopeKind::Block);
+            if let Some(binding) = pattern.bound() {
+                let type_info = builder.register_type(&TypeSpanned::dummy(Type::unknown()));
+                builder.define_var(binding, type_info);
+            }
+            self.block.build(builder);
+            builder.pop_scope();
+        } else {
+            self.block.build(builder);
+        }
+        if let Some(else_stmt) = &self.else_stmt {
+            match else_stmt {
+                ElseStmt::If(ifstmt) => ifstmt.build(builder),
+                ElseStmt::Block(block) => block.build(builder),
+            }
+        }
+        builder.pop_scope();
+    }
+
+    fn analyze_semantics(&self, analyzer: &mut Analyzer) -> ReturnStatus {
+        self.analyze_branches(analyzer, None)
+    }
+}
+
+impl Generate for IfStmt {
+    fn generate(&self, generator: &mut Generator) {
+        self.generate_branches(generator, false);
+    }
 }
 
 #[cfg(test)]
@@ -177,4 +330,21 @@ mod tests {
         let if_stmt = parse_if("if x == 5 && y != 3 { let z = x + y; }");
         assert!(if_stmt.else_stmt.is_none());
     }
+
+    #[test]
+    fn test_if_let_some() {
+        let if_stmt = parse_if("if let some(x) = find(3) { return x; } else { return 0; }");
+        let pattern = if_stmt.pattern.as_ref().unwrap();
+        assert_eq!(pattern.bound().unwrap().value(), "x");
+        assert!(if_stmt.else_stmt.is_some());
+        assert!(parse_if("if x > 0 { }").pattern.is_none());
+    }
+
+    #[test]
+    fn test_if_has_value() {
+        assert!(parse_if("if c { 1 } else if d { 2 } else { 3 }").has_value());
+        assert!(parse_if("if c { let x = 1; x } else { { 2 } }").has_value());
+        assert!(!parse_if("if c { 1 }").has_value());
+        assert!(!parse_if("if c { 1 } else { return 2; }").has_value());
+    }
 }
@@ -0,0 +1,111 @@
+use std::fmt::Display;
+
+use crate::generator::compile_trait::Generate;
+use crate::generator::instruction::Instruction;
+use crate::lexer::span::Span;
+use crate::lexer::tokens::Ident;
+use crate::parser::node::Node;
+use crate::parser::parse_trait::Parse;
+use crate::parser::parser::Parser;
+use crate::semantic_analyzer::analyze_trait::Analyze;
+use crate::semantic_analyzer::return_status::ReturnStatus;
+use crate::{t, tt};
+
+// `'name:` in front of a loop
+pub(super) fn parse_loop_label(parser: &mut Parser) -> crate::aliases::Result<Option<Node<Ident>>> {
+    if *parser.peek()? != tt!("'") {
+        return Ok(None);
+    }
+
+    parser.consume::<t!("'")>()?;
+    let label = parser.parse_node()?;
+    parser.consume::<t!(:)>()?;
+    Ok(Some(label))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum JumpKind {
+    Break,
+    Continue,
+}
+
+impl Display for JumpKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Break => write!(f, "break"),
+            Self::Continue => write!(f, "continue"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct JumpStmt {
+    kind: JumpKind,
+    label: Option<Node<Ident>>,
+    span: Span,
+}
+
+impl Parse for JumpStmt {
+    fn parse(parser: &mut Parser) -> crate::aliases::Result<Self> {
+        let (kind, span) = if let tt!(break) = parser.peek()? {
+            (JumpKind::Break, parser.consume::<t!(break)>()?.span())
+        } else {
+            (JumpKind::Continue, parser.consume::<t!(continue)>()?.span())
+        };
+
+        let mut label = None;
+        if let tt!("'") = parser.peek()? {
+            parser.consume::<t!("'")>()?;
+            label = Some(parser.parse_node()?);
+        }
+
+        parser.consume::<t!(;)>()?;
+        Ok(Self { kind, label, span })
+    }
+}
+
+impl Analyze for JumpStmt {
+    fn build(&self, _builder: &mut crate::semantic_analyzer::scope_builder::ScopeBuilder) {}
+
+    fn analyze_semantics(
+        &self,
+        analyzer: &mut crate::semantic_analyzer::analyzer::Analyzer,
+    ) -> ReturnStatus {
+        analyzer.resolve_jump(self.kind, self.label.as_ref(), self.span);
+        ReturnStatus::Never
+    }
+}
+
+impl Generate for JumpStmt {
+    fn generate(&self, generator: &mut crate::generator::generator::Generator) {
+        let target = generator.get_loop_target(self.kind, self.label.as_ref());
+        generator.push_instruction(Instruction::JMP(target));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{JumpKind, JumpStmt};
+    use crate::{lexer::Lexer, parser::parser::Parser, source::SourceFile};
+    use std::io::Cursor;
+
+    fn parse_jump(input: &str) -> JumpStmt {
+        let source = SourceFile::new(Cursor::new(input)).unwrap();
+        let mut parser = Parser::new(Lexer::new(&source).unwrap());
+        parser.parse::<JumpStmt>().unwrap()
+    }
+
+    #[test]
+    fn test_break() {
+        let jump = parse_jump("break;");
+        assert_eq!(jump.kind, JumpKind::Break);
+        assert!(jump.label.is_none());
+    }
+
+    #[test]
+    fn test_labeled_continue() {
+        let jump = parse_jump("continue 'outer;");
+        assert_eq!(jump.kind, JumpKind::Continue);
+        assert_eq!(jump.label.unwrap().value().as_str(), "outer");
+    }
+}
@@ -1,4 +1,5 @@
 use crate::{
+    aliases::TypeInfoRc,
     generator::{compile_trait::Generate, instruction::Instruction},
     get_symbol_mut,
     lexer::tokens::Ident,
@@ -11,7 +12,7 @@ use crate::{
     },
     semantic_analyzer::{
         analyze_trait::Analyze, err::SemanticError, return_status::ReturnStatus,
-        symbol_info::SymbolInfoKind,
+        struct_table::FieldIndex, symbol_info::SymbolInfoKind,
     },
     t, tt,
 };
@@ -19,14 +20,77 @@ use crate::{
 #[derive(Debug, Clone)]
 pub struct LetStmt {
     var: Node<Ident>,
+    // `let (a, b, ...) = ...` binds each element of a tuple rather than the
+    // tuple itself, `var` being the first of the names and these the rest
+    rest: Option<Vec<Node<Ident>>>,
     ttype: TypeSpanned,
     rhs: Option<Node<Expr>>,
 }
 
+impl LetStmt {
+    fn names(&self) -> impl Iterator<Item = &Node<Ident>> {
+        std::iter::once(&self.var).chain(self.rest.iter().flatten())
+    }
+
+    fn analyze_destructure(
+        &self,
+        count: usize,
+        expr_type: &TypeInfoRc,
+        analyzer: &mut crate::semantic_analyzer::analyzer::Analyzer,
+    ) {
+        let rhs = self.rhs.as_ref().unwrap();
+        let elements = match &expr_type.inner {
+            TypeInner::Tuple(elements) if elements.len() == count => elements,
+            _ => {
+                analyzer.report_semantic_error(
+                    SemanticError::DestructureMismatch(count, expr_type.clone()),
+                    rhs.span(),
+                );
+                return;
+            }
+        };
+
+        for (name, element) in self.names().zip(elements) {
+            let element = analyzer.register_type(&TypeSpanned::dummy(element.clone()));
+            get_symbol_mut!((analyzer, name) info {
+            if info.ty.inner == TypeInner::Unknown {
+                info.infer_type(&element);
+            }
+
+            if !info.ty.assign_compatible(&element.ttype) {
+                analyzer.report_semantic_error(
+                    SemanticError::LetTypeMismatch(info.ty.written().clone(), element.clone()),
+                    rhs.span(),
+                );
+            }
+
+            if let SymbolInfoKind::Variable(ref mut v) = &mut info.kind {
+                v.initialized = true;
+            }
+            });
+        }
+    }
+}
+
 impl Parse for LetStmt {
     fn parse(parser: &mut Parser) -> crate::aliases::Result<Self> {
         parser.consume::<t!(let)>()?;
-        let var = parser.parse_node::<Ident>()?;
+
+        let mut rest = None;
+        let var = if let tt!("(") = parser.peek()? {
+            parser.consume::<t!("(")>()?;
+            let var = parser.parse_node::<Ident>()?;
+            let mut names = vec![];
+            while let tt!(,) = parser.peek()? {
+                parser.consume::<t!(,)>()?;
+                names.push(parser.parse_node::<Ident>()?);
+            }
+            parser.consume::<t!(")")>()?;
+            rest = Some(names);
+            var
+        } else {
+            parser.parse_node::<Ident>()?
+        };
 
         let mut ttype = TypeSpanned::dummy(Type::unknown());
         if let tt!(:) = parser.peek()? {
@@ -42,14 +106,35 @@ impl Parse for LetStmt {
 
         parser.consume::<t!(;)>()?;
 
-        Ok(Self { var, ttype, rhs })
+        Ok(Self {
+            var,
+            rest,
+            ttype,
+            rhs,
+        })
     }
 }
 
 impl Analyze for LetStmt {
     fn build(&self, builder: &mut crate::semantic_analyzer::scope_builder::ScopeBuilder) {
+        if let Some(rhs) = &self.rhs {
+            rhs.build(builder);
+        }
         let type_info = builder.register_type(&self.ttype);
-        builder.define_var(&self.var, type_info)
+        if self.rest.is_none() {
+            return builder.define_var(&self.var, type_info);
+        }
+
+        // each name takes its type from the matching element of the annotation
+        let count = self.names().count();
+        let elements = match &type_info.inner {
+            TypeInner::Tuple(elements) if elements.len() == count => elements.clone(),
+            _ => vec![Type::unknown(); count],
+        };
+        for (name, ty) in self.names().zip(elements) {
+            let type_info = builder.register_type(&TypeSpanned::dummy(ty));
+            builder.define_var(name, type_info);
+        }
     }
 
     fn analyze_semantics(
@@ -60,10 +145,24 @@ impl Analyze for LetStmt {
             return ReturnStatus::Never;
         };
 
+        analyzer.expect(&self.ttype);
         let Some(expr_type) = analyzer.resolve_expr(rhs) else {
             return ReturnStatus::Never;
         };
 
+        if self.rest.is_some() {
+            let ttype = analyzer.expand_type(&self.ttype);
+            if ttype.inner != TypeInner::Unknown && !ttype.assign_compatible(&expr_type.ttype) {
+                analyzer.report_semantic_error(
+                    SemanticError::LetTypeMismatch(self.ttype.ty.clone(), expr_type.clone()),
+                    rhs.span(),
+                );
+                return ReturnStatus::Never;
+            }
+            self.analyze_destructure(self.names().count(), &expr_type, analyzer);
+            return ReturnStatus::Never;
+        }
+
         get_symbol_mut!((analyzer, self.var) info {
         if self.ttype.inner == TypeInner::Unknown {
             info.infer_type(&expr_type);
@@ -71,7 +170,7 @@ impl Analyze for LetStmt {
 
         if !info.ty.assign_compatible(&expr_type.ttype) {
             analyzer.report_semantic_error(
-                SemanticError::LetTypeMismatch(info.ty.ttype.clone(), expr_type.clone()),
+                SemanticError::LetTypeMismatch(info.ty.written().clone(), expr_type.clone()),
                 rhs.span(),
             );
         }
@@ -88,10 +187,25 @@ impl Analyze for LetStmt {
 
 impl Generate for LetStmt {
     fn generate(&self, generator: &mut crate::generator::generator::Generator) {
-        if let Some(expr) = &self.rhs {
-            generator.gen_expr(expr);
+        let Some(expr) = &self.rhs else {
+            return;
+        };
+
+        generator.gen_expr(expr);
+        let Some(rest) = &self.rest else {
             let id = generator.get_local_mapping(self.var.id());
             generator.push_instruction(Instruction::STORE_LOCAL(id));
+            return;
+        };
+
+        // the tuple stays on the stack until its last element is taken out
+        for (i, name) in self.names().enumerate() {
+            if i < rest.len() {
+                generator.push_instruction(Instruction::DUP);
+            }
+            generator.push_instruction(Instruction::FIELD(FieldIndex(i as u32)));
+            let id = generator.get_local_mapping(name.id());
+            generator.push_instruction(Instruction::STORE_LOCAL(id));
         }
     }
 }
@@ -137,6 +251,15 @@ mod tests {
     }
 
 
+    #[test]
+    fn test_parse_let_stmt_destructure() {
+        let stmt = parse_let("let (q, r): (int, int) = divmod(7, 2);");
+        let names: Vec<_> = stmt.names().map(|n| n.value().as_str()).collect();
+        assert_eq!(names, ["q", "r"]);
+        assert!(stmt.ttype.compatible(&Type::tuple(vec![Type::int(), Type::int()])));
+    }
+
+
     fn create_analyzer(input: &str) -> (crate::semantic_analyzer::analyzer::Analyzer, LetStmt) {
         let source = SourceFile::new(Cursor::new(input)).unwrap();
         let mut parser = Parser::new(Lexer::new(&source).unwrap());
@@ -1,30 +1,34 @@
 use crate::generator::compile_trait::Generate;
 use crate::generator::instruction::Instruction;
+use crate::lexer::tokens::Ident;
+use crate::parser::node::Node;
 use crate::parser::parse_trait::Parse;
 use crate::parser::parser::Parser;
 use crate::semantic_analyzer::analyze_trait::Analyze;
 use crate::semantic_analyzer::return_status::ReturnStatus;
-use crate::semantic_analyzer::scope::ScopeKind;
 use crate::t;
 
+use super::jump_stmt::parse_loop_label;
 use super::BlockStmt;
 
 #[derive(Debug, Clone)]
 pub struct LoopStmt {
+    label: Option<Node<Ident>>,
     block: BlockStmt,
 }
 
 impl Parse for LoopStmt {
     fn parse(parser: &mut Parser) -> crate::aliases::Result<Self> {
+        let label = parse_loop_label(parser)?;
         parser.consume::<t!(loop)>()?;
         let block = parser.parse::<BlockStmt>()?;
-        Ok(Self { block })
+        Ok(Self { label, block })
     }
 }
 
 impl Analyze for LoopStmt {
     fn build(&self, builder: &mut crate::semantic_analyzer::scope_builder::ScopeBuilder) {
-        builder.push_scope(ScopeKind::Loop);
+        builder.push_loop_scope(self.label.as_ref());
         self.block.build(builder);
         builder.pop_scope();
     }
@@ -35,17 +39,33 @@ impl Analyze for LoopStmt {
     ) -> ReturnStatus {
         analyzer.enter_scope();
         let block_status = self.block.analyze_semantics(analyzer);
+        let has_break = analyzer.loop_has_break();
         analyzer.exit_scope();
-        block_status
+
+        // without a break the loop can only be left by returning
+        if !has_break {
+            return ReturnStatus::Always;
+        }
+
+        match block_status {
+            ReturnStatus::Never => ReturnStatus::Never,
+            _ => ReturnStatus::Sometimes,
+        }
     }
 }
 
 impl Generate for LoopStmt {
     fn generate(&self, generator: &mut crate::generator::generator::Generator) {
         let loop_start = generator.create_label();
+        let loop_end = generator.create_label();
         generator.place_label(loop_start);
+
+        generator.push_loop(self.label.as_ref(), loop_end, loop_start);
         self.block.generate(generator);
+        generator.pop_loop();
+
         generator.push_instruction(Instruction::JMP(loop_start));
+        generator.place_label(loop_end);
     }
 }
 
@@ -70,4 +90,10 @@ mod tests {
     fn test_loop_stmt_with_body() {
         parse_loop("loop { if x > 10 { return; } x += 1; }");
     }
+
+    #[test]
+    fn test_labeled_loop_stmt() {
+        let loop_stmt = parse_loop("'outer: loop { loop { break 'outer; } }");
+        assert_eq!(loop_stmt.label.unwrap().value().as_str(), "outer");
+    }
 }
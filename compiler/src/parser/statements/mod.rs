@@ -1,8 +1,10 @@
 mod assign_stmt;
 mod block_stmt;
+mod const_stmt;
 mod for_stmt;
 mod global_stmt;
 mod if_stmt;
+mod jump_stmt;
 mod let_stmt;
 mod loop_stmt;
 mod return_stmt;
@@ -11,9 +13,11 @@ mod switch_stmt;
 mod while_stmt;
 
 pub use block_stmt::BlockStmt;
+pub use const_stmt::ConstStmt;
 pub use for_stmt::ForStmt;
 pub use global_stmt::GlobalStmt;
 pub use if_stmt::IfStmt;
+pub use jump_stmt::{JumpKind, JumpStmt};
 pub use let_stmt::LetStmt;
 pub use loop_stmt::LoopStmt;
 pub use return_stmt::ReturnStmt;
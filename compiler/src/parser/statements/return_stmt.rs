@@ -40,7 +40,11 @@ impl Parse for ReturnStmt {
 }
 
 impl Analyze for ReturnStmt {
-    fn build(&self, _builder: &mut crate::semantic_analyzer::scope_builder::ScopeBuilder) {}
+    fn build(&self, builder: &mut crate::semantic_analyzer::scope_builder::ScopeBuilder) {
+        if let Some(expr) = &self.expr {
+            expr.build(builder);
+        }
+    }
     fn analyze_semantics(
         &self,
         analyzer: &mut crate::semantic_analyzer::analyzer::Analyzer,
@@ -60,6 +64,7 @@ impl Analyze for ReturnStmt {
                             ttype: Type::void(),
                             size: 0,
                             idx: TypeIndex(0),
+                            alias: None,
                         }),
                         rt.clone(),
                     ),
@@ -70,6 +75,7 @@ impl Analyze for ReturnStmt {
             return ReturnStatus::Always;
         };
 
+        analyzer.expect(&rt.ttype);
         if let Some(expr_type) = analyzer.resolve_expr(expr) {
             if !expr_type.return_compatible(&rt.ttype) {
                 analyzer.report_semantic_error(
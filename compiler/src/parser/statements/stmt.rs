@@ -1,6 +1,7 @@
 use crate::{
-    generator::{compile_trait::Generate, instruction::Instruction},
+    generator::{compile_trait::Generate, generator::Generator, instruction::Instruction},
     get_symbol_mut,
+    lexer::span::Span,
     parser::{
         expr::{
             prefix::{PrefixOp, PrefixOpKind},
@@ -8,47 +9,84 @@ use crate::{
         },
         node::Node,
         parser::Parser,
+        types::TypeInner,
         Parse,
     },
     semantic_analyzer::{
-        analyze_trait::Analyze, err::SemanticError, return_status::ReturnStatus,
-        symbol_info::SymbolInfoKind,
+        analyze_trait::Analyze, analyzer::Analyzer, branch_unifier::BranchUnifier,
+        err::SemanticError, resolvers::expr_resolver::ExprResolver,
+        return_status::ReturnStatus, symbol_info::SymbolInfoKind,
     },
     t, tt,
 };
 
 use super::{
-    assign_stmt::AssignOp, BlockStmt, ForStmt, IfStmt, LetStmt, LoopStmt, ReturnStmt, SwitchStmt,
-    WhileStmt,
+    assign_stmt::AssignOp, BlockStmt, ForStmt, IfStmt, JumpStmt, LetStmt, LoopStmt, ReturnStmt,
+    SwitchStmt, WhileStmt,
 };
 
 #[derive(Debug, Clone)]
 pub enum Stmt {
     LetStmt(Node<LetStmt>),
     IfStmt(Node<IfStmt>),
-    ForStmt(Node<ForStmt>),
+    ForStmt(Box<Node<ForStmt>>),
     WhileStmt(Node<WhileStmt>),
     LoopStmt(Node<LoopStmt>),
     ReturnStmt(Node<ReturnStmt>),
     AssignStmt(Node<Expr>, AssignOp, Node<Expr>),
     BlockStmt(Node<BlockStmt>),
     SwitchStmt(Node<SwitchStmt>),
+    JumpStmt(Node<JumpStmt>),
     Expr(Node<Expr>),
 }
 
 impl Parse for Stmt {
     fn parse(parser: &mut Parser) -> crate::aliases::Result<Self> {
+        match Self::parse_in_block(parser, &mut None)? {
+            Some(s) => Ok(s),
+            // only a block can end in an expression without a `;`
+            None => parser.unexpected(";"),
+        }
+    }
+}
+
+impl Stmt {
+    // an expression right before the closing `}` of a block is its value
+    // rather than a statement, so it goes into `tail` without a `;`
+    pub(super) fn parse_in_block(
+        parser: &mut Parser,
+        tail: &mut Option<Box<Node<Expr>>>,
+    ) -> crate::aliases::Result<Option<Self>> {
         let s = match parser.peek()? {
             tt!("{") => Self::BlockStmt(parser.parse_node::<BlockStmt>()?),
             tt!(let) => Self::LetStmt(parser.parse_node::<LetStmt>()?),
             tt!(if) => Self::IfStmt(parser.parse_node::<IfStmt>()?),
-            tt!(for) => Self::ForStmt(parser.parse_node::<ForStmt>()?),
+            tt!(for) => Self::ForStmt(Box::new(parser.parse_node::<ForStmt>()?)),
             tt!(while) => Self::WhileStmt(parser.parse_node::<WhileStmt>()?),
             tt!(loop) => Self::LoopStmt(parser.parse_node::<LoopStmt>()?),
             tt!(return) => Self::ReturnStmt(parser.parse_node::<ReturnStmt>()?),
             tt!(switch) => Self::SwitchStmt(parser.parse_node()?),
+            tt!(break) | tt!(continue) => Self::JumpStmt(parser.parse_node()?),
+            tt!("'") => {
+                // look past the `'name:` label for the kind of loop it names
+                let mut fork = parser.fork();
+                fork.consume::<t!("'")>()?;
+                fork.consume::<t!(ident)>()?;
+                fork.consume::<t!(:)>()?;
+                match fork.peek()? {
+                    tt!(for) => Self::ForStmt(Box::new(parser.parse_node::<ForStmt>()?)),
+                    tt!(while) => Self::WhileStmt(parser.parse_node::<WhileStmt>()?),
+                    tt!(loop) => Self::LoopStmt(parser.parse_node::<LoopStmt>()?),
+                    _ => fork.unexpected("loop after label")?,
+                }
+            }
             _ => {
                 let expr = parser.parse_node::<Expr>()?;
+                if let tt!("}") = parser.peek()? {
+                    *tail = Some(Box::new(expr));
+                    return Ok(None);
+                }
+
                 if let Some(assign) = parser.try_parse::<AssignOp>() {
                     let s = Self::AssignStmt(expr, assign, parser.parse_node::<Expr>()?);
                     parser.consume::<t!(;)>()?;
@@ -60,7 +98,43 @@ impl Parse for Stmt {
             }
         };
 
-        Ok(s)
+        Ok(Some(s))
+    }
+
+    // whether the statement can give the block it ends a value
+    pub fn has_value(&self) -> bool {
+        match self {
+            Stmt::BlockStmt(block) => block.has_value(),
+            Stmt::IfStmt(ifs) => ifs.has_value(),
+            Stmt::SwitchStmt(switch) => switch.has_value(),
+            _ => false,
+        }
+    }
+
+    pub fn analyze_value(&self, analyzer: &mut Analyzer, unifier: &mut BranchUnifier) -> ReturnStatus {
+        match self {
+            Stmt::BlockStmt(block) => block.analyze_value(analyzer, unifier, block.span()),
+            Stmt::IfStmt(ifs) => ifs.analyze_value(analyzer, unifier, ifs.span()),
+            Stmt::SwitchStmt(switch) => switch.analyze_value(analyzer, unifier),
+            _ => self.analyze_semantics(analyzer),
+        }
+    }
+
+    pub fn generate_value(&self, generator: &mut Generator) {
+        match self {
+            Stmt::BlockStmt(block) => block.generate_value(generator),
+            Stmt::IfStmt(ifs) => ifs.generate_value(generator),
+            Stmt::SwitchStmt(switch) => switch.generate_value(generator),
+            _ => self.generate(generator),
+        }
+    }
+
+    // the value of an expression statement is never used
+    pub(super) fn generate_discarded(expr: &Node<Expr>, generator: &mut Generator) {
+        generator.gen_expr(expr);
+        if !generator.is_void(generator.get_expr_type(expr.id())) {
+            generator.push_instruction(Instruction::POP);
+        }
     }
 }
 
@@ -76,7 +150,12 @@ impl Analyze for Stmt {
             LoopStmt(loops) => loops.build(builder),
             ReturnStmt(returns) => returns.build(builder),
             SwitchStmt(switch) => switch.build(builder),
-            _ => (),
+            Expr(expr) => expr.build(builder),
+            AssignStmt(lhs, _, rhs) => {
+                lhs.build(builder);
+                rhs.build(builder);
+            }
+            JumpStmt(_) => (),
         }
     }
 
@@ -94,22 +173,44 @@ impl Analyze for Stmt {
             LoopStmt(loops) => loops.analyze_semantics(analyzer),
             ReturnStmt(returns) => returns.analyze_semantics(analyzer),
             SwitchStmt(switch) => switch.analyze_semantics(analyzer),
+            JumpStmt(jump) => jump.analyze_semantics(analyzer),
             Expr(expr) => {
                 analyzer.resolve_expr(expr);
                 ReturnStatus::Never
             }
-            AssignStmt(lhs, _, rhs) => {
+            AssignStmt(lhs, assign_op, rhs) => {
                 let Some(lhs_type) = analyzer.resolve_expr_write(lhs) else {
                     return ReturnStatus::Never;
                 };
+                analyzer.expect(&lhs_type.ttype);
                 let Some(rhs_type) = analyzer.resolve_expr(rhs) else {
                     return ReturnStatus::Never;
                 };
 
+                let span = Span::new(lhs.span().start, rhs.span().end);
+                if let Some(op) = assign_op.infix(span) {
+                    if !matches!(lhs_type.ttype.inner, TypeInner::Unknown) {
+                        if let Err(e) = ExprResolver::resolve_infix_types(&op, &lhs_type.ttype, &rhs_type.ttype, analyzer) {
+                            analyzer.report_error(e);
+                        }
+                    }
+                }
+
                 let mut lhs_type = &lhs_type.ttype;
 
                 if let Some(i) = lhs.lvalue() {
                     get_symbol_mut!((analyzer, i) info {
+                        // the capture itself is a copy, but what a boxed capture points at
+                        // is shared with the enclosing function
+                        let through_box = !matches!(**lhs, self::Expr::Ident(_))
+                            && matches!(info.ty.ttype.inner, TypeInner::Boxed(_));
+                        if analyzer.is_captured(&info) && !through_box {
+                            analyzer.report_semantic_error(
+                                SemanticError::AssignToCapture(lhs.clone()),
+                                lhs.span(),
+                            );
+                        }
+
                         match &mut info.kind {
                             SymbolInfoKind::Variable(ref mut v) => {
                                 if !lhs_type.mutable && v.initialized {
@@ -162,6 +263,12 @@ impl Analyze for Stmt {
                                     );
                                 }
                             }
+                            SymbolInfoKind::Constant(_) => {
+                                analyzer.report_semantic_error(
+                                    SemanticError::AssignToConst(lhs.clone()),
+                                    lhs.span(),
+                                );
+                            }
                             _ => {
                                 analyzer.report_semantic_error(SemanticError::AssignToRValue, lhs.span());
                             }
@@ -189,9 +296,8 @@ impl Generate for Stmt {
             Stmt::LoopStmt(loops) => loops.generate(generator),
             Stmt::ReturnStmt(returns) => returns.generate(generator),
             Stmt::SwitchStmt(switch) => switch.generate(generator),
-            Stmt::Expr(expr) => {
-                generator.gen_expr(expr);
-            }
+            Stmt::JumpStmt(jump) => jump.generate(generator),
+            Stmt::Expr(expr) => Self::generate_discarded(expr, generator),
             Stmt::AssignStmt(lhs, assign_op, rhs) => {
                 match assign_op {
                     AssignOp::Normal => {
@@ -201,7 +307,12 @@ impl Generate for Stmt {
                     | AssignOp::Sub
                     | AssignOp::Mult
                     | AssignOp::Div
-                    | AssignOp::Mod => {
+                    | AssignOp::Mod
+                    | AssignOp::BitAnd
+                    | AssignOp::BitOr
+                    | AssignOp::BitXor
+                    | AssignOp::Shl
+                    | AssignOp::Shr => {
                         generator.gen_expr(lhs);
                         generator.gen_expr(rhs);
 
@@ -211,6 +322,11 @@ impl Generate for Stmt {
                             AssignOp::Mult => Instruction::MUL,
                             AssignOp::Div => Instruction::DIV,
                             AssignOp::Mod => Instruction::MOD,
+                            AssignOp::BitAnd => Instruction::BIT_AND,
+                            AssignOp::BitOr => Instruction::BIT_OR,
+                            AssignOp::BitXor => Instruction::BIT_XOR,
+                            AssignOp::Shl => Instruction::SHL,
+                            AssignOp::Shr => Instruction::SHR,
                             _ => unreachable!(),
                         };
                         generator.push_instruction(infix_op);
@@ -233,6 +349,11 @@ impl Generate for Stmt {
                         generator.push_instruction(Instruction::STORE);
                     }
 
+                    Expr::Field(_, _) | Expr::Index(_, _) => {
+                        generator.gen_place(lhs);
+                        generator.push_instruction(Instruction::STORE);
+                    }
+
                     _ => panic!("Invalid lvalue in assignment"),
                 }
             }
@@ -1,40 +1,377 @@
+use std::fmt::Display;
+
 use crate::generator::compile_trait::Generate;
-use crate::generator::instruction::Instruction;
+use crate::generator::generator::Generator;
+use crate::generator::instruction::{Instruction, JumpTable, Label};
+use crate::lexer::tokens::{Ident, Lit};
+use crate::parser::types::{BaseType, Type, TypeInner, TypeSpanned};
 use crate::parser::{expr::Expr, node::Node, Parse};
 use crate::semantic_analyzer::analyze_trait::Analyze;
+use crate::semantic_analyzer::analyzer::Analyzer;
+use crate::semantic_analyzer::branch_unifier::BranchUnifier;
+use crate::semantic_analyzer::const_eval::ConstEvaluator;
 use crate::semantic_analyzer::err::SemanticError;
 use crate::semantic_analyzer::return_status::ReturnStatus;
-use crate::{t, tt};
+use crate::semantic_analyzer::struct_table::{
+    FieldIndex, VariantIndex, ERR_FIELD, ERR_VARIANT, NONE_VARIANT, OK_FIELD, OK_VARIANT,
+    SOME_FIELD, SOME_VARIANT,
+};
+use crate::semantic_analyzer::symbol_info::SymbolInfoKind;
+use crate::{get_symbol_mut, t, tt};
 
 use super::Stmt;
 
+#[derive(Debug, Clone)]
+pub struct VariantPattern {
+    enum_name: Node<Ident>,
+    variant: Node<Ident>,
+    bindings: Vec<Node<Ident>>,
+}
+
+impl VariantPattern {
+    // `_` skips a payload value without binding it
+    fn bound(&self) -> impl Iterator<Item = (usize, &Node<Ident>)> {
+        self.bindings
+            .iter()
+            .enumerate()
+            .filter(|(_, b)| b.value() != "_")
+    }
+}
+
+impl Parse for VariantPattern {
+    fn parse(parser: &mut crate::parser::parser::Parser) -> crate::aliases::Result<Self> {
+        let enum_name = parser.parse_path(true)?;
+        parser.consume::<t!(::)>()?;
+        let variant = parser.parse_node()?;
+
+        let mut bindings = vec![];
+        if let tt!("(") = parser.peek()? {
+            parser.consume::<t!("(")>()?;
+            loop {
+                if let tt!(")") = parser.peek()? {
+                    break;
+                }
+
+                bindings.push(parser.parse_node()?);
+
+                if let tt!(,) = parser.peek()? {
+                    parser.consume::<t!(,)>()?;
+                } else {
+                    break;
+                }
+            }
+            parser.consume::<t!(")")>()?;
+        }
+
+        Ok(Self {
+            enum_name,
+            variant,
+            bindings,
+        })
+    }
+}
+
+// `some(x)` or `none` matched against an optional, `ok(x)` or `err(e)`
+// against a result
+#[derive(Debug, Clone)]
+pub enum WrapperPattern {
+    Some(Node<Ident>),
+    None,
+    Ok(Node<Ident>),
+    Err(Node<Ident>),
+}
+
+impl WrapperPattern {
+    pub(super) fn variant(&self) -> VariantIndex {
+        match self {
+            Self::Some(_) => SOME_VARIANT,
+            Self::None => NONE_VARIANT,
+            Self::Ok(_) => OK_VARIANT,
+            Self::Err(_) => ERR_VARIANT,
+        }
+    }
+
+    // the field holding the bound value
+    pub(super) fn field(&self) -> FieldIndex {
+        match self {
+            Self::Some(_) | Self::None => SOME_FIELD,
+            Self::Ok(_) => OK_FIELD,
+            Self::Err(_) => ERR_FIELD,
+        }
+    }
+
+    pub(super) fn bound(&self) -> Option<&Node<Ident>> {
+        match self {
+            Self::Some(binding) | Self::Ok(binding) | Self::Err(binding)
+                if binding.value() != "_" =>
+            {
+                Some(binding)
+            }
+            _ => None,
+        }
+    }
+
+    // types the binding as the value the pattern unwraps; returns false if
+    // `matched` isn't something the pattern can match
+    pub(super) fn analyze(
+        &self,
+        matched: &crate::aliases::TypeInfoRc,
+        span: crate::lexer::span::Span,
+        analyzer: &mut crate::semantic_analyzer::analyzer::Analyzer,
+    ) -> bool {
+        let value = match (self, &matched.inner) {
+            (Self::Some(_) | Self::None, TypeInner::Optional(value)) => value,
+            (Self::Ok(_), TypeInner::Result(value, _)) => value,
+            (Self::Err(_), TypeInner::Result(_, error)) => error,
+            (Self::Some(_) | Self::None, _) => {
+                analyzer.report_semantic_error(
+                    SemanticError::NotAnOptional(matched.ttype.clone()),
+                    span,
+                );
+                return false;
+            }
+            _ => {
+                analyzer.report_semantic_error(SemanticError::NotAResult(matched.ttype.clone()), span);
+                return false;
+            }
+        };
+
+        let Some(binding) = self.bound() else {
+            return true;
+        };
+
+        let value = analyzer.register_type(&TypeSpanned {
+            ty: (**value).clone(),
+            span: binding.span(),
+        });
+        get_symbol_mut!((analyzer, binding) var {
+            var.infer_type(&value);
+            if let SymbolInfoKind::Variable(ref mut v) = var.kind {
+                v.initialized = true;
+            }
+        });
+        true
+    }
+}
+
+impl Parse for WrapperPattern {
+    fn parse(parser: &mut crate::parser::parser::Parser) -> crate::aliases::Result<Self> {
+        let wrap: fn(Node<Ident>) -> Self = match parser.peek()? {
+            tt!(none) => {
+                parser.consume::<t!(none)>()?;
+                return Ok(Self::None);
+            }
+            tt!(ok) => {
+                parser.consume::<t!(ok)>()?;
+                Self::Ok
+            }
+            tt!(err) => {
+                parser.consume::<t!(err)>()?;
+                Self::Err
+            }
+            _ => {
+                parser.consume::<t!(some)>()?;
+                Self::Some
+            }
+        };
+
+        parser.consume::<t!("(")>()?;
+        let binding = parser.parse_node()?;
+        parser.consume::<t!(")")>()?;
+        Ok(wrap(binding))
+    }
+}
+
+// `a` matches a single value, `a..b` and `a..=b` everything from `a` up to `b`
+#[derive(Debug, Clone)]
+pub enum CaseValue {
+    Single(Node<Expr>),
+    Range {
+        from: Node<Expr>,
+        to: Box<Node<Expr>>,
+        inclusive: bool,
+    },
+}
+
+impl CaseValue {
+    // a single value is a range holding just itself
+    fn bounds(&self) -> (&Node<Expr>, Option<&Node<Expr>>, bool) {
+        match self {
+            Self::Single(value) => (value, None, true),
+            Self::Range {
+                from,
+                to,
+                inclusive,
+            } => (from, Some(&**to), *inclusive),
+        }
+    }
+}
+
+impl Parse for CaseValue {
+    fn parse(parser: &mut crate::parser::parser::Parser) -> crate::aliases::Result<Self> {
+        let from = parser.parse_node()?;
+        if *parser.peek()? != tt!(..) {
+            return Ok(Self::Single(from));
+        }
+
+        parser.consume::<t!(..)>()?;
+        let inclusive = *parser.peek()? == tt!(=);
+        if inclusive {
+            parser.consume::<t!(=)>()?;
+        }
+        let to = Box::new(parser.parse_node()?);
+        Ok(Self::Range {
+            from,
+            to,
+            inclusive,
+        })
+    }
+}
+
+impl Display for CaseValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Single(value) => write!(f, "{value}"),
+            Self::Range {
+                from,
+                to,
+                inclusive,
+            } => write!(f, "{from}..{}{to}", if *inclusive { "=" } else { "" }),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum CasePattern {
+    Values(Vec<Node<CaseValue>>),
+    // `x if ..` names the switched on value for the guard and the arm
+    Binding(Node<Ident>),
+    Variant(Node<VariantPattern>),
+    Wrapper(Node<WrapperPattern>),
+    Default,
+}
+
+impl CasePattern {
+    // an identifier followed by a guard binds instead of being compared against
+    fn is_binding(parser: &mut crate::parser::parser::Parser) -> crate::aliases::Result<bool> {
+        if !matches!(parser.peek()?, tt!(ident)) {
+            return Ok(false);
+        }
+
+        let mut fork = parser.fork();
+        fork.consume::<t!(ident)>()?;
+        Ok(*fork.peek()? == tt!(if))
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct CaseStmt {
-    expr: Option<Node<Expr>>,
+    pattern: CasePattern,
+    guard: Option<Node<Expr>>,
     stmt: Stmt,
 }
 
 impl Parse for CaseStmt {
     fn parse(parser: &mut crate::parser::parser::Parser) -> crate::aliases::Result<Self> {
-        let expr = if let tt!(.) = parser.peek()? {
+        let pattern = if let tt!(.) = parser.peek()? {
             parser.consume::<t!(.)>()?;
-            None
+            CasePattern::Default
+        } else if let tt!(some) | tt!(none) | tt!(ok) | tt!(err) = parser.peek()? {
+            CasePattern::Wrapper(parser.parse_node()?)
+        } else if CasePattern::is_binding(parser)? {
+            CasePattern::Binding(parser.parse_node()?)
         } else {
-            Some(parser.parse_node()?)
+            let mut fork = parser.fork();
+            let is_variant = fork.parse_path(true).is_ok() && *fork.peek()? == tt!(::);
+            if is_variant {
+                CasePattern::Variant(parser.parse_node()?)
+            } else {
+                let mut values = vec![parser.parse_node()?];
+                while let tt!(,) = parser.peek()? {
+                    parser.consume::<t!(,)>()?;
+                    values.push(parser.parse_node()?);
+                }
+                CasePattern::Values(values)
+            }
         };
 
+        let mut guard = None;
+        if !matches!(pattern, CasePattern::Default) && *parser.peek()? == tt!(if) {
+            parser.consume::<t!(if)>()?;
+            guard = Some(parser.parse_node()?);
+        }
+
         parser.consume::<t!(->)>()?;
         let stmt = parser.parse()?;
         //parser.consume::<t!(,)>()?;
 
-        Ok(Self { expr, stmt })
+        Ok(Self {
+            pattern,
+            guard,
+            stmt,
+        })
     }
 }
 
+// the constant values unguarded arms matched so far; ints and chars as
+// inclusive ranges of their values
+#[derive(Default)]
+struct MatchedValues {
+    ints: Vec<(i128, i128)>,
+    others: Vec<Lit>,
+}
+
+impl MatchedValues {
+    fn ordinal(lit: &Lit) -> Option<i128> {
+        match lit {
+            Lit::LitInt(v) => Some(*v as i64 as i128),
+            Lit::LitSized(s) => Some(s.value),
+            Lit::LitChar(c) => Some(*c as i128),
+            _ => None,
+        }
+    }
+
+    // false if an earlier arm already matches some of `bounds`
+    fn insert(&mut self, bounds: &[Lit], inclusive: bool) -> bool {
+        let ordinals = bounds.iter().map(Self::ordinal).collect::<Option<Vec<_>>>();
+        match (ordinals.as_deref(), bounds) {
+            (Some(&[v]), _) => self.insert_ints(v, v),
+            (Some(&[low, high]), _) if inclusive => self.insert_ints(low, high),
+            (Some(&[low, high]), _) => match high.checked_sub(1) {
+                Some(high) => self.insert_ints(low, high),
+                None => true,
+            },
+            (_, [value]) if self.others.contains(value) => false,
+            (_, [value]) => {
+                self.others.push(value.clone());
+                true
+            }
+            // only int and char ranges are checked for overlaps
+            _ => true,
+        }
+    }
+
+    fn insert_ints(&mut self, low: i128, high: i128) -> bool {
+        if low > high {
+            return true;
+        }
+
+        let overlaps = self.ints.iter().any(|&(l, h)| low <= h && l <= high);
+        self.ints.push((low, high));
+        !overlaps
+    }
+}
+
+// smallest number of values worth a jump table, and the most entries one may have
+const JUMP_TABLE_MIN_VALUES: i128 = 4;
+const JUMP_TABLE_MAX_LEN: i128 = 1024;
+
+// used as a value, every arm has to evaluate to one; `pattern -> expr;` arms
+// give the value of the expression
 #[derive(Debug, Clone)]
 pub struct SwitchStmt {
     expr: Node<Expr>,
-    cases: Vec<CaseStmt>,
+    cases: Vec<Node<CaseStmt>>,
 }
 
 impl Parse for SwitchStmt {
@@ -47,7 +384,7 @@ impl Parse for SwitchStmt {
             if let tt!("}") | tt!(eof) = parser.peek()? {
                 break;
             }
-            match parser.parse::<CaseStmt>() {
+            match parser.parse_node::<CaseStmt>() {
                 Ok(case) => cases.push(case),
                 Err(e) => parser.report_error(e, &[tt!(,)])?,
             }
@@ -60,8 +397,39 @@ impl Parse for SwitchStmt {
 
 impl Analyze for SwitchStmt {
     fn build(&self, builder: &mut crate::semantic_analyzer::scope_builder::ScopeBuilder) {
+        self.expr.build(builder);
         for case in &self.cases {
             builder.push_scope(crate::semantic_analyzer::scope::ScopeKind::Block);
+            if let CasePattern::Values(values) = &case.pattern {
+                for value in values {
+                    let (from, to, _) = value.bounds();
+                    from.build(builder);
+                    if let Some(to) = to {
+                        to.build(builder);
+                    }
+                }
+            }
+            if let CasePattern::Binding(binding) = &case.pattern {
+                if binding.value() != "_" {
+                    let type_info = builder.register_type(&TypeSpanned::dummy(Type::unknown()));
+                    builder.define_var(binding, type_info);
+                }
+            }
+            if let CasePattern::Variant(pattern) = &case.pattern {
+                for (_, binding) in pattern.bound() {
+                    let type_info = builder.register_type(&TypeSpanned::dummy(Type::unknown()));
+                    builder.define_var(binding, type_info);
+                }
+            }
+            if let CasePattern::Wrapper(pattern) = &case.pattern {
+                if let Some(binding) = pattern.bound() {
+                    let type_info = builder.register_type(&TypeSpanned::dummy(Type::unknown()));
+                    builder.define_var(binding, type_info);
+                }
+            }
+            if let Some(guard) = &case.guard {
+                guard.build(builder);
+            }
             case.stmt.build(builder);
             builder.pop_scope();
         }
@@ -71,81 +439,501 @@ impl Analyze for SwitchStmt {
         &self,
         analyzer: &mut crate::semantic_analyzer::analyzer::Analyzer,
     ) -> crate::semantic_analyzer::return_status::ReturnStatus {
+        self.analyze_arms(analyzer, None)
+    }
+}
+
+impl SwitchStmt {
+    pub fn matched(&self) -> &Node<Expr> {
+        &self.expr
+    }
+
+    pub fn has_value(&self) -> bool {
+        !self.cases.is_empty()
+            && self
+                .cases
+                .iter()
+                .all(|case| matches!(case.stmt, Stmt::Expr(_)) || case.stmt.has_value())
+    }
+
+    pub fn analyze_value(&self, analyzer: &mut Analyzer, unifier: &mut BranchUnifier) -> ReturnStatus {
+        self.analyze_arms(analyzer, Some(unifier))
+    }
+
+    fn analyze_arm(
+        case: &Node<CaseStmt>,
+        analyzer: &mut Analyzer,
+        unifier: Option<&mut BranchUnifier>,
+    ) -> ReturnStatus {
+        let Some(unifier) = unifier else {
+            return case.stmt.analyze_semantics(analyzer);
+        };
+
+        match &case.stmt {
+            Stmt::Expr(expr) => {
+                if let Some(expected) = unifier.expected() {
+                    analyzer.expect(expected);
+                }
+                let value = analyzer.resolve_expr(expr);
+                unifier.add(true, ReturnStatus::Never, value, expr.span(), analyzer);
+                ReturnStatus::Never
+            }
+            stmt if stmt.has_value() => stmt.analyze_value(analyzer, unifier),
+            stmt => {
+                let status = stmt.analyze_semantics(analyzer);
+                unifier.add(false, status, None, case.span(), analyzer);
+                status
+            }
+        }
+    }
+
+    // with a unifier, the arms are analyzed as values of it
+    fn analyze_arms(&self, analyzer: &mut Analyzer, mut unifier: Option<&mut BranchUnifier>) -> ReturnStatus {
         let Some(main_expr_ty) = analyzer.resolve_expr(&self.expr) else {
             analyzer.report_semantic_error(SemanticError::UnresolvedType, self.expr.span());
             return ReturnStatus::Never;
         };
 
-        let has_default = self.cases.iter().any(|case| case.expr.is_none());
-        let mut return_status = if has_default { ReturnStatus::Always } else { ReturnStatus::Never };
+        // the variants of the enum or optional being matched on, with whether
+        // an unguarded arm covers them so far
+        let mut matched_variants: Option<Vec<(String, bool)>> = match &main_expr_ty.inner {
+            TypeInner::Base(BaseType::Custom(c)) => analyzer.get_enum(c.name()).map(|info| {
+                info.variants
+                    .iter()
+                    .map(|v| (format!("{}::{}", c.name(), v.name), false))
+                    .collect()
+            }),
+            TypeInner::Optional(_) => Some(vec![("none".into(), false), ("some".into(), false)]),
+            TypeInner::Result(_, _) => Some(vec![("ok".into(), false), ("err".into(), false)]),
+            _ => None,
+        };
+        let mut matched_values = MatchedValues::default();
+
+        let has_default = self
+            .cases
+            .iter()
+            .any(|case| matches!(case.pattern, CasePattern::Default));
+        let mut return_status = ReturnStatus::Always;
 
         for case in &self.cases {
             analyzer.enter_scope();
-            if let Some(case_expr) = &case.expr {
-                let Some(case_expr_ty) = analyzer.resolve_expr(&case_expr) else {
-                    analyzer.report_semantic_error(SemanticError::UnresolvedType, case_expr.span());
-                    analyzer.exit_scope();
-                    continue;
-                };
+            let guarded = case.guard.is_some();
+            let analyzed = match &case.pattern {
+                CasePattern::Values(values) => {
+                    for value in values {
+                        Self::analyze_case_value(value, &main_expr_ty, guarded, &mut matched_values, analyzer);
+                    }
+                    true
+                }
+                CasePattern::Binding(binding) => {
+                    if binding.value() != "_" {
+                        get_symbol_mut!((analyzer, binding) var {
+                            var.infer_type(&main_expr_ty);
+                            if let SymbolInfoKind::Variable(ref mut v) = var.kind {
+                                v.initialized = true;
+                            }
+                        });
+                    }
+                    true
+                }
+                CasePattern::Variant(pattern) => {
+                    match Self::analyze_pattern(pattern, &main_expr_ty, analyzer) {
+                        Some(idx) if !guarded => {
+                            Self::cover(&mut matched_variants, idx, pattern.span(), analyzer);
+                            true
+                        }
+                        Some(_) => true,
+                        None => false,
+                    }
+                }
+                CasePattern::Wrapper(pattern) => {
+                    let analyzed = pattern.analyze(&main_expr_ty, pattern.span(), analyzer);
+                    if analyzed && !guarded {
+                        Self::cover(&mut matched_variants, pattern.variant(), pattern.span(), analyzer);
+                    }
+                    analyzed
+                }
+                CasePattern::Default => true,
+            };
+
+            if !analyzed {
+                analyzer.exit_scope();
+                continue;
+            }
 
-                if !case_expr_ty.compatible(&main_expr_ty) {
-                    analyzer.report_semantic_error(
-                        SemanticError::SwitchTypeMismatch(case_expr_ty, main_expr_ty.clone()),
-                        case_expr.span(),
-                    );
+            if let Some(guard) = &case.guard {
+                if let Some(guard_ty) = analyzer.resolve_expr(guard) {
+                    if !guard_ty.compatible(&Type::bool()) {
+                        analyzer.report_semantic_error(SemanticError::IfTypeMismatch(guard_ty), guard.span());
+                    }
                 }
             }
 
-            let status = case.stmt.analyze_semantics(analyzer);
+            let status = Self::analyze_arm(case, analyzer, unifier.as_deref_mut());
             return_status = return_status.intersect(status);
             analyzer.exit_scope();
         }
 
+        let mut exhaustive = has_default;
+        if let (Some(matched), false) = (&matched_variants, has_default) {
+            let missing: Vec<_> = matched
+                .iter()
+                .filter(|(_, covered)| !covered)
+                .map(|(name, _)| name.clone())
+                .collect();
+
+            if !missing.is_empty() {
+                analyzer.report_semantic_error(
+                    SemanticError::NonExhaustiveMatch(missing),
+                    self.expr.span(),
+                );
+            }
+            exhaustive = true;
+        }
+
+        if !exhaustive {
+            // there'd be no value when nothing matches
+            if unifier.is_some() {
+                analyzer.report_semantic_error(SemanticError::MissingDefaultArm, self.expr.span());
+            }
+            return_status = return_status.intersect(ReturnStatus::Never);
+        }
+
         return_status
     }
+
+    // marks a variant as handled by an unguarded arm; a second one could never match
+    fn cover(
+        matched_variants: &mut Option<Vec<(String, bool)>>,
+        idx: VariantIndex,
+        span: crate::lexer::span::Span,
+        analyzer: &mut Analyzer,
+    ) {
+        let Some(matched) = matched_variants else {
+            return;
+        };
+
+        let (name, covered) = &mut matched[idx.0 as usize];
+        if *covered {
+            analyzer.report_semantic_error(SemanticError::DuplicateArm(name.clone()), span);
+        }
+        *covered = true;
+    }
+
+    // checks a value or range arm against the switched on value. constant
+    // ones are checked against earlier arms and kept for the jump table
+    fn analyze_case_value(
+        value: &Node<CaseValue>,
+        main_expr_ty: &crate::aliases::TypeInfoRc,
+        guarded: bool,
+        matched_values: &mut MatchedValues,
+        analyzer: &mut Analyzer,
+    ) {
+        let (from, to, inclusive) = value.bounds();
+        let mut types = vec![];
+        for expr in std::iter::once(from).chain(to) {
+            analyzer.expect(&main_expr_ty.ttype);
+            let Some(ty) = analyzer.resolve_expr(expr) else {
+                analyzer.report_semantic_error(SemanticError::UnresolvedType, expr.span());
+                return;
+            };
+
+            if !ty.compatible(main_expr_ty) {
+                analyzer.report_semantic_error(
+                    SemanticError::SwitchTypeMismatch(ty, main_expr_ty.clone()),
+                    expr.span(),
+                );
+                return;
+            }
+            types.push(ty);
+        }
+
+        let ordered = main_expr_ty.ttype.is_int()
+            || [Type::float(), Type::str(), Type::char()]
+                .iter()
+                .any(|ty| main_expr_ty.compatible(ty));
+        if to.is_some() && !ordered {
+            analyzer.report_semantic_error(
+                SemanticError::UnorderedRangeArm(main_expr_ty.clone()),
+                value.span(),
+            );
+            return;
+        }
+
+        let mut bounds = vec![];
+        for (expr, ty) in std::iter::once(from).chain(to).zip(&types) {
+            let Ok(lit) = ConstEvaluator::eval(expr, analyzer) else {
+                return;
+            };
+            if let Lit::LitInt(_) | Lit::LitSized(_) = lit {
+                analyzer.register_constant_value(expr.id(), &lit, &ty.ttype);
+            }
+            bounds.push(lit);
+        }
+
+        if !guarded && !matched_values.insert(&bounds, inclusive) {
+            analyzer.report_semantic_error(SemanticError::DuplicateArm(value.to_string()), value.span());
+        }
+    }
+
+    // checks a variant arm against the switched on value and types its
+    // bindings; returns the variant if the arm's body can be analyzed
+    fn analyze_pattern(
+        pattern: &Node<VariantPattern>,
+        main_expr_ty: &crate::aliases::TypeInfoRc,
+        analyzer: &mut crate::semantic_analyzer::analyzer::Analyzer,
+    ) -> Option<VariantIndex> {
+        let name = pattern.enum_name.value();
+        let Some(info) = analyzer.get_enum(name) else {
+            let error = if analyzer.is_type_defined(name) {
+                SemanticError::NotAnEnum(pattern.enum_name.clone())
+            } else {
+                SemanticError::UndefinedType(name.clone())
+            };
+            analyzer.report_semantic_error(error, pattern.enum_name.span());
+            return None;
+        };
+
+        let pattern_ty = analyzer.register_type(&TypeSpanned {
+            ty: Type::custom(name),
+            span: pattern.span(),
+        });
+        if !pattern_ty.compatible(main_expr_ty) {
+            analyzer.report_semantic_error(
+                SemanticError::SwitchTypeMismatch(pattern_ty, main_expr_ty.clone()),
+                pattern.span(),
+            );
+            return None;
+        }
+
+        let Some((idx, variant)) = info.variant(pattern.variant.value()) else {
+            analyzer.report_semantic_error(
+                SemanticError::UnknownVariant(pattern.variant.clone(), name.clone()),
+                pattern.variant.span(),
+            );
+            return None;
+        };
+
+        if pattern.bindings.len() != variant.fields.len() {
+            analyzer.report_semantic_error(
+                SemanticError::BindingCountMismatch(
+                    pattern.variant.clone(),
+                    pattern.bindings.len(),
+                    variant.fields.len(),
+                ),
+                pattern.span(),
+            );
+            return None;
+        }
+
+        for (i, binding) in pattern.bound() {
+            get_symbol_mut!((analyzer, binding) var {
+                var.infer_type(&variant.fields[i]);
+                if let SymbolInfoKind::Variable(ref mut v) = var.kind {
+                    v.initialized = true;
+                }
+            });
+            analyzer.register_field(binding.id(), info.field_index(idx, i));
+        }
+
+        analyzer.register_variant(pattern.id(), idx);
+        Some(idx)
+    }
 }
 
 impl Generate for SwitchStmt {
-    fn generate(&self, generator: &mut crate::generator::generator::Generator) {
+    fn generate(&self, generator: &mut Generator) {
+        self.generate_arms(generator, false);
+    }
+}
+
+impl SwitchStmt {
+    // leaves the value of the matching arm on the operand stack
+    pub fn generate_value(&self, generator: &mut Generator) {
+        self.generate_arms(generator, true);
+    }
+
+    fn generate_arms(&self, generator: &mut Generator, value: bool) {
         generator.gen_expr(&self.expr);
 
         let end_label = generator.create_label();
+        let no_match = generator.create_label();
         let case_labels: Vec<_> = self
             .cases
             .iter()
             .map(|_| generator.create_label())
             .collect();
+        // where the tests go on when a guard turns its arm down
+        let resume_labels: Vec<_> = self
+            .cases
+            .iter()
+            .map(|case| case.guard.as_ref().map(|_| generator.create_label()))
+            .collect();
 
-        for (i, case) in self.cases.iter().enumerate() {
-            let Some(case_expr) = &case.expr else {
-                generator.push_instruction(Instruction::JMP(case_labels[i]));
-                continue;
-            };
+        if let Some(table) = self.jump_table(generator, &case_labels, no_match) {
             generator.push_instruction(Instruction::DUP);
-            generator.gen_expr(case_expr);
-            generator.push_instruction(Instruction::EQ);
-            generator.push_instruction(Instruction::JMP_IF_TRUE(
-                case_labels[i],
-            ));
+            generator.push_jump_table(table);
+        } else {
+            for (i, case) in self.cases.iter().enumerate() {
+                Self::generate_test(&case.pattern, case_labels[i], generator);
+                if let Some(resume) = resume_labels[i] {
+                    generator.place_label(resume);
+                }
+            }
         }
 
+        generator.place_label(no_match);
         generator.push_instruction(Instruction::POP);
         generator.push_instruction(Instruction::JMP(end_label));
 
         for (i, case) in self.cases.iter().enumerate() {
             generator.place_label(case_labels[i]);
+            if let CasePattern::Binding(binding) = &case.pattern {
+                if binding.value() != "_" {
+                    generator.push_instruction(Instruction::DUP);
+                    let local = generator.get_local_mapping(binding.id());
+                    generator.push_instruction(Instruction::STORE_LOCAL(local));
+                }
+            }
+            if let CasePattern::Variant(pattern) = &case.pattern {
+                for (_, binding) in pattern.bound() {
+                    generator.push_instruction(Instruction::DUP);
+                    let idx = generator.get_field_mapping(binding.id());
+                    generator.push_instruction(Instruction::FIELD(idx));
+                    let local = generator.get_local_mapping(binding.id());
+                    generator.push_instruction(Instruction::STORE_LOCAL(local));
+                }
+            }
+            if let CasePattern::Wrapper(pattern) = &case.pattern {
+                if let Some(binding) = pattern.bound() {
+                    generator.push_instruction(Instruction::DUP);
+                    generator.push_instruction(Instruction::FIELD(pattern.field()));
+                    let local = generator.get_local_mapping(binding.id());
+                    generator.push_instruction(Instruction::STORE_LOCAL(local));
+                }
+            }
+            if let (Some(guard), Some(resume)) = (&case.guard, resume_labels[i]) {
+                generator.gen_expr(guard);
+                generator.push_instruction(Instruction::JMP_IF_FALSE(resume));
+            }
             generator.push_instruction(Instruction::POP);
-            case.stmt.generate(generator);
+            match &case.stmt {
+                Stmt::Expr(expr) if value => generator.gen_expr(expr),
+                stmt if value => stmt.generate_value(generator),
+                stmt => stmt.generate(generator),
+            }
             generator.push_instruction(Instruction::JMP(end_label));
         }
 
         generator.place_label(end_label);
     }
+
+    // jumps to `label` when the switched on value matches `pattern`
+    fn generate_test(pattern: &CasePattern, label: Label, generator: &mut Generator) {
+        match pattern {
+            CasePattern::Values(values) => {
+                for value in values {
+                    let (from, to, inclusive) = value.bounds();
+                    generator.push_instruction(Instruction::DUP);
+                    generator.gen_expr(from);
+                    let Some(to) = to else {
+                        generator.push_instruction(Instruction::EQ);
+                        generator.push_instruction(Instruction::JMP_IF_TRUE(label));
+                        continue;
+                    };
+
+                    let below = generator.create_label();
+                    generator.push_instruction(Instruction::GTE);
+                    generator.push_instruction(Instruction::JMP_IF_FALSE(below));
+                    generator.push_instruction(Instruction::DUP);
+                    generator.gen_expr(to);
+                    generator.push_instruction(if inclusive { Instruction::LTE } else { Instruction::LT });
+                    generator.push_instruction(Instruction::JMP_IF_TRUE(label));
+                    generator.place_label(below);
+                }
+            }
+            CasePattern::Variant(pattern) => {
+                generator.push_instruction(Instruction::DUP);
+                let idx = generator.get_variant_mapping(pattern.id());
+                generator.push_instruction(Instruction::IS_VARIANT(idx));
+                generator.push_instruction(Instruction::JMP_IF_TRUE(label));
+            }
+            CasePattern::Wrapper(pattern) => {
+                generator.push_instruction(Instruction::DUP);
+                generator.push_instruction(Instruction::IS_VARIANT(pattern.variant()));
+                generator.push_instruction(Instruction::JMP_IF_TRUE(label));
+            }
+            // the guard decides, if there is one
+            CasePattern::Binding(_) | CasePattern::Default => {
+                generator.push_instruction(Instruction::JMP(label));
+            }
+        }
+    }
+
+    // an int compared against enough unguarded constants packed closely
+    // together jumps straight to the matching arm instead
+    fn jump_table(&self, generator: &Generator, case_labels: &[Label], no_match: Label) -> Option<JumpTable> {
+        let const_int = |expr: &Node<Expr>| match generator.is_const(expr.id()) {
+            true => match generator.get_const_value(expr.id()) {
+                Lit::LitInt(v) => Some(*v as i64 as i128),
+                Lit::LitSized(s) => Some(s.value),
+                _ => None,
+            },
+            false => None,
+        };
+
+        let mut default = no_match;
+        let mut arms = vec![];
+        for (case, label) in self.cases.iter().zip(case_labels) {
+            match &case.pattern {
+                // nothing after it is reachable
+                CasePattern::Default => {
+                    default = *label;
+                    break;
+                }
+                CasePattern::Values(values) if case.guard.is_none() => {
+                    for value in values {
+                        let (from, to, inclusive) = value.bounds();
+                        let low = const_int(from)?;
+                        let high = match to {
+                            Some(to) if inclusive => const_int(to)?,
+                            Some(to) => const_int(to)? - 1,
+                            None => low,
+                        };
+                        arms.push((low, high, *label));
+                    }
+                }
+                _ => return None,
+            }
+        }
+
+        let covered: i128 = arms.iter().map(|(low, high, _)| (high - low + 1).max(0)).sum();
+        let low = arms.iter().map(|arm| arm.0).min()?;
+        let high = arms.iter().map(|arm| arm.1).max()?;
+        let len = high - low + 1;
+        if covered < JUMP_TABLE_MIN_VALUES || len > JUMP_TABLE_MAX_LEN || len > covered * 2 {
+            return None;
+        }
+
+        // the first arm matching a value wins it
+        let mut targets = vec![None; len as usize];
+        for (from, to, label) in arms {
+            for v in from..=to {
+                targets[(v - low) as usize].get_or_insert(label);
+            }
+        }
+
+        Some(JumpTable {
+            low: i64::try_from(low).ok()?,
+            default,
+            targets: targets.into_iter().map(|t| t.unwrap_or(default)).collect(),
+        })
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::SwitchStmt;
+    use super::{CasePattern, CaseValue, SwitchStmt};
     use crate::{lexer::Lexer, parser::parser::Parser, source::SourceFile};
     use std::io::Cursor;
 
@@ -157,6 +945,48 @@ mod tests {
         parser.parse::<SwitchStmt>().unwrap();
     }
 
+    #[test]
+    fn test_switch_variant_patterns() {
+        let source = SourceFile::new(Cursor::new(
+            "switch s { Shape::Circle(r) -> return r; Shape::Rect(w, _) -> return w; Shape::Empty -> return 0f; }",
+        ))
+        .unwrap();
+        let mut parser = Parser::new(Lexer::new(&source).unwrap());
+        let switch = parser.parse::<SwitchStmt>().unwrap();
+        let CasePattern::Variant(rect) = &switch.cases[1].pattern else {
+            panic!("expected variant pattern");
+        };
+        assert_eq!(rect.variant.value().as_str(), "Rect");
+        assert_eq!(rect.bindings.len(), 2);
+        assert_eq!(rect.bound().count(), 1);
+        assert!(matches!(&switch.cases[2].pattern, CasePattern::Variant(p) if p.bindings.is_empty()));
+    }
+
+    #[test]
+    fn test_switch_arm_patterns() {
+        let source = SourceFile::new(Cursor::new(
+            "switch x { 1, 2, 3 -> return 1; 10..20 -> return 2; 20..=30, 40 if y -> return 3; x if x > 5 -> return x; . -> return 0; }",
+        ))
+        .unwrap();
+        let mut parser = Parser::new(Lexer::new(&source).unwrap());
+        let switch = parser.parse::<SwitchStmt>().unwrap();
+
+        assert!(matches!(&switch.cases[0].pattern, CasePattern::Values(v) if v.len() == 3));
+        let CasePattern::Values(range) = &switch.cases[1].pattern else {
+            panic!("expected a range arm");
+        };
+        assert!(matches!(&*range[0], CaseValue::Range { inclusive: false, .. }));
+        let CasePattern::Values(values) = &switch.cases[2].pattern else {
+            panic!("expected value arms");
+        };
+        assert!(matches!(&*values[0], CaseValue::Range { inclusive: true, .. }));
+        assert!(matches!(&*values[1], CaseValue::Single(_)));
+        assert!(switch.cases[2].guard.is_some());
+        assert!(matches!(&switch.cases[3].pattern, CasePattern::Binding(b) if b.value() == "x"));
+        assert!(switch.cases[3].guard.is_some());
+        assert!(matches!(switch.cases[4].pattern, CasePattern::Default));
+    }
+
     #[test]
     fn test_switch_stmt_empty() {
         let source = SourceFile::new(Cursor::new("switch x {}")).unwrap();
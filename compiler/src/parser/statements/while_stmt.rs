@@ -1,6 +1,6 @@
 use crate::generator::compile_trait::Generate;
 use crate::generator::instruction::Instruction;
-use crate::lexer::tokens::Lit;
+use crate::lexer::tokens::{Ident, Lit};
 use crate::parser::node::Node;
 use crate::parser::parser::Parser;
 use crate::parser::types::Type;
@@ -8,30 +8,33 @@ use crate::parser::{expr::Expr, parse_trait::Parse};
 use crate::semantic_analyzer::analyze_trait::Analyze;
 use crate::semantic_analyzer::err::SemanticError;
 use crate::semantic_analyzer::return_status::ReturnStatus;
-use crate::semantic_analyzer::scope::ScopeKind;
 use crate::t;
 
+use super::jump_stmt::parse_loop_label;
 use super::BlockStmt;
 
 #[derive(Debug, Clone)]
 pub struct WhileStmt {
+    label: Option<Node<Ident>>,
     expr: Node<Expr>,
     block: BlockStmt,
 }
 
 impl Parse for WhileStmt {
     fn parse(parser: &mut Parser) -> crate::aliases::Result<Self> {
+        let label = parse_loop_label(parser)?;
         parser.consume::<t!(while)>()?;
         let expr = parser.parse_node::<Expr>()?;
         let block = parser.parse::<BlockStmt>()?;
 
-        Ok(Self { expr, block })
+        Ok(Self { label, expr, block })
     }
 }
 
 impl Analyze for WhileStmt {
     fn build(&self, builder: &mut crate::semantic_analyzer::scope_builder::ScopeBuilder) {
-        builder.push_scope(ScopeKind::Loop);
+        builder.push_loop_scope(self.label.as_ref());
+        self.expr.build(builder);
         self.block.build(builder);
         builder.pop_scope();
     }
@@ -51,12 +54,13 @@ impl Analyze for WhileStmt {
         }
 
         let block_status = self.block.analyze_semantics(analyzer);
+        let has_break = analyzer.loop_has_break();
         analyzer.exit_scope();
 
         let is_infinite_loop = match &*self.expr {
             Expr::Literal(lit) => matches!(lit.value(), Lit::LitBool(true)),
             _ => false,
-        };
+        } && !has_break;
 
         if is_infinite_loop {
             block_status
@@ -78,7 +82,9 @@ impl Generate for WhileStmt {
         generator.push_instruction(crate::generator::instruction::Instruction::JMP_IF_FALSE(
             loop_end,
         ));
+        generator.push_loop(self.label.as_ref(), loop_end, loop_start);
         self.block.generate(generator);
+        generator.pop_loop();
         generator.push_instruction(Instruction::JMP(loop_start));
         generator.place_label(loop_end);
     }
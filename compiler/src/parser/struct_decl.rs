@@ -0,0 +1,121 @@
+use super::{node::Node, parser::Parser, types::TypeSpanned, Parse};
+use crate::{
+    aliases::Result,
+    generator::compile_trait::Generate,
+    lexer::tokens::Ident,
+    semantic_analyzer::{analyze_trait::Analyze, return_status::ReturnStatus},
+    t, tt,
+};
+
+#[derive(Debug, Clone)]
+pub struct FieldDecl {
+    pub name: Node<Ident>,
+    pub ttype: TypeSpanned,
+}
+
+impl Parse for FieldDecl {
+    fn parse(parser: &mut Parser) -> Result<Self> {
+        let name = parser.parse_node()?;
+        parser.consume::<t!(:)>()?;
+        let ttype = parser.parse()?;
+        Ok(Self { name, ttype })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct StructDecl {
+    pub name: Node<Ident>,
+    pub fields: Vec<Node<FieldDecl>>,
+}
+
+impl Parse for StructDecl {
+    fn parse(parser: &mut Parser) -> Result<Self> {
+        parser.consume::<t!(struct)>()?;
+        let name = parser.parse_node()?;
+        parser.consume::<t!("{")>()?;
+
+        let mut fields = vec![];
+        loop {
+            if let tt!("}") = parser.peek()? {
+                break;
+            }
+
+            fields.push(parser.parse_node::<FieldDecl>()?);
+
+            if let tt!(,) = parser.peek()? {
+                parser.consume::<t!(,)>()?;
+            } else {
+                break;
+            }
+        }
+
+        parser.consume::<t!("}")>()?;
+
+        Ok(Self { name, fields })
+    }
+}
+
+impl StructDecl {
+    pub fn declare(&self, builder: &mut crate::semantic_analyzer::scope_builder::ScopeBuilder) {
+        builder.declare_struct(&self.name);
+    }
+}
+
+impl Analyze for StructDecl {
+    fn build(&self, builder: &mut crate::semantic_analyzer::scope_builder::ScopeBuilder) {
+        let fields = self
+            .fields
+            .iter()
+            .map(|field| (field.name.clone(), builder.register_type(&field.ttype)))
+            .collect();
+
+        builder.define_struct(&self.name, fields);
+    }
+
+    fn analyze_semantics(
+        &self,
+        analyzer: &mut crate::semantic_analyzer::analyzer::Analyzer,
+    ) -> ReturnStatus {
+        analyzer.check_struct_recursion(&self.name);
+        ReturnStatus::Never
+    }
+}
+
+impl Generate for StructDecl {
+    fn generate(&self, _generator: &mut crate::generator::generator::Generator) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::StructDecl;
+    use crate::{lexer::Lexer, parser::parser::Parser, parser::types::Type, source::SourceFile};
+    use std::io::Cursor;
+
+    fn parse_struct(input: &str) -> StructDecl {
+        let source = SourceFile::new(Cursor::new(input)).unwrap();
+        let mut parser = Parser::new(Lexer::new(&source).unwrap());
+        parser.parse::<StructDecl>().unwrap()
+    }
+
+    #[test]
+    fn test_simple_struct() {
+        let decl = parse_struct("struct Point { x: int, y: int }");
+        assert_eq!(decl.name.value().as_str(), "Point");
+        assert_eq!(decl.fields.len(), 2);
+        assert_eq!(decl.fields[0].name.value().as_str(), "x");
+        assert_eq!(decl.fields[1].ttype.ty, Type::int());
+    }
+
+    #[test]
+    fn test_struct_trailing_comma() {
+        let decl = parse_struct("struct Node { value: float, next: boxed Node, }");
+        assert_eq!(decl.fields.len(), 2);
+        assert_eq!(decl.fields[1].ttype.ty, Type::boxed(Type::custom("Node")));
+    }
+
+    #[test]
+    fn test_empty_struct() {
+        let decl = parse_struct("struct Unit {}");
+        assert!(decl.fields.is_empty());
+    }
+}
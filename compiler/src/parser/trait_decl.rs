@@ -0,0 +1,172 @@
+use super::{
+    func_decl::{FuncDelc, Param},
+    generics::TypeArgs,
+    node::Node,
+    parser::Parser,
+    types::{Type, TypeInner, TypeSpanned},
+    Parse,
+};
+use crate::{
+    aliases::Result,
+    err::HarpyError,
+    generator::compile_trait::Generate,
+    lexer::tokens::Ident,
+    semantic_analyzer::{analyze_trait::Analyze, err::SemanticError, return_status::ReturnStatus},
+    t, tt,
+};
+
+// `fn name(&self, ...) -> T;`, the receiver is left out of `params`
+#[derive(Debug, Clone)]
+pub struct MethodSig {
+    pub name: Node<Ident>,
+    pub mutable_self: bool,
+    pub params: Vec<Node<Param>>,
+    pub return_type: TypeSpanned,
+}
+
+impl Parse for MethodSig {
+    fn parse(parser: &mut Parser) -> Result<Self> {
+        parser.consume::<t!(fn)>()?;
+        let name: Node<Ident> = parser.parse_node()?;
+        parser.consume::<t!("(")>()?;
+        let mut params = vec![];
+        if let tt!(ident) | tt!(&) = parser.peek()? {
+            FuncDelc::parse_params(parser, &mut params)?;
+        }
+        parser.consume::<t!(")")>()?;
+
+        let mut return_type = TypeSpanned::dummy(Type::void());
+        if let tt!(->) = parser.peek()? {
+            parser.consume::<t!(->)>()?;
+            return_type = parser.parse::<TypeSpanned>()?;
+        }
+        parser.consume::<t!(;)>()?;
+
+        if !params.first().is_some_and(|p| p.is_self()) {
+            return HarpyError::semantic(SemanticError::MissingSelf(name.clone()), name.span());
+        }
+
+        // a trait object can only lend out the value it points at
+        let receiver = params.remove(0);
+        let TypeInner::Ref(pointee) = &receiver.ttype.ty.inner else {
+            return HarpyError::semantic(SemanticError::TraitSelfByValue(name.clone()), name.span());
+        };
+
+        Ok(Self {
+            mutable_self: pointee.mutable,
+            name,
+            params,
+            return_type,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TraitDecl {
+    pub name: Node<Ident>,
+    pub methods: Vec<Node<MethodSig>>,
+}
+
+impl TraitDecl {
+    pub fn declare(&self, builder: &mut crate::semantic_analyzer::scope_builder::ScopeBuilder) {
+        builder.declare_trait(&self.name);
+    }
+}
+
+impl Parse for TraitDecl {
+    fn parse(parser: &mut Parser) -> Result<Self> {
+        parser.consume::<t!(trait)>()?;
+        let name: Node<Ident> = parser.parse_node()?;
+        parser.consume::<t!("{")>()?;
+
+        // `Self` is whichever type the method gets called on
+        let this = Type::dyn_trait(&parser.module().qualify(name.value()));
+        parser.set_type_args(TypeArgs::from([("Self".to_owned(), this)]));
+        let mut methods = vec![];
+        let parsed = loop {
+            if let Ok(tt!("}")) = parser.peek() {
+                break Ok(());
+            }
+            match parser.parse_node::<MethodSig>() {
+                Ok(method) => methods.push(method),
+                Err(e) => break Err(e),
+            }
+        };
+        parser.set_type_args(Default::default());
+        parsed?;
+        parser.consume::<t!("}")>()?;
+
+        Ok(Self { name, methods })
+    }
+}
+
+impl Analyze for TraitDecl {
+    fn build(&self, builder: &mut crate::semantic_analyzer::scope_builder::ScopeBuilder) {
+        let methods = self
+            .methods
+            .iter()
+            .map(|method| {
+                let params = method
+                    .params
+                    .iter()
+                    .map(|param| builder.register_type(&param.ttype))
+                    .collect();
+                let return_type = builder.register_type(&method.return_type);
+                (method.name.clone(), method.mutable_self, params, return_type)
+            })
+            .collect();
+
+        builder.define_trait(&self.name, methods);
+    }
+
+    fn analyze_semantics(
+        &self,
+        _analyzer: &mut crate::semantic_analyzer::analyzer::Analyzer,
+    ) -> ReturnStatus {
+        ReturnStatus::Never
+    }
+}
+
+impl Generate for TraitDecl {
+    fn generate(&self, _generator: &mut crate::generator::generator::Generator) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TraitDecl;
+    use crate::{lexer::Lexer, parser::parser::Parser, source::SourceFile};
+    use std::io::Cursor;
+
+    fn parse_trait(input: &str) -> crate::aliases::Result<TraitDecl> {
+        let source = SourceFile::new(Cursor::new(input)).unwrap();
+        let mut parser = Parser::new(Lexer::new(&source).unwrap());
+        parser.parse::<TraitDecl>()
+    }
+
+    #[test]
+    fn test_trait_decl() {
+        let decl = parse_trait(
+            "trait Shape {
+                fn area(&self) -> float;
+                fn scale(&mut self, by: float);
+            }",
+        )
+        .unwrap();
+
+        assert_eq!(decl.name.value().as_str(), "Shape");
+        let names: Vec<_> = decl.methods.iter().map(|m| m.name.value().as_str()).collect();
+        assert_eq!(names, ["area", "scale"]);
+        assert!(!decl.methods[0].mutable_self);
+        assert!(decl.methods[1].mutable_self);
+        assert!(decl.methods[0].params.is_empty());
+        assert_eq!(decl.methods[1].params[0].ttype.ty.to_string(), "float");
+        assert_eq!(decl.methods[1].return_type.ty.to_string(), "void");
+    }
+
+    #[test]
+    fn test_trait_method_receivers() {
+        assert!(parse_trait("trait Shape { fn area() -> float; }").is_err());
+        assert!(parse_trait("trait Shape { fn area(self) -> float; }").is_err());
+        assert!(parse_trait("trait Shape { fn area(&self) -> float { return 1.0; } }").is_err());
+    }
+}
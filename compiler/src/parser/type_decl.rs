@@ -0,0 +1,92 @@
+use super::{node::Node, parser::Parser, types::TypeSpanned, Parse};
+use crate::{
+    aliases::Result,
+    generator::compile_trait::Generate,
+    lexer::tokens::Ident,
+    semantic_analyzer::{analyze_trait::Analyze, return_status::ReturnStatus},
+    t, tt,
+};
+
+// `type Name = Type;` is another name for the type, `newtype Name = Type;` a type of
+// its own that is only converted to and from the one it wraps with `as`
+#[derive(Debug, Clone)]
+pub struct TypeDecl {
+    pub name: Node<Ident>,
+    pub ttype: TypeSpanned,
+    pub newtype: bool,
+}
+
+impl Parse for TypeDecl {
+    fn parse(parser: &mut Parser) -> Result<Self> {
+        let newtype = if let tt!(newtype) = parser.peek()? {
+            parser.consume::<t!(newtype)>()?;
+            true
+        } else {
+            parser.consume::<t!(type)>()?;
+            false
+        };
+
+        let name = parser.parse_node()?;
+        parser.consume::<t!(=)>()?;
+        let ttype = parser.parse()?;
+        parser.consume::<t!(;)>()?;
+
+        Ok(Self {
+            name,
+            ttype,
+            newtype,
+        })
+    }
+}
+
+impl TypeDecl {
+    pub fn declare(&self, builder: &mut crate::semantic_analyzer::scope_builder::ScopeBuilder) {
+        builder.declare_type(&self.name, &self.ttype, self.newtype);
+    }
+}
+
+impl Analyze for TypeDecl {
+    fn build(&self, builder: &mut crate::semantic_analyzer::scope_builder::ScopeBuilder) {
+        builder.define_type(&self.name, &self.ttype);
+    }
+
+    fn analyze_semantics(
+        &self,
+        _analyzer: &mut crate::semantic_analyzer::analyzer::Analyzer,
+    ) -> ReturnStatus {
+        ReturnStatus::Never
+    }
+}
+
+impl Generate for TypeDecl {
+    fn generate(&self, _generator: &mut crate::generator::generator::Generator) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TypeDecl;
+    use crate::{lexer::Lexer, parser::parser::Parser, parser::types::Type, source::SourceFile};
+    use std::io::Cursor;
+
+    fn parse_decl(input: &str) -> TypeDecl {
+        let source = SourceFile::new(Cursor::new(input)).unwrap();
+        let mut parser = Parser::new(Lexer::new(&source).unwrap());
+        parser.parse::<TypeDecl>().unwrap()
+    }
+
+    #[test]
+    fn test_type_alias() {
+        let decl = parse_decl("type Counter = boxed mut int;");
+        assert_eq!(decl.name.value().as_str(), "Counter");
+        assert!(!decl.newtype);
+        assert_eq!(decl.ttype.ty.to_string(), "boxed mut int");
+    }
+
+    #[test]
+    fn test_newtype() {
+        let decl = parse_decl("newtype Meters = float;");
+        assert_eq!(decl.name.value().as_str(), "Meters");
+        assert!(decl.newtype);
+        assert_eq!(decl.ttype.ty, Type::float());
+    }
+}
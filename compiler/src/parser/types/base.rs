@@ -1,15 +1,22 @@
 use std::fmt::Display;
 
-use crate::{
-    lexer::tokens::Ident,
-    parser::{parser::Parser, Parse},
-};
+use crate::parser::{parser::Parser, Parse};
 
 use super::PrimitiveType;
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct CustomType(String);
 
+impl CustomType {
+    pub fn new(name: &str) -> Self {
+        Self(name.to_owned())
+    }
+
+    pub fn name(&self) -> &str {
+        &self.0
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum BaseType {
     Primitive(PrimitiveType),
@@ -31,7 +38,7 @@ impl Parse for BaseType {
             return Ok(Self::Primitive(t));
         }
 
-        let type_ident = parser.consume::<Ident>()?;
+        let type_ident = parser.parse_path(true)?;
         return Ok(Self::Custom(CustomType(type_ident.value().clone())));
     }
 }
@@ -5,12 +5,34 @@ use crate::{
     t, tt,
 };
 
+// `int` is the 64 bit signed int, `i64` is another name for it
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum PrimitiveType {
     Int,
     Str,
     Float,
     Bool,
+    Char,
+    I8,
+    I16,
+    I32,
+    U8,
+    U16,
+    U32,
+    U64,
+}
+
+// a constant of one of the sized int types
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SizedInt {
+    pub value: i128,
+    pub ty: PrimitiveType,
+}
+
+impl Display for SizedInt {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.value)
+    }
 }
 
 impl Parse for PrimitiveType {
@@ -32,6 +54,42 @@ impl Parse for PrimitiveType {
                 parser.consume::<t!(bool)>()?;
                 Self::Bool
             }
+            tt!(char) => {
+                parser.consume::<t!(char)>()?;
+                Self::Char
+            }
+            tt!(i8) => {
+                parser.consume::<t!(i8)>()?;
+                Self::I8
+            }
+            tt!(i16) => {
+                parser.consume::<t!(i16)>()?;
+                Self::I16
+            }
+            tt!(i32) => {
+                parser.consume::<t!(i32)>()?;
+                Self::I32
+            }
+            tt!(i64) => {
+                parser.consume::<t!(i64)>()?;
+                Self::Int
+            }
+            tt!(u8) => {
+                parser.consume::<t!(u8)>()?;
+                Self::U8
+            }
+            tt!(u16) => {
+                parser.consume::<t!(u16)>()?;
+                Self::U16
+            }
+            tt!(u32) => {
+                parser.consume::<t!(u32)>()?;
+                Self::U32
+            }
+            tt!(u64) => {
+                parser.consume::<t!(u64)>()?;
+                Self::U64
+            }
             _ => {
                 return parser.unexpected("primitive type");
             }
@@ -47,18 +105,87 @@ impl PrimitiveType {
             Self::Int => 0x01,
             Self::Float => 0x02,
             Self::Str => 0x03,
-            Self::Bool => 0x04
+            Self::Bool => 0x04,
+            Self::Char => 0x05,
+            Self::I8 => 0x06,
+            Self::I16 => 0x07,
+            Self::I32 => 0x08,
+            Self::U8 => 0x09,
+            Self::U16 => 0x0A,
+            Self::U32 => 0x0B,
+            Self::U64 => 0x0C,
         }
     }
+
+    // the width of the int types, `None` for everything else
+    pub fn int_bits(&self) -> Option<u32> {
+        match self {
+            Self::Int | Self::U64 => Some(64),
+            Self::I8 | Self::U8 => Some(8),
+            Self::I16 | Self::U16 => Some(16),
+            Self::I32 | Self::U32 => Some(32),
+            _ => None,
+        }
+    }
+
+    pub fn is_int(&self) -> bool {
+        self.int_bits().is_some()
+    }
+
+    // an int type other than `int`
+    pub fn is_sized_int(&self) -> bool {
+        self.is_int() && *self != Self::Int
+    }
+
+    pub fn is_signed(&self) -> bool {
+        matches!(self, Self::Int | Self::I8 | Self::I16 | Self::I32)
+    }
+
+    // the smallest and largest value of an int type
+    pub fn int_range(&self) -> Option<(i128, i128)> {
+        let bits = self.int_bits()?;
+        Some(match self.is_signed() {
+            true => (-(1 << (bits - 1)), (1 << (bits - 1)) - 1),
+            false => (0, (1 << bits) - 1),
+        })
+    }
+
+    // keeps only the low bits of `value`, like a cast between int types does
+    pub fn wrap(&self, value: i128) -> Option<i128> {
+        let bits = self.int_bits()?;
+        let value = value & ((1 << bits) - 1);
+        Some(match self.is_signed() && value >> (bits - 1) != 0 {
+            true => value - (1 << bits),
+            false => value,
+        })
+    }
+
+    pub fn saturate(&self, value: i128) -> Option<i128> {
+        let (min, max) = self.int_range()?;
+        Some(value.clamp(min, max))
+    }
+
+    pub fn fits(&self, value: i128) -> bool {
+        self.int_range()
+            .is_some_and(|(min, max)| (min..=max).contains(&value))
+    }
 }
 
 impl Display for PrimitiveType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let s = match self {
             Self::Int => "int",
-            Self::Float => "float",
             Self::Str => "str",
+            Self::Float => "float",
             Self::Bool => "bool",
+            Self::Char => "char",
+            Self::I8 => "i8",
+            Self::I16 => "i16",
+            Self::I32 => "i32",
+            Self::U8 => "u8",
+            Self::U16 => "u16",
+            Self::U32 => "u32",
+            Self::U64 => "u64",
         };
 
         write!(f, "{s}")
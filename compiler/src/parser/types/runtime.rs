@@ -7,5 +7,11 @@ pub enum RuntimeType {
     Base(BaseType),
     Boxed(RuntimeTypeIndex),
     Ref(RuntimeTypeIndex),
+    Array(RuntimeTypeIndex, u32),
+    Vector(RuntimeTypeIndex),
+    Optional(RuntimeTypeIndex),
+    Result(RuntimeTypeIndex, RuntimeTypeIndex),
+    Tuple(Vec<RuntimeTypeIndex>),
+    Function,
     Void,
 }
@@ -1,4 +1,5 @@
-use super::{BaseType, PrimitiveType};
+use super::{BaseType, CustomType, PrimitiveType};
+use crate::lexer::tokens::LitInt;
 use crate::parser::{parser::Parser, Parse};
 use crate::{t, tt};
 use std::fmt::Display;
@@ -8,6 +9,16 @@ pub enum TypeInner {
     Base(BaseType),
     Boxed(Box<Type>),
     Ref(Box<Type>),
+    Array(Box<Type>, usize),
+    Vector(Box<Type>),
+    Optional(Box<Type>),
+    // the value and error types of `result<T, E>`
+    Result(Box<Type>, Box<Type>),
+    Function(Vec<Type>, Box<Type>),
+    // `(T, U, ...)`, always at least two elements
+    Tuple(Vec<Type>),
+    // a value of some type implementing the trait, only usable behind a box
+    Dyn(String),
     Void,
     Unknown,
 }
@@ -20,13 +31,35 @@ pub struct Type {
 
 impl Parse for Type {
     fn parse(parser: &mut Parser) -> crate::aliases::Result<Self> {
+        let mut ty = Self::parse_unwrapped(parser)?;
+
+        // `T?` - the mutability stays with the optional as a whole
+        while let tt!(?) = parser.peek()? {
+            parser.consume::<t!(?)>()?;
+            let value = Self {
+                mutable: false,
+                inner: ty.inner,
+            };
+            ty = Self {
+                mutable: ty.mutable,
+                inner: TypeInner::Optional(Box::new(value)),
+            };
+        }
+
+        Ok(ty)
+    }
+}
+
+impl Type {
+    // a type without a trailing `?`, so `boxed Node?` is an optional box
+    fn parse_unwrapped(parser: &mut Parser) -> crate::aliases::Result<Self> {
         let mut mutable = false;
 
         if let tt!(&) = parser.peek()? {
             parser.consume::<t!(&)>()?;
 
             return Ok(Self {
-                inner: TypeInner::Ref(Box::new(parser.parse::<Type>()?)),
+                inner: TypeInner::Ref(Box::new(Self::parse_unwrapped(parser)?)),
                 mutable: false,
             });
         }
@@ -39,20 +72,103 @@ impl Parse for Type {
         let inner = match parser.peek()? {
             tt!(boxed) => {
                 parser.consume::<t!(boxed)>()?;
-                TypeInner::Boxed(Box::new(parser.parse::<Type>()?))
+                TypeInner::Boxed(Box::new(Self::parse_unwrapped(parser)?))
             }
             tt!(.) => {
                 parser.consume::<t!(.)>()?;
                 TypeInner::Unknown
             }
-            _ => TypeInner::Base(parser.parse()?),
+            tt!(dyn) => {
+                parser.consume::<t!(dyn)>()?;
+                let name = parser.parse_path(true)?;
+                TypeInner::Dyn(name.value().clone())
+            }
+            tt!(vec) => {
+                parser.consume::<t!(vec)>()?;
+                parser.consume::<t!(<)>()?;
+                let element = parser.parse::<Type>()?;
+                parser.consume::<t!(>)>()?;
+                TypeInner::Vector(Box::new(element))
+            }
+            tt!("[") => {
+                parser.consume::<t!("[")>()?;
+                let element = parser.parse::<Type>()?;
+                parser.consume::<t!(;)>()?;
+                let len = parser.consume::<LitInt>()?;
+                parser.consume::<t!("]")>()?;
+                TypeInner::Array(Box::new(element), *len.value() as usize)
+            }
+            tt!("(") => {
+                parser.consume::<t!("(")>()?;
+                let mut elements = vec![parser.parse::<Type>()?];
+                while let tt!(,) = parser.peek()? {
+                    parser.consume::<t!(,)>()?;
+                    elements.push(parser.parse::<Type>()?);
+                }
+                parser.consume::<t!(")")>()?;
+
+                // `(T)` is just a parenthesized type
+                if elements.len() == 1 {
+                    let ty = elements.remove(0);
+                    return Ok(Self {
+                        mutable: mutable || ty.mutable,
+                        inner: ty.inner,
+                    });
+                }
+                TypeInner::Tuple(elements)
+            }
+            tt!(fn) => {
+                parser.consume::<t!(fn)>()?;
+                parser.consume::<t!("(")>()?;
+                let mut params = vec![];
+                if !matches!(parser.peek()?, tt!(")")) {
+                    params.push(parser.parse::<Type>()?);
+                    while let tt!(,) = parser.peek()? {
+                        parser.consume::<t!(,)>()?;
+                        params.push(parser.parse::<Type>()?);
+                    }
+                }
+                parser.consume::<t!(")")>()?;
+
+                let mut ret = Type::void();
+                if let tt!(->) = parser.peek()? {
+                    parser.consume::<t!(->)>()?;
+                    ret = parser.parse::<Type>()?;
+                }
+                TypeInner::Function(params, Box::new(ret))
+            }
+            _ => {
+                let base = parser.parse::<BaseType>()?;
+                // a type parameter of the generic function being specialized
+                if let BaseType::Custom(c) = &base {
+                    if let Some(arg) = parser.type_arg(c.name()) {
+                        return Ok(Self {
+                            mutable: mutable || arg.mutable,
+                            inner: arg.inner.clone(),
+                        });
+                    }
+                }
+                // `result` only names the builtin when it is given type arguments
+                if let (BaseType::Custom(c), tt!(<)) = (&base, parser.peek()?) {
+                    if c.name() == "result" {
+                        parser.consume::<t!(<)>()?;
+                        let value = parser.parse::<Type>()?;
+                        parser.consume::<t!(,)>()?;
+                        let error = parser.parse::<Type>()?;
+                        parser.consume::<t!(>)>()?;
+                        return Ok(Self {
+                            mutable,
+                            inner: TypeInner::Result(Box::new(value), Box::new(error)),
+                        });
+                    }
+                }
+                TypeInner::Base(base)
+            }
         };
 
         Ok(Self { mutable, inner })
     }
-}
 
-impl Type {
     pub fn deref(&self) -> &Type {
         match &self.inner {
             TypeInner::Ref(inner) => inner.deref(),
@@ -88,6 +204,18 @@ impl Type {
         }
     }
 
+    // `int` or one of the sized int types
+    pub fn is_int(&self) -> bool {
+        matches!(&self.inner, TypeInner::Base(BaseType::Primitive(p)) if p.is_int())
+    }
+
+    pub fn char() -> Self {
+        Self {
+            mutable: false,
+            inner: TypeInner::Base(BaseType::Primitive(PrimitiveType::Char)),
+        }
+    }
+
 
     pub fn str() -> Self {
         Self {
@@ -96,6 +224,13 @@ impl Type {
         }
     }
 
+    pub fn custom(name: &str) -> Self {
+        Self {
+            mutable: false,
+            inner: TypeInner::Base(BaseType::Custom(CustomType::new(name))),
+        }
+    }
+
     pub fn void() -> Self {
         Self {
             mutable: false,
@@ -117,6 +252,91 @@ impl Type {
         }
     }
 
+    pub fn array(ty: Type, len: usize) -> Self {
+        Self {
+            mutable: false,
+            inner: TypeInner::Array(Box::new(ty), len),
+        }
+    }
+
+    pub fn vector(ty: Type) -> Self {
+        Self {
+            mutable: false,
+            inner: TypeInner::Vector(Box::new(ty)),
+        }
+    }
+
+    pub fn optional(ty: Type) -> Self {
+        Self {
+            mutable: false,
+            inner: TypeInner::Optional(Box::new(ty)),
+        }
+    }
+
+    pub fn result(value: Type, error: Type) -> Self {
+        Self {
+            mutable: false,
+            inner: TypeInner::Result(Box::new(value), Box::new(error)),
+        }
+    }
+
+    pub fn function(params: Vec<Type>, ret: Type) -> Self {
+        Self {
+            mutable: false,
+            inner: TypeInner::Function(params, Box::new(ret)),
+        }
+    }
+
+    pub fn tuple(elements: Vec<Type>) -> Self {
+        Self {
+            mutable: false,
+            inner: TypeInner::Tuple(elements),
+        }
+    }
+
+    pub fn dyn_trait(name: &str) -> Self {
+        Self {
+            mutable: false,
+            inner: TypeInner::Dyn(name.to_owned()),
+        }
+    }
+
+    pub fn custom_name(&self) -> Option<&str> {
+        match &self.inner {
+            TypeInner::Base(BaseType::Custom(c)) => Some(c.name()),
+            TypeInner::Boxed(t)
+            | TypeInner::Ref(t)
+            | TypeInner::Array(t, _)
+            | TypeInner::Vector(t)
+            | TypeInner::Optional(t) => t.custom_name(),
+            TypeInner::Result(value, error) => value.custom_name().or_else(|| error.custom_name()),
+            TypeInner::Function(params, ret) => params
+                .iter()
+                .find_map(|p| p.custom_name())
+                .or_else(|| ret.custom_name()),
+            TypeInner::Tuple(elements) => elements.iter().find_map(|e| e.custom_name()),
+            _ => None,
+        }
+    }
+
+    pub fn trait_name(&self) -> Option<&str> {
+        match &self.inner {
+            TypeInner::Dyn(name) => Some(name),
+            TypeInner::Boxed(t)
+            | TypeInner::Ref(t)
+            | TypeInner::Array(t, _)
+            | TypeInner::Vector(t)
+            | TypeInner::Optional(t) => t.trait_name(),
+            TypeInner::Result(value, error) => value.trait_name().or_else(|| error.trait_name()),
+            TypeInner::Function(params, ret) => params
+                .iter()
+                .find_map(|p| p.trait_name())
+                .or_else(|| ret.trait_name()),
+            TypeInner::Tuple(elements) => elements.iter().find_map(|e| e.trait_name()),
+            _ => None,
+        }
+    }
+
     pub fn is_ref(&self) -> bool {
         if let TypeInner::Ref(_) = &self.inner {
             return true;
@@ -128,6 +348,15 @@ impl Type {
         match &self.inner {
             TypeInner::Ref(_) => 16,
             TypeInner::Boxed(_) => 16,
+            TypeInner::Vector(_) => 16,
+            // function index + environment pointer
+            TypeInner::Function(_, _) => 24,
+            // aggregates can outgrow a u8, their layout comes from the struct table
+            TypeInner::Array(_, _) => 0,
+            TypeInner::Optional(_) => 0,
+            TypeInner::Result(_, _) => 0,
+            TypeInner::Tuple(_) => 0,
+            TypeInner::Dyn(_) => 0,
             TypeInner::Void => 0,
             TypeInner::Unknown => 0,
             TypeInner::Base(b) => match b {
@@ -136,7 +365,12 @@ impl Type {
                     PrimitiveType::Int => 8,
                     PrimitiveType::Str => 16,
                     PrimitiveType::Bool => 1,
+                    PrimitiveType::Char => 4,
                     PrimitiveType::Float => 8,
+                    PrimitiveType::I8 | PrimitiveType::U8 => 1,
+                    PrimitiveType::I16 | PrimitiveType::U16 => 2,
+                    PrimitiveType::I32 | PrimitiveType::U32 => 4,
+                    PrimitiveType::U64 => 8,
                 },
             },
         }
@@ -144,13 +378,38 @@ impl Type {
 
     pub fn verify_pointers(&self) -> bool {
         match &self.inner {
-            TypeInner::Boxed(b) => {
+            TypeInner::Boxed(b) | TypeInner::Vector(b) => {
                 if let TypeInner::Ref(_) = &b.inner {
                     return false;
                 }
                 b.verify_pointers()
             }
             TypeInner::Ref(r) => r.verify_pointers(),
+            TypeInner::Array(t, _) | TypeInner::Optional(t) => t.verify_pointers(),
+            TypeInner::Result(value, error) => value.verify_pointers() && error.verify_pointers(),
+            TypeInner::Function(params, ret) => {
+                params.iter().all(|p| p.verify_pointers()) && ret.verify_pointers()
+            }
+            TypeInner::Tuple(elements) => elements.iter().all(|e| e.verify_pointers()),
+            _ => true,
+        }
+    }
+
+    // `dyn Trait` has no size of its own, the box holds the actual value
+    pub fn verify_dyn(&self) -> bool {
+        match &self.inner {
+            TypeInner::Dyn(_) => false,
+            TypeInner::Boxed(b) if matches!(b.inner, TypeInner::Dyn(_)) => true,
+            TypeInner::Boxed(t)
+            | TypeInner::Ref(t)
+            | TypeInner::Array(t, _)
+            | TypeInner::Vector(t)
+            | TypeInner::Optional(t) => t.verify_dyn(),
+            TypeInner::Result(value, error) => value.verify_dyn() && error.verify_dyn(),
+            TypeInner::Function(params, ret) => {
+                params.iter().all(|p| p.verify_dyn()) && ret.verify_dyn()
+            }
+            TypeInner::Tuple(elements) => elements.iter().all(|e| e.verify_dyn()),
             _ => true,
         }
     }
@@ -161,6 +420,10 @@ impl Type {
                 lhs == rhs && (self.mutable || !other.mutable)
             }
 
+            (TypeInner::Dyn(lhs), TypeInner::Dyn(rhs)) => {
+                lhs == rhs && (self.mutable || !other.mutable)
+            }
+
             (TypeInner::Boxed(lhs), TypeInner::Boxed(rhs)) => {
                 (self.mutable || !other.mutable) && lhs.compatible(rhs)
             }
@@ -169,6 +432,34 @@ impl Type {
                 (self.mutable || !other.mutable) && lhs.compatible(rhs)
             }
 
+            (TypeInner::Array(lhs, l_len), TypeInner::Array(rhs, r_len)) => {
+                l_len == r_len && (self.mutable || !other.mutable) && lhs.compatible(rhs)
+            }
+
+            (TypeInner::Vector(lhs), TypeInner::Vector(rhs)) => {
+                (self.mutable || !other.mutable) && lhs.compatible(rhs)
+            }
+
+            (TypeInner::Optional(lhs), TypeInner::Optional(rhs)) => {
+                (self.mutable || !other.mutable) && lhs.compatible(rhs)
+            }
+
+            (TypeInner::Result(l_value, l_error), TypeInner::Result(r_value, r_error)) => {
+                (self.mutable || !other.mutable)
+                    && l_value.compatible(r_value)
+                    && l_error.compatible(r_error)
+            }
+
+            (TypeInner::Function(_, _), TypeInner::Function(_, _)) => {
+                (self.mutable || !other.mutable) && self.same_signature(other)
+            }
+
+            (TypeInner::Tuple(lhs), TypeInner::Tuple(rhs)) => {
+                (self.mutable || !other.mutable)
+                    && lhs.len() == rhs.len()
+                    && lhs.iter().zip(rhs).all(|(l, r)| l.compatible(r))
+            }
+
             (TypeInner::Void, TypeInner::Void) => true,
             _ => false,
         }
@@ -180,6 +471,10 @@ impl Type {
                 lhs == rhs && self.mutable == other.mutable
             }
 
+            (TypeInner::Dyn(lhs), TypeInner::Dyn(rhs)) => {
+                lhs == rhs && self.mutable == other.mutable
+            }
+
             (TypeInner::Boxed(lhs), TypeInner::Boxed(rhs)) => {
                 self.mutable == other.mutable && lhs.strict_compatible(rhs)
             }
@@ -188,6 +483,34 @@ impl Type {
                 self.mutable == other.mutable && lhs.strict_compatible(rhs)
             }
 
+            (TypeInner::Array(lhs, l_len), TypeInner::Array(rhs, r_len)) => {
+                l_len == r_len && self.mutable == other.mutable && lhs.strict_compatible(rhs)
+            }
+
+            (TypeInner::Vector(lhs), TypeInner::Vector(rhs)) => {
+                self.mutable == other.mutable && lhs.strict_compatible(rhs)
+            }
+
+            (TypeInner::Optional(lhs), TypeInner::Optional(rhs)) => {
+                self.mutable == other.mutable && lhs.strict_compatible(rhs)
+            }
+
+            (TypeInner::Result(l_value, l_error), TypeInner::Result(r_value, r_error)) => {
+                self.mutable == other.mutable
+                    && l_value.strict_compatible(r_value)
+                    && l_error.strict_compatible(r_error)
+            }
+
+            (TypeInner::Function(_, _), TypeInner::Function(_, _)) => {
+                self.mutable == other.mutable && self.same_signature(other)
+            }
+
+            (TypeInner::Tuple(lhs), TypeInner::Tuple(rhs)) => {
+                self.mutable == other.mutable
+                    && lhs.len() == rhs.len()
+                    && lhs.iter().zip(rhs).all(|(l, r)| l.strict_compatible(r))
+            }
+
             (TypeInner::Void, TypeInner::Void) => true,
             _ => false,
         }
@@ -196,6 +519,7 @@ impl Type {
     pub fn param_compatible(&self, arg: &Type) -> bool {
         match (&self.inner, &arg.inner) {
             (TypeInner::Base(lhs), TypeInner::Base(rhs)) => lhs == rhs,
+            (TypeInner::Dyn(lhs), TypeInner::Dyn(rhs)) => lhs == rhs,
 
             (TypeInner::Boxed(lhs), TypeInner::Boxed(arg_inner))
             | (TypeInner::Ref(lhs), TypeInner::Ref(arg_inner)) => {
@@ -205,6 +529,25 @@ impl Type {
                 lhs.param_compatible(arg_inner)
             }
 
+            (TypeInner::Array(lhs, l_len), TypeInner::Array(arg_inner, r_len)) => {
+                l_len == r_len && lhs.param_compatible(arg_inner)
+            }
+
+            (TypeInner::Vector(lhs), TypeInner::Vector(arg_inner))
+            | (TypeInner::Optional(lhs), TypeInner::Optional(arg_inner)) => {
+                lhs.param_compatible(arg_inner)
+            }
+
+            (TypeInner::Result(l_value, l_error), TypeInner::Result(a_value, a_error)) => {
+                l_value.param_compatible(a_value) && l_error.param_compatible(a_error)
+            }
+
+            (TypeInner::Function(_, _), TypeInner::Function(_, _)) => self.same_signature(arg),
+
+            (TypeInner::Tuple(lhs), TypeInner::Tuple(args)) => {
+                lhs.len() == args.len() && lhs.iter().zip(args).all(|(l, a)| l.param_compatible(a))
+            }
+
             (TypeInner::Void, TypeInner::Void) => true,
             _ => false,
         }
@@ -213,8 +556,21 @@ impl Type {
     pub fn return_compatible(&self, other: &Type) -> bool {
         match (&self.inner, &other.inner) {
             (TypeInner::Base(lhs), TypeInner::Base(rhs)) => lhs == rhs,
+            (TypeInner::Dyn(lhs), TypeInner::Dyn(rhs)) => lhs == rhs,
             (TypeInner::Boxed(lhs), TypeInner::Boxed(rhs)) => lhs.strict_compatible(rhs),
             (TypeInner::Ref(lhs), TypeInner::Ref(rhs)) => lhs.strict_compatible(rhs),
+            (TypeInner::Array(lhs, l_len), TypeInner::Array(rhs, r_len)) => {
+                l_len == r_len && lhs.return_compatible(rhs)
+            }
+            (TypeInner::Vector(lhs), TypeInner::Vector(rhs))
+            | (TypeInner::Optional(lhs), TypeInner::Optional(rhs)) => lhs.return_compatible(rhs),
+            (TypeInner::Result(l_value, l_error), TypeInner::Result(r_value, r_error)) => {
+                l_value.return_compatible(r_value) && l_error.return_compatible(r_error)
+            }
+            (TypeInner::Function(_, _), TypeInner::Function(_, _)) => self.same_signature(other),
+            (TypeInner::Tuple(lhs), TypeInner::Tuple(rhs)) => {
+                lhs.len() == rhs.len() && lhs.iter().zip(rhs).all(|(l, r)| l.return_compatible(r))
+            }
             (TypeInner::Void, TypeInner::Void) => true,
             _ => false,
         }
@@ -223,6 +579,7 @@ impl Type {
     pub fn assign_compatible(&self, rhs: &Type) -> bool {
         match (&self.inner, &rhs.inner) {
             (TypeInner::Base(l), TypeInner::Base(r)) => l == r,
+            (TypeInner::Dyn(l), TypeInner::Dyn(r)) => l == r,
             (TypeInner::Boxed(l), TypeInner::Boxed(r)) => l.assign_compatible(r),
             (TypeInner::Ref(l), TypeInner::Ref(r)) => {
                 if l.mutable {
@@ -231,10 +588,37 @@ impl Type {
                     l.assign_compatible(r)
                 }
             }
+            (TypeInner::Array(l, l_len), TypeInner::Array(r, r_len)) => {
+                l_len == r_len && l.assign_compatible(r)
+            }
+            (TypeInner::Vector(l), TypeInner::Vector(r))
+            | (TypeInner::Optional(l), TypeInner::Optional(r)) => l.assign_compatible(r),
+            (TypeInner::Result(l_value, l_error), TypeInner::Result(r_value, r_error)) => {
+                l_value.assign_compatible(r_value) && l_error.assign_compatible(r_error)
+            }
+            (TypeInner::Function(_, _), TypeInner::Function(_, _)) => self.same_signature(rhs),
+            (TypeInner::Tuple(l), TypeInner::Tuple(r)) => {
+                l.len() == r.len() && l.iter().zip(r).all(|(l, r)| l.assign_compatible(r))
+            }
             (TypeInner::Void, TypeInner::Void) => true,
             _ => false,
         }
     }
+
+    fn same_signature(&self, other: &Type) -> bool {
+        let (TypeInner::Function(l_params, l_ret), TypeInner::Function(r_params, r_ret)) =
+            (&self.inner, &other.inner)
+        else {
+            return false;
+        };
+
+        l_params.len() == r_params.len()
+            && l_params
+                .iter()
+                .zip(r_params)
+                .all(|(l, r)| l.return_compatible(r))
+            && l_ret.return_compatible(r_ret)
+    }
 }
 
 impl Display for TypeInner {
@@ -245,6 +629,22 @@ impl Display for TypeInner {
             TypeInner::Base(b) => b.to_string(),
             TypeInner::Boxed(b) => format!("boxed {b}"),
             TypeInner::Ref(r) => format!("&{r}"),
+            TypeInner::Array(t, len) => format!("[{t}; {len}]"),
+            TypeInner::Vector(t) => format!("vec<{t}>"),
+            TypeInner::Optional(t) => format!("{t}?"),
+            TypeInner::Result(value, error) => format!("result<{value}, {error}>"),
+            TypeInner::Dyn(name) => format!("dyn {name}"),
+            TypeInner::Tuple(elements) => {
+                let elements: Vec<_> = elements.iter().map(|e| e.to_string()).collect();
+                format!("({})", elements.join(", "))
+            }
+            TypeInner::Function(params, ret) => {
+                let params: Vec<_> = params.iter().map(|p| p.to_string()).collect();
+                match &ret.inner {
+                    TypeInner::Void => format!("fn({})", params.join(", ")),
+                    _ => format!("fn({}) -> {ret}", params.join(", ")),
+                }
+            }
         };
 
         write!(f, "{s}")
@@ -387,4 +787,120 @@ mod tests {
         assert!(!invalid_box_ref.verify_pointers(), "box<&T> should be invalid");
         assert!(valid_ref_box.verify_pointers(), "&box<T> should be valid");
     }
+
+    #[test]
+    fn test_array_type_compatibility() {
+        let three_ints = Type::array(Type::int(), 3);
+        let four_ints = Type::array(Type::int(), 4);
+        let three_bools = Type::array(Type::bool(), 3);
+
+        assert!(three_ints.assign_compatible(&three_ints), "same array types should be assignable");
+        assert!(!three_ints.assign_compatible(&four_ints), "array lengths must match");
+        assert!(!three_ints.assign_compatible(&three_bools), "element types must match");
+        assert_eq!(three_ints.to_string(), "[int; 3]");
+    }
+
+    #[test]
+    fn test_parse_array_type() {
+        use crate::{lexer::Lexer, source::SourceFile};
+        use std::io::Cursor;
+
+        let source = SourceFile::new(Cursor::new("mut [boxed int; 8]")).unwrap();
+        let mut parser = Parser::new(Lexer::new(&source).unwrap());
+        let ty = parser.parse::<Type>().unwrap();
+
+        assert!(ty.mutable);
+        assert_eq!(ty.inner, TypeInner::Array(Box::new(Type::boxed(Type::int())), 8));
+    }
+
+    #[test]
+    fn test_vector_type() {
+        use crate::{lexer::Lexer, source::SourceFile};
+        use std::io::Cursor;
+
+        let source = SourceFile::new(Cursor::new("vec<vec<float> >")).unwrap();
+        let mut parser = Parser::new(Lexer::new(&source).unwrap());
+        let ty = parser.parse::<Type>().unwrap();
+
+        assert_eq!(ty, Type::vector(Type::vector(Type::float())));
+        assert_eq!(ty.to_string(), "vec<vec<float>>");
+        assert!(!Type::vector(Type::refed(Type::int())).verify_pointers(), "vec<&T> should be invalid");
+    }
+
+    #[test]
+    fn test_nested_type_args_close_together() {
+        use crate::{lexer::Lexer, source::SourceFile};
+        use std::io::Cursor;
+
+        let source = SourceFile::new(Cursor::new("vec<vec<int>>")).unwrap();
+        let mut parser = Parser::new(Lexer::new(&source).unwrap());
+        let ty = parser.parse::<Type>().unwrap();
+
+        assert_eq!(ty, Type::vector(Type::vector(Type::int())));
+    }
+
+    #[test]
+    fn test_function_type() {
+        use crate::{lexer::Lexer, source::SourceFile};
+        use std::io::Cursor;
+
+        let source = SourceFile::new(Cursor::new("fn(int, vec<float>) -> bool")).unwrap();
+        let mut parser = Parser::new(Lexer::new(&source).unwrap());
+        let ty = parser.parse::<Type>().unwrap();
+
+        let params = vec![Type::int(), Type::vector(Type::float())];
+        assert_eq!(ty, Type::function(params.clone(), Type::bool()));
+        assert_eq!(ty.to_string(), "fn(int, vec<float>) -> bool");
+
+        let fewer = Type::function(vec![Type::int()], Type::bool());
+        assert!(!ty.assign_compatible(&fewer), "arity must match");
+        let other_ret = Type::function(params, Type::int());
+        assert!(!ty.assign_compatible(&other_ret), "return types must match");
+        assert_eq!(Type::function(vec![], Type::void()).to_string(), "fn()");
+    }
+
+    #[test]
+    fn test_optional_type() {
+        use crate::{lexer::Lexer, source::SourceFile};
+        use std::io::Cursor;
+
+        let source = SourceFile::new(Cursor::new("boxed Node? mut int??")).unwrap();
+        let mut parser = Parser::new(Lexer::new(&source).unwrap());
+
+        let ty = parser.parse::<Type>().unwrap();
+        assert_eq!(ty, Type::optional(Type::boxed(Type::custom("Node"))));
+        assert_eq!(ty.to_string(), "boxed Node?");
+
+        let ty = parser.parse::<Type>().unwrap();
+        assert!(ty.mutable);
+        assert_eq!(ty.inner, Type::optional(Type::optional(Type::int())).inner);
+
+        assert!(!Type::int().assign_compatible(&Type::optional(Type::int())));
+        assert!(!Type::optional(Type::int()).assign_compatible(&Type::int()));
+    }
+
+    #[test]
+    fn test_result_type() {
+        use crate::{lexer::Lexer, source::SourceFile};
+        use std::io::Cursor;
+
+        let source = SourceFile::new(Cursor::new("result<int, vec<str>>? result")).unwrap();
+        let mut parser = Parser::new(Lexer::new(&source).unwrap());
+
+        let ty = parser.parse::<Type>().unwrap();
+        assert_eq!(
+            ty,
+            Type::optional(Type::result(Type::int(), Type::vector(Type::str())))
+        );
+        assert_eq!(ty.to_string(), "result<int, vec<str>>?");
+
+        // without type arguments it's just a name
+        let ty = parser.parse::<Type>().unwrap();
+        assert_eq!(ty, Type::custom("result"));
+
+        let ok = Type::result(Type::int(), Type::str());
+        assert!(ok.assign_compatible(&Type::result(Type::int(), Type::str())));
+        assert!(!ok.assign_compatible(&Type::result(Type::int(), Type::bool())));
+        assert!(!ok.assign_compatible(&Type::int()));
+    }
 }
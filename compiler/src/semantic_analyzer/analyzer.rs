@@ -1,12 +1,15 @@
 use crate::aliases::{Result, SymbolInfoRef, TypeInfoRc};
 use crate::err::HarpyErrorKind;
-use crate::extensions::{ScopeRcExt, WeakScopeExt};
+use crate::extensions::{ScopeRcExt, SymbolInfoRefExt, WeakScopeExt};
+use crate::generator::instruction::LocalAddress;
 use crate::lexer::span::Span;
-use crate::lexer::tokens::Literal;
-use crate::parser::expr::expr::CallExpr;
+use crate::lexer::tokens::{Lit, Literal};
 use crate::parser::expr::Expr;
-use crate::parser::node::Node;
+use crate::parser::generics::{type_size, Instantiations};
+use crate::parser::node::{Node, NodeId};
+use crate::parser::module::ModuleGraph;
 use crate::parser::program::Program;
+use crate::parser::statements::JumpKind;
 use crate::parser::types::{Type, TypeInner, TypeSpanned};
 use crate::{aliases::ScopeRc, err::HarpyError, lexer::tokens::Ident};
 
@@ -14,9 +17,11 @@ use super::analyze_trait::Analyze;
 use super::err::SemanticError;
 use super::resolvers::expr_resolver::{ExprResolver, ResolveMode};
 use super::result::AnalysisResult;
-use super::scope::{Depth, ScopeKind};
+use super::scope::{Depth, Scope, ScopeKind};
 use super::scope_builder::ScopeBuilder;
-use super::symbol_info::{BorrowInfo, LiteralInfo, SymbolInfo, SymbolInfoKind};
+use super::struct_table::{EnumInfo, FieldIndex, FieldInfo, StructInfo, VariantIndex};
+use super::trait_table::{TraitInfo, VirtualIndex};
+use super::symbol_info::{BorrowInfo, LiteralInfo, SymbolInfo, SymbolInfoKind, VariableInfo};
 
 #[macro_export]
 macro_rules! get_symbol {
@@ -48,11 +53,54 @@ macro_rules! get_symbol_mut {
     };
 }
 
+// a variable of an enclosing function used inside a closure body, copied into
+// the closure's local `local` when the closure is created
+#[derive(Debug)]
+struct Capture {
+    name: String,
+    node_id: NodeId,
+    scope_depth: Depth,
+    ty: TypeInfoRc,
+    span: Span,
+    local: LocalAddress,
+}
+
+#[derive(Debug)]
+struct ClosureContext {
+    enclosing_scope: ScopeRc,
+    depth: Depth,
+    function: SymbolInfoRef,
+    captures: Vec<Capture>,
+}
+
+// how many times parsing and analysis are repeated to specialize generic
+// functions before giving up on an instantiation that never settles
+const MAX_INSTANTIATION_ROUNDS: usize = 32;
+
+// and before a type argument gets too large, one that doubles in every round
+// would take far too long to reach the round limit
+const MAX_TYPE_ARG_SIZE: usize = 64;
+
+// a call to a generic function whose specialization hasn't been parsed yet
+#[derive(Debug)]
+struct InstanceRequest {
+    name: String,
+    args: Vec<Type>,
+    span: Span,
+}
+
 #[derive(Debug)]
 pub struct Analyzer {
     errors: Vec<HarpyError>,
     #[allow(dead_code)]
     current_scope: ScopeRc,
+    closures: Vec<ClosureContext>,
+    requested: Vec<InstanceRequest>,
+    // file -> name of the module parsed from it
+    modules: Vec<String>,
+    // the type the next resolved expression is checked against, so `none`
+    // knows what it is
+    expected: Option<Type>,
     result: AnalysisResult,
 }
 
@@ -64,10 +112,34 @@ impl Analyzer {
         Self {
             errors,
             current_scope: result.scope_tree.clone(),
+            closures: vec![],
+            requested: vec![],
+            modules: vec![],
+            expected: None,
             result,
         }
     }
 
+    pub(in crate::semantic_analyzer) fn set_modules(&mut self, modules: &[String]) {
+        self.modules = modules.to_vec();
+    }
+
+    // items of a module other than the root live in the global scope as `module::item`,
+    // where the module's own code refers to them without the prefix
+    pub fn global_name(&self, ident: &Ident) -> String {
+        let module = self
+            .modules
+            .get(ident.span().start.file)
+            .map(String::as_str)
+            .unwrap_or_default();
+
+        if module.is_empty() || ident.value().contains("::") {
+            return ident.value().clone();
+        }
+
+        format!("{module}::{}", ident.value())
+    }
+
     pub fn enter_scope(&mut self) {
         let current = self.current_scope.clone();
         if let Some(next) = current.get_mut().next_unvisited_child() {
@@ -75,6 +147,127 @@ impl Analyzer {
         };
     }
 
+    // switches to the scope the builder made for the closure `name`
+    pub fn enter_closure(&mut self, name: &Node<Ident>) {
+        let scope = self.result.closures[&name.id()].scope.clone();
+        let function = scope.get().get_function_symbol().unwrap();
+        let depth = scope.get().depth();
+
+        let enclosing_scope = std::mem::replace(&mut self.current_scope, scope);
+        self.closures.push(ClosureContext {
+            enclosing_scope,
+            depth,
+            function,
+            captures: vec![],
+        });
+    }
+
+    // lays out the environment of the closure `name` and finds where each
+    // captured value lives in the enclosing function
+    pub fn exit_closure(&mut self, name: &Node<Ident>) {
+        let r = self.current_scope.get().resolve_borrows();
+        if let Err(e) = r {
+            self.report_error(e);
+        }
+
+        let ctx = self.closures.pop().unwrap();
+        self.current_scope = ctx.enclosing_scope;
+
+        let mut sources = Vec::with_capacity(ctx.captures.len());
+        let mut fields = Vec::with_capacity(ctx.captures.len());
+        for capture in ctx.captures {
+            let source = self
+                .capture(
+                    &capture.name,
+                    capture.node_id,
+                    capture.scope_depth,
+                    &capture.ty,
+                    capture.span,
+                )
+                .or_else(|| self.result.locals_map.get(&capture.node_id).copied());
+            if let Some(source) = source {
+                sources.push(source);
+            }
+            fields.push(FieldInfo {
+                name: capture.name,
+                ty: capture.ty,
+            });
+        }
+
+        let env = if fields.is_empty() {
+            None
+        } else {
+            let struct_table = &mut self.result.struct_table;
+            struct_table.declare(name.value(), name.id());
+            struct_table.define(name.value(), name.id(), fields);
+            Some(self.register_type_unchecked(&Type::custom(name.value())))
+        };
+
+        let info = self.result.closures.get_mut(&name.id()).unwrap();
+        info.env = env;
+        info.captures = sources;
+    }
+
+    // the local of the innermost closure that holds a copy of the variable
+    // declared by `node_id`, or None if the variable is the closure's own
+    fn capture(
+        &mut self,
+        name: &str,
+        node_id: NodeId,
+        scope_depth: Depth,
+        ty: &TypeInfoRc,
+        span: Span,
+    ) -> Option<LocalAddress> {
+        let ctx = self.closures.last_mut()?;
+        if scope_depth >= ctx.depth {
+            return None;
+        }
+
+        if let Some(capture) = ctx.captures.iter().find(|c| c.node_id == node_id) {
+            return Some(capture.local);
+        }
+
+        let local = {
+            let mut function = ctx.function.as_function_mut().unwrap();
+            let local = LocalAddress(function.locals.len().try_into().unwrap());
+            let mut var = VariableInfo::new();
+            var.initialized = true;
+            let symbol = SymbolInfo::new(
+                ty.clone(),
+                SymbolInfoKind::Variable(var),
+                node_id,
+                ctx.depth,
+                span,
+            );
+            function.locals.push(SymbolInfoRef::new(symbol.into()));
+            local
+        };
+
+        ctx.captures.push(Capture {
+            name: name.to_owned(),
+            node_id,
+            scope_depth,
+            ty: ty.clone(),
+            span,
+            local,
+        });
+
+        if ty.is_ref() {
+            let ident = Ident::new(name.to_owned(), span);
+            self.report_semantic_error(SemanticError::CaptureOfRef(ident), span);
+        }
+
+        Some(local)
+    }
+
+    pub fn is_captured(&self, symbol: &SymbolInfo) -> bool {
+        matches!(symbol.kind, SymbolInfoKind::Variable(_) | SymbolInfoKind::Param)
+            && self
+                .closures
+                .last()
+                .is_some_and(|ctx| symbol.scope_depth < ctx.depth)
+    }
+
     pub fn exit_scope(&mut self) {
         let parent = self.current_scope.get().parent.upgrade();
         if let Some(parent) = parent {
@@ -87,6 +280,29 @@ impl Analyzer {
         }
     }
 
+    // checks that a break or continue has a loop to jump to, remembering
+    // breaks so loops know whether they can finish
+    pub fn resolve_jump(&mut self, kind: JumpKind, label: Option<&Node<Ident>>, span: Span) {
+        let target = Scope::find_loop(&self.current_scope, label.map(|l| l.value().as_str()));
+        match (target, label) {
+            (Some(target), _) => {
+                if kind == JumpKind::Break {
+                    target.get_mut().has_break = true;
+                }
+            }
+            (None, Some(label)) => {
+                self.report_semantic_error(SemanticError::UndefinedLabel(label.clone()), label.span());
+            }
+            (None, None) => {
+                self.report_semantic_error(SemanticError::JumpOutsideLoop(kind), span);
+            }
+        }
+    }
+
+    pub fn loop_has_break(&self) -> bool {
+        self.current_scope.get().has_break
+    }
+
     pub fn report_semantic_error(&mut self, error: SemanticError, span: Span) -> &mut Self {
         self.errors
             .push(HarpyError::new(HarpyErrorKind::SemanticError(error), span));
@@ -98,7 +314,8 @@ impl Analyzer {
     }
 
     pub fn get_symbol(&mut self, ident: &Ident) -> Result<SymbolInfoRef> {
-        self.current_scope.get().lookup(ident)
+        let global_name = self.global_name(ident);
+        self.current_scope.get().lookup_as(ident, &global_name)
     }
 
     pub fn in_scopekind(&self, kind: ScopeKind) -> bool {
@@ -113,41 +330,12 @@ impl Analyzer {
         if let Expr::Ident(ident) = &**expr {
             if let Ok(sym_ref) = self.get_symbol(ident) {
                 let symbol = (*sym_ref).borrow();
-                if matches!(
-                    symbol.kind,
-                    SymbolInfoKind::Variable(_) | SymbolInfoKind::Param
-                ) {
-                    let original_node_id = symbol.node_id;
-                    if let Some(&local_addr) = self.result.locals_map.get(&original_node_id) {
-                        self.result.locals_map.insert(expr.id(), local_addr);
-                    }
-                }
+                self.map_ident_to_local_with_symbol(ident, expr.id(), &symbol);
             }
         }
 
         match ExprResolver::resolve_expr(expr, self, mode) {
-            Ok(t) => {
-                let type_info = self.register_type(&TypeSpanned {
-                    ty: t,
-                    span: expr.span(),
-                });
-                let info = SymbolInfo::new(
-                    type_info.clone(),
-                    SymbolInfoKind::Expr,
-                    expr.id(),
-                    self.current_scope.get().depth(),
-                    expr.span(),
-                );
-                
-                if let TypeInner::Unknown = type_info.inner {
-                    return Some(type_info)
-                }
-
-                self.result
-                    .node_info
-                    .insert(expr.id(), SymbolInfoRef::new(info.into()));
-                Some(type_info)
-            }
+            Ok(t) => Some(self.register_expr_type(expr.id(), expr.span(), t)),
             Err(e) => {
                 self.errors.push(e);
                 None
@@ -155,6 +343,53 @@ impl Analyzer {
         }
     }
 
+    pub fn register_expr_type(&mut self, id: NodeId, span: Span, ty: Type) -> TypeInfoRc {
+        let type_info = self.register_type(&TypeSpanned { ty, span });
+        let info = SymbolInfo::new(
+            type_info.clone(),
+            SymbolInfoKind::Expr,
+            id,
+            self.current_scope.get().depth(),
+            span,
+        );
+
+        if let TypeInner::Unknown = type_info.inner {
+            return type_info;
+        }
+
+        self.result
+            .node_info
+            .insert(id, SymbolInfoRef::new(info.into()));
+        type_info
+    }
+
+    // the type an already resolved expression was given
+    pub(in crate::semantic_analyzer) fn expr_type(&self, id: NodeId) -> Option<Type> {
+        self.result.node_info.get(&id).map(|info| info.get().ty.ttype.clone())
+    }
+
+    pub fn expect(&mut self, ty: &Type) {
+        self.expected = Some(self.expand_type(ty));
+    }
+
+    // a type as written in the source with its aliases expanded
+    pub fn expand_type(&self, ty: &Type) -> Type {
+        self.result.type_table.names().expand(ty)
+    }
+
+    // what a newtype wraps, with the aliases in it expanded
+    pub fn underlying_type(&self, ty: &Type) -> Option<Type> {
+        self.result.type_table.names().underlying(ty)
+    }
+
+    pub fn lower_type(&self, ty: &Type) -> Type {
+        self.result.type_table.names().lower(ty)
+    }
+
+    pub fn take_expected(&mut self) -> Option<Type> {
+        self.expected.take()
+    }
+
     pub fn resolve_expr(&mut self, expr: &Node<Expr>) -> Option<TypeInfoRc> {
         self.res_expr(expr, ResolveMode::Read)
     }
@@ -163,23 +398,36 @@ impl Analyzer {
         self.res_expr(expr, ResolveMode::Write)
     }
 
-    pub fn register_type(&mut self, ttype: &TypeSpanned) -> TypeInfoRc {
+    pub fn register_type(&mut self, written: &TypeSpanned) -> TypeInfoRc {
+        let ttype = &TypeSpanned {
+            ty: self.expand_type(written),
+            span: written.span(),
+        };
+
         if !ttype.verify_pointers() {
             self.report_error(HarpyError::new(
                 HarpyErrorKind::SemanticError(SemanticError::PointerToRef),
                 ttype.span(),
             ));
         }
-        self.result.type_table.register(ttype)
+
+        if !ttype.verify_dyn() {
+            self.report_error(HarpyError::new(
+                HarpyErrorKind::SemanticError(SemanticError::UnboxedDyn(written.ty.clone())),
+                ttype.span(),
+            ));
+        }
+        self.result.type_table.register(written)
     }
 
     fn register_type_unchecked(&mut self, ttype: &Type) -> TypeInfoRc {
         self.result.type_table.register(ttype)
     }
 
-    pub fn register_constant(&mut self, lit: &Node<Literal>, ty: &Type) {
+    // `value` can differ from the literal as written, like for a negated int
+    pub fn register_constant(&mut self, lit: &Node<Literal>, value: &Lit, ty: &Type) {
         let ttype = self.register_type_unchecked(ty);
-        let const_idx = self.result.constants.register(lit, &ttype);
+        let const_idx = self.result.constants.register_value(lit.id(), value, &ttype);
 
         let info = LiteralInfo { const_idx };
         let info = SymbolInfoKind::Literal(info);
@@ -194,13 +442,78 @@ impl Analyzer {
         self.result.node_info.insert(lit.id(), info);
     }
 
-    pub fn register_call(&mut self, ident: &Ident, call_expr: &Node<CallExpr>) {
-        match self.result.function_table.register_call(ident, call_expr) {
-            Some(()) => (),
-            None => self.report_error(HarpyError::new(
-                HarpyErrorKind::SemanticError(SemanticError::MissingSymbol(ident.clone())),
-                ident.span(),
-            )),
+    // a value computed at compile time, loaded from the const pool wherever `id` is used
+    // the value a node was registered with, literals included
+    pub fn constant_value(&self, id: NodeId) -> Option<Lit> {
+        self.result.constants.get_value(id).cloned()
+    }
+
+    pub fn register_constant_value(&mut self, id: NodeId, value: &Lit, ty: &Type) {
+        let ttype = self.register_type_unchecked(ty);
+        self.result.constants.register_value(id, value, &ttype);
+    }
+
+    // the functions declared with the name, by the name each of them is defined as,
+    // with their params and return type
+    pub fn overloads(&self, ident: &Ident) -> Vec<(String, Vec<TypeInfoRc>, TypeInfoRc)> {
+        let table = &self.result.function_table;
+        table
+            .overloads(&self.global_name(ident))
+            .iter()
+            .filter_map(|name| {
+                let symbol = table.get(table.index_of(name)?);
+                let params = symbol.as_function()?.params.clone();
+                let return_type = (*symbol).borrow().ty.clone();
+                Some((name.clone(), params, return_type))
+            })
+            .collect()
+    }
+
+    pub fn register_call(&mut self, ident: &Ident, id: NodeId) {
+        let name = Ident::new(self.global_name(ident), ident.span());
+        if self.result.function_table.register_call(&name, id).is_none() {
+            self.report_semantic_error(SemanticError::MissingSymbol(ident.clone()), ident.span());
+        }
+    }
+
+    pub fn get_struct(&self, name: &str) -> Option<StructInfo> {
+        self.result.struct_table.get(name).cloned()
+    }
+
+    // an alias has no methods of its own, they'd go to the type it stands for
+    pub fn is_type_defined(&self, name: &str) -> bool {
+        self.result.struct_table.contains(name) || self.result.type_table.names().is_newtype(name)
+    }
+
+    pub fn get_trait(&self, name: &str) -> Option<TraitInfo> {
+        self.result.trait_table.get(name).cloned()
+    }
+
+    pub fn implements(&self, ty: &str, tr: &str) -> bool {
+        self.result.trait_table.implements(ty, tr)
+    }
+
+    pub fn register_virtual_call(&mut self, id: NodeId, index: VirtualIndex) {
+        self.result.trait_table.register_call(id, index);
+    }
+
+    pub fn get_enum(&self, name: &str) -> Option<EnumInfo> {
+        self.result.struct_table.get_enum(name).cloned()
+    }
+
+    pub fn register_field(&mut self, id: NodeId, idx: FieldIndex) {
+        self.result.fields_map.insert(id, idx);
+    }
+
+    pub fn register_variant(&mut self, id: NodeId, idx: VariantIndex) {
+        self.result.variants_map.insert(id, idx);
+    }
+
+    pub fn check_struct_recursion(&mut self, name: &Node<Ident>) {
+        let table = &self.result.struct_table;
+        let names = self.result.type_table.names();
+        if table.is_declared_by(name.value(), name.id()) && table.is_recursive(name.value(), names) {
+            self.report_semantic_error(SemanticError::RecursiveStruct(name.clone()), name.span());
         }
     }
 
@@ -212,15 +525,88 @@ impl Analyzer {
         self.current_scope.get_mut().register_borrow(info);
     }
 
-    pub fn analyze(program: &Program) -> std::result::Result<AnalysisResult, Vec<HarpyError>> {
-        let mut s = ScopeBuilder::build_analyzer(program)?;
-        let _return_status = program.analyze_semantics(&mut s);
+    pub(in crate::semantic_analyzer) fn release_borrow(&self, original: &SymbolInfoRef, span: Span) {
+        self.current_scope.get_mut().release_borrow(original, span);
+    }
+
+    // generic functions are specialized by parsing them again with the type
+    // arguments inferred at their calls, so parsing and analysis repeat until
+    // every call has a specialization to go to; `program` ends up holding them
+    pub fn analyze(
+        program: &mut Program,
+        modules: &ModuleGraph,
+    ) -> std::result::Result<AnalysisResult, Vec<HarpyError>> {
+        let mut instances = Instantiations::new();
+        let mut rounds = 0;
+
+        loop {
+            let mut s = ScopeBuilder::build_analyzer(program)?;
+            let _return_status = program.analyze_semantics(&mut s);
+
+            let requested = std::mem::take(&mut s.requested);
+            let new = requested
+                .into_iter()
+                .filter(|r| instances.insert(&r.name, r.args.clone()))
+                .collect::<Vec<_>>();
+
+            if new.is_empty() {
+                if !s.errors.is_empty() {
+                    return Err(s.into_errors());
+                }
+                return Ok(s.result);
+            }
+
+            rounds += 1;
+            if rounds == MAX_INSTANTIATION_ROUNDS {
+                for request in new {
+                    s.report_semantic_error(
+                        SemanticError::InstantiationLimit(request.name),
+                        request.span,
+                    );
+                }
+                return Err(s.into_errors());
+            }
+
+            *program = modules.parse(&instances)?;
+        }
+    }
+
+    // an unknown module item is also a missing symbol, which would only repeat the error
+    fn into_errors(self) -> Vec<HarpyError> {
+        let is = |e: &HarpyError, f: fn(&SemanticError) -> bool| {
+            matches!(e.kind(), HarpyErrorKind::SemanticError(e) if f(e))
+        };
+        let unknown = self
+            .errors
+            .iter()
+            .filter(|e| is(e, |e| matches!(e, SemanticError::UnknownModuleItem(..))))
+            .map(|e| e.span().end)
+            .collect::<Vec<_>>();
 
-        if !s.errors.is_empty() {
-            return Err(s.errors);
+        self.errors
+            .into_iter()
+            .filter(|e| {
+                !(is(e, |e| matches!(e, SemanticError::MissingSymbol(..))) && unknown.contains(&e.span().end))
+            })
+            .collect()
+    }
+
+    // `name<args>` has to be parsed before the call can be compiled
+    pub fn request_instance(&mut self, name: &str, args: Vec<Type>, span: Span) {
+        if args.iter().any(|arg| type_size(arg) > MAX_TYPE_ARG_SIZE) {
+            self.report_semantic_error(SemanticError::InstantiationLimit(name.to_owned()), span);
+            return;
         }
 
-        Ok(s.result)
+        self.requested.push(InstanceRequest {
+            name: name.to_owned(),
+            args,
+            span,
+        });
+    }
+
+    pub fn has_function(&self, name: &str) -> bool {
+        self.result.function_table.contains(name)
     }
 
     pub fn check_return_borrow(&mut self, id: &Ident) {
@@ -246,9 +632,12 @@ impl Analyzer {
         }
     }
 
+    // `id` is the node that reads `ident`; inside a closure, variables of the
+    // enclosing function resolve to the closure's copy of them
     pub fn map_ident_to_local_with_symbol(
         &mut self,
-        ident_node: &Node<Ident>,
+        ident: &Ident,
+        id: NodeId,
         symbol: &SymbolInfo,
     ) {
         if matches!(
@@ -256,22 +645,729 @@ impl Analyzer {
             SymbolInfoKind::Variable(_) | SymbolInfoKind::Param
         ) {
             let original_node_id = symbol.node_id;
-            if let Some(&local_addr) = self.result.locals_map.get(&original_node_id) {
-                self.result.locals_map.insert(ident_node.id(), local_addr);
+            let local_addr = self
+                .capture(
+                    ident.value(),
+                    original_node_id,
+                    symbol.scope_depth,
+                    &symbol.ty,
+                    ident.span(),
+                )
+                .or_else(|| self.result.locals_map.get(&original_node_id).copied());
+            if let Some(local_addr) = local_addr {
+                self.result.locals_map.insert(id, local_addr);
             }
         }
     }
 
-    pub fn map_ident_to_global_with_symbol(
-        &mut self,
-        ident_node: &Node<Ident>,
-        symbol: &SymbolInfo,
-    ) {
+    pub fn map_ident_to_global_with_symbol(&mut self, id: NodeId, symbol: &SymbolInfo) {
         if matches!(symbol.kind, SymbolInfoKind::Global(_)) {
             let original_node_id = symbol.node_id;
             if let Some(global_addr) = self.result.global_table.get_mapping_opt(original_node_id) {
-                self.result.global_table.map_node_to_global(ident_node.id(), global_addr);
+                self.result.global_table.map_node_to_global(id, global_addr);
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    fn analyze_source(source_code: &str) -> Vec<HarpyError> {
+        analyze_modules(&[("main.hrpy", source_code)])
+    }
+
+    // the first file is the root module
+    fn analyze_modules(files: &[(&str, &str)]) -> Vec<HarpyError> {
+        let (modules, errors) = ModuleGraph::load_with(Path::new(files[0].0), |path| {
+            files
+                .iter()
+                .find(|(name, _)| Path::new(name) == path)
+                .map(|(_, text)| text.to_string())
+                .ok_or_else(|| std::io::ErrorKind::NotFound.into())
+        });
+        if !errors.is_empty() {
+            return errors;
+        }
+
+        let mut ast = match modules.parse(&Instantiations::new()) {
+            Ok(ast) => ast,
+            Err(errors) => return errors,
+        };
+        match Analyzer::analyze(&mut ast, &modules).and_then(|result| result.into_runtime()) {
+            Ok(_) => vec![],
+            Err(errors) => errors,
+        }
+    }
+
+    fn count_errors(errors: &[HarpyError], f: impl Fn(&SemanticError) -> bool) -> usize {
+        errors
+            .iter()
+            .filter(|e| match e.kind() {
+                HarpyErrorKind::SemanticError(err) => f(err),
+                _ => false,
+            })
+            .count()
+    }
+
+    #[test]
+    fn test_array_constant_index_out_of_range() {
+        let errors = analyze_source("fn main() -> int { let a = [1, 2]; return a[2]; }");
+        assert_eq!(count_errors(&errors, |e| matches!(e, SemanticError::IndexOutOfRange(2, 2))), 1);
+    }
+
+    #[test]
+    fn test_empty_array_takes_its_expected_type() {
+        let source = r#"
+            global g: [str; 0] = [];
+            fn total(a: [int; 0]) -> int { return 5; }
+            fn main() -> int {
+                let a: [int; 0] = [];
+                let b: mut [float; 0] = [];
+                b = [];
+                return total([]) + total(a);
+            }
+        "#;
+        assert!(analyze_source(source).is_empty());
+
+        let errors = analyze_source("fn main() { let a = []; }");
+        assert_eq!(count_errors(&errors, |e| matches!(e, SemanticError::CantInferType)), 1);
+    }
+
+    #[test]
+    fn test_vec_method_needs_mutable_receiver() {
+        let errors =
+            analyze_source("fn main() -> int { let v = vec[1]; v.push(2); return v.len(); }");
+        assert_eq!(count_errors(&errors, |e| matches!(e, SemanticError::AssignToConst(_))), 1);
+    }
+
+    #[test]
+    fn test_struct_unknown_field() {
+        let source = r#"
+            struct Point { x: int, y: int }
+            fn main() -> int {
+                let p = Point { x: 1, y: 2 };
+                return p.z;
+            }
+        "#;
+        let errors = analyze_source(source);
+        assert_eq!(count_errors(&errors, |e| matches!(e, SemanticError::UnknownField(..))), 1);
+    }
+
+    #[test]
+    fn test_enum_switch_must_be_exhaustive() {
+        let source = r#"
+            enum Shape { Circle(float), Rect(float, float), Empty }
+            fn main() -> int {
+                let s = Shape::Empty;
+                switch s {
+                    Shape::Circle(r) -> return 1;
+                }
+                return 0;
+            }
+        "#;
+        let errors = analyze_source(source);
+        assert_eq!(
+            count_errors(&errors, |e| matches!(
+                e,
+                SemanticError::NonExhaustiveMatch(missing)
+                    if missing == &["Shape::Rect", "Shape::Empty"]
+            )),
+            1
+        );
+    }
+
+    #[test]
+    fn test_enum_switch_with_default_is_exhaustive() {
+        let source = r#"
+            enum Shape { Circle(float), Rect(float, float), Empty }
+            fn main() -> int {
+                switch Shape::Empty {
+                    Shape::Circle(r) -> return 1;
+                    . -> return 0;
+                }
+            }
+        "#;
+        assert!(analyze_source(source).is_empty());
+    }
+
+    #[test]
+    fn test_jump_outside_loop() {
+        let source = r#"
+            fn main() -> int {
+                break;
+            }
+        "#;
+        let errors = analyze_source(source);
+        assert_eq!(
+            count_errors(&errors, |e| matches!(e, SemanticError::JumpOutsideLoop(JumpKind::Break))),
+            1
+        );
+    }
+
+    #[test]
+    fn test_undefined_loop_label() {
+        let source = r#"
+            fn main() -> int {
+                'a: loop { continue 'b; }
+            }
+        "#;
+        let errors = analyze_source(source);
+        assert_eq!(count_errors(&errors, |e| matches!(e, SemanticError::UndefinedLabel(_))), 1);
+    }
+
+    #[test]
+    fn test_loop_without_break_diverges() {
+        let diverging = r#"
+            fn main() -> int {
+                let n: mut int = 0;
+                loop { n += 1; }
+            }
+        "#;
+        assert!(analyze_source(diverging).is_empty());
+
+        let breaking = r#"
+            fn main() -> int {
+                loop { break; }
+            }
+        "#;
+        let errors = analyze_source(breaking);
+        assert_eq!(count_errors(&errors, |e| matches!(e, SemanticError::NotAllPathsReturn)), 1);
+    }
+
+    #[test]
+    fn test_closure_cannot_assign_capture() {
+        let source = r#"
+            fn main() -> int {
+                let n: mut int = 0;
+                let f = fn() { n = 1; };
+                return n;
+            }
+        "#;
+        let errors = analyze_source(source);
+        assert_eq!(count_errors(&errors, |e| matches!(e, SemanticError::AssignToCapture(_))), 1);
+    }
+
+    #[test]
+    fn test_closure_assigns_only_through_boxed_captures() {
+        let source = r#"
+            struct P { x: int }
+            fn main() -> int {
+                let counter: boxed mut int = box 0;
+                let q: mut P = P { x: 1 };
+                let f = fn() {
+                    *counter += 1;
+                    q.x = 2;
+                };
+                return *counter;
+            }
+        "#;
+        let errors = analyze_source(source);
+        // only the write to the copied struct is rejected
+        assert_eq!(count_errors(&errors, |e| matches!(e, SemanticError::AssignToCapture(_))), 1);
+    }
+
+    #[test]
+    fn test_generic_type_param_conflict() {
+        let source = r#"
+            fn max<T>(a: T, b: T) -> T {
+                if a > b { return a; }
+                return b;
+            }
+            fn main() -> int {
+                return max(1, 2.0);
+            }
+        "#;
+        let errors = analyze_source(source);
+        assert_eq!(count_errors(&errors, |e| matches!(e, SemanticError::TypeParamConflict(..))), 1);
+    }
+
+    #[test]
+    fn test_generic_instantiation_limit() {
+        // the argument doubles in every round, so the limit is on its size
+        let source = r#"
+            fn g<T>(n: int, a: T) {
+                if n > 0 { g(n - 1, (a, a)); }
+            }
+            fn main() {
+                g(40, 1);
+            }
+        "#;
+        let errors = analyze_source(source);
+        assert!(count_errors(&errors, |e| matches!(e, SemanticError::InstantiationLimit(..))) > 0);
+    }
+
+    #[test]
+    fn test_module_items_are_not_visible_unqualified() {
+        let main = r#"
+            import "geo.hrpy";
+            fn main() -> int { return norm(); }
+        "#;
+        let geo = "pub fn norm() -> int { return 1; }";
+        let errors = analyze_modules(&[("main.hrpy", main), ("geo.hrpy", geo)]);
+        assert_eq!(count_errors(&errors, |e| matches!(e, SemanticError::MissingSymbol(..))), 1);
+    }
+
+    #[test]
+    fn test_inaccessible_module_items() {
+        let main = r#"
+            import "geo.hrpy";
+            fn main() -> int {
+                let x = 1 + true;
+                return geo::secret() + geo::missing();
+            }
+        "#;
+        let geo = "fn secret() -> int { return 1; }";
+        let errors = analyze_modules(&[("main.hrpy", main), ("geo.hrpy", geo)]);
+        // reported along with the other analyzer errors instead of stopping the parse
+        assert_eq!(count_errors(&errors, |e| matches!(e, SemanticError::PrivateItem(..))), 1);
+        assert_eq!(count_errors(&errors, |e| matches!(e, SemanticError::UnknownModuleItem(..))), 1);
+        assert_eq!(count_errors(&errors, |e| matches!(e, SemanticError::InfixTypeMismatch(..))), 1);
+        assert_eq!(errors.len(), 3);
+    }
+
+    #[test]
+    fn test_invalid_cast() {
+        let source = r#"
+            fn main() -> bool {
+                let n = (2.5 as bool) as int;
+                return 1 as bool;
+            }
+        "#;
+        let errors = analyze_source(source);
+        assert_eq!(count_errors(&errors, |e| matches!(e, SemanticError::InvalidCast(..))), 2);
+        // a bad cast still has the type it casts to
+        assert_eq!(errors.len(), 2);
+    }
+
+    #[test]
+    fn test_bitwise_requires_int() {
+        let source = r#"
+            fn main() -> float {
+                let x: mut float = 1.5;
+                x |= 2.5;
+                return x & 1.0;
+            }
+        "#;
+        let errors = analyze_source(source);
+        assert_eq!(count_errors(&errors, |e| matches!(e, SemanticError::InfixTypeMismatch(..))), 2);
+        // the operators are named in words, like the arithmetic ones
+        assert_eq!(count_errors(&errors, |e| e.to_string().contains("bitwise and")), 1);
+    }
+
+    #[test]
+    fn test_optional_must_be_checked() {
+        let source = r#"
+            fn main() -> int {
+                let x: int? = some(1);
+                let y: int = x;
+                switch x {
+                    some(v) -> return v + x;
+                }
+            }
+        "#;
+        let errors = analyze_source(source);
+        assert_eq!(count_errors(&errors, |e| matches!(e, SemanticError::LetTypeMismatch(..))), 1);
+        assert_eq!(count_errors(&errors, |e| matches!(e, SemanticError::InfixTypeMismatch(..))), 1);
+        assert_eq!(
+            count_errors(&errors, |e| matches!(
+                e,
+                SemanticError::NonExhaustiveMatch(missing) if missing == &["none"]
+            )),
+            1
+        );
+    }
+
+    #[test]
+    fn test_none_needs_a_type() {
+        let source = r#"
+            fn main() -> int {
+                let x = none;
+                if let some(v) = 3 {
+                    return v;
+                }
+                return 0;
+            }
+        "#;
+        let errors = analyze_source(source);
+        assert_eq!(count_errors(&errors, |e| matches!(e, SemanticError::CantInferType)), 1);
+        assert_eq!(count_errors(&errors, |e| matches!(e, SemanticError::NotAnOptional(_))), 1);
+    }
+
+    #[test]
+    fn test_try_needs_a_matching_return_type() {
+        let source = r#"
+            fn parse(n: int) -> result<int, str> {
+                return ok(n);
+            }
+            fn flag(n: int) -> result<int, bool> {
+                return ok(parse(n)?);
+            }
+            fn plain(n: int) -> int {
+                return parse(n)?;
+            }
+            fn main() -> int {
+                let n = 3;
+                return n?;
+            }
+        "#;
+        let errors = analyze_source(source);
+        assert_eq!(count_errors(&errors, |e| matches!(e, SemanticError::ReturnTypeMismatch(..))), 2);
+        assert_eq!(count_errors(&errors, |e| matches!(e, SemanticError::InvalidTry(_))), 1);
+    }
+
+    #[test]
+    fn test_method_receivers() {
+        let source = r#"
+            struct Counter { n: int }
+            struct Holder { c: Counter }
+            impl Counter {
+                fn get(&self) -> int { return self.n; }
+                fn tick(&mut self) { self.n += 1; }
+            }
+            fn main() -> int {
+                let c = Counter { n: 1 };
+                c.tick();
+                let h = box Holder { c: Counter { n: 2 } };
+                let x = h.c.get();
+                let m: mut Counter = Counter { n: 3 };
+                let r = &m;
+                m.tick();
+                let y = c.missing();
+                return c.get(1);
+            }
+        "#;
+        let errors = analyze_source(source);
+        let has = |f: fn(&SemanticError) -> bool| count_errors(&errors, f) > 0;
+
+        assert!(has(|e| matches!(e, SemanticError::BorrowMutNonMutable)));
+        assert!(has(|e| matches!(e, SemanticError::InvalidBorrow)));
+        assert!(has(|e| matches!(e, SemanticError::ArgCountMismatch(..))));
+        assert!(has(|e| matches!(e, SemanticError::UnknownMethod(..))));
+        assert!(has(|e| matches!(e, SemanticError::CreatedMutableBorrowWhileImmutableBorrow)));
+    }
+
+    #[test]
+    fn test_trait_errors() {
+        let source = r#"
+            trait Shape {
+                fn area(&self) -> int;
+                fn grow(&mut self, by: int);
+            }
+            struct Square { side: int }
+            struct Line { len: int }
+            impl Shape for Square {
+                fn area(&self) -> float { return 1.0; }
+                fn perimeter(&self) -> int { return 4; }
+            }
+            impl Missing for Line {
+                fn len(&self) -> int { return self.len; }
+            }
+            fn measure(s: dyn Shape) {}
+            fn main() -> int {
+                let s: boxed dyn Shape = box Square { side: 3 };
+                s.grow(1);
+                let l: boxed dyn Shape = box Line { len: 1 };
+                return 0;
+            }
+        "#;
+        let errors = analyze_source(source);
+        let has = |f: fn(&SemanticError) -> bool| count_errors(&errors, f) > 0;
+
+        assert!(has(|e| matches!(e, SemanticError::TraitMethodMismatch(..))));
+        assert!(has(|e| matches!(e, SemanticError::NotATraitMethod(..))));
+        assert!(has(|e| matches!(e, SemanticError::MissingTraitMethod(..))));
+        assert!(has(|e| matches!(e, SemanticError::UndefinedTrait(_))));
+        assert!(has(|e| matches!(e, SemanticError::UnboxedDyn(_))));
+        assert!(has(|e| matches!(e, SemanticError::BorrowMutNonMutable)));
+        assert!(has(|e| matches!(e, SemanticError::LetTypeMismatch(..))));
+    }
+
+    #[test]
+    fn test_tuple_errors() {
+        let source = r#"
+            fn main() -> int {
+                let (a, b) = 5;
+                let (c, d) = (1, 2, 3);
+                let t = (1, 2);
+                let e = t.2;
+                let f = t.x;
+                return 0;
+            }
+        "#;
+        let errors = analyze_source(source);
+        assert_eq!(
+            count_errors(&errors, |e| matches!(e, SemanticError::DestructureMismatch(2, _))),
+            2
+        );
+        assert_eq!(count_errors(&errors, |e| matches!(e, SemanticError::UnknownField(..))), 2);
+    }
+
+    #[test]
+    fn test_constant_errors() {
+        let source = r#"
+            fn one() -> int { return 1; }
+            global g: int = one();
+            const A: int = one();
+            const B: int = g * 2;
+            const C: int = 1 / 0;
+            const D: int = E;
+            const E: int = 2;
+            const F: str = "a" + 1;
+            fn main() -> int {
+                E = 3;
+                return E;
+            }
+        "#;
+        let errors = analyze_source(source);
+        // F doesn't type check, so it isn't evaluated at all
+        assert_eq!(count_errors(&errors, |e| matches!(e, SemanticError::NotConstant(_))), 4);
+        assert_eq!(count_errors(&errors, |e| matches!(e, SemanticError::InfixTypeMismatch(..))), 1);
+        assert_eq!(count_errors(&errors, |e| matches!(e, SemanticError::AssignToConst(_))), 1);
+    }
+
+    #[test]
+    fn test_range_errors() {
+        let source = r#"
+            const NONE: int = 0;
+            fn main() -> int {
+                for i in 0..10 step 0 { }
+                for i in 0..10 step NONE { }
+                for i in 0..10 step 0.5 { }
+                return 0;
+            }
+        "#;
+        let errors = analyze_source(source);
+        assert_eq!(count_errors(&errors, |e| matches!(e, SemanticError::ZeroStep)), 2);
+        assert_eq!(count_errors(&errors, |e| matches!(e, SemanticError::ForTypeMismatch(..))), 1);
+    }
+
+    #[test]
+    fn test_branch_value_errors() {
+        let source = r#"
+            fn main() -> int {
+                let a = if true { 1 } else { 2.0 };
+                let b = if true { 1 };
+                let c = if true { 1 } else { let y = 2; };
+                let d = switch a { 1 -> 5; 2 -> 6; };
+                let e = switch a { 1 -> 5; . -> { return 0; } };
+                let f = { let y = 1; };
+                return e;
+            }
+        "#;
+        let errors = analyze_source(source);
+        assert_eq!(count_errors(&errors, |e| matches!(e, SemanticError::BranchTypeMismatch(..))), 1);
+        assert_eq!(count_errors(&errors, |e| matches!(e, SemanticError::MissingElse)), 1);
+        assert_eq!(count_errors(&errors, |e| matches!(e, SemanticError::MissingDefaultArm)), 1);
+        assert_eq!(count_errors(&errors, |e| matches!(e, SemanticError::NoBranchValue)), 2);
+        assert_eq!(errors.len(), 5);
+    }
+
+    #[test]
+    fn test_switch_arm_errors() {
+        let source = r#"
+            enum Shape { Circle(int), Empty }
+            const TWO: int = 2;
+            fn main() -> int {
+                let n = 3;
+                switch n {
+                    1, 2 -> return 1;
+                    TWO -> return 2;
+                    5..10 -> return 3;
+                    9..=12 -> return 4;
+                    x if x > 20 -> return x;
+                    x if x > 20 -> return x;
+                    y if y -> return 5;
+                }
+                switch true { false..true -> return 1; . -> return 2; }
+                switch Shape::Empty {
+                    Shape::Circle(r) if r > 1 -> return r;
+                    Shape::Circle(r) -> return r;
+                    Shape::Empty -> return 0;
+                    Shape::Empty -> return 9;
+                }
+            }
+        "#;
+        let errors = analyze_source(source);
+        // guarded arms never count as duplicates
+        assert_eq!(count_errors(&errors, |e| matches!(e, SemanticError::DuplicateArm(..))), 3);
+        assert_eq!(count_errors(&errors, |e| matches!(e, SemanticError::UnorderedRangeArm(..))), 1);
+        assert_eq!(count_errors(&errors, |e| matches!(e, SemanticError::IfTypeMismatch(..))), 1);
+        assert_eq!(errors.len(), 5);
+    }
+
+    #[test]
+    fn test_char_errors() {
+        let source = r#"
+            fn main() -> int {
+                for c in 10 { }
+                let a = 'a' + 'b';
+                let f = 1.5 as char;
+                let b: bool = 'a' == "a";
+                return 0;
+            }
+        "#;
+        let errors = analyze_source(source);
+        assert_eq!(count_errors(&errors, |e| matches!(e, SemanticError::ForTypeMismatch(..))), 1);
+        assert_eq!(count_errors(&errors, |e| matches!(e, SemanticError::InfixTypeMismatch(..))), 2);
+        assert_eq!(count_errors(&errors, |e| matches!(e, SemanticError::InvalidCast(..))), 1);
+    }
+
+    #[test]
+    fn test_sized_int_errors() {
+        let source = r#"
+            fn main() -> int {
+                let a: u8 = 256;
+                let b: i8 = -129;
+                let c: u64 = -1;
+                let d: u16 = 65535;
+                let e = d + (1 as u8);
+                let f = -d;
+                let g = 1.5 +% 2.5;
+                let h: i32 = 2147483647;
+                return 0;
+            }
+        "#;
+        let errors = analyze_source(source);
+        assert_eq!(count_errors(&errors, |e| matches!(e, SemanticError::IntOutOfRange(..))), 3);
+        assert_eq!(count_errors(&errors, |e| matches!(e, SemanticError::InfixTypeMismatch(..))), 2);
+        assert_eq!(count_errors(&errors, |e| matches!(e, SemanticError::PrefixTypeMismatch(..))), 1);
+    }
+
+    #[test]
+    fn test_sized_int_elements_out_of_range() {
+        let source = r#"
+            fn main() {
+                let a: [u8; 2] = [1, 256];
+                let b: [i8; 4] = [-129; 4];
+                let c: boxed u16 = box 65536;
+            }
+        "#;
+        let errors = analyze_source(source);
+        assert_eq!(count_errors(&errors, |e| matches!(e, SemanticError::IntOutOfRange(..))), 3);
+    }
+
+    #[test]
+    fn test_type_alias_errors() {
+        let source = r#"
+            type Counter = boxed mut int;
+            type Loop = (int, Loop);
+            newtype Meters = float;
+            newtype Seconds = float;
+            struct Meters { x: int }
+
+            fn bump(c: Counter) {
+                *c += 1;
+            }
+
+            fn main() -> int {
+                let m: Meters = 1.5;
+                let s = 2.0 as Seconds;
+                let a = m + s;
+                let b = s as Meters;
+                let f: float = s;
+                bump(3);
+                return 0;
+            }
+        "#;
+        let errors = analyze_source(source);
+        assert_eq!(count_errors(&errors, |e| matches!(e, SemanticError::RecursiveType(..))), 1);
+        assert_eq!(count_errors(&errors, |e| matches!(e, SemanticError::DuplicateSymbol(..))), 1);
+        assert_eq!(count_errors(&errors, |e| matches!(e, SemanticError::LetTypeMismatch(..))), 2);
+        assert_eq!(count_errors(&errors, |e| matches!(e, SemanticError::InfixTypeMismatch(..))), 1);
+        assert_eq!(count_errors(&errors, |e| matches!(e, SemanticError::InvalidCast(..))), 1);
+        // the parameter is named by its alias rather than as `boxed mut int`
+        assert_eq!(
+            count_errors(&errors, |e| matches!(e, SemanticError::ArgTypeMismatch(..))
+                && e.to_string().contains("Counter")),
+            1
+        );
+    }
+
+    #[test]
+    fn test_overloads_by_literal_arguments() {
+        let source = r#"
+            fn f(x: u8) -> int { return 1; }
+            fn f(x: str) -> int { return 2; }
+            fn g(x: int?) -> int { return 3; }
+            fn g(x: str) -> int { return 4; }
+            fn k(x: u8) -> int { return 5; }
+            fn k(x: u16) -> int { return 6; }
+
+            fn main() -> int {
+                let a = f(7) + g(none) + k(300);
+                let b = k(7);
+                return a;
+            }
+        "#;
+        let errors = analyze_source(source);
+        // `7` fits both a u8 and a u16, `300` only the u16
+        assert_eq!(errors.len(), 1);
+        assert_eq!(
+            count_errors(&errors, |e| matches!(
+                e,
+                SemanticError::AmbiguousCall(_, candidates) if candidates == &["k(u8)", "k(u16)"]
+            )),
+            1
+        );
+    }
+
+    #[test]
+    fn test_overload_errors() {
+        let source = r#"
+            fn abs(x: int) -> int { return x; }
+            fn abs(x: float) -> float { return x; }
+            fn abs(y: int) -> int { return y; }
+            fn pick(a: &int, b: &mut int) -> int { return *a; }
+            fn pick(a: &mut int, b: &int) -> int { return *b; }
+
+            fn main() {
+                let x: mut int = 1;
+                let y: mut int = 2;
+                let p = pick(&mut x, &mut y);
+                let s = abs("a");
+                let f = abs;
+            }
+        "#;
+        let errors = analyze_source(source);
+        // overloads need different params
+        assert_eq!(count_errors(&errors, |e| matches!(e, SemanticError::DuplicateSymbol(..))), 1);
+        assert_eq!(count_errors(&errors, |e| matches!(e, SemanticError::NoMatchingOverload(..))), 1);
+        // the error names every function the call could go to
+        assert_eq!(
+            count_errors(&errors, |e| matches!(
+                e,
+                SemanticError::AmbiguousCall(_, candidates)
+                    if candidates == &["pick(&int, &mut int)", "pick(&mut int, &int)"]
+            )),
+            1
+        );
+        assert_eq!(
+            count_errors(&errors, |e| matches!(
+                e,
+                SemanticError::AmbiguousCall(i, _) if i.value() == "abs"
+            )),
+            1
+        );
+    }
+
+    #[test]
+    fn test_generic_function_is_not_overloaded() {
+        let generic_first = r#"
+            fn f<T>(x: T) -> T { return x; }
+            fn f(y: int) -> int { return y; }
+            fn main() -> int { return f(1); }
+        "#;
+        let generic_last = r#"
+            fn f(y: int) -> int { return y; }
+            fn f<T>(x: T) -> T { return x; }
+            fn main() -> int { return f(1); }
+        "#;
+        for source in [generic_first, generic_last] {
+            let errors = analyze_source(source);
+            assert_eq!(count_errors(&errors, |e| matches!(e, SemanticError::DuplicateSymbol(..))), 1);
+        }
+    }
+}
@@ -0,0 +1,98 @@
+use crate::{
+    aliases::{Result, TypeInfoRc},
+    err::HarpyError,
+    lexer::span::Span,
+    parser::types::Type,
+};
+
+use super::{analyzer::Analyzer, err::SemanticError, return_status::ReturnStatus};
+
+// collects the values the branches of an if, switch or block expression
+// evaluate to; the first one decides the type the rest are checked against
+pub struct BranchUnifier {
+    expected: Option<Type>,
+    ty: Option<Type>,
+    // branches that don't evaluate to anything
+    missing: Vec<Span>,
+    unresolved: bool,
+}
+
+impl BranchUnifier {
+    pub fn new(expected: Option<&Type>) -> Self {
+        Self {
+            expected: expected.cloned(),
+            ty: None,
+            missing: vec![],
+            unresolved: false,
+        }
+    }
+
+    // what the next branch should evaluate to, if it is known yet
+    pub fn expected(&self) -> Option<&Type> {
+        self.ty.as_ref().or(self.expected.as_ref())
+    }
+
+    // `has_value` is whether the branch ends in something that evaluates to a
+    // value; when it does but `value` is missing, its error is already reported.
+    // a branch that always returns doesn't need a value at all
+    pub fn add(
+        &mut self,
+        has_value: bool,
+        status: ReturnStatus,
+        value: Option<TypeInfoRc>,
+        span: Span,
+        analyzer: &mut Analyzer,
+    ) {
+        let Some(value) = value else {
+            if has_value {
+                self.unresolved = true;
+            } else if status != ReturnStatus::Always {
+                self.missing.push(span);
+            }
+            return;
+        };
+
+        match &self.ty {
+            Some(ty) if !ty.assign_compatible(&value.ttype) => {
+                analyzer.report_semantic_error(
+                    SemanticError::BranchTypeMismatch(value.ttype.clone(), ty.clone()),
+                    span,
+                );
+            }
+            Some(_) => (),
+            None => {
+                self.ty = Some(Type {
+                    mutable: false,
+                    inner: value.inner.clone(),
+                })
+            }
+        }
+    }
+
+    // the type of the whole expression spanning `span`. without any branch
+    // to take it from, the first one missing a value is the error of the
+    // expression itself
+    pub fn finish(self, span: Span, analyzer: &mut Analyzer) -> Result<Type> {
+        // the branches that should have given it are already reported
+        if self.ty.is_none() && self.unresolved && self.missing.is_empty() {
+            return Ok(self.expected.unwrap_or(Type::unknown()));
+        }
+
+        let mut missing = self.missing.into_iter();
+        let ty = match self.ty {
+            Some(ty) => ty,
+            None => {
+                let first = missing.next().unwrap_or(span);
+                missing.for_each(|span| {
+                    analyzer.report_semantic_error(SemanticError::NoBranchValue, span);
+                });
+                return HarpyError::semantic(SemanticError::NoBranchValue, first);
+            }
+        };
+
+        missing.for_each(|span| {
+            analyzer.report_semantic_error(SemanticError::NoBranchValue, span);
+        });
+        Ok(ty)
+    }
+}
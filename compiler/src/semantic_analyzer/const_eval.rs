@@ -0,0 +1,328 @@
+use crate::{
+    aliases::Result,
+    err::HarpyError,
+    lexer::tokens::{Ident, Lit},
+    parser::{
+        expr::{
+            infix::{InfixOp, InfixOpKind},
+            prefix::{PrefixOp, PrefixOpKind},
+            Expr,
+        },
+        node::Node,
+        types::{BaseType, PrimitiveType, SizedInt, TypeInner},
+    },
+};
+
+use super::{analyzer::Analyzer, err::SemanticError, symbol_info::SymbolInfoKind};
+
+fn int(value: i64) -> Lit {
+    Lit::LitInt(value as u64)
+}
+
+// `int` keeps its own literal, the other int types carry their type along
+fn sized(value: i128, ty: PrimitiveType) -> Lit {
+    match ty {
+        PrimitiveType::Int => int(value as i64),
+        _ => Lit::LitSized(SizedInt { value, ty }),
+    }
+}
+
+fn int_value(lit: &Lit) -> Option<(i128, PrimitiveType)> {
+    match lit {
+        Lit::LitInt(i) => Some((*i as i64 as i128, PrimitiveType::Int)),
+        Lit::LitSized(s) => Some((s.value, s.ty)),
+        _ => None,
+    }
+}
+
+fn float(value: f64) -> Lit {
+    Lit::LitFloat(value.to_bits())
+}
+
+// computes an already type checked expression the way the vm would; whatever
+// would fail at runtime (overflow, division by zero, bad shifts) isn't constant
+pub struct ConstEvaluator;
+
+impl ConstEvaluator {
+    pub fn eval(expr: &Node<Expr>, analyzer: &mut Analyzer) -> Result<Lit> {
+        let value = match &**expr {
+            // int literals were registered with the type they were resolved to
+            Expr::Literal(lit) => analyzer.constant_value(lit.id()).or_else(|| Some(lit.value().clone())),
+            Expr::Ident(ident) => Self::eval_ident(ident, analyzer),
+            Expr::Prefix(op, rhs) => {
+                // a negated int literal is registered already negated
+                if let (PrefixOpKind::Minus, Expr::Literal(lit)) = (&op.op, &***rhs) {
+                    if let (Lit::LitInt(_), Some(value)) = (lit.value(), analyzer.constant_value(lit.id())) {
+                        return Ok(value);
+                    }
+                }
+
+                let rhs = Self::eval(rhs, analyzer)?;
+                Self::eval_prefix(op, rhs)
+            }
+            // the right side is only evaluated when the left one doesn't decide the result
+            Expr::Infix(lhs, op, rhs) if matches!(op.op, InfixOpKind::And | InfixOpKind::Or) => {
+                match Self::eval(lhs, analyzer)? {
+                    Lit::LitBool(b) if b == (op.op == InfixOpKind::Or) => Some(Lit::LitBool(b)),
+                    Lit::LitBool(_) => Some(Self::eval(rhs, analyzer)?),
+                    _ => None,
+                }
+            }
+            Expr::Infix(lhs, op, rhs) => {
+                let lhs = Self::eval(lhs, analyzer)?;
+                let rhs = Self::eval(rhs, analyzer)?;
+                Self::eval_infix(op, lhs, rhs)
+            }
+            Expr::Cast(cast) => {
+                let value = Self::eval(&cast.expr, analyzer)?;
+                Self::eval_cast(&analyzer.lower_type(&cast.ty).inner, value)
+            }
+            _ => None,
+        };
+
+        match value {
+            Some(value) => Ok(value),
+            None => HarpyError::semantic(SemanticError::NotConstant(expr.clone()), expr.span()),
+        }
+    }
+
+    fn eval_ident(ident: &Node<Ident>, analyzer: &mut Analyzer) -> Option<Lit> {
+        let symbol = analyzer.get_symbol(ident).ok()?;
+        let symbol = symbol.borrow();
+        match &symbol.kind {
+            SymbolInfoKind::Constant(c) => c.value.clone(),
+            _ => None,
+        }
+    }
+
+    fn eval_prefix(op: &PrefixOp, rhs: Lit) -> Option<Lit> {
+        let value = match (&op.op, rhs) {
+            (PrefixOpKind::Plus, rhs @ (Lit::LitInt(_) | Lit::LitSized(_) | Lit::LitFloat(_))) => rhs,
+            (PrefixOpKind::Minus, Lit::LitFloat(f)) => float(-f64::from_bits(f)),
+            (PrefixOpKind::Neg, Lit::LitBool(b)) => Lit::LitBool(!b),
+            (PrefixOpKind::Minus, rhs) => {
+                let (i, ty) = int_value(&rhs)?;
+                let value = -i;
+                ty.fits(value).then(|| sized(value, ty))?
+            }
+            (PrefixOpKind::BitNot, rhs) => {
+                let (i, ty) = int_value(&rhs)?;
+                sized(ty.wrap(!i)?, ty)
+            }
+            _ => return None,
+        };
+
+        Some(value)
+    }
+
+    fn eval_infix(op: &InfixOp, lhs: Lit, rhs: Lit) -> Option<Lit> {
+        match (lhs, rhs) {
+            (Lit::LitInt(a), Lit::LitInt(b)) => Self::eval_int(&op.op, a as i64, b as i64),
+            (Lit::LitSized(a), Lit::LitSized(b)) if a.ty == b.ty => {
+                Self::eval_sized(&op.op, a.value, b.value, a.ty)
+            }
+            // the amount to shift by can be of any int type
+            (lhs, rhs) if matches!(op.op, InfixOpKind::Shl | InfixOpKind::Shr) => {
+                let (a, ty) = int_value(&lhs)?;
+                let (b, _) = int_value(&rhs)?;
+                Self::eval_sized(&op.op, a, b, ty)
+            }
+            (Lit::LitFloat(a), Lit::LitFloat(b)) => {
+                Self::eval_float(&op.op, f64::from_bits(a), f64::from_bits(b))
+            }
+            (Lit::LitBool(a), Lit::LitBool(b)) => match op.op {
+                InfixOpKind::Eq => Some(Lit::LitBool(a == b)),
+                InfixOpKind::Neq => Some(Lit::LitBool(a != b)),
+                _ => None,
+            },
+            (Lit::LitChar(a), Lit::LitChar(b)) => match op.op {
+                InfixOpKind::Gt
+                | InfixOpKind::Lt
+                | InfixOpKind::GtEq
+                | InfixOpKind::LtEq
+                | InfixOpKind::Eq
+                | InfixOpKind::Neq => Self::eval_int(&op.op, a as i64, b as i64),
+                _ => None,
+            },
+            // the vm can concatenate strings but not compare them
+            (Lit::LitStr(a), Lit::LitStr(b)) if op.op == InfixOpKind::Plus => {
+                Some(Lit::LitStr(a + &b))
+            }
+            _ => None,
+        }
+    }
+
+    fn eval_int(op: &InfixOpKind, a: i64, b: i64) -> Option<Lit> {
+        Self::eval_sized(op, a as i128, b as i128, PrimitiveType::Int)
+    }
+
+    // every int type fits in an i128, so the result is computed there and then
+    // checked, wrapped or saturated into `ty`
+    fn eval_sized(op: &InfixOpKind, a: i128, b: i128, ty: PrimitiveType) -> Option<Lit> {
+        use InfixOpKind::*;
+
+        let bits = ty.int_bits()? as i128;
+        let checked = |value: Option<i128>| value.filter(|v| ty.fits(*v)).map(|v| sized(v, ty));
+
+        let value = match op {
+            Plus => checked(a.checked_add(b))?,
+            Minus => checked(a.checked_sub(b))?,
+            Mult => checked(a.checked_mul(b))?,
+            Div => checked(a.checked_div(b))?,
+            Mod => checked(a.checked_rem(b))?,
+            WrapPlus => sized(ty.wrap(a + b)?, ty),
+            WrapMinus => sized(ty.wrap(a - b)?, ty),
+            WrapMult => sized(ty.wrap(a.wrapping_mul(b))?, ty),
+            SatPlus => sized(ty.saturate(a + b)?, ty),
+            SatMinus => sized(ty.saturate(a - b)?, ty),
+            SatMult => sized(ty.saturate(a.checked_mul(b).unwrap_or(match (a < 0) == (b < 0) {
+                true => i128::MAX,
+                false => i128::MIN,
+            }))?, ty),
+            BitAnd => sized(a & b, ty),
+            BitOr => sized(a | b, ty),
+            BitXor => sized(a ^ b, ty),
+            Shl if (0..bits).contains(&b) => sized(ty.wrap(a << b)?, ty),
+            Shr if (0..bits).contains(&b) => sized(a >> b, ty),
+            Gt => Lit::LitBool(a > b),
+            Lt => Lit::LitBool(a < b),
+            GtEq => Lit::LitBool(a >= b),
+            LtEq => Lit::LitBool(a <= b),
+            Eq => Lit::LitBool(a == b),
+            Neq => Lit::LitBool(a != b),
+            _ => return None,
+        };
+
+        Some(value)
+    }
+
+    fn eval_float(op: &InfixOpKind, a: f64, b: f64) -> Option<Lit> {
+        use InfixOpKind::*;
+
+        let value = match op {
+            Plus => float(a + b),
+            Minus => float(a - b),
+            Mult => float(a * b),
+            Div => float(a / b),
+            Mod => float(a % b),
+            Gt => Lit::LitBool(a > b),
+            Lt => Lit::LitBool(a < b),
+            GtEq => Lit::LitBool(a >= b),
+            LtEq => Lit::LitBool(a <= b),
+            Eq => Lit::LitBool(a == b),
+            Neq => Lit::LitBool(a != b),
+            _ => return None,
+        };
+
+        Some(value)
+    }
+
+    fn eval_cast(to: &TypeInner, value: Lit) -> Option<Lit> {
+        let TypeInner::Base(BaseType::Primitive(to)) = to else {
+            return None;
+        };
+
+        let value = match (to, value) {
+            (PrimitiveType::Float, Lit::LitFloat(f)) => Lit::LitFloat(f),
+            (PrimitiveType::Float, i) => float(int_value(&i)?.0 as f64),
+            // a float saturates into the int type, anything else keeps its low bits
+            (t, Lit::LitFloat(f)) if t.is_int() => sized(t.saturate(f64::from_bits(f) as i128)?, *t),
+            (t, Lit::LitBool(b)) if t.is_int() => sized(b as i128, *t),
+            (t, Lit::LitChar(c)) if t.is_int() => sized(t.wrap(c as i128)?, *t),
+            (t, i) if t.is_int() => sized(t.wrap(int_value(&i)?.0)?, *t),
+            // only unicode scalar values make a char
+            (PrimitiveType::Char, Lit::LitChar(c)) => Lit::LitChar(c),
+            (PrimitiveType::Char, i) => Lit::LitChar(char::from_u32(u32::try_from(int_value(&i)?.0).ok()?)?),
+            // into a newtype over them
+            (PrimitiveType::Bool, b @ Lit::LitBool(_)) => b,
+            (PrimitiveType::Str, s @ Lit::LitStr(_)) => s,
+            _ => return None,
+        };
+
+        Some(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::span::Span;
+
+    fn op(op: InfixOpKind) -> InfixOp {
+        InfixOp::new(op, Span::default())
+    }
+
+    #[test]
+    fn test_eval_int_matches_vm_arithmetic() {
+        assert_eq!(ConstEvaluator::eval_int(&InfixOpKind::Div, -7, 2), Some(int(-3)));
+        assert_eq!(ConstEvaluator::eval_int(&InfixOpKind::Mod, -7, 2), Some(int(-1)));
+        assert_eq!(ConstEvaluator::eval_int(&InfixOpKind::Shr, -8, 1), Some(int(-4)));
+        assert_eq!(ConstEvaluator::eval_int(&InfixOpKind::Lt, 1, 2), Some(Lit::LitBool(true)));
+    }
+
+    #[test]
+    fn test_eval_int_leaves_runtime_errors_alone() {
+        assert_eq!(ConstEvaluator::eval_int(&InfixOpKind::Div, 1, 0), None);
+        assert_eq!(ConstEvaluator::eval_int(&InfixOpKind::Mod, 1, 0), None);
+        assert_eq!(ConstEvaluator::eval_int(&InfixOpKind::Plus, i64::MAX, 1), None);
+        assert_eq!(ConstEvaluator::eval_int(&InfixOpKind::Shl, 1, 64), None);
+        assert_eq!(ConstEvaluator::eval_int(&InfixOpKind::Shl, 1, -1), None);
+    }
+
+    #[test]
+    fn test_eval_sized_overflow() {
+        let u8 = PrimitiveType::U8;
+        let eval = |op, a, b| ConstEvaluator::eval_sized(&op, a, b, u8);
+        assert_eq!(eval(InfixOpKind::Plus, 200, 100), None);
+        assert_eq!(eval(InfixOpKind::WrapPlus, 200, 100), Some(sized(44, u8)));
+        assert_eq!(eval(InfixOpKind::SatPlus, 200, 100), Some(sized(255, u8)));
+        assert_eq!(eval(InfixOpKind::SatMinus, 1, 2), Some(sized(0, u8)));
+        assert_eq!(eval(InfixOpKind::Shl, 1, 8), None);
+
+        let i8 = PrimitiveType::I8;
+        assert_eq!(ConstEvaluator::eval_sized(&InfixOpKind::Div, -128, -1, i8), None);
+        assert_eq!(ConstEvaluator::eval_sized(&InfixOpKind::WrapMult, -128, -1, i8), Some(sized(-128, i8)));
+
+        let u64 = TypeInner::Base(BaseType::Primitive(PrimitiveType::U64));
+        assert_eq!(ConstEvaluator::eval_cast(&u64, int(-1)), Some(sized(u64::MAX as i128, PrimitiveType::U64)));
+        assert_eq!(ConstEvaluator::eval_cast(&u64, float(-2.5)), Some(sized(0, PrimitiveType::U64)));
+    }
+
+    #[test]
+    fn test_eval_infix_mixed_literals() {
+        let concat = ConstEvaluator::eval_infix(
+            &op(InfixOpKind::Plus),
+            Lit::LitStr("ab".to_string()),
+            Lit::LitStr("cd".to_string()),
+        );
+        assert_eq!(concat, Some(Lit::LitStr("abcd".to_string())));
+
+        let half = ConstEvaluator::eval_infix(&op(InfixOpKind::Div), float(1.0), float(2.0));
+        assert_eq!(half, Some(float(0.5)));
+
+        let str_eq = ConstEvaluator::eval_infix(
+            &op(InfixOpKind::Eq),
+            Lit::LitStr("a".to_string()),
+            Lit::LitStr("a".to_string()),
+        );
+        assert_eq!(str_eq, None);
+    }
+
+    #[test]
+    fn test_eval_cast() {
+        let int_type = TypeInner::Base(BaseType::Primitive(PrimitiveType::Int));
+        let float_type = TypeInner::Base(BaseType::Primitive(PrimitiveType::Float));
+
+        assert_eq!(ConstEvaluator::eval_cast(&int_type, float(-2.75)), Some(int(-2)));
+        assert_eq!(ConstEvaluator::eval_cast(&int_type, Lit::LitBool(true)), Some(int(1)));
+        assert_eq!(ConstEvaluator::eval_cast(&float_type, int(-3)), Some(float(-3.0)));
+        assert_eq!(ConstEvaluator::eval_cast(&float_type, Lit::LitBool(true)), None);
+
+        let char_type = TypeInner::Base(BaseType::Primitive(PrimitiveType::Char));
+        assert_eq!(ConstEvaluator::eval_cast(&int_type, Lit::LitChar('é')), Some(int(233)));
+        assert_eq!(ConstEvaluator::eval_cast(&char_type, int(97)), Some(Lit::LitChar('a')));
+        // surrogates aren't unicode scalar values
+        assert_eq!(ConstEvaluator::eval_cast(&char_type, int(0xD800)), None);
+        assert_eq!(ConstEvaluator::eval_cast(&char_type, int(-1)), None);
+    }
+}
@@ -49,19 +49,24 @@ impl ConstPool {
     }
 
     pub fn register(&mut self, lit: &Node<Literal>, info: &TypeInfoRc) -> ConstIndex {
-        if let Some(i) = self.map.get(lit.value()) {
-            self.node_map.insert(lit.id(), *i);
+        self.register_value(lit.id(), lit.value(), info)
+    }
+
+    // `id` doesn't have to be a literal; folded expressions and constants are loaded the same way
+    pub fn register_value(&mut self, id: NodeId, lit: &Lit, info: &TypeInfoRc) -> ConstIndex {
+        if let Some(i) = self.map.get(lit) {
+            self.node_map.insert(id, *i);
             return *i;
         }
 
         //FIX!!!
         let i = ConstIndex(self.pool.len().try_into().unwrap());
         self.pool.push(ConstInfo {
-            lit: lit.value().clone(),
+            lit: lit.clone(),
             type_idx: info.idx,
         });
-        self.map.insert(lit.value().clone(), i);
-        self.node_map.insert(lit.id(), i);
+        self.map.insert(lit.clone(), i);
+        self.node_map.insert(id, i);
         i
     }
 
@@ -69,6 +74,10 @@ impl ConstPool {
         self.pool.get(id.0 as usize).map(|c| &c.lit)
     }
 
+    pub fn get_value(&self, id: NodeId) -> Option<&Lit> {
+        self.node_map.get(&id).and_then(|i| self.get(*i))
+    }
+
     pub(in crate::semantic_analyzer) fn to_runtime(
         self,
         type_table: &RuntimeConversionTypeTable,
@@ -103,6 +112,10 @@ impl RuntimeConstPool {
         self.node_map[&idx]
     }
 
+    pub fn contains_mapping(&self, idx: NodeId) -> bool {
+        self.node_map.contains_key(&idx)
+    }
+
     pub fn iter(&self) -> std::slice::Iter<'_, RuntimeConstInfo> {
         self.pool.iter()
     }
@@ -125,6 +138,7 @@ mod tests {
             size: ty.calc_size(),
             ttype: ty,
             idx,
+            alias: None,
         })
     }
 
@@ -186,6 +200,21 @@ mod tests {
         assert_eq!(pool.pool.len(), 2);
     }
 
+    #[test]
+    fn test_const_pool_register_value_shares_literal_entry() {
+        let mut pool = ConstPool::new();
+        let int_type = create_type_info(Type::int(), TypeIndex(1));
+
+        let idx1 = pool.register(&literal(Lit::LitInt(7)), &int_type);
+        let idx2 = pool.register_value(NodeId(5), &Lit::LitInt(7), &int_type);
+        let idx3 = pool.register_value(NodeId(6), &Lit::LitInt(8), &int_type);
+
+        assert_eq!(idx1, idx2);
+        assert_eq!(idx3, ConstIndex(2));
+        assert_eq!(pool.node_map[&NodeId(5)], idx1);
+        assert_eq!(pool.node_map[&NodeId(6)], idx3);
+    }
+
     #[test]
     fn test_const_pool_to_runtime_conversion() {
         let mut pool = ConstPool::new();
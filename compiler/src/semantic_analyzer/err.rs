@@ -7,7 +7,8 @@ use crate::{
     parser::{
         expr::{infix::InfixOp, prefix::PrefixOp, Expr},
         node::Node,
-        types::Type,
+        statements::JumpKind,
+        types::{PrimitiveType, Type},
     },
 };
 
@@ -23,6 +24,7 @@ pub enum SemanticError {
     PrefixTypeMismatch(PrefixOp, Type),
     InfixTypeMismatch(InfixOp, Type, Type),
     LetTypeMismatch(Type, TypeInfoRc),
+    DestructureMismatch(usize, TypeInfoRc),
     ForTypeMismatch(TypeInfoRc, Type),
     WhileTypeMismatch(TypeInfoRc),
     IfTypeMismatch(TypeInfoRc),
@@ -43,8 +45,60 @@ pub enum SemanticError {
     ReturnRefToLocal,
     AssignToRValue,
     UninitializedVar,
+    TooManyLocals(Node<Ident>),
     CantInferType,
     NotAllPathsReturn,
+    UndefinedType(String),
+    RecursiveStruct(Node<Ident>),
+    RecursiveType(Node<Ident>),
+    DuplicateField(Node<Ident>),
+    UnknownField(Node<Ident>, String),
+    MissingField(String, String),
+    NoFields(Type),
+    NotIndexable(Type),
+    InvalidIndexType(Type),
+    IndexOutOfRange(u64, usize),
+    ArrayElementMismatch(Type, Type),
+    UnknownMethod(Node<Ident>, Type),
+    NotAnEnum(Node<Ident>),
+    DuplicateVariant(Node<Ident>),
+    UnknownVariant(Node<Ident>, String),
+    BindingCountMismatch(Node<Ident>, usize, usize),
+    NonExhaustiveMatch(Vec<String>),
+    JumpOutsideLoop(JumpKind),
+    UndefinedLabel(Node<Ident>),
+    AssignToCapture(Node<Expr>),
+    CaptureOfRef(Ident),
+    TypeParamConflict(String, Type, Type),
+    CantInferTypeParam(String),
+    InstantiationLimit(String),
+    InvalidCast(Type, Type),
+    NotAnOptional(Type),
+    NotAResult(Type),
+    InvalidTry(Type),
+    MissingSelf(Node<Ident>),
+    UndefinedTrait(String),
+    TraitSelfByValue(Node<Ident>),
+    NotATraitMethod(Node<Ident>, String),
+    MissingTraitMethod(String, String),
+    TraitMethodMismatch(Node<Ident>, String),
+    UnboxedDyn(Type),
+    ImportFailed(String, std::io::Error),
+    ImportCycle(Vec<String>),
+    DuplicateImport(String),
+    UnknownModuleItem(String, String),
+    PrivateItem(String, String),
+    NotConstant(Node<Expr>),
+    ZeroStep,
+    NoBranchValue,
+    BranchTypeMismatch(Type, Type),
+    MissingElse,
+    MissingDefaultArm,
+    DuplicateArm(String),
+    UnorderedRangeArm(TypeInfoRc),
+    IntOutOfRange(i128, PrimitiveType),
+    AmbiguousCall(Ident, Vec<String>),
+    NoMatchingOverload(Ident, Vec<Type>, Vec<String>),
 }
 
 impl Display for SemanticError {
@@ -64,7 +118,7 @@ impl Display for SemanticError {
 
             ArgTypeMismatch(got, expected) => format!(
                 "incorrect arguments; expected {}{}{} got {}{}{}",
-                Green, expected.ttype, Reset, Red, got, Reset,
+                Green, expected, Reset, Red, got, Reset,
             ),
 
             PrefixTypeMismatch(op, ty) => {
@@ -84,6 +138,11 @@ impl Display for SemanticError {
                 Red, ty, Reset, Green, i, Reset
             ),
 
+            DestructureMismatch(count, ty) => format!(
+                "cannot destructure {}{}{} into {}{}{} variables",
+                Red, ty, Reset, Green, count, Reset
+            ),
+
             ForTypeMismatch(got, expected) => format!(
                 "type mismatch, expected {}{}{} got {}{}{}",
                 Green, expected, Reset, Red, got, Reset,
@@ -140,8 +199,161 @@ impl Display for SemanticError {
             ReturnRefToLocal => format!("cannot {Red}return{Reset} a {Green}reference{Reset} to a {Red}local{Reset} variable"),
             AssignToRValue => format!("cannot {Green}assign{Reset} to {Red}rvalue{Reset}"),
             UninitializedVar => format!("{Green}variable{Reset} not {Red}initialized{Reset}"),
+            TooManyLocals(i) => format!(
+                "too many {Green}locals{Reset}; {Red}{}{Reset} doesn't fit in its function",
+                i.value()
+            ),
             CantInferType => format!("{Red}Cannot{Reset} {Green}infer{Reset} type; consider giving it a concrete {Green}type{Reset}"),
-            NotAllPathsReturn => format!("not all code paths return a value")
+            NotAllPathsReturn => format!("not all code paths return a value"),
+            UndefinedType(name) => format!("use of undeclared type {Red}{name}{Reset}"),
+            RecursiveStruct(name) => format!(
+                "{Red}{}{Reset} contains itself by value; consider a {Green}boxed{Reset} field",
+                name.value()
+            ),
+            RecursiveType(name) => format!(
+                "type {Red}{}{Reset} is defined in terms of itself",
+                name.value()
+            ),
+            DuplicateField(name) => format!("field {Red}{}{Reset} is already defined", name.value()),
+            UnknownField(field, ty) => format!(
+                "{Green}{ty}{Reset} has no field named {Red}{}{Reset}",
+                field.value()
+            ),
+            MissingField(field, ty) => format!("missing field {Red}{field}{Reset} in {Green}{ty}{Reset}"),
+            NoFields(ty) => format!("{Red}{ty}{Reset} is not a {Green}struct{Reset}"),
+            NotIndexable(ty) => format!("cannot index into a value of type {Red}{ty}{Reset}"),
+            InvalidIndexType(ty) => format!(
+                "array index must be {Green}int{Reset}, got {Red}{ty}{Reset}"
+            ),
+            IndexOutOfRange(index, len) => format!(
+                "index {Red}{index}{Reset} is out of range for an array of length {Green}{len}{Reset}"
+            ),
+            ArrayElementMismatch(got, expected) => format!(
+                "array elements must share a type; expected {Green}{expected}{Reset} got {Red}{got}{Reset}"
+            ),
+            UnknownMethod(method, ty) => format!(
+                "{Green}{ty}{Reset} has no method named {Red}{}{Reset}",
+                method.value()
+            ),
+            NotAnEnum(name) => format!("{Red}{}{Reset} is not an {Green}enum{Reset}", name.value()),
+            DuplicateVariant(name) => format!("variant {Red}{}{Reset} is already defined", name.value()),
+            UnknownVariant(variant, ty) => format!(
+                "{Green}{ty}{Reset} has no variant named {Red}{}{Reset}",
+                variant.value()
+            ),
+            BindingCountMismatch(variant, got, expected) => format!(
+                "{Green}{}{Reset} holds {Green}{expected}{Reset} values, got {Red}{got}{Reset} bindings",
+                variant.value()
+            ),
+            NonExhaustiveMatch(missing) => format!(
+                "non-exhaustive switch; missing {Red}{}{Reset}",
+                missing.join(", ")
+            ),
+            JumpOutsideLoop(kind) => format!("{Red}{kind}{Reset} used outside a {Green}loop{Reset}"),
+            UndefinedLabel(label) => format!(
+                "use of undeclared loop label {Red}'{}{Reset}",
+                label.value()
+            ),
+            AssignToCapture(expr) => format!(
+                "cannot {Green}assign{Reset} to {Red}{expr}{Reset}; closures capture variables by value"
+            ),
+            CaptureOfRef(i) => format!(
+                "closure cannot capture the reference {Red}{}{Reset}; it may outlive what it points to",
+                i.value()
+            ),
+            TypeParamConflict(param, first, second) => format!(
+                "type parameter {Red}{param}{Reset} can't be both {Green}{first}{Reset} and {Green}{second}{Reset}"
+            ),
+            CantInferTypeParam(param) => format!(
+                "cannot {Green}infer{Reset} the type parameter {Red}{param}{Reset}"
+            ),
+            InstantiationLimit(name) => format!(
+                "{Red}{name}{Reset} keeps instantiating itself with new type arguments"
+            ),
+            InvalidCast(from, to) => format!(
+                "cannot cast {Red}{from}{Reset} to {Green}{to}{Reset}"
+            ),
+            NotAnOptional(ty) => format!(
+                "{Red}{ty}{Reset} is not an {Green}optional{Reset}; only optionals match {Green}some{Reset} and {Green}none{Reset}"
+            ),
+            NotAResult(ty) => format!(
+                "{Red}{ty}{Reset} is not a {Green}result{Reset}; only results match {Green}ok{Reset} and {Green}err{Reset}"
+            ),
+            InvalidTry(ty) => format!(
+                "cannot apply {Green}?{Reset} to {Red}{ty}{Reset}; it needs an {Green}optional{Reset} or a {Green}result{Reset}"
+            ),
+            MissingSelf(method) => format!(
+                "method {Red}{}{Reset} has to take {Green}self{Reset}, {Green}&self{Reset} or {Green}&mut self{Reset} first",
+                method.value()
+            ),
+            UndefinedTrait(name) => format!("use of undeclared trait {Red}{name}{Reset}"),
+            TraitSelfByValue(method) => format!(
+                "trait method {Red}{}{Reset} has to take {Green}&self{Reset} or {Green}&mut self{Reset}",
+                method.value()
+            ),
+            NotATraitMethod(method, tr) => format!(
+                "{Red}{}{Reset} is not a method of trait {Green}{tr}{Reset}",
+                method.value()
+            ),
+            MissingTraitMethod(method, tr) => format!(
+                "missing method {Red}{method}{Reset} of trait {Green}{tr}{Reset}"
+            ),
+            TraitMethodMismatch(method, tr) => format!(
+                "{Red}{}{Reset} doesn't match its signature in trait {Green}{tr}{Reset}",
+                method.value()
+            ),
+            UnboxedDyn(ty) => format!(
+                "{Red}{ty}{Reset} can only be used behind a {Green}box{Reset}"
+            ),
+            ImportFailed(path, err) => format!("can't import {Red}{path}{Reset}: {err}"),
+            ImportCycle(chain) => format!(
+                "{Red}import cycle{Reset}: {Green}{}{Reset}",
+                chain.join(" -> ")
+            ),
+            DuplicateImport(alias) => format!(
+                "a module is already imported as {Red}{alias}{Reset}"
+            ),
+            UnknownModuleItem(module, item) => format!(
+                "module {Green}{module}{Reset} has no item named {Red}{item}{Reset}"
+            ),
+            PrivateItem(module, item) => format!(
+                "{Red}{item}{Reset} is private to module {Green}{module}{Reset}"
+            ),
+            NotConstant(expr) => format!(
+                "{Red}{expr}{Reset} can't be evaluated at compile time"
+            ),
+            ZeroStep => format!("a range can't {Green}step{Reset} by {Red}0{Reset}"),
+            NoBranchValue => format!("this doesn't evaluate to a {Red}value{Reset}"),
+            BranchTypeMismatch(got, expected) => format!(
+                "branches must evaluate to the same type; expected {Green}{expected}{Reset} got {Red}{got}{Reset}"
+            ),
+            MissingElse => format!(
+                "{Green}if{Reset} used as a value needs an {Red}else{Reset} branch"
+            ),
+            MissingDefaultArm => format!(
+                "{Green}switch{Reset} used as a value needs a default {Red}.{Reset} arm"
+            ),
+            DuplicateArm(pattern) => format!("{Red}{pattern}{Reset} overlaps an earlier arm"),
+            IntOutOfRange(value, ty) => format!(
+                "{Red}{value}{Reset} doesn't fit in {Green}{ty}{Reset}"
+            ),
+            UnorderedRangeArm(ty) => format!(
+                "range arms only match {Green}int{Reset}, {Green}float{Reset}, {Green}str{Reset} or {Green}char{Reset} values, got {Red}{ty}{Reset}"
+            ),
+            AmbiguousCall(i, candidates) => format!(
+                "{Red}{}{Reset} is ambiguous, it could be any of {Green}{}{Reset}",
+                i.value(),
+                candidates.join(", ")
+            ),
+            NoMatchingOverload(i, args, candidates) => {
+                let args: Vec<_> = args.iter().map(Type::to_string).collect();
+                format!(
+                    "no {Red}{}{Reset} takes ({Red}{}{Reset}), there are {Green}{}{Reset}",
+                    i.value(),
+                    args.join(", "),
+                    candidates.join(", ")
+                )
+            }
         };
 
         write!(f, "{s}")
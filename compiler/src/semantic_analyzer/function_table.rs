@@ -1,12 +1,11 @@
 use std::collections::HashMap;
 
 use crate::{
-    aliases::{Result, SymbolInfoRef},
+    aliases::{Result, SymbolInfoRef, TypeInfoRc},
     err::HarpyError,
     extensions::SymbolInfoRefExt,
     lexer::tokens::Ident,
     parser::{
-        expr::expr::CallExpr,
         node::{Node, NodeId},
         types::TypeInner,
     },
@@ -27,6 +26,8 @@ pub struct FunctionTable {
     map: HashMap<String, FuncIndex>,
     call_map: HashMap<NodeId, FuncIndex>,
     func_delc_map: HashMap<NodeId, FuncIndex>,
+    // name as declared -> the names the functions declared with it are registered as
+    overloads: HashMap<String, Vec<String>>,
 }
 
 impl FunctionTable {
@@ -36,9 +37,27 @@ impl FunctionTable {
             map: HashMap::new(),
             call_map: HashMap::new(),
             func_delc_map: HashMap::new(),
+            overloads: HashMap::new(),
         }
     }
 
+    // `abs(float)`, the name an overload is registered as
+    pub fn mangle(name: &str, params: &[TypeInfoRc]) -> String {
+        let params = params.iter().map(|p| p.ttype.to_string()).collect::<Vec<_>>();
+        format!("{name}({})", params.join(", "))
+    }
+
+    pub fn add_overload(&mut self, name: &str, registered_as: &str) {
+        self.overloads
+            .entry(name.to_owned())
+            .or_default()
+            .push(registered_as.to_owned());
+    }
+
+    pub fn overloads(&self, name: &str) -> &[String] {
+        self.overloads.get(name).map_or(&[], Vec::as_slice)
+    }
+
     pub fn register(&mut self, name: &Node<Ident>, info: SymbolInfoRef) -> FuncIndex {
         if let Some(idx) = self.map.get(name.value()) {
             return *idx;
@@ -52,13 +71,20 @@ impl FunctionTable {
         idx
     }
 
-    pub fn register_call(&mut self, name: &Ident, call_expr: &Node<CallExpr>) -> Option<()> {
-        if let Some(idx) = self.map.get(name.value()) {
-            self.call_map.insert(call_expr.id(), *idx);
-            return Some(());
-        }
+    // `id` is the node calling the function, or naming it as a value: a call,
+    // the method of a method call or an identifier
+    pub fn register_call(&mut self, name: &Ident, id: NodeId) -> Option<()> {
+        let idx = self.map.get(name.value())?;
+        self.call_map.insert(id, *idx);
+        Some(())
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.map.contains_key(name)
+    }
 
-        None
+    pub fn index_of(&self, name: &str) -> Option<FuncIndex> {
+        self.map.get(name).copied()
     }
 
     pub fn get(&self, idx: FuncIndex) -> SymbolInfoRef {
@@ -149,6 +175,10 @@ impl RuntimeFunctionTable {
         self.call_map[&idx]
     }
 
+    pub fn contains_mapping(&self, id: NodeId) -> bool {
+        self.call_map.contains_key(&id)
+    }
+
     pub fn get_function_delc_mapping(&self, id: NodeId) -> FuncIndex {
         self.func_delc_map[&id]
     }
@@ -253,14 +283,14 @@ mod tests {
         let function = create_function_symbol(NodeId(0), vec![], vec![], int_type);
         let _func_idx = table.register(&func_name, function);
         
-        let result = table.register_call(&func_name, &call_expr);
+        let result = table.register_call(&func_name, call_expr.id());
         assert!(result.is_some());
         
         let non_existent_call = Node::dummy(CallExpr {
             ident: (*dummy_ident("non_existent")).clone(),
             args: vec![],
         });
-        let result2 = table.register_call(&Ident { span: Span::default(), value: "non_existent".to_string() }, &non_existent_call);
+        let result2 = table.register_call(&Ident { span: Span::default(), value: "non_existent".to_string() }, non_existent_call.id());
         assert!(result2.is_none());
     }
 
@@ -370,4 +400,19 @@ mod tests {
             assert_eq!(func_info.params.len(), 0);
         }
     }
+
+    #[test]
+    fn test_function_table_overloads() {
+        let mut table = FunctionTable::new();
+        let mut type_table = TypeTable::new();
+        let params = [type_table.register(&Type::float()), type_table.register(&Type::int())];
+
+        let mangled = FunctionTable::mangle("abs", &params);
+        assert_eq!(mangled, "abs(float, int)");
+        assert!(table.overloads("abs").is_empty());
+
+        table.add_overload("abs", "abs");
+        table.add_overload("abs", &mangled);
+        assert_eq!(table.overloads("abs"), ["abs", "abs(float, int)"]);
+    }
 }
@@ -1,5 +1,7 @@
 pub mod analyze_trait;
 pub mod analyzer;
+pub mod branch_unifier;
+pub mod const_eval;
 pub mod const_pool;
 pub mod err;
 pub mod function_table;
@@ -9,5 +11,7 @@ pub mod result;
 pub mod return_status;
 pub mod scope;
 pub mod scope_builder;
+pub mod struct_table;
 pub mod symbol_info;
+pub mod trait_table;
 pub mod type_table;
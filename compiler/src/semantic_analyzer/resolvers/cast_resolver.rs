@@ -0,0 +1,38 @@
+use crate::{
+    aliases::Result,
+    err::HarpyError,
+    lexer::span::Span,
+    parser::types::{BaseType, PrimitiveType, Type, TypeInner},
+    semantic_analyzer::err::SemanticError,
+};
+
+fn primitive(ttype: &Type) -> Option<PrimitiveType> {
+    match &ttype.inner {
+        TypeInner::Base(BaseType::Primitive(p)) => Some(*p),
+        _ => None,
+    }
+}
+
+pub struct CastResolver;
+
+impl CastResolver {
+    // ints and float convert into each other, bool only into ints, char to and from ints
+    pub fn resolve(from: &Type, to: &Type, span: Span) -> Result<Type> {
+        use PrimitiveType::*;
+
+        let valid = match (primitive(from), primitive(to)) {
+            (Some(f), Some(t)) if t.is_int() => f.is_int() || matches!(f, Float | Bool | Char),
+            (Some(f), Some(Float)) => f.is_int() || f == Float,
+            (Some(f), Some(Char)) => f.is_int() || f == Char,
+            _ => false,
+        };
+        if !valid {
+            return HarpyError::semantic(SemanticError::InvalidCast(from.clone(), to.clone()), span);
+        }
+
+        Ok(Type {
+            mutable: false,
+            inner: to.inner.clone(),
+        })
+    }
+}
@@ -1,26 +1,44 @@
 use crate::{
-    aliases::Result,
+    aliases::{Result, SymbolInfoRef, TypeInfoRc},
     err::HarpyError,
     extensions::SymbolInfoRefExt,
-    lexer::tokens::{Ident, Lit, Literal},
+    lexer::{
+        span::Span,
+        tokens::{Ident, Lit, Literal},
+    },
     parser::{
         expr::{
-            expr::{CallExpr, SpannedExpr},
-            infix::InfixOp,
-            prefix::PrefixOp,
+            expr::{
+                ArrayExpr, CallExpr, CastExpr, MethodCallExpr, OptionalExpr, ResultExpr,
+                SpannedExpr, StructExpr, TryExpr, TupleExpr, VariantExpr, VecExpr,
+            },
+            infix::{InfixOp, InfixOpKind},
+            prefix::{PrefixOp, PrefixOpKind},
             Expr,
         },
+        generics::{infer_type_args, substitute, Instantiations, TypeArgs},
+        impl_decl::ImplDecl,
         node::Node,
-        types::{BaseType, PrimitiveType, Type, TypeInner},
+        types::{BaseType, PrimitiveType, SizedInt, Type, TypeInner, TypeSpanned},
     },
     semantic_analyzer::{
         analyzer::Analyzer,
+        branch_unifier::BranchUnifier,
         err::SemanticError,
-        symbol_info::{BorrowInfo, SymbolInfoKind},
+        analyze_trait::Analyze,
+        return_status::ReturnStatus,
+        struct_table::FieldIndex,
+        symbol_info::{BorrowInfo, GenericInfo, SymbolInfoKind},
     },
 };
 
-use super::{infix_resolver::InfixResolver, prefix_resolver::PrefixResolver};
+use super::{
+    cast_resolver::CastResolver, infix_resolver::InfixResolver, prefix_resolver::PrefixResolver,
+};
+
+// the name an overload is defined as, with the types of the arguments resolved
+// while picking it, `None` for those to resolve against its params
+type PickedOverload = (Ident, Option<Vec<Option<Type>>>);
 
 #[derive(Debug, Clone, Copy)]
 pub enum ResolveMode {
@@ -31,13 +49,15 @@ pub enum ResolveMode {
 pub struct ExprResolver;
 
 impl ExprResolver {
-    fn resolve_lit(lit: &Node<Literal>, analyzer: &mut Analyzer) -> Type {
+    fn resolve_lit(lit: &Node<Literal>, expected: Option<&Type>, analyzer: &mut Analyzer) -> Result<Type> {
         let inner = match lit.value() {
-            Lit::LitInt(_) => BaseType::Primitive(PrimitiveType::Int),
+            Lit::LitInt(value) => return Self::resolve_int(lit, *value as i128, expected, analyzer),
             Lit::LitFloat(_) => BaseType::Primitive(PrimitiveType::Float),
             Lit::LitStr(_) => BaseType::Primitive(PrimitiveType::Str),
             Lit::LitBool(_) => BaseType::Primitive(PrimitiveType::Bool),
-            Lit::LitVoid => return Type::void(),
+            Lit::LitChar(_) => BaseType::Primitive(PrimitiveType::Char),
+            Lit::LitSized(s) => BaseType::Primitive(s.ty),
+            Lit::LitVoid => return Ok(Type::void()),
         };
 
         let t = Type {
@@ -45,12 +65,56 @@ impl ExprResolver {
             inner: TypeInner::Base(inner),
         };
 
-        analyzer.register_constant(lit, &t);
-        t
+        analyzer.register_constant(lit, lit.value(), &t);
+        Ok(t)
+    }
+
+    // an int literal is an `int` unless a sized int is expected, and has to
+    // fit the type it ends up with
+    fn resolve_int(
+        lit: &Node<Literal>,
+        value: i128,
+        expected: Option<&Type>,
+        analyzer: &mut Analyzer,
+    ) -> Result<Type> {
+        let ty = match expected.and_then(Self::sized_int) {
+            Some(ty) => ty,
+            None => PrimitiveType::Int,
+        };
+        if !ty.fits(value) {
+            return HarpyError::semantic(SemanticError::IntOutOfRange(value, ty), lit.span());
+        }
+
+        let t = Type {
+            mutable: false,
+            inner: TypeInner::Base(BaseType::Primitive(ty)),
+        };
+        let value = match ty {
+            PrimitiveType::Int => Lit::LitInt(value as i64 as u64),
+            ty => Lit::LitSized(SizedInt { value, ty }),
+        };
+
+        analyzer.register_constant(lit, &value, &t);
+        Ok(t)
+    }
+
+    fn sized_int(ty: &Type) -> Option<PrimitiveType> {
+        match ty.inner {
+            TypeInner::Base(BaseType::Primitive(p)) if p.is_sized_int() => Some(p),
+            _ => None,
+        }
+    }
+
+    fn int_literal(expr: &Expr) -> Option<&Node<Literal>> {
+        match expr {
+            Expr::Literal(lit) if matches!(lit.value(), Lit::LitInt(_)) => Some(lit),
+            _ => None,
+        }
     }
 
     fn resolve_ident(
         ident: &Node<Ident>,
+        expected: Option<&Type>,
         analyzer: &mut Analyzer,
         mode: ResolveMode,
     ) -> Result<Type> {
@@ -67,12 +131,36 @@ impl ExprResolver {
             }
         }
 
-        match symbol.kind {
+        match &symbol.kind {
             SymbolInfoKind::Variable(_) | SymbolInfoKind::Param => {
-                analyzer.map_ident_to_local_with_symbol(ident, &symbol);
+                analyzer.map_ident_to_local_with_symbol(ident, ident.id(), &symbol);
             }
             SymbolInfoKind::Global(_) => {
-                analyzer.map_ident_to_global_with_symbol(ident, &symbol);
+                analyzer.map_ident_to_global_with_symbol(ident.id(), &symbol);
+            }
+            // a constant without a value is reported where it is evaluated
+            SymbolInfoKind::Constant(c) => {
+                if let Some(value) = &c.value {
+                    analyzer.register_constant_value(ident.id(), value, &symbol.ty.ttype);
+                }
+            }
+            // a function used as a value rather than called
+            SymbolInfoKind::Function(f) => {
+                let overloads = analyzer.overloads(ident);
+                if overloads.len() > 1 {
+                    drop(symbol);
+                    return Self::resolve_overloaded_value(ident, overloads, expected, analyzer);
+                }
+                analyzer.register_call(ident, ident.id());
+                let params = f.params.iter().map(|p| p.ttype.clone()).collect();
+                return Ok(Type::function(params, symbol.ty.ttype.clone()));
+            }
+            // there are no arguments to infer the type parameters from
+            SymbolInfoKind::Generic(g) => {
+                return HarpyError::semantic(
+                    SemanticError::CantInferTypeParam(g.type_params[0].clone()),
+                    ident.span(),
+                );
             }
             _ => {}
         }
@@ -80,36 +168,340 @@ impl ExprResolver {
         Ok(symbol.ty.ttype.clone())
     }
 
-    fn resolve_call(
+    // infers the type arguments from the arguments and calls the matching
+    // specialization, asking for it to be made if it doesn't exist yet
+    fn resolve_generic_call(
         expr: &Node<CallExpr>,
+        generic: &GenericInfo,
         analyzer: &mut Analyzer,
         mode: ResolveMode,
     ) -> Result<Type> {
         let ident = &expr.ident;
-        let params = &expr.args;
+        if expr.args.len() != generic.params.len() {
+            return HarpyError::semantic(
+                SemanticError::ArgCountMismatch(
+                    ident.clone(),
+                    expr.args.len(),
+                    generic.params.len(),
+                ),
+                ident.span(),
+            );
+        }
+
+        let mut arg_types = Vec::with_capacity(expr.args.len());
+        let mut type_args = TypeArgs::new();
+        for (arg, param) in expr.args.iter().zip(&generic.params) {
+            let ty = Self::resolve_expr(arg, analyzer, mode)?;
+            if let Err((name, previous)) =
+                infer_type_args(param, &ty, &generic.type_params, &mut type_args)
+            {
+                return HarpyError::semantic(
+                    SemanticError::TypeParamConflict(name, previous, ty),
+                    arg.span(),
+                );
+            }
+            arg_types.push(ty);
+        }
+
+        let mut args = Vec::with_capacity(generic.type_params.len());
+        for name in &generic.type_params {
+            let Some(arg) = type_args.get(name) else {
+                return HarpyError::semantic(
+                    SemanticError::CantInferTypeParam(name.clone()),
+                    ident.span(),
+                );
+            };
+            args.push(arg.clone());
+        }
+
+        for ((arg, ty), param) in expr.args.iter().zip(arg_types).zip(&generic.params) {
+            let param = substitute(param, &type_args);
+            if !param.param_compatible(&ty) {
+                let param = analyzer.register_type(&TypeSpanned {
+                    ty: param,
+                    span: arg.span(),
+                });
+                return HarpyError::semantic(SemanticError::ArgTypeMismatch(ty, param), arg.span());
+            }
+        }
+
+        let template = analyzer.global_name(ident);
+        let name = Instantiations::mangle(&template, &args);
+        if analyzer.has_function(&name) {
+            analyzer.register_call(&Ident::new(name, ident.span()), expr.id());
+        } else {
+            analyzer.request_instance(&template, args, ident.span());
+        }
+
+        Ok(substitute(&generic.return_type, &type_args))
+    }
 
+    // calls through a variable, parameter or global holding a function
+    fn resolve_indirect_call(
+        expr: &Node<CallExpr>,
+        analyzer: &mut Analyzer,
+        mode: ResolveMode,
+    ) -> Result<Type> {
+        let ident = &expr.ident;
         let sym_ref = analyzer.get_symbol(ident)?;
-        let symbol = (*sym_ref).borrow();
+        let (params, ret) = {
+            let symbol = (*sym_ref).borrow();
+            let TypeInner::Function(params, ret) = &symbol.ty.inner else {
+                return HarpyError::semantic(SemanticError::NotAFunc(ident.clone()), ident.span());
+            };
+
+            match &symbol.kind {
+                SymbolInfoKind::Variable(v) if !v.initialized => {
+                    return HarpyError::semantic(SemanticError::UninitializedVar, ident.span());
+                }
+                SymbolInfoKind::Global(_) => {
+                    analyzer.map_ident_to_global_with_symbol(expr.id(), &symbol)
+                }
+                _ => analyzer.map_ident_to_local_with_symbol(ident, expr.id(), &symbol),
+            }
+            (params.clone(), ret.clone())
+        };
+
+        if expr.args.len() != params.len() {
+            return HarpyError::semantic(
+                SemanticError::ArgCountMismatch(ident.clone(), expr.args.len(), params.len()),
+                ident.span(),
+            );
+        }
+
+        for (arg, param) in expr.args.iter().zip(&params) {
+            analyzer.expect(param);
+            let ty = Self::resolve_expr(arg, analyzer, mode)?;
+            if !param.param_compatible(&ty) {
+                let param = analyzer.register_type(&TypeSpanned {
+                    ty: param.clone(),
+                    span: arg.span(),
+                });
+                return HarpyError::semantic(SemanticError::ArgTypeMismatch(ty, param), arg.span());
+            }
+        }
+
+        Ok(*ret.clone())
+    }
+
+    // picks the function of an overloaded name by the arguments, giving the name it
+    // is defined as and, when they had to be resolved to pick it, the argument types.
+    // `skip` leaves out a method's receiver. an argument typed by what it's passed to,
+    // like `7` or `none`, is tried against each candidate and left to be resolved
+    // again for the one picked
+    fn pick_overload(
+        ident: &Ident,
+        args: &[Node<Expr>],
+        skip: usize,
+        analyzer: &mut Analyzer,
+        mode: ResolveMode,
+    ) -> Result<Option<PickedOverload>> {
+        let overloads = analyzer.overloads(ident);
+        if overloads.len() < 2 {
+            return Ok(None);
+        }
+
+        let pick = |name: &String| Ident::new(name.clone(), ident.span());
+        let candidates: Vec<_> =
+            overloads.iter().filter(|(_, params, _)| params.len() == args.len() + skip).collect();
+        // the only one taking that many arguments checks them against its params as usual
+        if let [(name, _, _)] = candidates.as_slice() {
+            return Ok(Some((pick(name), None)));
+        }
+
+        let resolved = args
+            .iter()
+            .map(|arg| match Self::typed_by_param(arg) {
+                true => Ok(None),
+                false => Self::resolve_expr(arg, analyzer, mode).map(Some),
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        // the candidates every argument can be passed to, with the types they get
+        let mut accepted = vec![];
+        for candidate in &candidates {
+            let types = args
+                .iter()
+                .zip(&candidate.1[skip..])
+                .zip(&resolved)
+                .map(|((arg, param), ty)| match ty {
+                    Some(ty) => Ok(ty.clone()),
+                    None => {
+                        analyzer.expect(param);
+                        Self::resolve_expr(arg, analyzer, mode)
+                    }
+                })
+                .collect::<Result<Vec<_>>>();
+            if let Ok(types) = types {
+                accepted.push((*candidate, types));
+            }
+        }
+
+        let fitting = |exact: bool| -> Vec<_> {
+            accepted
+                .iter()
+                .filter(|((_, params, _), types)| {
+                    params[skip..].iter().zip(types).all(|(param, arg)| match exact {
+                        true => param.inner == arg.inner,
+                        false => param.param_compatible(arg),
+                    })
+                })
+                .collect()
+        };
+
+        let exact = fitting(true);
+        let fitting = if exact.len() == 1 { exact } else { fitting(false) };
+        let signatures = |overloads: Vec<&Vec<TypeInfoRc>>| {
+            overloads.iter().map(|params| Self::signature(ident, &params[skip..])).collect()
+        };
+        match fitting.as_slice() {
+            [((name, _, _), _)] => Ok(Some((pick(name), Some(resolved)))),
+            [] => {
+                let arg_types = args
+                    .iter()
+                    .zip(resolved)
+                    .map(|(arg, ty)| {
+                        ty.unwrap_or_else(|| {
+                            Self::resolve_expr(arg, analyzer, mode).unwrap_or_else(|_| Type::unknown())
+                        })
+                    })
+                    .collect();
+                HarpyError::semantic(
+                    SemanticError::NoMatchingOverload(
+                        Self::declared_name(ident),
+                        arg_types,
+                        signatures(overloads.iter().map(|(_, params, _)| params).collect()),
+                    ),
+                    ident.span(),
+                )
+            }
+            _ => HarpyError::semantic(
+                SemanticError::AmbiguousCall(
+                    Self::declared_name(ident),
+                    signatures(fitting.iter().map(|((_, params, _), _)| params).collect()),
+                ),
+                ident.span(),
+            ),
+        }
+    }
+
+    // literals, `none` and empty collections get their type from the param
+    fn typed_by_param(expr: &Expr) -> bool {
+        match expr {
+            Expr::Literal(_) => Self::int_literal(expr).is_some(),
+            Expr::Prefix(PrefixOp { op: PrefixOpKind::Minus, .. }, rhs) => {
+                Self::int_literal(rhs).is_some()
+            }
+            Expr::Optional(optional) => {
+                optional.wrapped.as_ref().is_none_or(|value| Self::typed_by_param(value))
+            }
+            Expr::Result(_) => true,
+            Expr::Vec(vec) => vec.element.is_none() && vec.elements.is_empty(),
+            Expr::Box(value) => Self::typed_by_param(value),
+            _ => false,
+        }
+    }
+
+    // an overloaded function used as a value is picked by the function type expected of it
+    fn resolve_overloaded_value(
+        ident: &Node<Ident>,
+        overloads: Vec<(String, Vec<TypeInfoRc>, TypeInfoRc)>,
+        expected: Option<&Type>,
+        analyzer: &mut Analyzer,
+    ) -> Result<Type> {
+        let function_type = |params: &[TypeInfoRc], return_type: &TypeInfoRc| {
+            Type::function(params.iter().map(|p| p.ttype.clone()).collect(), return_type.ttype.clone())
+        };
+        let picked: Vec<_> = overloads
+            .iter()
+            .filter(|(_, params, return_type)| {
+                expected.is_some_and(|e| e.inner == function_type(params, return_type).inner)
+            })
+            .collect();
+
+        let [(name, params, return_type)] = picked.as_slice() else {
+            let signatures = overloads.iter().map(|(_, params, _)| Self::signature(ident, params)).collect();
+            return HarpyError::semantic(
+                SemanticError::AmbiguousCall(Self::declared_name(ident), signatures),
+                ident.span(),
+            );
+        };
+        analyzer.register_call(&Ident::new(name.clone(), ident.span()), ident.id());
+        Ok(function_type(params, return_type))
+    }
+
+    // `abs(int)`, under the name the function was declared with
+    fn signature(ident: &Ident, params: &[TypeInfoRc]) -> String {
+        let params: Vec<_> = params.iter().map(|p| p.ttype.to_string()).collect();
+        format!("{}({})", Self::declared_name(ident).value(), params.join(", "))
+    }
+
+    // a method is looked up as `Type::method`, but written as `method`
+    fn declared_name(ident: &Ident) -> Ident {
+        let name = ident.value().rsplit("::").next().unwrap_or_default();
+        Ident::new(name.to_owned(), ident.span())
+    }
+
+    fn resolve_call(
+        expr: &Node<CallExpr>,
+        analyzer: &mut Analyzer,
+        mode: ResolveMode,
+    ) -> Result<Type> {
+        let mut ident = &expr.ident;
+        let params = &expr.args;
+
+        let mut sym_ref = analyzer.get_symbol(ident)?;
+        let mut arg_types = None;
+        let picked;
+        if matches!((*sym_ref).borrow().kind, SymbolInfoKind::Function(_)) {
+            if let Some((overload, types)) = Self::pick_overload(ident, params, 0, analyzer, mode)? {
+                picked = overload;
+                ident = &picked;
+                sym_ref = analyzer.get_symbol(ident)?;
+                arg_types = types.map(Vec::into_iter);
+            }
+        }
 
-        let func_info = match &symbol.kind {
-            SymbolInfoKind::Function(f) => f,
-            _ => return HarpyError::semantic(SemanticError::NotAFunc(ident.clone()), ident.span()),
+        // the arguments may call this function again, so don't hold the symbol borrowed
+        let (func_params, return_type) = {
+            let symbol = (*sym_ref).borrow();
+            match &symbol.kind {
+                SymbolInfoKind::Function(f) => (f.params.clone(), symbol.ty.ttype.clone()),
+                SymbolInfoKind::Generic(g) => {
+                    let generic = g.clone();
+                    drop(symbol);
+                    return Self::resolve_generic_call(expr, &generic, analyzer, mode);
+                }
+                SymbolInfoKind::Variable(_) | SymbolInfoKind::Param | SymbolInfoKind::Global(_) => {
+                    drop(symbol);
+                    return Self::resolve_indirect_call(expr, analyzer, mode);
+                }
+                _ => {
+                    return HarpyError::semantic(SemanticError::NotAFunc(ident.clone()), ident.span())
+                }
+            }
         };
 
-        if params.len() != func_info.params.len() {
+        if params.len() != func_params.len() {
             return HarpyError::semantic(
                 SemanticError::ArgCountMismatch(
                     ident.clone(),
                     params.len(),
-                    func_info.params.len(),
+                    func_params.len(),
                 ),
                 ident.span(),
             );
         }
 
-        for (param_expr, param_type) in params.iter().zip(&func_info.params) {
-            let ttype = Self::resolve_expr(param_expr, analyzer, mode)?;
+        for (param_expr, param_type) in params.iter().zip(&func_params) {
             let param_t = &param_type.ttype;
+            let ttype = match arg_types.as_mut().and_then(Iterator::next).flatten() {
+                Some(ttype) => ttype,
+                None => {
+                    analyzer.expect(param_t);
+                    Self::resolve_expr(param_expr, analyzer, mode)?
+                }
+            };
             if !param_t.param_compatible(&ttype) {
                 return HarpyError::semantic(
                     SemanticError::ArgTypeMismatch(ttype, param_type.clone()),
@@ -118,17 +510,28 @@ impl ExprResolver {
             }
         }
 
-        analyzer.register_call(ident, expr);
+        analyzer.register_call(ident, expr.id());
 
-        Ok(symbol.ty.ttype.clone())
+        Ok(return_type)
     }
 
     fn resolve_prefix(
         op: &PrefixOp,
         rhs: &Node<Expr>,
+        expected: Option<&Type>,
         analyzer: &mut Analyzer,
         mode: ResolveMode,
     ) -> Result<Type> {
+        // the literal holds the negated value, `-128` fits an i8 while 128 doesn't
+        if let (PrefixOpKind::Minus, Some(lit)) = (&op.op, Self::int_literal(rhs)) {
+            let Lit::LitInt(value) = lit.value() else { unreachable!() };
+            let ty = Self::resolve_int(lit, -(*value as i128), expected, analyzer)?;
+            return Self::resolve_prefix_type(op, &ty, analyzer);
+        }
+
+        if let Some(expected) = expected.filter(|e| Self::sized_int(e).is_some()) {
+            analyzer.expect(expected);
+        }
         let rhs_type = match mode {
             ResolveMode::Read => analyzer.resolve_expr(rhs),
             ResolveMode::Write => analyzer.resolve_expr_write(rhs),
@@ -137,7 +540,83 @@ impl ExprResolver {
         let Some(rhs_type) = rhs_type else {
             return HarpyError::semantic(SemanticError::UnresolvedType, rhs.span());
         };
-        PrefixResolver::resolve(op, &rhs_type.ttype)
+        Self::resolve_prefix_type(op, &rhs_type.ttype, analyzer)
+    }
+
+    // a newtype has the operators of the type it wraps, giving back the newtype
+    fn resolve_prefix_type(op: &PrefixOp, ty: &Type, analyzer: &Analyzer) -> Result<Type> {
+        let lowered = analyzer.lower_type(ty);
+        if lowered == *ty {
+            return PrefixResolver::resolve(op, ty);
+        }
+
+        let Ok(result) = PrefixResolver::resolve(op, &lowered) else {
+            return HarpyError::semantic(
+                SemanticError::PrefixTypeMismatch(op.clone(), ty.clone()),
+                op.span(),
+            );
+        };
+        Ok(Self::rewrap(result, &lowered, ty))
+    }
+
+    // only between two values of the same newtype
+    pub fn resolve_infix_types(op: &InfixOp, lhs: &Type, rhs: &Type, analyzer: &Analyzer) -> Result<Type> {
+        let (lowered_lhs, lowered_rhs) = (analyzer.lower_type(lhs), analyzer.lower_type(rhs));
+        if lowered_lhs == *lhs && lowered_rhs == *rhs {
+            return InfixResolver::resolve(op, lhs, rhs);
+        }
+
+        match InfixResolver::resolve(op, &lowered_lhs, &lowered_rhs) {
+            Ok(result) if lhs.inner == rhs.inner => Ok(Self::rewrap(result, &lowered_lhs, lhs)),
+            _ => HarpyError::semantic(
+                SemanticError::InfixTypeMismatch(op.clone(), lhs.clone(), rhs.clone()),
+                op.span(),
+            ),
+        }
+    }
+
+    // comparisons still give a bool
+    fn rewrap(result: Type, lowered: &Type, newtype: &Type) -> Type {
+        if result.inner != lowered.inner {
+            return result;
+        }
+
+        Type {
+            mutable: result.mutable,
+            inner: newtype.inner.clone(),
+        }
+    }
+
+    fn resolve_cast(cast: &CastExpr, analyzer: &mut Analyzer, mode: ResolveMode) -> Result<Type> {
+        let from = match mode {
+            ResolveMode::Read => analyzer.resolve_expr(&cast.expr),
+            ResolveMode::Write => analyzer.resolve_expr_write(&cast.expr),
+        };
+
+        let Some(from) = from else {
+            return HarpyError::semantic(SemanticError::UnresolvedType, cast.expr.span());
+        };
+        let span = Span::new(cast.expr.span().start, cast.ty.span().end);
+        let to = analyzer.expand_type(&cast.ty);
+        let lowered = (analyzer.lower_type(&from.ttype), analyzer.lower_type(&to));
+        let valid = match (lowered.0 != from.ttype, lowered.1 != to) {
+            (false, false) => CastResolver::resolve(&from.ttype, &to, span).is_ok(),
+            // one newtype only becomes another through the type it wraps
+            (true, true) if from.inner != to.inner => false,
+            _ => lowered.0.inner == lowered.1.inner || CastResolver::resolve(&lowered.0, &lowered.1, span).is_ok(),
+        };
+        // the cast still has the type it asks for, so what uses it is checked as usual
+        if !valid {
+            analyzer.report_semantic_error(
+                SemanticError::InvalidCast(from.written().clone(), cast.ty.ty.clone()),
+                span,
+            );
+        }
+
+        Ok(Type {
+            mutable: false,
+            inner: to.inner,
+        })
     }
 
     fn resolve_borrow(expr: &SpannedExpr, mutable: bool, analyzer: &mut Analyzer) -> Result<Type> {
@@ -152,7 +631,7 @@ impl ExprResolver {
             return HarpyError::semantic(SemanticError::BorrowMutNonMutable, expr.span());
         }
         {
-            let mut var = symbol.as_variable_mut().ok_or_else(|| {
+            let var = symbol.as_variable().ok_or_else(|| {
                 HarpyError::new(
                     crate::err::HarpyErrorKind::SemanticError(SemanticError::InvalidVarBorrow(
                         symbol.get().kind.clone(),
@@ -164,47 +643,832 @@ impl ExprResolver {
             if !var.initialized {
                 return HarpyError::semantic(SemanticError::UninitializedVar, i.span());
             }
+        }
+        Self::borrow_variable(&symbol, mutable, expr.span(), analyzer)?;
+
+        analyzer.map_ident_to_local_with_symbol(i, i.id(), &symbol.get());
+
+        Ok(Type {
+            mutable,
+            inner: TypeInner::Ref(Box::new(Type {
+                mutable,
+                inner: ttype.inner.clone(),
+            })),
+        })
+    }
+
+    // records a borrow of the variable `symbol`, unless it conflicts with the
+    // borrows it already has
+    fn borrow_variable(
+        symbol: &SymbolInfoRef,
+        mutable: bool,
+        span: Span,
+        analyzer: &Analyzer,
+    ) -> Result<()> {
+        let mut var = symbol.as_variable_mut().unwrap();
+        if mutable && var.immutably_borrowed_count != 0 {
+            return HarpyError::semantic(SemanticError::CreatedMutableBorrowWhileImmutableBorrow, span);
+        }
+
+        if var.mutably_borrowed {
+            return HarpyError::semantic(SemanticError::AlreadyMutablyBorrowed, span);
+        }
+
+        if mutable {
+            var.mutably_borrowed = true;
+        } else {
+            var.immutably_borrowed_count += 1;
+        }
+
+        analyzer.register_borrow(BorrowInfo {
+            depth: analyzer.current_depth(),
+            original: symbol.clone(),
+            borrow_span: span,
+        });
+        Ok(())
+    }
+
+    fn resolve_box(expr: &Node<Expr>, expected: Option<&Type>, analyzer: &mut Analyzer) -> Result<Type> {
+        if let Some(TypeInner::Boxed(pointee)) = expected.map(|e| &e.inner) {
+            analyzer.expect(pointee);
+        }
+        let ty = Self::resolve_expr(expr, analyzer, ResolveMode::Read)?;
+        let ty = analyzer.register_expr_type(expr.id(), expr.span(), ty);
+        Ok(Type::boxed(ty.ttype.clone()))
+    }
+
+    // follows refs and boxes down to the value they point at; the result is
+    // mutable if the innermost pointee is
+    fn auto_deref(ty: &Type) -> (&Type, bool) {
+        let mut container = ty;
+        let mut mutable = container.mutable;
+        while let TypeInner::Ref(inner) | TypeInner::Boxed(inner) = &container.inner {
+            mutable = inner.mutable;
+            container = inner;
+        }
+        (container, mutable)
+    }
+
+    fn resolve_field(
+        base: &Node<Expr>,
+        field: &Node<Ident>,
+        analyzer: &mut Analyzer,
+    ) -> Result<Type> {
+        let base_type = Self::resolve_expr(base, analyzer, ResolveMode::Read)?;
+        let base_type = analyzer.register_expr_type(base.id(), base.span(), base_type);
+        let (container, mutable) = Self::auto_deref(&base_type.ttype);
+
+        // `.0`, `.1`, ... pick the elements of a tuple
+        if let TypeInner::Tuple(elements) = &container.inner {
+            let Some((idx, element)) = field
+                .value()
+                .parse::<usize>()
+                .ok()
+                .and_then(|idx| Some((idx, elements.get(idx)?)))
+            else {
+                return HarpyError::semantic(
+                    SemanticError::UnknownField(field.clone(), container.to_string()),
+                    field.span(),
+                );
+            };
+
+            analyzer.register_field(field.id(), FieldIndex(idx as u32));
+            return Ok(Type {
+                mutable,
+                inner: element.inner.clone(),
+            });
+        }
+
+        let TypeInner::Base(BaseType::Custom(custom)) = &container.inner else {
+            return HarpyError::semantic(SemanticError::NoFields(container.clone()), base.span());
+        };
+
+        let Some(info) = analyzer.get_struct(custom.name()) else {
+            if analyzer.get_enum(custom.name()).is_some() {
+                return HarpyError::semantic(SemanticError::NoFields(container.clone()), base.span());
+            }
+            return HarpyError::semantic(
+                SemanticError::UndefinedType(custom.name().to_owned()),
+                base.span(),
+            );
+        };
+
+        let Some((idx, field_info)) = info.field(field.value()) else {
+            return HarpyError::semantic(
+                SemanticError::UnknownField(field.clone(), custom.name().to_owned()),
+                field.span(),
+            );
+        };
+
+        analyzer.register_field(field.id(), idx);
+
+        Ok(Type {
+            mutable,
+            inner: field_info.ty.inner.clone(),
+        })
+    }
 
-            if mutable && var.immutably_borrowed_count != 0 {
+    fn resolve_struct(
+        expr: &Node<StructExpr>,
+        analyzer: &mut Analyzer,
+        mode: ResolveMode,
+    ) -> Result<Type> {
+        let name = expr.name.value();
+        let Some(info) = analyzer.get_struct(name) else {
+            if analyzer.get_enum(name).is_some() {
                 return HarpyError::semantic(
-                    SemanticError::CreatedMutableBorrowWhileImmutableBorrow,
-                    expr.span(),
+                    SemanticError::NoFields(Type::custom(name)),
+                    expr.name.span(),
+                );
+            }
+            return HarpyError::semantic(
+                SemanticError::UndefinedType(name.clone()),
+                expr.name.span(),
+            );
+        };
+
+        let mut initialized = vec![false; info.fields.len()];
+
+        for init in &expr.fields {
+            let Some((idx, field)) = info.field(init.name.value()) else {
+                return HarpyError::semantic(
+                    SemanticError::UnknownField(init.name.clone(), name.clone()),
+                    init.name.span(),
+                );
+            };
+
+            if initialized[idx.0 as usize] {
+                return HarpyError::semantic(
+                    SemanticError::DuplicateField(init.name.clone()),
+                    init.name.span(),
                 );
             }
+            initialized[idx.0 as usize] = true;
 
-            if var.mutably_borrowed {
-                return HarpyError::semantic(SemanticError::AlreadyMutablyBorrowed, expr.span());
+            analyzer.expect(&field.ty.ttype);
+            let value_type = Self::resolve_expr(&init.value, analyzer, mode)?;
+            let value_type =
+                analyzer.register_expr_type(init.value.id(), init.value.span(), value_type);
+
+            if !field.ty.assign_compatible(&value_type.ttype) {
+                return HarpyError::semantic(
+                    SemanticError::LetTypeMismatch(field.ty.written().clone(), value_type),
+                    init.value.span(),
+                );
             }
 
-            if mutable {
-                var.mutably_borrowed = true;
-            } else {
-                var.immutably_borrowed_count += 1;
+            analyzer.register_field(init.name.id(), idx);
+        }
+
+        if let Some(missing) = initialized.iter().position(|i| !i) {
+            return HarpyError::semantic(
+                SemanticError::MissingField(info.fields[missing].name.clone(), name.clone()),
+                expr.span(),
+            );
+        }
+
+        let ty = Type::custom(name);
+        analyzer.register_expr_type(expr.id(), expr.span(), ty.clone());
+        Ok(ty)
+    }
+
+    fn resolve_variant(
+        expr: &Node<VariantExpr>,
+        analyzer: &mut Analyzer,
+        mode: ResolveMode,
+    ) -> Result<Type> {
+        let name = expr.enum_name.value();
+        let Some(info) = analyzer.get_enum(name) else {
+            if analyzer.get_struct(name).is_some() {
+                return HarpyError::semantic(
+                    SemanticError::NotAnEnum(expr.enum_name.clone()),
+                    expr.enum_name.span(),
+                );
             }
+            return HarpyError::semantic(
+                SemanticError::UndefinedType(name.clone()),
+                expr.enum_name.span(),
+            );
+        };
 
-            analyzer.register_borrow(BorrowInfo {
-                depth: analyzer.current_depth(),
-                original: symbol.clone(),
-                borrow_span: expr.span(),
+        let Some((idx, variant)) = info.variant(expr.variant.value()) else {
+            return HarpyError::semantic(
+                SemanticError::UnknownVariant(expr.variant.clone(), name.clone()),
+                expr.variant.span(),
+            );
+        };
+
+        if expr.args.len() != variant.fields.len() {
+            return HarpyError::semantic(
+                SemanticError::ArgCountMismatch(
+                    (*expr.variant).clone(),
+                    expr.args.len(),
+                    variant.fields.len(),
+                ),
+                expr.span(),
+            );
+        }
+
+        for (i, (arg, field)) in expr.args.iter().zip(&variant.fields).enumerate() {
+            analyzer.expect(field);
+            let ty = Self::resolve_expr(arg, analyzer, mode)?;
+            let ty = analyzer.register_expr_type(arg.id(), arg.span(), ty);
+            if !field.assign_compatible(&ty.ttype) {
+                return HarpyError::semantic(
+                    SemanticError::ArgTypeMismatch(ty.ttype.clone(), field.clone()),
+                    arg.span(),
+                );
+            }
+            analyzer.register_field(arg.id(), info.field_index(idx, i));
+        }
+
+        analyzer.register_variant(expr.id(), idx);
+
+        let ty = Type::custom(name);
+        analyzer.register_expr_type(expr.id(), expr.span(), ty.clone());
+        Ok(ty)
+    }
+
+    // `none` takes its type from where it's used, `some(x)` from `x`
+    fn resolve_optional(
+        expr: &Node<OptionalExpr>,
+        expected: Option<Type>,
+        analyzer: &mut Analyzer,
+        mode: ResolveMode,
+    ) -> Result<Type> {
+        let expected_inner = match expected.map(|ty| ty.inner) {
+            Some(TypeInner::Optional(inner)) => Some(*inner),
+            _ => None,
+        };
+
+        let inner = match &expr.wrapped {
+            Some(value) => {
+                if let Some(inner) = &expected_inner {
+                    analyzer.expect(inner);
+                }
+                let ty = Self::resolve_expr(value, analyzer, mode)?;
+                let ty = analyzer.register_expr_type(value.id(), value.span(), ty);
+                Type {
+                    mutable: false,
+                    inner: ty.inner.clone(),
+                }
+            }
+            None => match expected_inner {
+                Some(inner) => inner,
+                None => return HarpyError::semantic(SemanticError::CantInferType, expr.span()),
+            },
+        };
+
+        let ty = Type::optional(inner);
+        analyzer.register_expr_type(expr.id(), expr.span(), ty.clone());
+        Ok(ty)
+    }
+
+    // the half of a result that isn't given comes from where it's used
+    fn resolve_result(
+        expr: &Node<ResultExpr>,
+        expected: Option<Type>,
+        analyzer: &mut Analyzer,
+        mode: ResolveMode,
+    ) -> Result<Type> {
+        let Some(TypeInner::Result(value, error)) = expected.map(|ty| ty.inner) else {
+            return HarpyError::semantic(SemanticError::CantInferType, expr.span());
+        };
+
+        let wrapped = expr.wrapped();
+        analyzer.expect(match &**expr {
+            ResultExpr::Ok(_) => &value,
+            ResultExpr::Err(_) => &error,
+        });
+        let ty = Self::resolve_expr(wrapped, analyzer, mode)?;
+        let ty = analyzer.register_expr_type(wrapped.id(), wrapped.span(), ty);
+        let ty = Type {
+            mutable: false,
+            inner: ty.inner.clone(),
+        };
+
+        let ty = match &**expr {
+            ResultExpr::Ok(_) => Type::result(ty, *error),
+            ResultExpr::Err(_) => Type::result(*value, ty),
+        };
+        analyzer.register_expr_type(expr.id(), expr.span(), ty.clone());
+        Ok(ty)
+    }
+
+    // `x?` passes `none` or the error on to the caller, so the enclosing function
+    // has to be able to return it
+    fn resolve_try(
+        expr: &Node<TryExpr>,
+        analyzer: &mut Analyzer,
+        mode: ResolveMode,
+    ) -> Result<Type> {
+        let operand = Self::resolve_expr(&expr.expr, analyzer, mode)?;
+        let operand = analyzer.register_expr_type(expr.expr.id(), expr.expr.span(), operand);
+
+        let Some(function) = analyzer.get_func_info() else {
+            return HarpyError::semantic(SemanticError::ReturnNotInFunc, expr.span());
+        };
+        let ret = function.get().ty.clone();
+
+        let (value, propagated) = match (&operand.inner, &ret.inner) {
+            (TypeInner::Optional(value), TypeInner::Optional(_)) => (value, ret.ttype.clone()),
+            (TypeInner::Result(value, error), TypeInner::Result(ret_value, _)) => {
+                (value, Type::result(*ret_value.clone(), *error.clone()))
+            }
+            (TypeInner::Optional(_) | TypeInner::Result(_, _), _) => {
+                return HarpyError::semantic(
+                    SemanticError::ReturnTypeMismatch(operand.clone(), ret),
+                    expr.span(),
+                )
+            }
+            _ => {
+                return HarpyError::semantic(
+                    SemanticError::InvalidTry(operand.ttype.clone()),
+                    expr.span(),
+                )
+            }
+        };
+
+        if !propagated.return_compatible(&ret.ttype) {
+            let propagated = analyzer.register_type(&TypeSpanned {
+                ty: propagated,
+                span: expr.span(),
             });
+            return HarpyError::semantic(
+                SemanticError::ReturnTypeMismatch(propagated, ret),
+                expr.span(),
+            );
+        }
+
+        // the generator builds the early return value from the function's return type
+        analyzer.register_expr_type(expr.id(), expr.span(), ret.ttype.clone());
+        Ok(Type {
+            mutable: false,
+            inner: value.inner.clone(),
+        })
+    }
+
+    fn resolve_index(
+        base: &Node<Expr>,
+        index: &Node<Expr>,
+        analyzer: &mut Analyzer,
+    ) -> Result<Type> {
+        let base_type = Self::resolve_expr(base, analyzer, ResolveMode::Read)?;
+        let base_type = analyzer.register_expr_type(base.id(), base.span(), base_type);
+        let (container, mutable) = Self::auto_deref(&base_type.ttype);
+
+        let (element, len) = match &container.inner {
+            TypeInner::Array(element, len) => (element, Some(*len)),
+            TypeInner::Vector(element) => (element, None),
+            _ => {
+                return HarpyError::semantic(
+                    SemanticError::NotIndexable(container.clone()),
+                    base.span(),
+                )
+            }
+        };
+
+        let index_type = Self::resolve_expr(index, analyzer, ResolveMode::Read)?;
+        if !Type::int().assign_compatible(&index_type) {
+            return HarpyError::semantic(SemanticError::InvalidIndexType(index_type), index.span());
         }
 
-        analyzer.map_ident_to_local_with_symbol(i, &symbol.get());
+        // vectors are only bounds checked at runtime
+        if let (Expr::Literal(lit), Some(len)) = (&**index, len) {
+            if let Lit::LitInt(i) = lit.value() {
+                if *i >= len as u64 {
+                    return HarpyError::semantic(
+                        SemanticError::IndexOutOfRange(*i, len),
+                        index.span(),
+                    );
+                }
+            }
+        }
 
         Ok(Type {
             mutable,
-            inner: TypeInner::Ref(Box::new(Type {
-                mutable,
-                inner: ttype.inner.clone(),
-            })),
+            inner: element.inner.clone(),
         })
     }
 
-    fn resolve_box(expr: &Node<Expr>, analyzer: &mut Analyzer) -> Result<Type> {
-        if let Some(ty) = analyzer.resolve_expr(expr) {
-            Ok(Type::boxed(ty.ttype.clone()))
-        } else {
-            HarpyError::semantic(SemanticError::UnresolvedType, expr.span())
+    fn resolve_tuple(
+        expr: &Node<TupleExpr>,
+        expected: Option<&Type>,
+        analyzer: &mut Analyzer,
+        mode: ResolveMode,
+    ) -> Result<Type> {
+        let expected = match expected.map(|ty| &ty.inner) {
+            Some(TypeInner::Tuple(elements)) if elements.len() == expr.elements.len() => {
+                Some(elements)
+            }
+            _ => None,
+        };
+
+        let mut element_types = Vec::with_capacity(expr.elements.len());
+        for (i, element) in expr.elements.iter().enumerate() {
+            if let Some(expected) = expected {
+                analyzer.expect(&expected[i]);
+            }
+            let ty = Self::resolve_expr(element, analyzer, mode)?;
+            let ty = analyzer.register_expr_type(element.id(), element.span(), ty);
+            element_types.push(Type {
+                mutable: false,
+                inner: ty.inner.clone(),
+            });
+        }
+
+        let ty = Type::tuple(element_types);
+        analyzer.register_expr_type(expr.id(), expr.span(), ty.clone());
+        Ok(ty)
+    }
+
+    // the expected element type only guides the elements, the first one still
+    // decides the array's type
+    fn resolve_array(
+        expr: &Node<ArrayExpr>,
+        expected: Option<&Type>,
+        analyzer: &mut Analyzer,
+        mode: ResolveMode,
+    ) -> Result<Type> {
+        let expected_element = match expected.map(|e| &e.inner) {
+            Some(TypeInner::Array(element, _)) => Some(&**element),
+            _ => None,
+        };
+        let ty = match &**expr {
+            ArrayExpr::List(elements) => {
+                let Some((first, rest)) = elements.split_first() else {
+                    // an empty array only gets its type from where it goes
+                    let Some(element) = expected_element else {
+                        return HarpyError::semantic(SemanticError::CantInferType, expr.span());
+                    };
+                    let element = Type { mutable: false, inner: element.inner.clone() };
+                    let ty = Type::array(element, 0);
+                    analyzer.register_expr_type(expr.id(), expr.span(), ty.clone());
+                    return Ok(ty);
+                };
+
+                if let Some(expected) = expected_element {
+                    analyzer.expect(expected);
+                }
+                let element_type = Self::resolve_expr(first, analyzer, mode)?;
+                let element_type = Type {
+                    mutable: false,
+                    inner: analyzer
+                        .register_expr_type(first.id(), first.span(), element_type)
+                        .inner
+                        .clone(),
+                };
+
+                for element in rest {
+                    analyzer.expect(&element_type);
+                    let ty = Self::resolve_expr(element, analyzer, mode)?;
+                    if !element_type.assign_compatible(&ty) {
+                        return HarpyError::semantic(
+                            SemanticError::ArrayElementMismatch(ty, element_type),
+                            element.span(),
+                        );
+                    }
+                    analyzer.register_expr_type(element.id(), element.span(), ty);
+                }
+
+                Type::array(element_type, elements.len())
+            }
+            ArrayExpr::Repeat(value, len) => {
+                if let Some(expected) = expected_element {
+                    analyzer.expect(expected);
+                }
+                let value_type = Self::resolve_expr(value, analyzer, mode)?;
+                let value_type = analyzer.register_expr_type(value.id(), value.span(), value_type);
+                Type::array(
+                    Type {
+                        mutable: false,
+                        inner: value_type.inner.clone(),
+                    },
+                    *len,
+                )
+            }
+        };
+
+        analyzer.register_expr_type(expr.id(), expr.span(), ty.clone());
+        Ok(ty)
+    }
+
+    // without an explicit element type the elements are checked against the
+    // expected one, so they can be coerced to it
+    fn resolve_vec(
+        expr: &Node<VecExpr>,
+        expected: Option<&Type>,
+        analyzer: &mut Analyzer,
+        mode: ResolveMode,
+    ) -> Result<Type> {
+        let mut element_type = None;
+        if let Some(ty) = &expr.element {
+            if let Some(name) = analyzer.expand_type(ty).custom_name() {
+                if !analyzer.is_type_defined(name) {
+                    return HarpyError::semantic(
+                        SemanticError::UndefinedType(name.to_owned()),
+                        ty.span,
+                    );
+                }
+            }
+            if let Some(name) = ty.trait_name() {
+                if analyzer.get_trait(name).is_none() {
+                    return HarpyError::semantic(
+                        SemanticError::UndefinedTrait(name.to_owned()),
+                        ty.span,
+                    );
+                }
+            }
+            element_type = Some(analyzer.register_type(ty).ttype.clone());
+        } else if let Some(TypeInner::Vector(element)) = expected.map(|e| &e.inner) {
+            element_type = Some((**element).clone());
+        }
+
+        for element in &expr.elements {
+            if let Some(expected) = &element_type {
+                analyzer.expect(expected);
+            }
+            let ty = Self::resolve_expr(element, analyzer, mode)?;
+            let ty = analyzer.register_expr_type(element.id(), element.span(), ty);
+
+            match &element_type {
+                Some(expected) if !expected.assign_compatible(&ty) => {
+                    return HarpyError::semantic(
+                        SemanticError::ArrayElementMismatch(ty.ttype.clone(), expected.clone()),
+                        element.span(),
+                    );
+                }
+                Some(_) => (),
+                None => {
+                    element_type = Some(Type {
+                        mutable: false,
+                        inner: ty.inner.clone(),
+                    })
+                }
+            }
+        }
+
+        let Some(element_type) = element_type else {
+            return HarpyError::semantic(SemanticError::CantInferType, expr.span());
+        };
+
+        let ty = Type::vector(element_type);
+        analyzer.register_expr_type(expr.id(), expr.span(), ty.clone());
+        Ok(ty)
+    }
+
+    // the builtin vector and string methods, as (params, return type, whether
+    // they mutate the receiver)
+    fn builtin_method(receiver: &Type, method: &str) -> Option<(Vec<Type>, Type, bool)> {
+        match &receiver.inner {
+            TypeInner::Vector(element) => {
+                let element = Type {
+                    mutable: false,
+                    inner: element.inner.clone(),
+                };
+
+                Some(match method {
+                    "push" => (vec![element], Type::void(), true),
+                    "pop" => (vec![], element, true),
+                    "len" => (vec![], Type::int(), false),
+                    "get" => (vec![Type::int()], element, false),
+                    "set" => (vec![Type::int(), element], Type::void(), true),
+                    _ => return None,
+                })
+            }
+            // strings are measured and sliced in bytes; iterating one is how to get its chars
+            TypeInner::Base(BaseType::Primitive(PrimitiveType::Str)) => Some(match method {
+                "len" => (vec![], Type::int(), false),
+                "substring" => (vec![Type::int(), Type::int()], Type::str(), false),
+                _ => return None,
+            }),
+            _ => None,
+        }
+    }
+
+    fn resolve_method_call(
+        call: &MethodCallExpr,
+        analyzer: &mut Analyzer,
+        mode: ResolveMode,
+    ) -> Result<Type> {
+        let receiver = &call.receiver;
+        let receiver_type = Self::resolve_expr(receiver, analyzer, ResolveMode::Read)?;
+        let receiver_type = analyzer.register_expr_type(receiver.id(), receiver.span(), receiver_type);
+        let (container, mutable) = Self::auto_deref(&receiver_type.ttype);
+
+        if let TypeInner::Base(BaseType::Custom(custom)) = &container.inner {
+            let name = ImplDecl::method_name(custom.name(), call.method.value());
+            return Self::resolve_impl_method(call, &name, &receiver_type.ttype, analyzer, mode);
+        }
+
+        if let TypeInner::Dyn(tr) = &container.inner {
+            return Self::resolve_dyn_method(call, tr, mutable, analyzer, mode);
+        }
+
+        let Some((params, ret, mutates)) = Self::builtin_method(container, call.method.value())
+        else {
+            return HarpyError::semantic(
+                SemanticError::UnknownMethod(call.method.clone(), container.clone()),
+                call.method.span(),
+            );
+        };
+
+        if mutates && !mutable {
+            return HarpyError::semantic(
+                SemanticError::AssignToConst(receiver.clone()),
+                receiver.span(),
+            );
+        }
+
+        if call.args.len() != params.len() {
+            return HarpyError::semantic(
+                SemanticError::ArgCountMismatch(
+                    (*call.method).clone(),
+                    call.args.len(),
+                    params.len(),
+                ),
+                call.method.span(),
+            );
+        }
+
+        for (arg, param) in call.args.iter().zip(params) {
+            analyzer.expect(&param);
+            let ty = Self::resolve_expr(arg, analyzer, mode)?;
+            if !param.assign_compatible(&ty) {
+                let param = analyzer.register_type(&TypeSpanned {
+                    ty: param,
+                    span: arg.span(),
+                });
+                return HarpyError::semantic(SemanticError::ArgTypeMismatch(ty, param), arg.span());
+            }
+        }
+
+        Ok(ret)
+    }
+
+    // a method from an impl block gets the receiver as its first argument. for
+    // `&self` and `&mut self` the receiver is borrowed while the call is made,
+    // unless it is a reference or box already
+    fn resolve_impl_method(
+        call: &MethodCallExpr,
+        name: &str,
+        receiver_type: &Type,
+        analyzer: &mut Analyzer,
+        mode: ResolveMode,
+    ) -> Result<Type> {
+        let receiver = &call.receiver;
+        let (container, mutable) = Self::auto_deref(receiver_type);
+        let unknown = || {
+            HarpyError::semantic(
+                SemanticError::UnknownMethod(call.method.clone(), container.clone()),
+                call.method.span(),
+            )
+        };
+
+        let mut method = Ident::new(name.to_owned(), call.method.span());
+        let mut arg_types = None;
+        if let Some((overload, types)) = Self::pick_overload(&method, &call.args, 1, analyzer, mode)? {
+            method = overload;
+            arg_types = types.map(Vec::into_iter);
+        }
+        let Ok(sym_ref) = analyzer.get_symbol(&method) else {
+            return unknown();
+        };
+        let (params, return_type) = {
+            let symbol = (*sym_ref).borrow();
+            let SymbolInfoKind::Function(f) = &symbol.kind else {
+                return unknown();
+            };
+            (f.params.clone(), symbol.ty.ttype.clone())
+        };
+
+        // impl blocks only hold functions taking `self` first
+        let (receiver_param, params) = params.split_first().unwrap();
+        if call.args.len() != params.len() {
+            return HarpyError::semantic(
+                SemanticError::ArgCountMismatch((*call.method).clone(), call.args.len(), params.len()),
+                call.method.span(),
+            );
+        }
+
+        for (arg, param) in call.args.iter().zip(params) {
+            let ty = match arg_types.as_mut().and_then(Iterator::next).flatten() {
+                Some(ty) => ty,
+                None => {
+                    analyzer.expect(&param.ttype);
+                    Self::resolve_expr(arg, analyzer, mode)?
+                }
+            };
+            if !param.param_compatible(&ty) {
+                return HarpyError::semantic(
+                    SemanticError::ArgTypeMismatch(ty, param.clone()),
+                    arg.span(),
+                );
+            }
+        }
+
+        if let TypeInner::Ref(pointee) = &receiver_param.inner {
+            if pointee.mutable && !mutable {
+                return HarpyError::semantic(SemanticError::BorrowMutNonMutable, receiver.span());
+            }
+
+            // a box or a reference is passed on as it is, otherwise the receiver has to
+            // be on the stack, the collector only knows about addresses of whole heap objects
+            let by_ref = matches!(receiver_type.inner, TypeInner::Ref(_) | TypeInner::Boxed(_));
+            if !by_ref && !Self::is_stack_place(receiver, analyzer) {
+                return HarpyError::semantic(SemanticError::InvalidBorrow, receiver.span());
+            }
+
+            if !by_ref {
+                let root = receiver.lvalue().unwrap();
+                let symbol = analyzer.get_symbol(root)?;
+                if symbol.as_variable().is_some() {
+                    Self::borrow_variable(&symbol, pointee.mutable, receiver.span(), analyzer)?;
+                    analyzer.release_borrow(&symbol, receiver.span());
+                }
+            }
+        }
+
+        analyzer.register_call(&method, call.method.id());
+        Ok(return_type)
+    }
+
+    // the receiver is always passed by address, the function is picked at runtime
+    // by the type of the boxed value
+    fn resolve_dyn_method(
+        call: &MethodCallExpr,
+        tr: &str,
+        mutable: bool,
+        analyzer: &mut Analyzer,
+        mode: ResolveMode,
+    ) -> Result<Type> {
+        let Some(method) = analyzer
+            .get_trait(tr)
+            .and_then(|info| info.method(call.method.value()).cloned())
+        else {
+            return HarpyError::semantic(
+                SemanticError::UnknownMethod(call.method.clone(), Type::dyn_trait(tr)),
+                call.method.span(),
+            );
+        };
+
+        if call.args.len() != method.params.len() {
+            return HarpyError::semantic(
+                SemanticError::ArgCountMismatch((*call.method).clone(), call.args.len(), method.params.len()),
+                call.method.span(),
+            );
+        }
+
+        for (arg, param) in call.args.iter().zip(&method.params) {
+            analyzer.expect(&param.ttype);
+            let ty = Self::resolve_expr(arg, analyzer, mode)?;
+            if !param.param_compatible(&ty) {
+                return HarpyError::semantic(
+                    SemanticError::ArgTypeMismatch(ty, param.clone()),
+                    arg.span(),
+                );
+            }
+        }
+
+        if method.mutable_self && !mutable {
+            return HarpyError::semantic(SemanticError::BorrowMutNonMutable, call.receiver.span());
+        }
+
+        analyzer.register_virtual_call(call.method.id(), method.index);
+        Ok(method.return_type.ttype.clone())
+    }
+
+    // locals, their fields and array elements, and whatever a reference points at
+    fn is_stack_place(expr: &Node<Expr>, analyzer: &mut Analyzer) -> bool {
+        let (base, indexed) = match &**expr {
+            Expr::Ident(i) => {
+                return analyzer.get_symbol(i).is_ok_and(|s| {
+                    matches!(s.get().kind, SymbolInfoKind::Variable(_) | SymbolInfoKind::Param)
+                })
+            }
+            Expr::Prefix(PrefixOp { op, .. }, rhs) if *op == PrefixOpKind::Star => {
+                return analyzer
+                    .expr_type(rhs.id())
+                    .is_some_and(|ty| matches!(ty.inner, TypeInner::Ref(_)))
+            }
+            Expr::Field(base, _) => (base, false),
+            Expr::Index(base, _) => (base, true),
+            _ => return false,
+        };
+
+        let Some(base_type) = analyzer.expr_type(base.id()) else {
+            return false;
+        };
+        let mut ty = &base_type;
+        while let TypeInner::Ref(inner) = &ty.inner {
+            ty = inner;
+        }
+
+        match &ty.inner {
+            TypeInner::Boxed(_) => false,
+            TypeInner::Vector(_) if indexed => false,
+            _ if !std::ptr::eq(ty, &base_type) => true,
+            _ => Self::is_stack_place(base, analyzer),
         }
     }
 
@@ -212,23 +1476,119 @@ impl ExprResolver {
         lhs: &Expr,
         op: &InfixOp,
         rhs: &Expr,
+        expected: Option<&Type>,
         analyzer: &mut Analyzer,
         mode: ResolveMode,
     ) -> Result<Type> {
-        let lhs_type = Self::resolve_expr(lhs, analyzer, mode)?;
-        let rhs_type = Self::resolve_expr(rhs, analyzer, mode)?;
-        InfixResolver::resolve(op, &lhs_type, &rhs_type)
+        // int literals take the sized int type of the other operand, or of the
+        // whole expression for the operators giving a value of their operands' type
+        let expected = match op.op {
+            InfixOpKind::Eq
+            | InfixOpKind::Neq
+            | InfixOpKind::Lt
+            | InfixOpKind::Gt
+            | InfixOpKind::LtEq
+            | InfixOpKind::GtEq
+            | InfixOpKind::And
+            | InfixOpKind::Or => None,
+            _ => expected.filter(|e| Self::sized_int(e).is_some()),
+        };
+        let shift = matches!(op.op, InfixOpKind::Shl | InfixOpKind::Shr);
+        let resolve = |expr: &Expr, hint: Option<&Type>, analyzer: &mut Analyzer| {
+            if let Some(hint) = hint.filter(|h| Self::sized_int(h).is_some()).or(expected) {
+                analyzer.expect(hint);
+            }
+            Self::resolve_expr(expr, analyzer, mode)
+        };
+
+        // `1 + x` types the literal once the type of `x` is known
+        if !shift && Self::int_literal(lhs).is_some() && Self::int_literal(rhs).is_none() {
+            let rhs_type = resolve(rhs, None, analyzer)?;
+            let lhs_type = resolve(lhs, Some(&rhs_type), analyzer)?;
+            return Self::resolve_infix_types(op, &lhs_type, &rhs_type, analyzer);
+        }
+
+        let lhs_type = resolve(lhs, None, analyzer)?;
+        let rhs_type = resolve(rhs, Some(&lhs_type), analyzer)?;
+        Self::resolve_infix_types(op, &lhs_type, &rhs_type, analyzer)
+    }
+
+    // an if, switch or block used as a value evaluates to what its branches
+    // do; their errors are reported as they're analyzed
+    fn resolve_branches(
+        span: Span,
+        expected: Option<&Type>,
+        analyzer: &mut Analyzer,
+        analyze: impl FnOnce(&mut Analyzer, &mut BranchUnifier) -> ReturnStatus,
+    ) -> Result<Type> {
+        let mut unifier = BranchUnifier::new(expected);
+        analyze(analyzer, &mut unifier);
+        unifier.finish(span, analyzer)
     }
 
     pub fn resolve_expr(expr: &Expr, analyzer: &mut Analyzer, mode: ResolveMode) -> Result<Type> {
-        match expr {
-            Expr::Literal(l) => Ok(Self::resolve_lit(l, analyzer)),
-            Expr::Ident(i) => Self::resolve_ident(i, analyzer, mode),
+        let expected = analyzer.take_expected();
+        let ty = match expr {
+            Expr::Literal(l) => Self::resolve_lit(l, expected.as_ref(), analyzer),
+            Expr::Ident(i) => Self::resolve_ident(i, expected.as_ref(), analyzer, mode),
             Expr::Call(expr) => Self::resolve_call(expr, analyzer, mode),
-            Expr::Prefix(op, rhs) => Self::resolve_prefix(op, rhs, analyzer, mode),
-            Expr::Infix(lhs, op, rhs) => Self::resolve_infix(lhs, op, rhs, analyzer, mode),
+            Expr::Prefix(op, rhs) => Self::resolve_prefix(op, rhs, expected.as_ref(), analyzer, mode),
+            Expr::Infix(lhs, op, rhs) => {
+                Self::resolve_infix(lhs, op, rhs, expected.as_ref(), analyzer, mode)
+            }
             Expr::Borrow(expr, mutable) => Self::resolve_borrow(expr, *mutable, analyzer),
-            Expr::Box(expr) => Self::resolve_box(expr, analyzer),
+            Expr::Box(expr) => Self::resolve_box(expr, expected.as_ref(), analyzer),
+            Expr::Field(base, field) => Self::resolve_field(base, field, analyzer),
+            Expr::Struct(expr) => Self::resolve_struct(expr, analyzer, mode),
+            Expr::Index(base, index) => Self::resolve_index(base, index, analyzer),
+            Expr::Array(expr) => Self::resolve_array(expr, expected.as_ref(), analyzer, mode),
+            Expr::Vec(expr) => Self::resolve_vec(expr, expected.as_ref(), analyzer, mode),
+            Expr::MethodCall(call) => Self::resolve_method_call(call, analyzer, mode),
+            Expr::Variant(expr) => Self::resolve_variant(expr, analyzer, mode),
+            Expr::Closure(closure) => {
+                closure.analyze_semantics(analyzer);
+                Ok(closure.signature())
+            }
+            Expr::Cast(cast) => Self::resolve_cast(cast, analyzer, mode),
+            Expr::Optional(expr) => Self::resolve_optional(expr, expected.clone(), analyzer, mode),
+            Expr::Result(expr) => Self::resolve_result(expr, expected.clone(), analyzer, mode),
+            Expr::Try(expr) => Self::resolve_try(expr, analyzer, mode),
+            Expr::Tuple(expr) => Self::resolve_tuple(expr, expected.as_ref(), analyzer, mode),
+            Expr::If(expr) => Self::resolve_branches(expr.span(), expected.as_ref(), analyzer, |a, u| {
+                expr.analyze_value(a, u, expr.span())
+            }),
+            Expr::Switch(expr) => Self::resolve_branches(expr.span(), expected.as_ref(), analyzer, |a, u| {
+                expr.analyze_value(a, u)
+            }),
+            Expr::Block(expr) => Self::resolve_branches(expr.span(), expected.as_ref(), analyzer, |a, u| {
+                expr.analyze_value(a, u, expr.span())
+            }),
+        }?;
+
+        Ok(Self::coerce(ty, expected.as_ref(), analyzer))
+    }
+
+    // a box of a type implementing a trait turns into a trait object where one is expected
+    fn coerce(ty: Type, expected: Option<&Type>, analyzer: &Analyzer) -> Type {
+        let (Some(TypeInner::Boxed(expected)), TypeInner::Boxed(inner)) =
+            (expected.map(|e| &e.inner), &ty.inner)
+        else {
+            return ty;
+        };
+
+        match (&expected.inner, &inner.inner) {
+            (TypeInner::Dyn(tr), TypeInner::Base(BaseType::Custom(custom)))
+                if analyzer.implements(custom.name(), tr) =>
+            {
+                Type {
+                    mutable: ty.mutable,
+                    inner: TypeInner::Boxed(Box::new(Type {
+                        mutable: inner.mutable,
+                        inner: TypeInner::Dyn(tr.clone()),
+                    })),
+                }
+            }
+            _ => ty,
         }
     }
 }
@@ -332,6 +1692,38 @@ mod tests {
     }
 
 
+    #[test]
+    fn test_string_concat_and_compare() {
+        let (mut analyzer, expr) = create_analyzer("\"ab\" + \"c\"");
+        let ty =
+            ExprResolver::resolve_expr(&expr, &mut analyzer, super::ResolveMode::Read).unwrap();
+        assert_eq!(ty, Type::str());
+
+        let (mut analyzer, expr) = create_analyzer("\"ab\" < \"c\"");
+        let ty =
+            ExprResolver::resolve_expr(&expr, &mut analyzer, super::ResolveMode::Read).unwrap();
+        assert_eq!(ty, Type::bool());
+
+        let (mut analyzer, expr) = create_analyzer("\"ab\" * \"c\"");
+        assert!(ExprResolver::resolve_expr(&expr, &mut analyzer, super::ResolveMode::Read).is_err());
+    }
+
+    #[test]
+    fn test_string_methods() {
+        let (mut analyzer, expr) = create_analyzer("\"hello\".substring(1, 3).len()");
+        let ty =
+            ExprResolver::resolve_expr(&expr, &mut analyzer, super::ResolveMode::Read).unwrap();
+        assert_eq!(ty, Type::int());
+
+        let (mut analyzer, expr) = create_analyzer("\"hello\".push(1)");
+        let err =
+            ExprResolver::resolve_expr(&expr, &mut analyzer, super::ResolveMode::Read).unwrap_err();
+        assert!(matches!(
+            err.kind(),
+            crate::err::HarpyErrorKind::SemanticError(SemanticError::UnknownMethod(..)),
+        ));
+    }
+
     #[test]
     fn test_nested_box() {
         let (mut analyzer, expr) = create_analyzer("box box box 3.14");
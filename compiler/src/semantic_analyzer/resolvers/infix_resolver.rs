@@ -31,40 +31,49 @@ impl InfixResolver {
             (
                 TypeInner::Base(BaseType::Primitive(lhs_p)),
                 TypeInner::Base(BaseType::Primitive(rhs_p)),
-            ) => match op.op {
-                InfixOpKind::Plus
-                | InfixOpKind::Minus
-                | InfixOpKind::Mult
-                | InfixOpKind::Div
-                | InfixOpKind::Mod => {
-                    matches!(
-                        (lhs_p, rhs_p),
-                        (PrimitiveType::Int, PrimitiveType::Int)
-                            | (PrimitiveType::Float, PrimitiveType::Float)
-                    )
-                }
+            ) => {
+                // ints only mix with ints of the same type, bar the amount to shift by
+                let ints = lhs_p == rhs_p && lhs_p.is_int();
+                let same = |p: PrimitiveType| *lhs_p == p && *rhs_p == p;
 
-                InfixOpKind::Lt | InfixOpKind::Gt | InfixOpKind::GtEq | InfixOpKind::LtEq => {
-                    matches!(
-                        (lhs_p, rhs_p),
-                        (PrimitiveType::Int, PrimitiveType::Int)
-                            | (PrimitiveType::Float, PrimitiveType::Float)
-                    )
-                }
+                match op.op {
+                    InfixOpKind::Plus => ints || same(PrimitiveType::Float) || same(PrimitiveType::Str),
 
-                InfixOpKind::Neq | InfixOpKind::Eq => {
-                    matches!(
-                        (lhs_p, rhs_p),
-                        (PrimitiveType::Int, PrimitiveType::Int)
-                            | (PrimitiveType::Float, PrimitiveType::Float)
-                            | (PrimitiveType::Bool, PrimitiveType::Bool)
-                    )
-                }
+                    InfixOpKind::Minus
+                    | InfixOpKind::Mult
+                    | InfixOpKind::Div
+                    | InfixOpKind::Mod => ints || same(PrimitiveType::Float),
+
+                    InfixOpKind::WrapPlus
+                    | InfixOpKind::WrapMinus
+                    | InfixOpKind::WrapMult
+                    | InfixOpKind::SatPlus
+                    | InfixOpKind::SatMinus
+                    | InfixOpKind::SatMult
+                    | InfixOpKind::BitAnd
+                    | InfixOpKind::BitOr
+                    | InfixOpKind::BitXor => ints,
+
+                    InfixOpKind::Shl | InfixOpKind::Shr => lhs_p.is_int() && rhs_p.is_int(),
+
+                    InfixOpKind::Lt | InfixOpKind::Gt | InfixOpKind::GtEq | InfixOpKind::LtEq => {
+                        ints
+                            || same(PrimitiveType::Float)
+                            || same(PrimitiveType::Str)
+                            || same(PrimitiveType::Char)
+                    }
+
+                    InfixOpKind::Neq | InfixOpKind::Eq => {
+                        ints
+                            || same(PrimitiveType::Float)
+                            || same(PrimitiveType::Bool)
+                            || same(PrimitiveType::Str)
+                            || same(PrimitiveType::Char)
+                    }
 
-                InfixOpKind::And | InfixOpKind::Or => {
-                    matches!((lhs_p, rhs_p), (PrimitiveType::Bool, PrimitiveType::Bool))
+                    InfixOpKind::And | InfixOpKind::Or => same(PrimitiveType::Bool),
                 }
-            },
+            }
 
             _ => false,
         }
@@ -76,7 +85,18 @@ impl InfixResolver {
             | InfixOpKind::Minus
             | InfixOpKind::Mult
             | InfixOpKind::Div
-            | InfixOpKind::Mod => lhs.clone(),
+            | InfixOpKind::Mod
+            | InfixOpKind::WrapPlus
+            | InfixOpKind::WrapMinus
+            | InfixOpKind::WrapMult
+            | InfixOpKind::SatPlus
+            | InfixOpKind::SatMinus
+            | InfixOpKind::SatMult
+            | InfixOpKind::BitAnd
+            | InfixOpKind::BitOr
+            | InfixOpKind::BitXor
+            | InfixOpKind::Shl
+            | InfixOpKind::Shr => lhs.clone(),
 
             InfixOpKind::Eq
             | InfixOpKind::Lt
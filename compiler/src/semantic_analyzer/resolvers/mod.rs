@@ -1,3 +1,4 @@
+pub mod cast_resolver;
 pub mod expr_resolver;
 pub mod infix_resolver;
 pub mod prefix_resolver;
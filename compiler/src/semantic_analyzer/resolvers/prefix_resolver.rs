@@ -63,15 +63,14 @@ mod primitive {
                 _ => false,
             },
             PrefixOpKind::Plus => match ttype {
-                PrimitiveType::Int => true,
                 PrimitiveType::Float => true,
-                _ => false,
+                t => t.is_int(),
             },
             PrefixOpKind::Minus => match ttype {
-                PrimitiveType::Int => true,
                 PrimitiveType::Float => true,
-                _ => false,
+                t => t.is_int() && t.is_signed(),
             },
+            PrefixOpKind::BitNot => ttype.is_int(),
         }
     }
 }
@@ -100,8 +99,17 @@ mod type_inner {
     pub(super) fn validate(op: &PrefixOp, ttype: &Type) -> bool {
         match ttype {
             TypeInner::Base(b) => base::validate(op, b),
+            // the value behind a trait object has no static type to copy out as
+            TypeInner::Boxed(t) if matches!(t.inner, TypeInner::Dyn(_)) => false,
             TypeInner::Boxed(_) => boxed::validate(op),
             TypeInner::Ref(t) => refr::validate(op, t),
+            TypeInner::Array(_, _) => false,
+            TypeInner::Vector(_) => false,
+            TypeInner::Optional(_) => false,
+            TypeInner::Result(_, _) => false,
+            TypeInner::Tuple(_) => false,
+            TypeInner::Function(_, _) => false,
+            TypeInner::Dyn(_) => false,
             TypeInner::Unknown => false,
             TypeInner::Void => false,
         }
@@ -128,6 +136,13 @@ mod ttype {
                 TypeInner::Base(_) => unreachable!(),
                 TypeInner::Boxed(t) => *t.clone(),
                 TypeInner::Ref(t) => *t.clone(),
+                TypeInner::Array(_, _) => unreachable!(),
+                TypeInner::Vector(_) => unreachable!(),
+                TypeInner::Optional(_) => unreachable!(),
+                TypeInner::Result(_, _) => unreachable!(),
+                TypeInner::Tuple(_) => unreachable!(),
+                TypeInner::Function(_, _) => unreachable!(),
+                TypeInner::Dyn(_) => unreachable!(),
                 TypeInner::Unknown => unreachable!(),
                 TypeInner::Void => unreachable!(),
             },
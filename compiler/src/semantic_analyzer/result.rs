@@ -13,7 +13,9 @@ use super::{
     function_table::{FunctionTable, RuntimeFunctionTable},
     global_table::{GlobalTable, RuntimeGlobalTable},
     scope::{Depth, Scope},
-    symbol_info::SymbolInfoKind,
+    struct_table::{FieldIndex, RuntimeStructTable, StructTable, VariantIndex},
+    symbol_info::{ClosureInfo, RuntimeClosureInfo, SymbolInfoKind},
+    trait_table::{RuntimeTraitTable, TraitTable},
     type_table::{RuntimeConversionTypeTable, RuntimeTypeIndex, RuntimeTypeTable, TypeTable},
 };
 
@@ -41,7 +43,12 @@ pub struct AnalysisResult {
     pub constants: ConstPool,
     pub function_table: FunctionTable,
     pub global_table: GlobalTable,
+    pub struct_table: StructTable,
+    pub trait_table: TraitTable,
     pub locals_map: HashMap<NodeId, LocalAddress>,
+    pub fields_map: HashMap<NodeId, FieldIndex>,
+    pub variants_map: HashMap<NodeId, VariantIndex>,
+    pub closures: HashMap<NodeId, ClosureInfo>,
     pub main_id: Option<crate::semantic_analyzer::function_table::FuncIndex>,
 }
 
@@ -56,7 +63,12 @@ impl AnalysisResult {
             constants: ConstPool::new(),
             function_table: FunctionTable::new(),
             global_table: GlobalTable::new(),
+            struct_table: StructTable::new(),
+            trait_table: TraitTable::new(),
             locals_map: HashMap::new(),
+            fields_map: HashMap::new(),
+            variants_map: HashMap::new(),
+            closures: HashMap::new(),
             main_id: None,
         }
     }
@@ -73,16 +85,28 @@ impl AnalysisResult {
 
         let type_table = self.type_table.into_conversion().map_err(|e| vec![e])?;
         let constants = self.constants.to_runtime(&type_table);
+        let trait_table = self.trait_table.into_runtime(&type_table, &self.function_table);
         let function_table = self.function_table.into_runtime(&type_table)?;
         let global_table = self.global_table.into_runtime(&type_table)?;
+        let struct_table = self.struct_table.into_runtime(&type_table);
         let expr_map = node_info_to_expr_map(&self.node_info, &type_table);
+        let closures = self
+            .closures
+            .into_iter()
+            .map(|(id, info)| (id, info.into_runtime(&type_table)))
+            .collect();
 
         Ok(RuntimeAnalysisResult {
             constants,
             type_table: type_table.into_runtime(),
             function_table,
             global_table,
+            struct_table,
+            trait_table,
             locals_map: self.locals_map,
+            fields_map: self.fields_map,
+            variants_map: self.variants_map,
+            closures,
             main_id,
             expr_map,
         })
@@ -95,7 +119,12 @@ pub struct RuntimeAnalysisResult {
     pub constants: RuntimeConstPool,
     pub function_table: RuntimeFunctionTable,
     pub global_table: RuntimeGlobalTable,
+    pub struct_table: RuntimeStructTable,
+    pub trait_table: RuntimeTraitTable,
     pub locals_map: HashMap<NodeId, LocalAddress>,
+    pub fields_map: HashMap<NodeId, FieldIndex>,
+    pub variants_map: HashMap<NodeId, VariantIndex>,
+    pub closures: HashMap<NodeId, RuntimeClosureInfo>,
     pub main_id: crate::semantic_analyzer::function_table::FuncIndex,
     pub expr_map: HashMap<NodeId, RuntimeTypeIndex>,
 }
@@ -9,7 +9,7 @@ use crate::{
     aliases::{Result, ScopeRc, SymbolInfoRef},
     err::HarpyError,
     extensions::{ScopeRcExt, SymbolInfoRefExt, WeakScopeExt},
-    lexer::tokens::Ident,
+    lexer::{span::Span, tokens::Ident},
     parser::node::Node,
 };
 
@@ -42,6 +42,9 @@ pub struct Scope {
     kind: ScopeKind,
     depth: Depth,
     borrows: Vec<BorrowInfo>,
+    // only set on loop scopes
+    pub(in crate::semantic_analyzer) label: Option<String>,
+    pub(in crate::semantic_analyzer) has_break: bool,
 }
 
 impl Scope {
@@ -58,6 +61,8 @@ impl Scope {
             visited: false,
             depth,
             borrows: vec![],
+            label: None,
+            has_break: false,
         }
     }
 
@@ -76,6 +81,11 @@ impl Scope {
         Ok(s)
     }
 
+    // only looks in this scope and doesn't count as a use
+    pub(in crate::semantic_analyzer) fn defined_here(&self, name: &str) -> Option<SymbolInfoRef> {
+        self.symbols.get(name).cloned()
+    }
+
     pub(in crate::semantic_analyzer) fn next_unvisited_child(&mut self) -> Option<ScopeRc> {
         for child in &self.children {
             if !child.get().visited {
@@ -86,13 +96,31 @@ impl Scope {
         None
     }
 
+    // closure scopes are analyzed where the closure appears, not in tree order
+    pub(in crate::semantic_analyzer) fn mark_visited(&mut self) {
+        self.visited = true;
+    }
+
     pub(in crate::semantic_analyzer) fn lookup(&self, ident: &Ident) -> Result<SymbolInfoRef> {
-        if let Some(s) = self.symbols.get(ident.value()) {
+        self.lookup_as(ident, ident.value())
+    }
+
+    // `global_name` is what the ident is called in the global scope
+    pub(in crate::semantic_analyzer) fn lookup_as(
+        &self,
+        ident: &Ident,
+        global_name: &str,
+    ) -> Result<SymbolInfoRef> {
+        let name = match self.kind {
+            ScopeKind::Global => global_name,
+            _ => ident.value(),
+        };
+        if let Some(s) = self.symbols.get(name) {
             (**s).borrow_mut().ref_count += 1;
             return Ok(s.clone());
         }
 
-        match self.parent.upgrade().map(|p| p.get().lookup(ident)) {
+        match self.parent.upgrade().map(|p| p.get().lookup_as(ident, global_name)) {
             Some(s) => s,
             None => HarpyError::semantic(SemanticError::MissingSymbol(ident.clone()), ident.span()),
         }
@@ -109,6 +137,25 @@ impl Scope {
             .unwrap_or(false)
     }
 
+    // the innermost enclosing loop, or the one named `label`; the search
+    // stops at the enclosing function
+    pub(in crate::semantic_analyzer) fn find_loop(
+        scope: &ScopeRc,
+        label: Option<&str>,
+    ) -> Option<ScopeRc> {
+        let current = scope.get();
+        match &current.kind {
+            ScopeKind::Loop if label.is_none() || current.label.as_deref() == label => {
+                return Some(scope.clone())
+            }
+            ScopeKind::Function(_) | ScopeKind::Global => return None,
+            _ => (),
+        }
+
+        let parent = current.parent.upgrade()?;
+        Self::find_loop(&parent, label)
+    }
+
     pub(in crate::semantic_analyzer) fn get_function_symbol(&self) -> Option<SymbolInfoRef> {
         if let ScopeKind::Function(name) = &self.kind {
             return self.parent.upgrade_then(|p| p.symbols.get(name).cloned())?;
@@ -137,16 +184,34 @@ impl Scope {
                 }
             }
 
-            let mut var = borrow.original.as_variable_mut().unwrap();
-            if var.mutably_borrowed {
-                var.mutably_borrowed = false;
-            } else {
-                var.immutably_borrowed_count -= 1;
-            }
+            Self::end_borrow(borrow);
         }
 
         Ok(())
     }
+
+    // ends a borrow before the scope does, like the one a method call takes of its receiver
+    pub(in crate::semantic_analyzer) fn release_borrow(&mut self, original: &SymbolInfoRef, span: Span) {
+        let Some(i) = self
+            .borrows
+            .iter()
+            .rposition(|b| Rc::ptr_eq(&b.original, original) && b.borrow_span == span)
+        else {
+            return;
+        };
+
+        let borrow = self.borrows.remove(i);
+        Self::end_borrow(&borrow);
+    }
+
+    fn end_borrow(borrow: &BorrowInfo) {
+        let mut var = borrow.original.as_variable_mut().unwrap();
+        if var.mutably_borrowed {
+            var.mutably_borrowed = false;
+        } else {
+            var.immutably_borrowed_count -= 1;
+        }
+    }
 }
 
 #[cfg(test)]
@@ -188,4 +253,18 @@ mod tests {
         assert!(block_scope.in_scopekind(ScopeKind::Global));
         assert!(!block_scope.in_scopekind(ScopeKind::Loop));
     }
+
+    #[test]
+    fn test_find_loop() {
+        let func = Rc::new(RefCell::new(Scope::new(ScopeKind::Function("f".into()), None, Depth(1))));
+        let outer = Rc::new(RefCell::new(Scope::new(ScopeKind::Loop, Some(&func), Depth(2))));
+        outer.borrow_mut().label = Some("outer".into());
+        let inner = Rc::new(RefCell::new(Scope::new(ScopeKind::Loop, Some(&outer), Depth(3))));
+        let block = Rc::new(RefCell::new(Scope::new(ScopeKind::Block, Some(&inner), Depth(4))));
+
+        assert!(Rc::ptr_eq(&Scope::find_loop(&block, None).unwrap(), &inner));
+        assert!(Rc::ptr_eq(&Scope::find_loop(&block, Some("outer")).unwrap(), &outer));
+        assert!(Scope::find_loop(&block, Some("missing")).is_none());
+        assert!(Scope::find_loop(&func, None).is_none());
+    }
 }
@@ -4,16 +4,21 @@ use crate::{
     extensions::{ScopeRcExt, SymbolInfoRefExt, WeakScopeExt},
     generator::instruction::{LocalAddress},
     lexer::tokens::Ident,
-    parser::{node::{Node}, program::Program, types::TypeSpanned},
+    parser::{node::{Node}, program::Program, types::{Type, TypeInner, TypeSpanned}},
 };
 
 use super::{
     analyze_trait::Analyze,
     analyzer::Analyzer,
     err::SemanticError,
+    function_table::FunctionTable,
     result::AnalysisResult,
     scope::{Scope, ScopeKind},
-    symbol_info::{FunctionInfo, GlobalInfo, SymbolInfo, SymbolInfoKind, VariableInfo},
+    struct_table::{FieldInfo, VariantInfo},
+    symbol_info::{
+        ClosureInfo, ConstantInfo, FunctionInfo, GenericInfo, GlobalInfo, SymbolInfo,
+        SymbolInfoKind, VariableInfo,
+    },
 };
 
 pub struct ScopeBuilder {
@@ -44,6 +49,19 @@ impl ScopeBuilder {
         self.current_scope = new_scope;
     }
 
+    pub fn push_loop_scope(&mut self, label: Option<&Node<Ident>>) {
+        self.push_scope(ScopeKind::Loop);
+        self.current_scope.get_mut().label = label.map(|l| l.value().clone());
+    }
+
+    pub fn push_closure_scope(&mut self, name: &Node<Ident>) {
+        self.push_scope(ScopeKind::Function(name.value().clone()));
+        self.current_scope.get_mut().mark_visited();
+        self.result
+            .closures
+            .insert(name.id(), ClosureInfo::new(self.current_scope.clone()));
+    }
+
     pub fn pop_scope(&mut self) {
         let parent = self.current_scope.get().parent.upgrade();
         if let Some(parent) = parent {
@@ -86,14 +104,17 @@ impl ScopeBuilder {
             return;
         };
 
-        let Some(func) = self.current_scope.get().get_function_symbol() else {
+        let Some(symbol) = self.current_scope.get().get_function_symbol() else {
             return;
         };
-        let mut func = func.as_function_mut().unwrap();
-        self.result.locals_map.insert(
-            ident.id(),
-            LocalAddress(func.locals.len().try_into().unwrap()),
-        );
+        // the function's name clashed with another symbol, which is already reported
+        let Some(mut func) = symbol.as_function_mut() else {
+            return;
+        };
+        let Some(address) = self.local_address(ident, func.locals.len()) else {
+            return;
+        };
+        self.result.locals_map.insert(ident.id(), address);
         func.locals.push(sym);
         func.params.push(ty);
     }
@@ -107,18 +128,33 @@ impl ScopeBuilder {
             return;
         };
 
-        let Some(func) = self.current_scope.get().get_function_symbol() else {
+        let Some(symbol) = self.current_scope.get().get_function_symbol() else {
             return;
         };
-        let mut func = func.as_function_mut().unwrap();
-        //FIX!!!
-        self.result.locals_map.insert(
-            ident.id(),
-            LocalAddress(func.locals.len().try_into().unwrap()),
-        );
+        let Some(mut func) = symbol.as_function_mut() else {
+            return;
+        };
+        let Some(address) = self.local_address(ident, func.locals.len()) else {
+            return;
+        };
+        self.result.locals_map.insert(ident.id(), address);
         func.locals.push(sym);
     }
 
+    // locals are addressed with 16 bits
+    fn local_address(&mut self, ident: &Node<Ident>, index: usize) -> Option<LocalAddress> {
+        match index.try_into() {
+            Ok(index) => Some(LocalAddress(index)),
+            Err(_) => {
+                self.report_error(HarpyError::new(
+                    HarpyErrorKind::SemanticError(SemanticError::TooManyLocals(ident.clone())),
+                    ident.span(),
+                ));
+                None
+            }
+        }
+    }
+
     pub fn define_func(&mut self, ident: &Node<Ident>, ty: TypeInfoRc) {
         let s = self.define_symbol(ident, ty, SymbolInfoKind::Function(FunctionInfo::new()));
         if let Some(s) = s {
@@ -129,14 +165,248 @@ impl ScopeBuilder {
         }
     }
 
-    pub fn register_type(&mut self, ttype: &TypeSpanned) -> TypeInfoRc {
+    // the first function of a name keeps it, the others need different params and
+    // get them mangled into theirs
+    pub fn overload_name(&mut self, ident: &Node<Ident>, params: &[TypeInfoRc]) -> String {
+        let name = ident.value();
+        // a generic function, or anything else holding the name, can't be overloaded
+        let clashes = self
+            .current_scope
+            .get()
+            .defined_here(name)
+            .is_some_and(|other| other.as_function().is_none());
+        let table = &mut self.result.function_table;
+        let overloads = table.overloads(name).to_vec();
+        let taken = clashes || overloads.iter().filter_map(|other| table.index_of(other)).any(|idx| {
+            table.get(idx).as_function().is_some_and(|f| {
+                f.params.len() == params.len()
+                    && f.params.iter().zip(params).all(|(a, b)| a.inner == b.inner)
+            })
+        });
+
+        let defined_as = if overloads.is_empty() {
+            name.clone()
+        } else {
+            FunctionTable::mangle(name, params)
+        };
+        // its body is still checked, under a name nothing can call
+        if taken {
+            self.report_error(HarpyError::new(
+                HarpyErrorKind::SemanticError(SemanticError::DuplicateSymbol(ident.clone())),
+                ident.span(),
+            ));
+            return format!("{defined_as}#{}", ident.id().0);
+        }
+
+        table.add_overload(name, &defined_as);
+        defined_as
+    }
+
+    pub fn define_generic(
+        &mut self,
+        ident: &Node<Ident>,
+        type_params: &[Node<Ident>],
+        info: GenericInfo,
+    ) {
+        for (i, param) in type_params.iter().enumerate() {
+            if type_params[..i].iter().any(|p| p.value() == param.value()) {
+                self.report_error(HarpyError::new(
+                    HarpyErrorKind::SemanticError(SemanticError::DuplicateSymbol(param.clone())),
+                    param.span(),
+                ));
+            }
+        }
+
+        // only specializations have a real return type
+        let ty = self.result.type_table.register(&TypeSpanned::dummy(Type::void()));
+        self.define_symbol(ident, ty, SymbolInfoKind::Generic(info));
+    }
+
+    pub fn register_type(&mut self, written: &TypeSpanned) -> TypeInfoRc {
+        let ttype = &TypeSpanned {
+            ty: self.result.type_table.names().expand(written),
+            span: written.span(),
+        };
+
         if !ttype.verify_pointers() {
             self.report_error(HarpyError::new(
                 HarpyErrorKind::SemanticError(SemanticError::PointerToRef),
                 ttype.span(),
             ));
         }
-        self.result.type_table.register(ttype)
+
+        if !ttype.verify_dyn() {
+            self.report_error(HarpyError::new(
+                HarpyErrorKind::SemanticError(SemanticError::UnboxedDyn(written.ty.clone())),
+                ttype.span(),
+            ));
+        }
+
+        if let Some(name) = ttype.custom_name() {
+            if !self.result.struct_table.contains(name)
+                && !self.result.type_table.names().contains(name)
+            {
+                self.report_error(HarpyError::new(
+                    HarpyErrorKind::SemanticError(SemanticError::UndefinedType(name.to_owned())),
+                    ttype.span(),
+                ));
+            }
+        }
+
+        if let Some(name) = ttype.trait_name() {
+            if !self.result.trait_table.contains(name) {
+                self.report_error(HarpyError::new(
+                    HarpyErrorKind::SemanticError(SemanticError::UndefinedTrait(name.to_owned())),
+                    ttype.span(),
+                ));
+            }
+        }
+        self.result.type_table.register(written)
+    }
+
+    pub fn declare_struct(&mut self, ident: &Node<Ident>) {
+        if self.result.trait_table.contains(ident.value())
+            || self.result.type_table.names().contains(ident.value())
+            || !self.result.struct_table.declare(ident.value(), ident.id())
+        {
+            self.report_error(HarpyError::new(
+                HarpyErrorKind::SemanticError(SemanticError::DuplicateSymbol(ident.clone())),
+                ident.span(),
+            ));
+        }
+    }
+
+    pub fn define_struct(&mut self, ident: &Node<Ident>, fields: Vec<(Node<Ident>, TypeInfoRc)>) {
+        let mut infos: Vec<FieldInfo> = Vec::with_capacity(fields.len());
+
+        for (name, ty) in fields {
+            if infos.iter().any(|f| f.name == *name.value()) {
+                self.report_error(HarpyError::new(
+                    HarpyErrorKind::SemanticError(SemanticError::DuplicateField(name.clone())),
+                    name.span(),
+                ));
+                continue;
+            }
+
+            if ty.inner == TypeInner::Unknown {
+                self.report_error(HarpyError::new(
+                    HarpyErrorKind::SemanticError(SemanticError::CantInferType),
+                    name.span(),
+                ));
+                continue;
+            }
+
+            infos.push(FieldInfo {
+                name: name.value().clone(),
+                ty,
+            });
+        }
+
+        self.result.struct_table.define(ident.value(), ident.id(), infos);
+    }
+
+    pub fn declare_enum(&mut self, ident: &Node<Ident>) {
+        if self.result.type_table.names().contains(ident.value())
+            || !self.result.struct_table.declare_enum(ident.value(), ident.id())
+        {
+            self.report_error(HarpyError::new(
+                HarpyErrorKind::SemanticError(SemanticError::DuplicateSymbol(ident.clone())),
+                ident.span(),
+            ));
+        }
+    }
+
+    pub fn define_enum(&mut self, ident: &Node<Ident>, variants: Vec<(Node<Ident>, Vec<TypeInfoRc>)>) {
+        let mut infos: Vec<VariantInfo> = Vec::with_capacity(variants.len());
+
+        for (name, fields) in variants {
+            if infos.iter().any(|v| v.name == *name.value()) {
+                self.report_error(HarpyError::new(
+                    HarpyErrorKind::SemanticError(SemanticError::DuplicateVariant(name.clone())),
+                    name.span(),
+                ));
+                continue;
+            }
+
+            infos.push(VariantInfo {
+                name: name.value().clone(),
+                fields,
+            });
+        }
+
+        self.result.struct_table.define_enum(ident.value(), ident.id(), infos);
+    }
+
+    pub fn declare_trait(&mut self, ident: &Node<Ident>) {
+        if self.result.struct_table.contains(ident.value())
+            || self.result.type_table.names().contains(ident.value())
+            || !self.result.trait_table.declare(ident.value(), ident.id())
+        {
+            self.report_error(HarpyError::new(
+                HarpyErrorKind::SemanticError(SemanticError::DuplicateSymbol(ident.clone())),
+                ident.span(),
+            ));
+        }
+    }
+
+    pub fn declare_type(&mut self, ident: &Node<Ident>, ttype: &TypeSpanned, newtype: bool) {
+        if self.result.struct_table.contains(ident.value())
+            || self.result.trait_table.contains(ident.value())
+            || !self.result.type_table.declare(ident.value(), ttype, newtype)
+        {
+            self.report_error(HarpyError::new(
+                HarpyErrorKind::SemanticError(SemanticError::DuplicateSymbol(ident.clone())),
+                ident.span(),
+            ));
+        }
+    }
+
+    // the declared type can only be checked once every type name is known
+    pub fn define_type(&mut self, ident: &Node<Ident>, ttype: &TypeSpanned) {
+        if self.result.type_table.names().is_recursive(ident.value()) {
+            self.report_error(HarpyError::new(
+                HarpyErrorKind::SemanticError(SemanticError::RecursiveType(ident.clone())),
+                ident.span(),
+            ));
+            return;
+        }
+
+        self.register_type(ttype);
+    }
+
+    // (name, whether the receiver is `&mut self`, params without the receiver, return type)
+    pub fn define_trait(
+        &mut self,
+        ident: &Node<Ident>,
+        methods: Vec<(Node<Ident>, bool, Vec<TypeInfoRc>, TypeInfoRc)>,
+    ) {
+        let mut infos: Vec<(String, bool, Vec<TypeInfoRc>, TypeInfoRc)> =
+            Vec::with_capacity(methods.len());
+
+        for (name, mutable_self, params, return_type) in methods {
+            if infos.iter().any(|m| m.0 == *name.value()) {
+                self.report_error(HarpyError::new(
+                    HarpyErrorKind::SemanticError(SemanticError::DuplicateSymbol(name.clone())),
+                    name.span(),
+                ));
+                continue;
+            }
+
+            infos.push((name.value().clone(), mutable_self, params, return_type));
+        }
+
+        self.result.trait_table.define(ident.value(), ident.id(), infos);
+    }
+
+    // recorded while building, so a box can be turned into a trait object
+    // before the impl block is reached
+    pub fn implement_trait(&mut self, ty: &Node<Ident>, tr: &Node<Ident>) {
+        if !self.result.trait_table.implement(ty.value(), tr.value()) {
+            self.report_error(HarpyError::new(
+                HarpyErrorKind::SemanticError(SemanticError::DuplicateSymbol(tr.clone())),
+                tr.span(),
+            ));
+        }
     }
 
     pub fn define_global(&mut self, ident: &Node<Ident>, ty: TypeInfoRc) {
@@ -150,13 +420,24 @@ impl ScopeBuilder {
         }
     }
 
+    // a constant has no storage; its value is filled in when it is analyzed
+    pub fn define_const(&mut self, ident: &Node<Ident>, ty: TypeInfoRc) {
+        self.define_symbol(ident, ty, SymbolInfoKind::Constant(ConstantInfo { value: None }));
+    }
+
     pub(in crate::semantic_analyzer) fn build_analyzer(
         program: &Program,
     ) -> std::result::Result<Analyzer, Vec<HarpyError>> {
         let mut s = Self::new();
         program.build(&mut s);
 
-        Ok(Analyzer::new(s.result, s.errors))
+        // the parser only notes uses of items an import doesn't give access to
+        let mut errors = program.import_errors().iter().map(|e| e.error()).collect::<Vec<_>>();
+        errors.extend(s.errors);
+
+        let mut analyzer = Analyzer::new(s.result, errors);
+        analyzer.set_modules(program.module_names());
+        Ok(analyzer)
     }
 
     pub fn into_analyzer(self) -> Analyzer {
@@ -0,0 +1,525 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    aliases::TypeInfoRc,
+    parser::{
+        node::NodeId,
+        types::{BaseType, Type, TypeInner},
+    },
+};
+
+use super::type_table::{RuntimeConversionTypeTable, RuntimeTypeIndex, TypeNames};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct FieldIndex(pub u32);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct VariantIndex(pub u32);
+
+// enum values start with the index of the variant they hold
+pub const ENUM_TAG_SIZE: u32 = 8;
+
+// `T?` is laid out like an enum of `none` and `some(T)`
+pub const NONE_VARIANT: VariantIndex = VariantIndex(0);
+pub const SOME_VARIANT: VariantIndex = VariantIndex(1);
+pub const SOME_FIELD: FieldIndex = FieldIndex(0);
+
+// and `result<T, E>` like one of `ok(T)` and `err(E)`
+pub const OK_VARIANT: VariantIndex = VariantIndex(0);
+pub const ERR_VARIANT: VariantIndex = VariantIndex(1);
+pub const OK_FIELD: FieldIndex = FieldIndex(0);
+pub const ERR_FIELD: FieldIndex = FieldIndex(1);
+
+#[derive(Debug, Clone)]
+pub struct FieldInfo {
+    pub name: String,
+    pub ty: TypeInfoRc,
+}
+
+#[derive(Debug, Clone)]
+pub struct StructInfo {
+    pub fields: Vec<FieldInfo>,
+}
+
+impl StructInfo {
+    pub fn field(&self, name: &str) -> Option<(FieldIndex, &FieldInfo)> {
+        self.fields
+            .iter()
+            .enumerate()
+            .find(|(_, f)| f.name == name)
+            .map(|(i, f)| (FieldIndex(i as u32), f))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct VariantInfo {
+    pub name: String,
+    pub fields: Vec<TypeInfoRc>,
+}
+
+#[derive(Debug, Clone)]
+pub struct EnumInfo {
+    pub variants: Vec<VariantInfo>,
+}
+
+impl EnumInfo {
+    pub fn variant(&self, name: &str) -> Option<(VariantIndex, &VariantInfo)> {
+        self.variants
+            .iter()
+            .enumerate()
+            .find(|(_, v)| v.name == name)
+            .map(|(i, v)| (VariantIndex(i as u32), v))
+    }
+
+    // the payloads of all variants share one field index space, in declaration order
+    pub fn field_index(&self, variant: VariantIndex, field: usize) -> FieldIndex {
+        let start: usize = self.variants[..variant.0 as usize]
+            .iter()
+            .map(|v| v.fields.len())
+            .sum();
+        FieldIndex((start + field) as u32)
+    }
+}
+
+#[derive(Debug)]
+pub struct StructTable {
+    structs: HashMap<String, StructInfo>,
+    enums: HashMap<String, EnumInfo>,
+    declarations: HashMap<String, NodeId>,
+}
+
+impl StructTable {
+    pub(in crate::semantic_analyzer) fn new() -> Self {
+        Self {
+            structs: HashMap::new(),
+            enums: HashMap::new(),
+            declarations: HashMap::new(),
+        }
+    }
+
+    pub(in crate::semantic_analyzer) fn declare(&mut self, name: &str, id: NodeId) -> bool {
+        if self.declarations.contains_key(name) {
+            return false;
+        }
+
+        self.declarations.insert(name.to_owned(), id);
+        self.structs
+            .insert(name.to_owned(), StructInfo { fields: vec![] });
+        true
+    }
+
+    // a redeclared struct keeps the fields of its first declaration
+    pub(in crate::semantic_analyzer) fn define(
+        &mut self,
+        name: &str,
+        id: NodeId,
+        fields: Vec<FieldInfo>,
+    ) {
+        if self.is_declared_by(name, id) {
+            self.structs.insert(name.to_owned(), StructInfo { fields });
+        }
+    }
+
+    pub(in crate::semantic_analyzer) fn declare_enum(&mut self, name: &str, id: NodeId) -> bool {
+        if self.declarations.contains_key(name) {
+            return false;
+        }
+
+        self.declarations.insert(name.to_owned(), id);
+        self.enums
+            .insert(name.to_owned(), EnumInfo { variants: vec![] });
+        true
+    }
+
+    pub(in crate::semantic_analyzer) fn define_enum(
+        &mut self,
+        name: &str,
+        id: NodeId,
+        variants: Vec<VariantInfo>,
+    ) {
+        if self.is_declared_by(name, id) {
+            self.enums.insert(name.to_owned(), EnumInfo { variants });
+        }
+    }
+
+    pub fn is_declared_by(&self, name: &str, id: NodeId) -> bool {
+        self.declarations.get(name) == Some(&id)
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.structs.contains_key(name) || self.enums.contains_key(name)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&StructInfo> {
+        self.structs.get(name)
+    }
+
+    pub fn get_enum(&self, name: &str) -> Option<&EnumInfo> {
+        self.enums.get(name)
+    }
+
+    fn by_value_structs<'a>(
+        &'a self,
+        name: &str,
+        names: &'a TypeNames,
+    ) -> impl Iterator<Item = String> + 'a {
+        let struct_fields = self
+            .structs
+            .get(name)
+            .into_iter()
+            .flat_map(|s| s.fields.iter().map(|f| &f.ty));
+        let variant_fields = self
+            .enums
+            .get(name)
+            .into_iter()
+            .flat_map(|e| e.variants.iter().flat_map(|v| v.fields.iter()));
+
+        struct_fields
+            .chain(variant_fields)
+            .flat_map(|ty| Self::stored_structs(ty, names))
+    }
+
+    // the structs a value of `ty` stores inline, looking through arrays,
+    // optionals, results, tuples and newtypes
+    fn stored_structs(ty: &Type, names: &TypeNames) -> Vec<String> {
+        match &ty.inner {
+            TypeInner::Base(BaseType::Custom(c)) => match names.underlying(ty) {
+                Some(underlying) => Self::stored_structs(&underlying, names),
+                None => vec![c.name().to_owned()],
+            },
+            TypeInner::Array(element, _) | TypeInner::Optional(element) => {
+                Self::stored_structs(element, names)
+            }
+            TypeInner::Result(value, error) => {
+                let mut stored = Self::stored_structs(value, names);
+                stored.extend(Self::stored_structs(error, names));
+                stored
+            }
+            TypeInner::Tuple(elements) => elements
+                .iter()
+                .flat_map(|e| Self::stored_structs(e, names))
+                .collect(),
+            _ => vec![],
+        }
+    }
+
+    pub fn is_recursive(&self, name: &str, names: &TypeNames) -> bool {
+        let mut visited = HashSet::new();
+        let mut pending: Vec<String> = self.by_value_structs(name, names).collect();
+
+        while let Some(current) = pending.pop() {
+            if current == name {
+                return true;
+            }
+
+            if !visited.contains(&current) {
+                pending.extend(self.by_value_structs(&current, names));
+                visited.insert(current);
+            }
+        }
+
+        false
+    }
+
+    fn size_of(&self, ty: &Type, names: &TypeNames, sizes: &mut HashMap<String, u32>) -> u32 {
+        match &ty.inner {
+            TypeInner::Base(BaseType::Custom(c)) => match names.underlying(ty) {
+                Some(underlying) => self.size_of(&underlying, names, sizes),
+                None => self.struct_size(c.name(), names, sizes),
+            },
+            TypeInner::Array(element, len) => self.size_of(element, names, sizes) * *len as u32,
+            TypeInner::Optional(value) => ENUM_TAG_SIZE + self.size_of(value, names, sizes),
+            TypeInner::Result(value, error) => {
+                ENUM_TAG_SIZE
+                    + self
+                        .size_of(value, names, sizes)
+                        .max(self.size_of(error, names, sizes))
+            }
+            TypeInner::Tuple(elements) => {
+                elements.iter().map(|e| self.size_of(e, names, sizes)).sum()
+            }
+            _ => ty.calc_size() as u32,
+        }
+    }
+
+    fn struct_size(&self, name: &str, names: &TypeNames, sizes: &mut HashMap<String, u32>) -> u32 {
+        if let Some(size) = sizes.get(name) {
+            return *size;
+        }
+
+        // an enum is as large as its tag plus its largest payload
+        let size = match self.enums.get(name) {
+            Some(info) => {
+                let payload = info
+                    .variants
+                    .iter()
+                    .map(|v| v.fields.iter().map(|f| self.size_of(f, names, sizes)).sum())
+                    .max()
+                    .unwrap_or(0);
+                ENUM_TAG_SIZE + payload
+            }
+            None => self.structs[name]
+                .fields
+                .iter()
+                .map(|f| self.size_of(&f.ty, names, sizes))
+                .sum(),
+        };
+
+        sizes.insert(name.to_owned(), size);
+        size
+    }
+
+    pub(in crate::semantic_analyzer) fn into_runtime(
+        self,
+        type_table: &RuntimeConversionTypeTable,
+    ) -> RuntimeStructTable {
+        let names = type_table.names();
+        let mut sizes = HashMap::new();
+        let mut structs = HashMap::new();
+
+        for (name, info) in &self.structs {
+            let mut offset = 0;
+            let mut fields = Vec::with_capacity(info.fields.len());
+
+            for field in &info.fields {
+                fields.push(RuntimeFieldInfo {
+                    offset,
+                    type_index: type_table.get_mapping(&field.ty.idx),
+                });
+                offset += self.size_of(&field.ty, names, &mut sizes);
+            }
+
+            structs.insert(
+                name.clone(),
+                RuntimeStructInfo {
+                    size: offset,
+                    fields,
+                },
+            );
+        }
+
+        let mut enums = HashMap::new();
+        for (name, info) in &self.enums {
+            let variants = info
+                .variants
+                .iter()
+                .map(|variant| {
+                    let mut offset = ENUM_TAG_SIZE;
+                    variant
+                        .fields
+                        .iter()
+                        .map(|ty| {
+                            let field = RuntimeFieldInfo {
+                                offset,
+                                type_index: type_table.get_mapping(&ty.idx),
+                            };
+                            offset += self.size_of(ty, names, &mut sizes);
+                            field
+                        })
+                        .collect()
+                })
+                .collect();
+
+            enums.insert(
+                name.clone(),
+                RuntimeEnumInfo {
+                    size: self.struct_size(name, names, &mut sizes),
+                    variants,
+                },
+            );
+        }
+
+        RuntimeStructTable { structs, enums }
+    }
+}
+
+#[derive(Debug)]
+pub struct RuntimeFieldInfo {
+    pub offset: u32,
+    pub type_index: RuntimeTypeIndex,
+}
+
+#[derive(Debug)]
+pub struct RuntimeStructInfo {
+    pub size: u32,
+    pub fields: Vec<RuntimeFieldInfo>,
+}
+
+#[derive(Debug)]
+pub struct RuntimeEnumInfo {
+    pub size: u32,
+    pub variants: Vec<Vec<RuntimeFieldInfo>>,
+}
+
+#[derive(Debug)]
+pub struct RuntimeStructTable {
+    structs: HashMap<String, RuntimeStructInfo>,
+    enums: HashMap<String, RuntimeEnumInfo>,
+}
+
+impl RuntimeStructTable {
+    pub fn get(&self, name: &str) -> &RuntimeStructInfo {
+        &self.structs[name]
+    }
+
+    pub fn get_enum(&self, name: &str) -> Option<&RuntimeEnumInfo> {
+        self.enums.get(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::semantic_analyzer::{symbol_info::TypeInfo, type_table::TypeIndex};
+
+    fn define(table: &mut StructTable, name: &str, fields: Vec<FieldInfo>) {
+        table.declare(name, NodeId(0));
+        table.define(name, NodeId(0), fields);
+    }
+
+    fn field(name: &str, ty: Type) -> FieldInfo {
+        FieldInfo {
+            name: name.to_owned(),
+            ty: TypeInfoRc::new(TypeInfo {
+                size: ty.calc_size(),
+                ttype: ty,
+                idx: TypeIndex(0),
+                alias: None,
+            }),
+        }
+    }
+
+    fn define_enum(table: &mut StructTable, name: &str, variants: Vec<(&str, Vec<Type>)>) {
+        let variants = variants
+            .into_iter()
+            .map(|(name, fields)| VariantInfo {
+                name: name.to_owned(),
+                fields: fields.into_iter().map(|ty| field("", ty).ty).collect(),
+            })
+            .collect();
+        table.declare_enum(name, NodeId(0));
+        table.define_enum(name, NodeId(0), variants);
+    }
+
+    #[test]
+    fn test_field_lookup() {
+        let mut table = StructTable::new();
+        define(&mut table, "Point", vec![field("x", Type::int()), field("y", Type::int())]);
+        let (idx, info) = table.get("Point").unwrap().field("y").unwrap();
+        assert_eq!(idx, FieldIndex(1));
+        assert_eq!(info.ty.ttype, Type::int());
+        assert!(table.get("Point").unwrap().field("z").is_none());
+    }
+
+    #[test]
+    fn test_recursion_through_value() {
+        let mut table = StructTable::new();
+        define(&mut table, "A", vec![field("b", Type::custom("B"))]);
+        define(&mut table, "B", vec![field("a", Type::custom("A"))]);
+        assert!(table.is_recursive("A", &TypeNames::default()));
+        assert!(table.is_recursive("B", &TypeNames::default()));
+    }
+
+    #[test]
+    fn test_recursion_through_pointer_is_allowed() {
+        let mut table = StructTable::new();
+        define(
+            &mut table,
+            "Node",
+            vec![field("value", Type::int()), field("next", Type::boxed(Type::custom("Node")))],
+        );
+        assert!(!table.is_recursive("Node", &TypeNames::default()));
+    }
+
+    #[test]
+    fn test_recursion_through_array() {
+        let mut table = StructTable::new();
+        define(&mut table, "Grid", vec![field("cells", Type::array(Type::custom("Grid"), 4))]);
+        assert!(table.is_recursive("Grid", &TypeNames::default()));
+    }
+
+    #[test]
+    fn test_struct_size() {
+        let mut table = StructTable::new();
+        define(&mut table, "Inner", vec![field("flag", Type::bool()), field("value", Type::float())]);
+        define(&mut table, "Outer", vec![field("inner", Type::custom("Inner")), field("name", Type::str())]);
+        let mut sizes = HashMap::new();
+        assert_eq!(table.struct_size("Outer", &TypeNames::default(), &mut sizes), 9 + 16);
+    }
+
+    #[test]
+    fn test_struct_with_array_size() {
+        let mut table = StructTable::new();
+        define(&mut table, "Point", vec![field("x", Type::int()), field("y", Type::int())]);
+        define(&mut table, "Path", vec![field("points", Type::array(Type::custom("Point"), 3))]);
+        let mut sizes = HashMap::new();
+        assert_eq!(table.struct_size("Path", &TypeNames::default(), &mut sizes), 3 * 16);
+    }
+
+    #[test]
+    fn test_enum_size_and_field_indices() {
+        let mut table = StructTable::new();
+        define_enum(
+            &mut table,
+            "Shape",
+            vec![
+                ("Circle", vec![Type::float()]),
+                ("Rect", vec![Type::float(), Type::float()]),
+                ("Empty", vec![]),
+            ],
+        );
+        let mut sizes = HashMap::new();
+        assert_eq!(table.struct_size("Shape", &TypeNames::default(), &mut sizes), ENUM_TAG_SIZE + 16);
+
+        let info = table.get_enum("Shape").unwrap();
+        let (idx, _) = info.variant("Rect").unwrap();
+        assert_eq!(idx, VariantIndex(1));
+        assert_eq!(info.field_index(idx, 1), FieldIndex(2));
+        assert!(table.contains("Shape"));
+        assert!(table.get("Shape").is_none());
+    }
+
+    #[test]
+    fn test_recursion_through_variant() {
+        let mut table = StructTable::new();
+        define_enum(&mut table, "List", vec![("Cons", vec![Type::int(), Type::custom("List")])]);
+        assert!(table.is_recursive("List", &TypeNames::default()));
+    }
+
+    #[test]
+    fn test_optional_field_size_and_recursion() {
+        let mut table = StructTable::new();
+        define(&mut table, "Slot", vec![field("value", Type::optional(Type::int()))]);
+        define(&mut table, "Chain", vec![field("next", Type::optional(Type::custom("Chain")))]);
+        let mut sizes = HashMap::new();
+        assert_eq!(table.struct_size("Slot", &TypeNames::default(), &mut sizes), ENUM_TAG_SIZE + 8);
+        assert!(!table.is_recursive("Slot", &TypeNames::default()));
+        assert!(table.is_recursive("Chain", &TypeNames::default()));
+    }
+
+    #[test]
+    fn test_newtype_field_size() {
+        let mut names = TypeNames::default();
+        names.declare("Meters", &Type::float(), true);
+        names.declare("Pair", &Type::tuple(vec![Type::int(), Type::int()]), true);
+        let mut table = StructTable::new();
+        define(&mut table, "Run", vec![field("d", Type::custom("Meters")), field("n", Type::int())]);
+        define_enum(&mut table, "Leg", vec![("Walk", vec![Type::custom("Pair")]), ("Rest", vec![])]);
+        let mut sizes = HashMap::new();
+        assert_eq!(table.struct_size("Run", &names, &mut sizes), 16);
+        assert_eq!(table.struct_size("Leg", &names, &mut sizes), ENUM_TAG_SIZE + 16);
+        assert!(!table.is_recursive("Run", &names));
+    }
+
+    #[test]
+    fn test_recursion_through_newtype() {
+        let mut names = TypeNames::default();
+        names.declare("Wrapped", &Type::custom("Outer"), true);
+        names.declare("Boxed", &Type::boxed(Type::custom("Outer")), true);
+        let mut table = StructTable::new();
+        define(&mut table, "Outer", vec![field("inner", Type::custom("Wrapped"))]);
+        define_enum(&mut table, "Chain", vec![("Link", vec![Type::custom("Boxed")])]);
+        assert!(table.is_recursive("Outer", &names));
+        assert!(!table.is_recursive("Chain", &names));
+    }
+}
@@ -1,9 +1,10 @@
 use std::{fmt::Display, ops::Deref};
 
 use crate::{
-    aliases::{Result, SymbolInfoRef, TypeInfoRc},
+    aliases::{Result, ScopeRc, SymbolInfoRef, TypeInfoRc},
     err::HarpyError,
-    lexer::span::Span,
+    generator::instruction::LocalAddress,
+    lexer::{span::Span, tokens::Lit},
     parser::{
         node::NodeId,
         types::{RuntimeType, Type, TypeInner},
@@ -32,6 +33,29 @@ impl Type {
             TypeInner::Boxed(t) => {
                 RuntimeType::Boxed(type_table.get_mapping(&type_table.get_type_index(t)))
             }
+            TypeInner::Vector(t) => {
+                RuntimeType::Vector(type_table.get_mapping(&type_table.get_type_index(t)))
+            }
+            TypeInner::Optional(t) => {
+                RuntimeType::Optional(type_table.get_mapping(&type_table.get_type_index(t)))
+            }
+            TypeInner::Result(value, error) => RuntimeType::Result(
+                type_table.get_mapping(&type_table.get_type_index(value)),
+                type_table.get_mapping(&type_table.get_type_index(error)),
+            ),
+            TypeInner::Array(t, len) => RuntimeType::Array(
+                type_table.get_mapping(&type_table.get_type_index(t)),
+                *len as u32,
+            ),
+            TypeInner::Function(_, _) => RuntimeType::Function,
+            TypeInner::Tuple(elements) => RuntimeType::Tuple(
+                elements
+                    .iter()
+                    .map(|e| type_table.get_mapping(&type_table.get_type_index(e)))
+                    .collect(),
+            ),
+            // the box carries the type of the value it holds
+            TypeInner::Dyn(_) => RuntimeType::Void,
             TypeInner::Base(b) => RuntimeType::Base(b.clone()),
         };
 
@@ -44,6 +68,8 @@ pub struct TypeInfo {
     pub ttype: Type,
     pub size: u8,
     pub idx: TypeIndex,
+    // the type as written when it goes by an alias, `ttype` is always expanded
+    pub alias: Option<Type>,
 }
 
 #[derive(Debug, Clone)]
@@ -82,6 +108,14 @@ pub struct FunctionInfo {
     pub locals: Vec<SymbolInfoRef>,
 }
 
+// the signature of a generic function, in terms of its type parameters
+#[derive(Debug, Clone)]
+pub struct GenericInfo {
+    pub type_params: Vec<String>,
+    pub params: Vec<Type>,
+    pub return_type: Type,
+}
+
 #[derive(Debug, Clone)]
 pub struct RuntimeFunctionInfo {
     pub params: Vec<RuntimeTypeIndex>,
@@ -89,9 +123,47 @@ pub struct RuntimeFunctionInfo {
     pub return_type: RuntimeTypeIndex,
 }
 
+// `captures` are the enclosing function's locals copied into the environment,
+// in field order; closures without captures have no environment
+#[derive(Debug, Clone)]
+pub struct ClosureInfo {
+    pub scope: ScopeRc,
+    pub env: Option<TypeInfoRc>,
+    pub captures: Vec<LocalAddress>,
+}
+
+#[derive(Debug, Clone)]
+pub struct RuntimeClosureInfo {
+    pub env: Option<RuntimeTypeIndex>,
+    pub captures: Vec<LocalAddress>,
+}
+
+impl ClosureInfo {
+    pub fn new(scope: ScopeRc) -> Self {
+        Self {
+            scope,
+            env: None,
+            captures: vec![],
+        }
+    }
+
+    pub fn into_runtime(self, type_table: &RuntimeConversionTypeTable) -> RuntimeClosureInfo {
+        RuntimeClosureInfo {
+            env: self.env.map(|env| type_table.get_mapping(&env.idx)),
+            captures: self.captures,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct GlobalInfo;
 
+// `value` stays empty until the initializer is evaluated, or if it can't be
+#[derive(Debug, Clone)]
+pub struct ConstantInfo {
+    pub value: Option<Lit>,
+}
+
 #[derive(Debug, Clone)]
 pub struct LiteralInfo {
     pub const_idx: ConstIndex,
@@ -105,8 +177,10 @@ pub struct RuntimeLiteralInfo {
 #[derive(Debug, Clone)]
 pub enum SymbolInfoKind {
     Function(FunctionInfo),
+    Generic(GenericInfo),
     Variable(VariableInfo),
     Global(GlobalInfo),
+    Constant(ConstantInfo),
     Literal(LiteralInfo),
     Param,
     Expr,
@@ -130,7 +204,13 @@ impl Deref for TypeInfo {
 
 impl Display for TypeInfo {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.ttype)
+        write!(f, "{}", self.written())
+    }
+}
+
+impl TypeInfo {
+    pub fn written(&self) -> &Type {
+        self.alias.as_ref().unwrap_or(&self.ttype)
     }
 }
 
@@ -179,6 +259,10 @@ impl SymbolInfo {
             },
             size: ttype.size,
             idx: ttype.idx,
+            alias: ttype.alias.as_ref().map(|alias| Type {
+                mutable: self.ty.mutable,
+                inner: alias.inner.clone(),
+            }),
         })
     }
 }
@@ -190,8 +274,10 @@ impl Display for SymbolInfoKind {
             Self::Param => "parameter",
             Self::Literal(_) => "literal",
             Self::Function(_) => "function",
+            Self::Generic(_) => "generic function",
             Self::Variable(_) => "variable",
             Self::Global(_) => "global variable",
+            Self::Constant(_) => "constant",
         };
 
         write!(f, "{s}")
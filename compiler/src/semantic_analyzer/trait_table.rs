@@ -0,0 +1,229 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    aliases::TypeInfoRc,
+    parser::{impl_decl::ImplDecl, node::NodeId, types::Type},
+};
+
+use super::{
+    function_table::{FuncIndex, FunctionTable},
+    type_table::{RuntimeConversionTypeTable, RuntimeTypeIndex},
+};
+
+// numbers every method of every trait, so a call through a trait object only
+// has to look up the pair of the receiver's type and this index
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct VirtualIndex(pub u32);
+
+#[derive(Debug, Clone)]
+pub struct TraitMethod {
+    pub name: String,
+    pub mutable_self: bool,
+    // without the receiver
+    pub params: Vec<TypeInfoRc>,
+    pub return_type: TypeInfoRc,
+    pub index: VirtualIndex,
+}
+
+#[derive(Debug, Clone)]
+pub struct TraitInfo {
+    pub methods: Vec<TraitMethod>,
+}
+
+impl TraitInfo {
+    pub fn method(&self, name: &str) -> Option<&TraitMethod> {
+        self.methods.iter().find(|m| m.name == name)
+    }
+}
+
+#[derive(Debug)]
+pub struct TraitTable {
+    traits: HashMap<String, TraitInfo>,
+    declarations: HashMap<String, NodeId>,
+    // (type, trait)
+    impls: HashSet<(String, String)>,
+    next_index: u32,
+    call_map: HashMap<NodeId, VirtualIndex>,
+}
+
+impl TraitTable {
+    pub(in crate::semantic_analyzer) fn new() -> Self {
+        Self {
+            traits: HashMap::new(),
+            declarations: HashMap::new(),
+            impls: HashSet::new(),
+            next_index: 0,
+            call_map: HashMap::new(),
+        }
+    }
+
+    pub(in crate::semantic_analyzer) fn declare(&mut self, name: &str, id: NodeId) -> bool {
+        if self.declarations.contains_key(name) {
+            return false;
+        }
+
+        self.declarations.insert(name.to_owned(), id);
+        self.traits
+            .insert(name.to_owned(), TraitInfo { methods: vec![] });
+        true
+    }
+
+    // the methods are given their indices here, a redeclared trait keeps the
+    // methods of its first declaration
+    pub(in crate::semantic_analyzer) fn define(
+        &mut self,
+        name: &str,
+        id: NodeId,
+        methods: Vec<(String, bool, Vec<TypeInfoRc>, TypeInfoRc)>,
+    ) {
+        if self.declarations.get(name) != Some(&id) {
+            return;
+        }
+
+        let methods = methods
+            .into_iter()
+            .map(|(name, mutable_self, params, return_type)| {
+                let index = VirtualIndex(self.next_index);
+                self.next_index += 1;
+                TraitMethod {
+                    name,
+                    mutable_self,
+                    params,
+                    return_type,
+                    index,
+                }
+            })
+            .collect();
+        self.traits.insert(name.to_owned(), TraitInfo { methods });
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.traits.contains_key(name)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&TraitInfo> {
+        self.traits.get(name)
+    }
+
+    pub(in crate::semantic_analyzer) fn implement(&mut self, ty: &str, tr: &str) -> bool {
+        self.impls.insert((ty.to_owned(), tr.to_owned()))
+    }
+
+    pub fn implements(&self, ty: &str, tr: &str) -> bool {
+        self.impls.contains(&(ty.to_owned(), tr.to_owned()))
+    }
+
+    pub(in crate::semantic_analyzer) fn register_call(&mut self, id: NodeId, index: VirtualIndex) {
+        self.call_map.insert(id, index);
+    }
+
+    // the vtable of each implementation lists the functions of the methods
+    // the impl block defined, missing ones have been reported already
+    pub(in crate::semantic_analyzer) fn into_runtime(
+        self,
+        type_table: &RuntimeConversionTypeTable,
+        function_table: &FunctionTable,
+    ) -> RuntimeTraitTable {
+        let mut param_counts = vec![0; self.next_index as usize];
+        for method in self.traits.values().flat_map(|t| &t.methods) {
+            // the receiver is a param as well
+            param_counts[method.index.0 as usize] = method.params.len() as u16 + 1;
+        }
+
+        let mut impls: Vec<_> = self.impls.into_iter().collect();
+        impls.sort();
+
+        let mut vtables = vec![];
+        for (ty, tr) in impls {
+            let (Some(info), Some(type_idx)) = (
+                self.traits.get(&tr),
+                type_table.find_type_index(&Type::custom(&ty)),
+            ) else {
+                continue;
+            };
+
+            let methods = info
+                .methods
+                .iter()
+                .filter_map(|method| {
+                    let func = function_table.index_of(&ImplDecl::method_name(&ty, &method.name))?;
+                    Some((method.index, func))
+                })
+                .collect();
+            vtables.push((type_table.get_mapping(&type_idx), methods));
+        }
+
+        RuntimeTraitTable {
+            param_counts,
+            vtables,
+            call_map: self.call_map,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct RuntimeTraitTable {
+    // indexed by the virtual index of the method
+    pub param_counts: Vec<u16>,
+    pub vtables: Vec<(RuntimeTypeIndex, Vec<(VirtualIndex, FuncIndex)>)>,
+    call_map: HashMap<NodeId, VirtualIndex>,
+}
+
+impl RuntimeTraitTable {
+    pub fn get_mapping(&self, id: NodeId) -> VirtualIndex {
+        self.call_map[&id]
+    }
+
+    pub fn contains_mapping(&self, id: NodeId) -> bool {
+        self.call_map.contains_key(&id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::semantic_analyzer::type_table::TypeTable;
+
+    fn shape_table() -> TraitTable {
+        let mut types = TypeTable::new();
+        let float = types.register(&Type::float());
+        let void = types.register(&Type::void());
+        let mut table = TraitTable::new();
+        assert!(table.declare("Shape", NodeId(0)));
+        table.define(
+            "Shape",
+            NodeId(0),
+            vec![
+                ("area".to_owned(), false, vec![], float.clone()),
+                ("scale".to_owned(), true, vec![float], void),
+            ],
+        );
+        table
+    }
+
+    #[test]
+    fn test_methods_are_numbered_in_order() {
+        let table = shape_table();
+        let shape = table.get("Shape").unwrap();
+        assert_eq!(shape.method("area").unwrap().index, VirtualIndex(0));
+        assert_eq!(shape.method("scale").unwrap().index, VirtualIndex(1));
+        assert!(shape.method("perimeter").is_none());
+    }
+
+    #[test]
+    fn test_redeclared_trait_keeps_first_definition() {
+        let mut table = shape_table();
+        assert!(!table.declare("Shape", NodeId(1)));
+        table.define("Shape", NodeId(1), vec![]);
+        assert_eq!(table.get("Shape").unwrap().methods.len(), 2);
+    }
+
+    #[test]
+    fn test_implements() {
+        let mut table = shape_table();
+        assert!(table.implement("Circle", "Shape"));
+        assert!(!table.implement("Circle", "Shape"));
+        assert!(table.implements("Circle", "Shape"));
+        assert!(!table.implements("Square", "Shape"));
+    }
+}
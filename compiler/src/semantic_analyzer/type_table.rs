@@ -2,7 +2,7 @@ use std::collections::HashMap;
 
 use crate::{
     aliases::{Result, TypeInfoRc},
-    parser::types::{RuntimeType, Type, TypeInner},
+    parser::types::{BaseType, RuntimeType, Type, TypeInner},
 };
 
 use super::symbol_info::{RuntimeTypeInfo, TypeInfo};
@@ -13,10 +13,174 @@ pub struct TypeIndex(pub usize);
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct RuntimeTypeIndex(pub u32);
 
+#[derive(Debug, Clone)]
+struct NamedType {
+    ty: Type,
+    newtype: bool,
+}
+
+// `type` and `newtype` declarations. an alias is replaced by what it stands for,
+// a newtype stays a type of its own that is represented like the one it wraps
+#[derive(Debug, Clone, Default)]
+pub struct TypeNames {
+    names: HashMap<String, NamedType>,
+}
+
+impl TypeNames {
+    pub(in crate::semantic_analyzer) fn declare(&mut self, name: &str, ty: &Type, newtype: bool) -> bool {
+        if self.names.contains_key(name) {
+            return false;
+        }
+
+        self.names.insert(name.to_owned(), NamedType { ty: ty.clone(), newtype });
+        true
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.names.contains_key(name)
+    }
+
+    pub fn is_newtype(&self, name: &str) -> bool {
+        self.names.get(name).is_some_and(|n| n.newtype)
+    }
+
+    // the aliases in `ttype` replaced by what they stand for
+    pub fn expand(&self, ttype: &Type) -> Type {
+        self.expand_with(ttype, &mut vec![])
+    }
+
+    // an alias leading back to itself is left as it is, it's reported when declared
+    fn expand_with(&self, ttype: &Type, seen: &mut Vec<String>) -> Type {
+        let mut expand = |t: &Type| Box::new(self.expand_with(t, seen));
+        let inner = match &ttype.inner {
+            TypeInner::Base(BaseType::Custom(c)) => {
+                let name = c.name();
+                let Some(named) = self.names.get(name) else {
+                    return ttype.clone();
+                };
+                if named.newtype || seen.iter().any(|s| s == name) {
+                    return ttype.clone();
+                }
+
+                seen.push(name.to_owned());
+                let ty = self.expand_with(&named.ty, seen);
+                seen.pop();
+                return Type {
+                    mutable: ttype.mutable || ty.mutable,
+                    inner: ty.inner,
+                };
+            }
+            TypeInner::Boxed(t) => TypeInner::Boxed(expand(t)),
+            TypeInner::Ref(t) => TypeInner::Ref(expand(t)),
+            TypeInner::Array(t, len) => TypeInner::Array(expand(t), *len),
+            TypeInner::Vector(t) => TypeInner::Vector(expand(t)),
+            TypeInner::Optional(t) => TypeInner::Optional(expand(t)),
+            TypeInner::Result(value, error) => TypeInner::Result(expand(value), expand(error)),
+            TypeInner::Function(params, ret) => TypeInner::Function(
+                params.iter().map(|p| *expand(p)).collect(),
+                expand(ret),
+            ),
+            TypeInner::Tuple(elements) => {
+                TypeInner::Tuple(elements.iter().map(|e| *expand(e)).collect())
+            }
+            _ => return ttype.clone(),
+        };
+
+        Type {
+            mutable: ttype.mutable,
+            inner,
+        }
+    }
+
+    // the type a newtype wraps, aliases expanded
+    pub fn underlying(&self, ttype: &Type) -> Option<Type> {
+        let TypeInner::Base(BaseType::Custom(c)) = &ttype.inner else {
+            return None;
+        };
+
+        let named = self.names.get(c.name()).filter(|n| n.newtype)?;
+        if self.is_recursive(c.name()) {
+            return None;
+        }
+        Some(self.expand(&named.ty))
+    }
+
+    // the type as it's represented at runtime: aliases expanded and
+    // newtypes replaced by what they wrap
+    pub fn lower(&self, ttype: &Type) -> Type {
+        let mut ty = self.expand(ttype);
+        while let Some(underlying) = self.underlying(&ty) {
+            ty = Type {
+                mutable: ty.mutable,
+                inner: underlying.inner,
+            };
+        }
+        ty
+    }
+
+    // whether the declaration of `name` can't be expanded, an alias mentioning itself
+    // or a newtype holding itself by value
+    pub fn is_recursive(&self, name: &str) -> bool {
+        let Some(named) = self.names.get(name) else {
+            return false;
+        };
+        self.mentions(&named.ty, name, named.newtype, &mut vec![])
+    }
+
+    // `by_value` skips what is behind a pointer and also looks through newtypes
+    fn mentions(&self, ttype: &Type, name: &str, by_value: bool, seen: &mut Vec<String>) -> bool {
+        match &ttype.inner {
+            TypeInner::Base(BaseType::Custom(c)) => {
+                if c.name() == name {
+                    return true;
+                }
+                if seen.iter().any(|s| s == c.name()) {
+                    return false;
+                }
+
+                seen.push(c.name().to_owned());
+                match self.names.get(c.name()) {
+                    Some(named) if by_value || !named.newtype => {
+                        self.mentions(&named.ty, name, by_value, seen)
+                    }
+                    _ => false,
+                }
+            }
+            TypeInner::Boxed(_)
+            | TypeInner::Ref(_)
+            | TypeInner::Vector(_)
+            | TypeInner::Function(_, _)
+                if by_value =>
+            {
+                false
+            }
+            TypeInner::Boxed(t)
+            | TypeInner::Ref(t)
+            | TypeInner::Array(t, _)
+            | TypeInner::Vector(t)
+            | TypeInner::Optional(t) => self.mentions(t, name, by_value, seen),
+            TypeInner::Result(value, error) => {
+                self.mentions(value, name, by_value, seen) || self.mentions(error, name, by_value, seen)
+            }
+            TypeInner::Function(params, ret) => {
+                params.iter().any(|p| self.mentions(p, name, by_value, seen))
+                    || self.mentions(ret, name, by_value, seen)
+            }
+            TypeInner::Tuple(elements) => {
+                elements.iter().any(|e| self.mentions(e, name, by_value, seen))
+            }
+            _ => false,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct TypeTable {
     pool: Vec<TypeInfoRc>,
     map: HashMap<Type, TypeIndex>,
+    names: TypeNames,
+    // types written with an alias, sharing the index of their expansion
+    aliased: HashMap<Type, TypeInfoRc>,
 }
 
 impl TypeTable {
@@ -28,11 +192,25 @@ impl TypeTable {
             ttype: Type::void(),
             size: 0,
             idx: TypeIndex(0),
+            alias: None,
         }));
 
         map.insert(Type::void(), TypeIndex(0));
 
-        Self { pool, map }
+        Self {
+            pool,
+            map,
+            names: TypeNames::default(),
+            aliased: HashMap::new(),
+        }
+    }
+
+    pub(in crate::semantic_analyzer) fn declare(&mut self, name: &str, ty: &Type, newtype: bool) -> bool {
+        self.names.declare(name, ty, newtype)
+    }
+
+    pub fn names(&self) -> &TypeNames {
+        &self.names
     }
 
     pub(in crate::semantic_analyzer) fn register(&mut self, ttype: &Type) -> TypeInfoRc {
@@ -40,18 +218,51 @@ impl TypeTable {
             return self.pool[info.0].clone();
         }
 
+        if let Some(info) = self.aliased.get(ttype) {
+            return info.clone();
+        }
+
+        // the info is the expanded type's, only printed the way it was written
+        let expanded = self.names.expand(ttype);
+        if expanded != *ttype {
+            let info = self.register(&expanded);
+            let rc = TypeInfoRc::new(TypeInfo {
+                alias: Some(ttype.clone()),
+                ..(*info).clone()
+            });
+            self.aliased.insert(ttype.clone(), rc.clone());
+            return rc;
+        }
+
+        // registered first so the newtype can be lowered to it
+        let underlying = self.names.underlying(ttype).map(|u| self.register(&u));
+
         match &ttype.inner {
-            TypeInner::Boxed(b) | TypeInner::Ref(b) => {
+            TypeInner::Boxed(b)
+            | TypeInner::Ref(b)
+            | TypeInner::Array(b, _)
+            | TypeInner::Vector(b)
+            | TypeInner::Optional(b) => {
                 self.register(b);
             }
+            TypeInner::Result(value, error) => {
+                self.register(value);
+                self.register(error);
+            }
+            TypeInner::Tuple(elements) => {
+                for element in elements {
+                    self.register(element);
+                }
+            }
             _ => (),
         }
 
         let i = self.pool.len();
         let rc = TypeInfoRc::new(TypeInfo {
             ttype: ttype.clone(),
-            size: ttype.calc_size(),
+            size: underlying.map_or_else(|| ttype.calc_size(), |u| u.size),
             idx: TypeIndex(i),
+            alias: None,
         });
 
         self.pool.push(rc.clone());
@@ -63,7 +274,7 @@ impl TypeTable {
     pub(in crate::semantic_analyzer) fn into_conversion(
         self,
     ) -> Result<RuntimeConversionTypeTable> {
-        let mut conversion_type_table = RuntimeConversionTypeTable::new(self.map);
+        let mut conversion_type_table = RuntimeConversionTypeTable::new(self.map, self.names);
         for ty in self.pool {
             if ty.inner == TypeInner::Unknown {
                 continue;
@@ -81,19 +292,28 @@ pub struct RuntimeConversionTypeTable {
     map: HashMap<RuntimeType, RuntimeTypeIndex>,
     old_map: HashMap<Type, TypeIndex>,
     runtime_mapping: HashMap<TypeIndex, RuntimeTypeIndex>,
+    names: TypeNames,
 }
 
 impl RuntimeConversionTypeTable {
-    fn new(old_map: HashMap<Type, TypeIndex>) -> Self {
+    fn new(old_map: HashMap<Type, TypeIndex>, names: TypeNames) -> Self {
         Self {
             pool: vec![],
             map: HashMap::new(),
             runtime_mapping: HashMap::new(),
             old_map,
+            names,
         }
     }
 
     fn register(&mut self, type_info: TypeInfoRc) -> Result<()> {
+        // a newtype is the type it wraps at runtime
+        if let Some(underlying) = self.names.underlying(&type_info.ttype) {
+            let idx = self.get_mapping(&self.get_type_index(&underlying));
+            self.runtime_mapping.insert(type_info.idx, idx);
+            return Ok(());
+        }
+
         let runtime_info = type_info.into_runtime(self)?;
 
         if let Some(idx) = self.map.get(&runtime_info.ttype) {
@@ -121,14 +341,26 @@ impl RuntimeConversionTypeTable {
         self.old_map[ty]
     }
 
+    pub(in crate::semantic_analyzer) fn names(&self) -> &TypeNames {
+        &self.names
+    }
+
+    pub(in crate::semantic_analyzer) fn find_type_index(&self, ty: &Type) -> Option<TypeIndex> {
+        self.old_map.get(ty).copied()
+    }
+
     pub(in crate::semantic_analyzer) fn into_runtime(self) -> RuntimeTypeTable {
-        RuntimeTypeTable { pool: self.pool }
+        RuntimeTypeTable {
+            pool: self.pool,
+            names: self.names,
+        }
     }
 }
 
 #[derive(Debug)]
 pub struct RuntimeTypeTable {
     pool: Vec<RuntimeTypeInfo>,
+    names: TypeNames,
 }
 
 impl RuntimeTypeTable {
@@ -139,6 +371,10 @@ impl RuntimeTypeTable {
     pub fn iter(&self) -> std::slice::Iter<'_, RuntimeTypeInfo> {
         self.pool.iter()
     }
+
+    pub fn names(&self) -> &TypeNames {
+        &self.names
+    }
 }
 
 #[cfg(test)]
@@ -182,4 +418,23 @@ mod tests {
         assert_eq!(first.idx, second.idx);
         assert_eq!(table.pool.len(), 2);
     }
+
+    #[test]
+    fn test_type_table_register_alias() {
+        let mut table = TypeTable::new();
+        let counter = Type::boxed(Type::int());
+        table.declare("Counter", &counter, false);
+        table.declare("Meters", &Type::float(), true);
+
+        let expanded = table.register(&counter);
+        let aliased = table.register(&Type::custom("Counter"));
+        assert_eq!(aliased.idx, expanded.idx);
+        assert_eq!(aliased.ttype, counter);
+        assert_eq!(aliased.to_string(), "Counter");
+
+        let meters = table.register(&Type::custom("Meters"));
+        assert_eq!(meters.ttype, Type::custom("Meters"));
+        assert_eq!(meters.size, Type::float().calc_size());
+        assert_eq!(table.names().lower(&Type::custom("Meters")), Type::float());
+    }
 }
@@ -1,6 +1,7 @@
 use std::{
     io::BufRead,
     ops::{Index, Range, RangeFrom, RangeTo},
+    path::{Path, PathBuf},
 };
 
 use crate::aliases::Result;
@@ -39,6 +40,39 @@ impl SourceFile {
     }
 }
 
+// every file of a program, indexed by the `file` of the positions pointing into it
+#[derive(Debug, Default)]
+pub struct SourceMap {
+    files: Vec<(PathBuf, SourceFile)>,
+}
+
+impl SourceMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, path: PathBuf, file: SourceFile) -> usize {
+        self.files.push((path, file));
+        self.files.len() - 1
+    }
+
+    pub fn get(&self, file: usize) -> Option<&SourceFile> {
+        self.files.get(file).map(|(_, f)| f)
+    }
+
+    pub fn path(&self, file: usize) -> Option<&Path> {
+        self.files.get(file).map(|(p, _)| p.as_path())
+    }
+
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+}
+
 impl Index<Range<usize>> for SourceFile {
     type Output = str;
     fn index(&self, index: Range<usize>) -> &Self::Output {
@@ -111,6 +145,17 @@ mod tests {
         assert_eq!(source.get_line(2), Some(""));
     }
 
+    #[test]
+    fn test_source_map() {
+        let mut map = SourceMap::new();
+        let main = map.add("main.hrpy".into(), SourceFile::new(Cursor::new("a")).unwrap());
+        let geo = map.add("geo.hrpy".into(), SourceFile::new(Cursor::new("b")).unwrap());
+        assert_eq!((main, geo), (0, 1));
+        assert_eq!(map.get(geo).unwrap().text, "b");
+        assert_eq!(map.path(main), Some(Path::new("main.hrpy")));
+        assert!(map.get(2).is_none());
+    }
+
     #[test]
     fn test_source_file_indexing_range() {
         let reader = Cursor::new("hello world");
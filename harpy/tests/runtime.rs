@@ -0,0 +1,206 @@
+// whole programs compiled and run on the vm, checked by what main returns
+
+fn compile(name: &str, source: &str) -> Vec<u8> {
+    let path = std::env::temp_dir().join(format!("harpy_{name}_{}.hrpy", std::process::id()));
+    std::fs::write(&path, source).unwrap();
+    let bytecode = harpy_compiler::compile_file(path.to_str().unwrap());
+    std::fs::remove_file(&path).unwrap();
+    bytecode.unwrap()
+}
+
+fn run(name: &str, source: &str) -> String {
+    let result = harpy_vm::eval_bytecode(&compile(name, source)).unwrap();
+    result.unwrap_or_default()
+}
+
+#[test]
+fn test_return_from_if_expression_in_void_function() {
+    // the `1` pushed before the early return must not pile up across calls
+    let source = r#"
+        fn step(c: bool, i: int) {
+            let x = 1 + if c { return; } else { i };
+        }
+
+        fn main() -> int {
+            for i in 0 => 1000 {
+                step(true, i);
+            }
+            return 7;
+        }
+    "#;
+    assert_eq!(run("void_return", source), "7");
+}
+
+#[test]
+fn test_closure_counts_through_boxed_capture() {
+    let source = r#"
+        struct P { x: int }
+
+        fn main() -> int {
+            let counter: boxed mut int = box 0;
+            let bump = fn(by: int) { *counter += by; };
+            for i in 0 => 5 {
+                bump(i);
+            }
+            let p: boxed mut P = box P { x: 1 };
+            let set = fn() { p.x = 40; };
+            set();
+            return *counter + p.x;
+        }
+    "#;
+    assert_eq!(run("boxed_capture", source), "50");
+}
+
+#[test]
+fn test_sized_int_arrays_and_boxes() {
+    // the literals take the element and pointee types they are expected to have
+    let source = r#"
+        global g: [u8; 4] = [1, 2, 3, 4];
+
+        fn main() -> int {
+            let a: mut [u8; 16] = [0; 16];
+            a[3] = 250;
+            a[3] = a[3] +% 10;
+            let b: [u8; 4] = [1, 2, 3, 255];
+            let c: boxed mut u16 = box 65535;
+            *c = *c +| 1;
+            let o: [int?; 2] = [none, some(1)];
+            let one_or_zero: mut int = 0;
+            if let some(v) = o[1] {
+                one_or_zero = v;
+            }
+            return (a[3] as int) + (b[3] as int) + (*c as int) + (g[0] as int) + one_or_zero;
+        }
+    "#;
+    assert_eq!(run("sized_elements", source), "65796");
+}
+
+#[test]
+fn test_overloads_dispatch_by_argument_types() {
+    let source = r#"
+        fn f(x: u8) -> int { return 1; }
+        fn f(x: str) -> int { return 2; }
+        fn g(x: int?) -> int { return 10; }
+        fn g(x: str) -> int { return 20; }
+        fn h(x: u8) -> int { return 100; }
+        fn h(x: i64) -> int { return 200; }
+        fn h(x: float) -> int { return 300; }
+
+        fn main() -> int {
+            return f(7) + f("a") + g(none) + g(some(3)) + h(300) + h(2.5) + h(-1);
+        }
+    "#;
+    assert_eq!(run("overloads", source), "723");
+}
+
+#[test]
+fn test_substring_of_non_ascii_string() {
+    // `len` and `substring` count bytes: the first 3 hold `h` and `é`
+    let source = r#"
+        fn main() -> int {
+            let s = "héllo wörld";
+            let count: mut int = 0;
+            for c in s.substring(0, 3) {
+                count += 1;
+            }
+            return count * 100 + s.substring(3, 13).len();
+        }
+    "#;
+    assert_eq!(run("substring", source), "210");
+
+    let len = r#"
+        fn main() -> int {
+            return "héllo".len();
+        }
+    "#;
+    assert_eq!(run("byte_len", len), "6");
+
+    // `é` takes the bytes 1 and 2
+    let split = r#"
+        fn main() -> int {
+            let count: mut int = 0;
+            for c in "héllo".substring(0, 2) {
+                count += 1;
+            }
+            return count;
+        }
+    "#;
+    let error = harpy_vm::eval_bytecode(&compile("split_char", split)).unwrap_err();
+    assert_eq!(format!("{error:?}"), "NotCharBoundary(2)");
+}
+
+#[test]
+fn test_switch_jump_table_dispatch() {
+    // values on both sides of the table and in its holes take the default arm
+    let source = r#"
+        fn dense(n: int) -> int {
+            return switch n {
+                0 -> 0;
+                1, 2 -> 1;
+                4..=5 -> 2;
+                . -> 3;
+            };
+        }
+
+        fn main() -> int {
+            let digits: mut int = 0;
+            for n in -2..8 {
+                digits = digits * 10 + dense(n);
+            }
+            return digits;
+        }
+    "#;
+    assert_eq!(run("jump_table", source), "3301132233");
+}
+
+#[test]
+fn test_sized_int_wrapping_and_saturating() {
+    let source = r#"
+        fn main() -> int {
+            let small: u8 = 250;
+            let low: i8 = -128;
+            let high: i8 = 127;
+            let wide: i16 = 300;
+            let short: u16 = 300;
+
+            let total: mut int = 0;
+            total += (small +% 10) as int;
+            total += (small +| 10) as int * 10;
+            total += ((3 as u8) -| 5) as int;
+            total += (high +% 1) as int + (low -| 1) as int;
+            total += (wide *% wide) as int;
+            total += (short *| short) as int;
+            return total;
+        }
+    "#;
+    // 4 + 2550 + 0 - 256 + 24464 + 65535
+    assert_eq!(run("sized_overflow", source), "92297");
+
+    let checked = r#"
+        fn main() -> int {
+            let small: u8 = 250;
+            return (small + 10) as int;
+        }
+    "#;
+    let error = harpy_vm::eval_bytecode(&compile("sized_checked", checked)).unwrap_err();
+    assert_eq!(format!("{error:?}"), "IntegerOverflow");
+}
+
+#[test]
+fn test_char_iteration_over_multi_byte_chars() {
+    // one char each of 1, 2, 3 and 4 bytes
+    let source = r#"
+        fn main() -> int {
+            let s = "aé€😀";
+            let count: mut int = 0;
+            let sum: mut int = 0;
+            for c in s {
+                count += 1;
+                sum += c as int;
+            }
+            return s.len() * 10000000 + count * 1000000 + sum;
+        }
+    "#;
+    // 97 + 233 + 8364 + 128512
+    assert_eq!(run("char_iteration", source), "104137206");
+}
@@ -10,10 +10,16 @@ pub enum ParseError {
 pub enum RuntimeError {
     ParserError(ParseError),
     OutOfBounds,
+    IndexOutOfBounds(i64, usize),
+    // a string split inside a multi-byte char
+    NotCharBoundary(i64),
+    EmptyVector,
     StackOverflow,
     BadStack,
     InvalidOpcode,
     InvalidOperation,
+    IntegerOverflow,
+    DivisionByZero,
     IO(std::io::Error),
     Halt,
 }
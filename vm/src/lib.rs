@@ -12,6 +12,14 @@ mod parser;
 mod runtime;
 
 pub fn run_bytecode(bytecode: &[u8]) -> Result<()> {
+    if let Some(result) = eval_bytecode(bytecode)? {
+        println!("{result}");
+    }
+    Ok(())
+}
+
+// runs the program without printing what main returned
+pub fn eval_bytecode(bytecode: &[u8]) -> Result<Option<String>> {
     let reader = ByteReader::new(bytecode, HEADER_SIZE);
     let header = Header::parse(reader)?;
 
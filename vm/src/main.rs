@@ -27,5 +27,8 @@ fn main() -> Result<()> {
 
     let mut runtime = header.into_runtime(&bytes)?;
 
-    runtime.run()
+    if let Some(result) = runtime.run()? {
+        println!("{result}");
+    }
+    Ok(())
 }
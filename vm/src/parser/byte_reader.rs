@@ -38,6 +38,15 @@ impl<'reader> ByteReader<'reader> {
         T::read_safe(self)
     }
 
+    pub fn read_bytes(&mut self, n: usize) -> Result<&'reader [u8]> {
+        let start = self.offset;
+        self.offset += n;
+        if self.offset > self.size {
+            return Err(RuntimeError::OutOfBounds);
+        }
+        Ok(&self.bytes[start..start + n])
+    }
+
     pub fn skip(&mut self, n: usize) {
         self.offset += n
     }
@@ -52,6 +52,7 @@ impl<'const_data> ConstPool<'const_data> {
                         PrimitiveType::Bool => VmValue::Bool(reader.read()?),
                         PrimitiveType::Float => VmValue::Float(reader.read()?),
                         PrimitiveType::Str => unreachable!(),
+                        _ => t.construct(&mut reader)?,
                     };
                     consts.push(v);
                 }
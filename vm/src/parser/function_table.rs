@@ -20,6 +20,8 @@ pub struct CodeAddress(pub u64);
 pub struct FunctionInfo {
     pub code_offset: CodeAddress,
     pub param_count: usize,
+    // nothing is handed back to the caller when it's 0
+    pub return_size: usize,
     pub local_types: Vec<TypeId>,
 
     pub stack_size: usize,
@@ -37,6 +39,7 @@ impl FunctionTable {
 
         while let Ok(offset) = reader.read::<u64>() {
             let param_count = reader.read::<u16>()? as usize;
+            let return_type: TypeId = reader.read_safe()?;
             let local_count: usize = reader.read::<u16>()? as usize;
 
             let mut local_types = Vec::with_capacity(local_count);
@@ -54,6 +57,7 @@ impl FunctionTable {
             func_infos.push(FunctionInfo {
                 code_offset: CodeAddress(offset),
                 param_count,
+                return_size: type_table[return_type].size().0,
                 local_types,
                 stack_size,
                 local_offsets: offsets,
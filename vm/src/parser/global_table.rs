@@ -5,7 +5,7 @@ use crate::runtime::values::VmValue;
 
 use super::{
     byte_reader::ByteReader,
-    type_table::{TypeId, TypeTable},
+    type_table::{TypeId, TypeSize, TypeTable},
 };
 
 #[derive(Debug, Clone, Copy)]
@@ -58,6 +58,21 @@ impl GlobalTable {
         type_info.construct(&mut reader)
     }
 
+    pub fn get_global_address(&self, global_id: GlobalIndex) -> VmValue {
+        let global_info = &self.global_infos[global_id.0];
+        VmValue::GlobalRef(global_info.offset, global_info.type_id)
+    }
+
+    pub fn read_at(&self, offset: usize, type_id: TypeId, type_table: &TypeTable) -> Result<VmValue> {
+        let type_info = &type_table[type_id];
+        let mut reader = ByteReader::new(&self.global_memory[offset..], type_info.size().0);
+        type_info.construct(&mut reader)
+    }
+
+    pub fn write_at(&mut self, offset: usize, value: VmValue, size: TypeSize) {
+        value.write_bytes(&mut self.global_memory[offset..offset + size.0]);
+    }
+
     pub fn write_global(&mut self, global_id: GlobalIndex, value: VmValue) {
         let global_info = &self.global_infos[global_id.0];
         let memory_slice = &mut self.global_memory[global_info.offset..global_info.offset + global_info.size];
@@ -6,6 +6,7 @@ use super::{
     function_table::{FunctionIndex, FunctionTable},
     global_table::GlobalTable,
     type_table::TypeTable,
+    vtable::VirtualTable,
 };
 
 #[derive(Debug)]
@@ -21,6 +22,7 @@ pub struct Header {
     global_table_offset: u32,
     const_pool_offset: u32,
     function_table_offset: u32,
+    vtable_offset: u32,
     bytecode_offset: u32,
     bytecode_size: u32,
 }
@@ -48,6 +50,7 @@ impl Header {
             global_table_offset: bytes.read()?,
             const_pool_offset: bytes.read()?,
             function_table_offset: bytes.read()?,
+            vtable_offset: bytes.read()?,
             bytecode_offset: bytes.read()?,
             bytecode_size: bytes.read()?,
         })
@@ -73,15 +76,19 @@ impl Header {
         )?;
         let ft = FunctionTable::parse(
             ByteReader::new(
-                &bytecode[self.function_table_offset as usize..self.bytecode_offset as usize],
-                (self.bytecode_offset - self.function_table_offset) as usize,
+                &bytecode[self.function_table_offset as usize..self.vtable_offset as usize],
+                (self.vtable_offset - self.function_table_offset) as usize,
             ),
             &tt,
         )?;
+        let vt = VirtualTable::parse(ByteReader::new(
+            &bytecode[self.vtable_offset as usize..self.bytecode_offset as usize],
+            (self.bytecode_offset - self.vtable_offset) as usize,
+        ))?;
 
         let bc = &bytecode
             [self.bytecode_offset as usize..(self.bytecode_offset + self.bytecode_size) as usize];
 
-        Runtime::new(self, tt, gt, cp, ft, bc)
+        Runtime::new(self, tt, gt, cp, ft, vt, bc)
     }
 }
@@ -4,3 +4,4 @@ pub mod function_table;
 pub mod global_table;
 pub mod header;
 pub mod type_table;
+pub mod vtable;
@@ -2,11 +2,14 @@ use std::ops::Index;
 
 use crate::{
     aliases::Result,
-    err::ParseError,
-    runtime::values::{HeapAddress, VmValue},
+    err::{ParseError, RuntimeError},
+    runtime::values::{HeapAddress, IntKind, VmValue},
 };
 
-use super::byte_reader::{ByteReader, ReadSafe};
+use super::{
+    byte_reader::{ByteReader, ReadSafe},
+    function_table::FunctionIndex,
+};
 
 #[derive(Debug, Clone, Copy)]
 pub struct TypeId(pub usize);
@@ -24,6 +27,14 @@ pub enum PrimitiveType {
     Float = 0x2,
     Str = 0x3,
     Bool = 0x4,
+    Char = 0x5,
+    I8 = 0x6,
+    I16 = 0x7,
+    I32 = 0x8,
+    U8 = 0x9,
+    U16 = 0xA,
+    U32 = 0xB,
+    U64 = 0xC,
 }
 
 impl ReadSafe for PrimitiveType {
@@ -33,21 +44,53 @@ impl ReadSafe for PrimitiveType {
             2 => Self::Float,
             3 => Self::Str,
             4 => Self::Bool,
+            5 => Self::Char,
+            6 => Self::I8,
+            7 => Self::I16,
+            8 => Self::I32,
+            9 => Self::U8,
+            0xA => Self::U16,
+            0xB => Self::U32,
+            0xC => Self::U64,
             _ => return Err(ParseError::UnknownTypeId.into()),
         })
     }
 }
 
 impl PrimitiveType {
+    pub fn int_kind(&self) -> Option<IntKind> {
+        Some(match self {
+            Self::Int => IntKind::I64,
+            Self::I8 => IntKind::I8,
+            Self::I16 => IntKind::I16,
+            Self::I32 => IntKind::I32,
+            Self::U8 => IntKind::U8,
+            Self::U16 => IntKind::U16,
+            Self::U32 => IntKind::U32,
+            Self::U64 => IntKind::U64,
+            _ => return None,
+        })
+    }
+
     pub fn construct(&self, reader: &mut ByteReader) -> Result<VmValue> {
         Ok(match self {
             Self::Int => VmValue::Int(reader.read()?),
             Self::Float => VmValue::Float(reader.read()?),
             Self::Bool => VmValue::Bool(reader.read()?),
+            Self::Char => VmValue::Char(
+                char::from_u32(reader.read()?).ok_or(RuntimeError::InvalidOperation)?,
+            ),
             Self::Str => VmValue::StringHandle {
                 len: reader.read()?,
                 ptr: reader.read_safe()?,
             },
+            Self::I8 => VmValue::SizedInt(reader.read::<i8>()? as i64, IntKind::I8),
+            Self::I16 => VmValue::SizedInt(reader.read::<i16>()? as i64, IntKind::I16),
+            Self::I32 => VmValue::SizedInt(reader.read::<i32>()? as i64, IntKind::I32),
+            Self::U8 => VmValue::SizedInt(reader.read::<u8>()? as i64, IntKind::U8),
+            Self::U16 => VmValue::SizedInt(reader.read::<u16>()? as i64, IntKind::U16),
+            Self::U32 => VmValue::SizedInt(reader.read::<u32>()? as i64, IntKind::U32),
+            Self::U64 => VmValue::SizedInt(reader.read::<u64>()? as i64, IntKind::U64),
         })
     }
 }
@@ -59,6 +102,214 @@ pub struct TypeSize(pub usize);
 #[allow(unused)]
 pub struct Pointee(pub usize);
 
+#[derive(Debug, Clone, Copy)]
+pub struct Field {
+    pub offset: usize,
+    pub type_id: TypeId,
+}
+
+impl ReadSafe for Field {
+    fn read_safe(reader: &mut ByteReader) -> Result<Self> {
+        Ok(Self {
+            offset: reader.read::<u32>()? as usize,
+            type_id: reader.read_safe()?,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct CustomLayout {
+    pub id: TypeId,
+    pub size: TypeSize,
+    pub fields: Vec<Field>,
+}
+
+impl CustomLayout {
+    fn parse(id: TypeId, reader: &mut ByteReader) -> Result<Self> {
+        let size = TypeSize(reader.read::<u32>()? as usize);
+        let field_count = reader.read::<u16>()?;
+        let mut fields = Vec::with_capacity(field_count as usize);
+        for _ in 0..field_count {
+            fields.push(reader.read_safe()?);
+        }
+
+        Ok(Self { id, size, fields })
+    }
+
+    // a tuple is laid out like a struct with its elements packed in order
+    fn parse_tuple(id: TypeId, tt: &[Type], reader: &mut ByteReader) -> Result<Self> {
+        let element_count = reader.read::<u16>()?;
+        let mut offset = 0;
+        let mut fields = Vec::with_capacity(element_count as usize);
+        for _ in 0..element_count {
+            let type_id: TypeId = reader.read_safe()?;
+            fields.push(Field { offset, type_id });
+            offset += tt.get(type_id.0).ok_or(ParseError::UnknownTypeId)?.size().0;
+        }
+
+        Ok(Self {
+            id,
+            size: TypeSize(offset),
+            fields,
+        })
+    }
+
+    pub fn field(&self, index: usize) -> Result<Field> {
+        self.fields
+            .get(index)
+            .copied()
+            .ok_or(RuntimeError::InvalidOperation)
+    }
+}
+
+#[derive(Debug)]
+pub struct EnumLayout {
+    pub layout: CustomLayout,
+    // the range of `layout.fields` holding each variant's payload
+    pub variants: Vec<std::ops::Range<usize>>,
+}
+
+impl EnumLayout {
+    pub const TAG_SIZE: usize = 8;
+
+    fn parse(id: TypeId, reader: &mut ByteReader) -> Result<Self> {
+        let size = TypeSize(reader.read::<u32>()? as usize);
+        let variant_count = reader.read::<u16>()?;
+        let mut fields = vec![];
+        let mut variants = Vec::with_capacity(variant_count as usize);
+        for _ in 0..variant_count {
+            let field_count = reader.read::<u16>()? as usize;
+            let start = fields.len();
+            for _ in 0..field_count {
+                fields.push(reader.read_safe()?);
+            }
+            variants.push(start..start + field_count);
+        }
+
+        Ok(Self {
+            layout: CustomLayout { id, size, fields },
+            variants,
+        })
+    }
+
+    // `T?` is laid out as an enum whose first variant is `none` and whose
+    // second, `some`, holds a single `T`
+    fn parse_optional(id: TypeId, tt: &[Type], reader: &mut ByteReader) -> Result<Self> {
+        let value: TypeId = reader.read_safe()?;
+        let value_size = tt.get(value.0).ok_or(ParseError::UnknownTypeId)?.size().0;
+
+        Ok(Self {
+            layout: CustomLayout {
+                id,
+                size: TypeSize(Self::TAG_SIZE + value_size),
+                fields: vec![Field {
+                    offset: Self::TAG_SIZE,
+                    type_id: value,
+                }],
+            },
+            variants: vec![0..0, 0..1],
+        })
+    }
+
+    // `result<T, E>` is an enum of `ok(T)` and `err(E)`, both payloads
+    // starting right after the tag
+    fn parse_result(id: TypeId, tt: &[Type], reader: &mut ByteReader) -> Result<Self> {
+        let value: TypeId = reader.read_safe()?;
+        let error: TypeId = reader.read_safe()?;
+        let size_of = |ty: TypeId| -> Result<usize> {
+            Ok(tt.get(ty.0).ok_or(ParseError::UnknownTypeId)?.size().0)
+        };
+        let payload_size = size_of(value)?.max(size_of(error)?);
+
+        Ok(Self {
+            layout: CustomLayout {
+                id,
+                size: TypeSize(Self::TAG_SIZE + payload_size),
+                fields: vec![
+                    Field {
+                        offset: Self::TAG_SIZE,
+                        type_id: value,
+                    },
+                    Field {
+                        offset: Self::TAG_SIZE,
+                        type_id: error,
+                    },
+                ],
+            },
+            variants: vec![0..1, 1..2],
+        })
+    }
+
+    pub fn tag(bytes: &[u8]) -> usize {
+        usize::from_be_bytes(bytes[0..Self::TAG_SIZE].try_into().unwrap())
+    }
+
+    // the fields of the variant `bytes` currently holds
+    pub fn active_fields(&self, bytes: &[u8]) -> &[Field] {
+        match self.variants.get(Self::tag(bytes)) {
+            Some(range) => &self.layout.fields[range.clone()],
+            None => &[],
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ArrayLayout {
+    pub id: TypeId,
+    pub element: TypeId,
+    pub element_size: usize,
+    pub len: usize,
+}
+
+impl ArrayLayout {
+    fn parse(id: TypeId, tt: &[Type], reader: &mut ByteReader) -> Result<Self> {
+        let element: TypeId = reader.read_safe()?;
+        let len = reader.read::<u32>()? as usize;
+        let element_size = tt
+            .get(element.0)
+            .ok_or(ParseError::UnknownTypeId)?
+            .size()
+            .0;
+
+        Ok(Self {
+            id,
+            element,
+            element_size,
+            len,
+        })
+    }
+
+    pub fn element_offset(&self, index: i64) -> Result<usize> {
+        if index < 0 || index as usize >= self.len {
+            return Err(RuntimeError::IndexOutOfBounds(index, self.len));
+        }
+
+        Ok(index as usize * self.element_size)
+    }
+}
+
+#[derive(Debug)]
+pub struct VectorLayout {
+    pub element: TypeId,
+    pub element_size: usize,
+}
+
+impl VectorLayout {
+    fn parse(tt: &[Type], reader: &mut ByteReader) -> Result<Self> {
+        let element: TypeId = reader.read_safe()?;
+        let element_size = tt
+            .get(element.0)
+            .ok_or(ParseError::UnknownTypeId)?
+            .size()
+            .0;
+
+        Ok(Self {
+            element,
+            element_size,
+        })
+    }
+}
+
 #[derive(Debug)]
 #[allow(unused)]
 pub enum Type {
@@ -66,7 +317,14 @@ pub enum Type {
     Primitive(PrimitiveType, TypeSize),
     Pointer(Pointee),
     Ref(Pointee),
-    Custom(TypeSize),
+    Custom(CustomLayout),
+    Tuple(CustomLayout),
+    Enum(EnumLayout),
+    Optional(EnumLayout),
+    Result(EnumLayout),
+    Array(ArrayLayout),
+    Vector(VectorLayout),
+    Function,
 }
 
 impl Type {
@@ -76,24 +334,53 @@ impl Type {
             Self::Primitive(_, size) => *size,
             Self::Pointer(_) => TypeSize(16),
             Self::Ref(_) => TypeSize(16),
-            Self::Custom(size) => *size,
+            Self::Custom(layout) | Self::Tuple(layout) => layout.size,
+            Self::Enum(e) | Self::Optional(e) | Self::Result(e) => e.layout.size,
+            Self::Array(layout) => TypeSize(layout.element_size * layout.len),
+            Self::Vector(_) => TypeSize(16),
+            Self::Function => TypeSize(24),
         }
     }
 }
 
 impl Type {
+    // structs, tuples and enums all address their values by field index
+    pub fn fields(&self) -> Option<&CustomLayout> {
+        match self {
+            Self::Custom(layout) | Self::Tuple(layout) => Some(layout),
+            Self::Enum(e) | Self::Optional(e) | Self::Result(e) => Some(&e.layout),
+            _ => None,
+        }
+    }
+
     pub fn construct(&self, reader: &mut ByteReader) -> Result<VmValue> {
         let v = match self {
             Type::Void => VmValue::Int(0),
-            Type::Ref(_) => VmValue::Ref(reader.read_safe()?, TypeId(reader.read()?)),
+            Type::Ref(_) => VmValue::address(reader.read()?, reader.read()?),
             Type::Primitive(p, _) => p.construct(reader)?,
             Type::Pointer(_) => {
                 VmValue::Pointer(HeapAddress(reader.read()?), TypeId(reader.read()?))
             }
-            Type::Custom(s) => {
-                reader.skip(s.0);
-                VmValue::Int(0)
+            Type::Vector(_) => {
+                VmValue::Vector(HeapAddress(reader.read()?), TypeId(reader.read()?))
             }
+            Type::Function => VmValue::Closure(
+                FunctionIndex(reader.read()?),
+                HeapAddress(reader.read()?),
+                TypeId(reader.read()?),
+            ),
+            Type::Custom(layout) | Type::Tuple(layout) => VmValue::Aggregate(
+                layout.id,
+                reader.read_bytes(layout.size.0)?.into(),
+            ),
+            Type::Enum(e) | Type::Optional(e) | Type::Result(e) => VmValue::Aggregate(
+                e.layout.id,
+                reader.read_bytes(e.layout.size.0)?.into(),
+            ),
+            Type::Array(layout) => VmValue::Aggregate(
+                layout.id,
+                reader.read_bytes(self.size().0)?.into(),
+            ),
         };
 
         Ok(v)
@@ -115,7 +402,14 @@ impl TypeTable {
                 1 => Type::Primitive(reader.read_safe()?, TypeSize(reader.read::<u8>()? as usize)),
                 2 => Type::Pointer(Pointee(reader.read::<u32>()? as usize)),
                 3 => Type::Ref(Pointee(reader.read::<u32>()? as usize)),
-                4 => Type::Custom(TypeSize(reader.read::<u8>()? as usize)),
+                4 => Type::Custom(CustomLayout::parse(TypeId(tt.len()), &mut reader)?),
+                5 => Type::Array(ArrayLayout::parse(TypeId(tt.len()), &tt, &mut reader)?),
+                6 => Type::Vector(VectorLayout::parse(&tt, &mut reader)?),
+                7 => Type::Enum(EnumLayout::parse(TypeId(tt.len()), &mut reader)?),
+                8 => Type::Function,
+                9 => Type::Optional(EnumLayout::parse_optional(TypeId(tt.len()), &tt, &mut reader)?),
+                10 => Type::Result(EnumLayout::parse_result(TypeId(tt.len()), &tt, &mut reader)?),
+                11 => Type::Tuple(CustomLayout::parse_tuple(TypeId(tt.len()), &tt, &mut reader)?),
                 _ => return Err(crate::err::ParseError::UnknownTypeId.into()),
             });
         }
@@ -0,0 +1,58 @@
+use std::collections::HashMap;
+
+use crate::{aliases::Result, err::RuntimeError};
+
+use super::{byte_reader::ByteReader, function_table::FunctionIndex, type_table::TypeId};
+
+#[derive(Debug, Clone, Copy)]
+pub struct VirtualIndex(pub usize);
+
+// the param count of every trait method, receiver included, and the function
+// each implementing type runs for it
+#[derive(Debug)]
+pub struct VirtualTable {
+    param_counts: Vec<usize>,
+    // (type, method)
+    methods: HashMap<(usize, usize), FunctionIndex>,
+}
+
+impl VirtualTable {
+    pub fn parse(mut reader: ByteReader) -> Result<Self> {
+        let method_count = reader.read::<u32>()? as usize;
+        let mut param_counts = Vec::with_capacity(method_count);
+        for _ in 0..method_count {
+            param_counts.push(reader.read::<u16>()? as usize);
+        }
+
+        let mut methods = HashMap::new();
+        let vtable_count = reader.read::<u32>()?;
+        for _ in 0..vtable_count {
+            let type_id = reader.read::<u32>()? as usize;
+            let entry_count = reader.read::<u16>()?;
+            for _ in 0..entry_count {
+                let method = reader.read::<u32>()? as usize;
+                let func = FunctionIndex(reader.read::<u32>()? as usize);
+                methods.insert((type_id, method), func);
+            }
+        }
+
+        Ok(Self {
+            param_counts,
+            methods,
+        })
+    }
+
+    pub fn param_count(&self, method: VirtualIndex) -> Result<usize> {
+        self.param_counts
+            .get(method.0)
+            .copied()
+            .ok_or(RuntimeError::InvalidOperation)
+    }
+
+    pub fn get(&self, type_id: TypeId, method: VirtualIndex) -> Result<FunctionIndex> {
+        self.methods
+            .get(&(type_id.0, method.0))
+            .copied()
+            .ok_or(RuntimeError::InvalidOperation)
+    }
+}
@@ -3,24 +3,29 @@ use std::collections::HashMap;
 use crate::{
     aliases::Result,
     parser::{
-        byte_reader::ByteReader,
         function_table::{FunctionIndex, FunctionTable},
         global_table::GlobalTable,
-        type_table::{Type, TypeTable},
+        type_table::{PrimitiveType, Type, TypeSize, TypeTable, VectorLayout},
     },
 };
 
 use super::{
-    heap::Heap,
+    heap::{Heap, VectorHeader},
     operand_stack::OperandStack,
     stack::Stack,
-    values::{HeapAddress, StackAddress, VmValue},
+    values::{HeapAddress, VmValue},
 };
 
-use crate::parser::header::Header;
-
 use crate::parser::type_table::TypeId;
 
+#[derive(Debug, Default)]
+struct CopyState {
+    address_map: HashMap<HeapAddress, HeapAddress>,
+    // runs of objects already moved to the new heap whose contents still have
+    // to be traced, as (address, type, count)
+    unscanned: Vec<(HeapAddress, TypeId, usize)>,
+}
+
 #[derive(Debug)]
 pub struct GarbageCollector {
     bytes_allocated_at_last_gc: usize,
@@ -47,26 +52,26 @@ impl GarbageCollector {
         global_table: &mut GlobalTable,
         function_table: &FunctionTable,
         type_table: &TypeTable,
-        header: &Header,
     ) -> Result<()> {
-        let mut address_map = HashMap::new();
+        let mut state = CopyState::default();
         let old_heap_size = heap.bytes_allocated();
 
         heap.start_copying_gc();
 
-        self.copy_and_update_operand_stack(&mut address_map, operand_stack, heap, type_table);
+        self.copy_and_update_operand_stack(&mut state, operand_stack, heap, type_table);
 
-        self.copy_and_update_global_table(&mut address_map, global_table, heap, type_table)?;
+        self.copy_and_update_global_table(&mut state, global_table, heap, type_table)?;
 
         self.copy_and_update_stack_frames(
-            &mut address_map,
+            &mut state,
             stack,
             heap,
             function_table,
             type_table,
-            header,
         )?;
 
+        self.scan_copied_objects(&mut state, heap, type_table);
+
         heap.finish_copying_gc();
 
         let new_heap_size = heap.bytes_allocated();
@@ -85,133 +90,258 @@ impl GarbageCollector {
 
     fn copy_and_update_operand_stack(
         &self,
-        address_map: &mut HashMap<HeapAddress, HeapAddress>,
+        state: &mut CopyState,
         operand_stack: &mut OperandStack,
         heap: &mut Heap,
         type_table: &TypeTable,
     ) {
         for value in operand_stack.iter_mut() {
-            if let VmValue::Pointer(old_addr, type_id) = value {
-                let new_addr =
-                    self.copy_object_if_needed(address_map, *old_addr, *type_id, heap, type_table);
-                *old_addr = new_addr; // Update the pointer in-place
+            match value {
+                VmValue::Pointer(old_addr, type_id) => {
+                    let new_addr =
+                        self.copy_object_if_needed(state, *old_addr, *type_id, heap, type_table);
+                    *old_addr = new_addr; // Update the pointer in-place
+                }
+                VmValue::Aggregate(type_id, bytes) => {
+                    self.trace_memory(state, bytes, *type_id, heap, type_table);
+                }
+                VmValue::Vector(old_addr, type_id) => {
+                    if let Type::Vector(layout) = &type_table[*type_id] {
+                        *old_addr = self.copy_vector_if_needed(state, *old_addr, layout, heap);
+                    }
+                }
+                VmValue::StringHandle { len, ptr } if ptr.0 != 0 => {
+                    *ptr = self.copy_string_if_needed(state, *ptr, *len, heap);
+                }
+                VmValue::Closure(_, env, type_id) if env.0 != 0 => {
+                    *env = self.copy_object_if_needed(state, *env, *type_id, heap, type_table);
+                }
+                _ => (),
             }
         }
     }
 
     fn copy_and_update_global_table(
         &self,
-        address_map: &mut HashMap<HeapAddress, HeapAddress>,
+        state: &mut CopyState,
         global_table: &mut GlobalTable,
         heap: &mut Heap,
         type_table: &TypeTable,
     ) -> Result<()> {
         for i in 0..global_table.global_infos.len() {
             let global_info = &global_table.global_infos[i];
-            
-            if matches!(&type_table[global_info.type_id], Type::Pointer(_)) {
-                let global_data = &global_table.global_memory[global_info.offset..global_info.offset + global_info.size];
-                let mut reader = ByteReader::new(global_data, global_info.size);
-                
-                if let Ok(VmValue::Pointer(old_addr, pointed_type_id)) = 
-                    type_table[global_info.type_id].construct(&mut reader) 
-                {
-                    let new_addr = self.copy_object_if_needed(
-                        address_map,
-                        old_addr,
-                        pointed_type_id,
-                        heap,
-                        type_table,
-                    );
-                    
-                    let new_pointer = VmValue::Pointer(new_addr, pointed_type_id);
-                    let memory_slice = &mut global_table.global_memory[global_info.offset..global_info.offset + global_info.size];
-                    new_pointer.write_bytes(memory_slice);
-                }
-            }
+            let (offset, size, type_id) = (global_info.offset, global_info.size, global_info.type_id);
+
+            let memory_slice = &mut global_table.global_memory[offset..offset + size];
+            self.trace_memory(state, memory_slice, type_id, heap, type_table);
         }
         Ok(())
     }
 
     fn copy_and_update_stack_frames(
         &self,
-        address_map: &mut HashMap<HeapAddress, HeapAddress>,
+        state: &mut CopyState,
         stack: &mut Stack,
         heap: &mut Heap,
         function_table: &FunctionTable,
         type_table: &TypeTable,
-        header: &Header,
     ) -> Result<()> {
         let mut current_fp = stack.get_frame_pointer();
+        let mut func_index = stack.current_function;
 
         while current_fp.0 >= 16 {
-            let func_index = if current_fp.0 == 16 {
-                header.main_index
-            } else {
-                let saved_func_start = current_fp.0 - 16;
-                let saved_func_bytes = stack.read_frame_data(saved_func_start + 8, 8);
-                let func_idx = usize::from_be_bytes(saved_func_bytes.try_into().unwrap());
-                FunctionIndex(func_idx)
-            };
-
             let func_info = &function_table[func_index];
 
             for (i, &local_type_id) in func_info.local_types.iter().enumerate() {
-                if matches!(&type_table[local_type_id], Type::Pointer(_)) {
-                    let (local_offset, _) = func_info.local_offsets[i];
-                    let local_addr = current_fp.0 + local_offset;
-
-                    let local_type_info = &type_table[local_type_id];
-                    let local_data = stack.read_data_at(local_addr, local_type_info.size().0);
-                    let mut reader = ByteReader::new(local_data, local_type_info.size().0);
-
-                    if let Ok(VmValue::Pointer(old_addr, pointed_type_id)) =
-                        local_type_info.construct(&mut reader)
-                    {
-                        let new_addr = self.copy_object_if_needed(
-                            address_map,
-                            old_addr,
-                            pointed_type_id,
-                            heap,
-                            type_table,
-                        );
-
-                        let new_pointer = VmValue::Pointer(new_addr, pointed_type_id);
-                        stack
-                            .write_at(StackAddress(local_addr), new_pointer, local_type_info)
-                            .unwrap();
-                    }
-                }
+                let (local_offset, size) = func_info.local_offsets[i];
+                let local_addr = current_fp.0 + local_offset;
+
+                let local_data = stack.data_at_mut(local_addr, size);
+                self.trace_memory(state, local_data, local_type_id, heap, type_table);
             }
 
             if current_fp.0 == 16 {
                 break;
             }
 
+            // each frame header stores the caller's frame pointer and function
             let saved_fp_start = current_fp.0 - 16;
             let saved_fp_bytes = stack.read_frame_data(saved_fp_start, 8);
-            let old_fp = usize::from_be_bytes(saved_fp_bytes.try_into().unwrap());
-            current_fp = StackAddress(old_fp);
+            let saved_func_bytes = stack.read_frame_data(saved_fp_start + 8, 8);
+            func_index = FunctionIndex(usize::from_be_bytes(saved_func_bytes.try_into().unwrap()));
+            current_fp =
+                super::values::StackAddress(usize::from_be_bytes(saved_fp_bytes.try_into().unwrap()));
         }
 
         Ok(())
     }
 
+    fn scan_copied_objects(&self, state: &mut CopyState, heap: &mut Heap, type_table: &TypeTable) {
+        while let Some((addr, type_id, count)) = state.unscanned.pop() {
+            let size = type_table[type_id].size().0;
+            if size == 0 {
+                continue;
+            }
+
+            let mut data = heap.read_copied(addr, TypeSize(size * count)).to_vec();
+            for object in data.chunks_exact_mut(size) {
+                self.trace_memory(state, object, type_id, heap, type_table);
+            }
+            heap.write_copied(addr, &data);
+        }
+    }
+
+    /// Walks a value of type `type_id` laid out in `memory`, moving every heap
+    /// object it points to and rewriting the pointers in place.
+    fn trace_memory(
+        &self,
+        state: &mut CopyState,
+        memory: &mut [u8],
+        type_id: TypeId,
+        heap: &mut Heap,
+        type_table: &TypeTable,
+    ) {
+        match &type_table[type_id] {
+            Type::Pointer(_) | Type::Ref(_) => {
+                let address = usize::from_be_bytes(memory[0..8].try_into().unwrap());
+                let tagged_type = usize::from_be_bytes(memory[8..16].try_into().unwrap());
+
+                // references into the stack or to globals don't move
+                let VmValue::Pointer(old_addr, pointed_type_id) = VmValue::address(address, tagged_type)
+                else {
+                    return;
+                };
+
+                // zeroed slots (uninitialized locals) point at the void type
+                if type_table[pointed_type_id].size().0 == 0 {
+                    return;
+                }
+
+                let new_addr =
+                    self.copy_object_if_needed(state, old_addr, pointed_type_id, heap, type_table);
+                VmValue::Pointer(new_addr, pointed_type_id).write_bytes(&mut memory[0..16]);
+            }
+            Type::Custom(layout) | Type::Tuple(layout) => {
+                for field in &layout.fields {
+                    let size = type_table[field.type_id].size().0;
+                    let field_memory = &mut memory[field.offset..field.offset + size];
+                    self.trace_memory(state, field_memory, field.type_id, heap, type_table);
+                }
+            }
+            Type::Enum(e) | Type::Optional(e) | Type::Result(e) => {
+                for field in e.active_fields(memory).to_vec() {
+                    let size = type_table[field.type_id].size().0;
+                    let field_memory = &mut memory[field.offset..field.offset + size];
+                    self.trace_memory(state, field_memory, field.type_id, heap, type_table);
+                }
+            }
+            Type::Array(layout) if layout.element_size > 0 => {
+                for element in memory.chunks_exact_mut(layout.element_size) {
+                    self.trace_memory(state, element, layout.element, heap, type_table);
+                }
+            }
+            Type::Primitive(PrimitiveType::Str, _) => {
+                let len = usize::from_be_bytes(memory[0..8].try_into().unwrap());
+                let old_addr = HeapAddress(usize::from_be_bytes(memory[8..16].try_into().unwrap()));
+
+                // constant-pool strings (and zeroed slots) have a null pointer
+                if old_addr.0 == 0 {
+                    return;
+                }
+
+                let new_addr = self.copy_string_if_needed(state, old_addr, len, heap);
+                VmValue::heap_string(len, new_addr).write_bytes(&mut memory[0..16]);
+            }
+            Type::Vector(layout) => {
+                let old_addr = HeapAddress(usize::from_be_bytes(memory[0..8].try_into().unwrap()));
+                let vector_type_id = TypeId(usize::from_be_bytes(memory[8..16].try_into().unwrap()));
+
+                // zeroed slots (uninitialized locals) don't name a vector type
+                if !matches!(type_table[vector_type_id], Type::Vector(_)) {
+                    return;
+                }
+
+                let new_addr = self.copy_vector_if_needed(state, old_addr, layout, heap);
+                VmValue::Vector(new_addr, vector_type_id).write_bytes(&mut memory[0..16]);
+            }
+            Type::Function => {
+                let old_addr = HeapAddress(usize::from_be_bytes(memory[8..16].try_into().unwrap()));
+                let env_type_id = TypeId(usize::from_be_bytes(memory[16..24].try_into().unwrap()));
+
+                // plain functions (and zeroed slots) have no environment
+                if old_addr.0 == 0 {
+                    return;
+                }
+
+                let new_addr =
+                    self.copy_object_if_needed(state, old_addr, env_type_id, heap, type_table);
+                memory[8..16].copy_from_slice(&new_addr.0.to_be_bytes());
+            }
+            _ => (),
+        }
+    }
+
     fn copy_object_if_needed(
         &self,
-        address_map: &mut HashMap<HeapAddress, HeapAddress>,
+        state: &mut CopyState,
         old_addr: HeapAddress,
         type_id: TypeId,
         heap: &mut Heap,
         type_table: &TypeTable,
     ) -> HeapAddress {
-        if let Some(&new_addr) = address_map.get(&old_addr) {
+        if let Some(&new_addr) = state.address_map.get(&old_addr) {
             new_addr
         } else {
             let size = type_table[type_id].size();
             let new_addr = heap.copy_object_from_old(old_addr, size);
-            address_map.insert(old_addr, new_addr);
+            state.address_map.insert(old_addr, new_addr);
+            state.unscanned.push((new_addr, type_id, 1));
             new_addr
         }
     }
+
+    fn copy_string_if_needed(
+        &self,
+        state: &mut CopyState,
+        old_addr: HeapAddress,
+        len: usize,
+        heap: &mut Heap,
+    ) -> HeapAddress {
+        if let Some(&new_addr) = state.address_map.get(&old_addr) {
+            return new_addr;
+        }
+
+        // strings hold no pointers, so there is nothing left to scan
+        let new_addr = heap.copy_object_from_old(old_addr, TypeSize(len.max(1)));
+        state.address_map.insert(old_addr, new_addr);
+        new_addr
+    }
+
+    /// Moves a vector's header together with its element buffer. Only the
+    /// first `len` elements are live, so only those are traced.
+    fn copy_vector_if_needed(
+        &self,
+        state: &mut CopyState,
+        old_addr: HeapAddress,
+        layout: &VectorLayout,
+        heap: &mut Heap,
+    ) -> HeapAddress {
+        if let Some(&new_addr) = state.address_map.get(&old_addr) {
+            return new_addr;
+        }
+
+        let new_addr = heap.copy_object_from_old(old_addr, VectorHeader::SIZE);
+        state.address_map.insert(old_addr, new_addr);
+
+        let mut header =
+            VectorHeader::from_bytes(heap.read_copied(new_addr, VectorHeader::SIZE));
+        if header.capacity > 0 {
+            let buffer_size = TypeSize(header.capacity * layout.element_size);
+            header.data = heap.copy_object_from_old(header.data, buffer_size);
+            heap.write_copied(new_addr, &header.to_bytes());
+            state.unscanned.push((header.data, layout.element, header.len));
+        }
+
+        new_addr
+    }
 }
@@ -8,6 +8,40 @@ use crate::{
 
 use super::values::{HeapAddress, VmValue};
 
+/// The heap object a vector handle points at. The elements live in a
+/// separate buffer of `capacity` slots that is reallocated as the vector grows.
+#[derive(Debug, Clone, Copy)]
+pub struct VectorHeader {
+    pub len: usize,
+    pub capacity: usize,
+    pub data: HeapAddress,
+}
+
+impl VectorHeader {
+    pub const SIZE: TypeSize = TypeSize(24);
+
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        let word = |i: usize| usize::from_be_bytes(bytes[i * 8..i * 8 + 8].try_into().unwrap());
+        Self {
+            len: word(0),
+            capacity: word(1),
+            data: HeapAddress(word(2)),
+        }
+    }
+
+    pub fn to_bytes(self) -> [u8; 24] {
+        let mut bytes = [0; 24];
+        bytes[0..8].copy_from_slice(&self.len.to_be_bytes());
+        bytes[8..16].copy_from_slice(&self.capacity.to_be_bytes());
+        bytes[16..24].copy_from_slice(&self.data.0.to_be_bytes());
+        bytes
+    }
+}
+
+// address 0 is never handed out: a null pointer marks constant-pool strings
+// and the buffer of an empty vector
+const RESERVED: usize = 8;
+
 #[derive(Debug)]
 pub struct Heap {
     data: Vec<u8>,
@@ -22,7 +56,7 @@ impl Heap {
         let initial_capacity = 1024 * 1024;
         Self {
             data: Vec::with_capacity(initial_capacity),
-            next_free: 0,
+            next_free: RESERVED,
             copy_buffer: Vec::with_capacity(initial_capacity),
             copy_next_free: 0,
         }
@@ -41,13 +75,24 @@ impl Heap {
         data_addr
     }
 
+    pub fn alloc_bytes(&mut self, bytes: &[u8]) -> HeapAddress {
+        // empty strings still get an address of their own
+        let addr = self.alloc(TypeSize(bytes.len().max(1)));
+        self.data[addr.0..addr.0 + bytes.len()].copy_from_slice(bytes);
+        addr
+    }
+
+    pub fn read_bytes(&self, addr: HeapAddress, len: usize) -> &[u8] {
+        &self.data[addr.0..addr.0 + len]
+    }
+
     pub fn write_value(&mut self, addr: HeapAddress, value: VmValue, size: TypeSize) {
         let memory_slice = &mut self.data[addr.0..addr.0 + size.0];
         value.write_bytes(memory_slice);
     }
 
     pub fn read_value(
-        &mut self,
+        &self,
         addr: HeapAddress,
         type_id: TypeId,
         type_table: &TypeTable,
@@ -57,15 +102,27 @@ impl Heap {
         type_info.construct(&mut reader)
     }
 
+    pub fn read_vector(&self, addr: HeapAddress) -> VectorHeader {
+        VectorHeader::from_bytes(&self.data[addr.0..addr.0 + VectorHeader::SIZE.0])
+    }
+
+    pub fn write_vector(&mut self, addr: HeapAddress, header: VectorHeader) {
+        self.data[addr.0..addr.0 + VectorHeader::SIZE.0].copy_from_slice(&header.to_bytes());
+    }
+
+    pub fn copy_within(&mut self, from: HeapAddress, to: HeapAddress, size: usize) {
+        self.data.copy_within(from.0..from.0 + size, to.0);
+    }
+
     // GC support methods
     pub fn bytes_allocated(&self) -> usize {
-        self.next_free
+        self.next_free - RESERVED
     }
 
     pub fn start_copying_gc(&mut self) {
         self.copy_buffer.clear();
         self.copy_buffer.resize(self.data.len(), 0);
-        self.copy_next_free = 0;
+        self.copy_next_free = RESERVED;
     }
 
     pub fn copy_object_from_old(&mut self, old_addr: HeapAddress, size: TypeSize) -> HeapAddress {
@@ -79,6 +136,14 @@ impl Heap {
         new_addr
     }
 
+    pub fn read_copied(&self, addr: HeapAddress, size: TypeSize) -> &[u8] {
+        &self.copy_buffer[addr.0..addr.0 + size.0]
+    }
+
+    pub fn write_copied(&mut self, addr: HeapAddress, data: &[u8]) {
+        self.copy_buffer[addr.0..addr.0 + data.len()].copy_from_slice(data);
+    }
+
     pub fn finish_copying_gc(&mut self) {
         std::mem::swap(&mut self.data, &mut self.copy_buffer);
         self.next_free = self.copy_next_free;
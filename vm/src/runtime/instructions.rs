@@ -36,6 +36,7 @@ macro_rules! instructions {
                   use $crate::parser::function_table::CodeAddress;
                   use $crate::parser::const_pool::ConstIndex;
                   use $crate::parser::function_table::FunctionIndex;
+                  use $crate::parser::vtable::VirtualIndex;
                   #[allow(unused)]
                   pub(super) fn execute($runtime: &mut $crate::runtime::runtime::Runtime) -> Result<()> {
                       $($(let $arg_name = $runtime.bytecode.read::<$arg_type>()?.try_into().unwrap();)*)?
@@ -60,9 +61,28 @@ instructions!(
     STORE_LOCAL(id: LocalIndex<u16>) = 0x12 => (rt) => rt.store_local(LocalIndex(id)),
     LOAD_GLOBAL(id: GlobalIndex<u16>) = 0x13 => (rt) => rt.load_global(GlobalIndex(id)),
     STORE_GLOBAL(id: GlobalIndex<u16>) = 0x14 => (rt) => rt.store_global(GlobalIndex(id)),
+    PUSH_ADDR_GLOBAL(id: GlobalIndex<u16>) = 0x15 => (rt) => {
+        rt.push_addr_global(GlobalIndex(id));
+        Ok(())
+    },
     LOAD = 0x31 => (rt) => rt.load(),
     STORE = 0x32 => (rt) => rt.store(),
+    FIELD(idx: FieldIndex<u32>) = 0x33 => (rt) => rt.field(idx),
+    SET_FIELD(idx: FieldIndex<u32>) = 0x34 => (rt) => rt.set_field(idx),
+    INDEX = 0x35 => (rt) => rt.index(),
+    SET_INDEX(idx: ElementIndex<u32>) = 0x36 => (rt) => rt.set_index(idx),
+    FILL = 0x37 => (rt) => rt.fill(),
+    IS_VARIANT(idx: VariantIndex<u32>) = 0x38 => (rt) => rt.is_variant(idx),
+    SET_VARIANT(idx: VariantIndex<u32>) = 0x39 => (rt) => rt.set_variant(idx),
     BOX_ALLOC(id: TypeId<u32>) = 0x40 => (rt) => rt.box_alloc(TypeId(id)),
+    NEW_AGGREGATE(id: TypeId<u32>) = 0x41 => (rt) => rt.new_aggregate(TypeId(id)),
+    VEC_NEW(id: TypeId<u32>) = 0x42 => (rt) => rt.vec_new(TypeId(id)),
+    VEC_PUSH = 0x43 => (rt) => rt.vec_push(),
+    VEC_POP = 0x44 => (rt) => rt.vec_pop(),
+    VEC_LEN = 0x45 => (rt) => rt.vec_len(),
+    STR_LEN = 0x46 => (rt) => rt.str_len(),
+    STR_SLICE = 0x47 => (rt) => rt.str_slice(),
+    STR_NEXT = 0x48 => (rt) => rt.str_next(),
     ADD = 0x50 => (rt) => rt.add(),
     SUB = 0x51 => (rt) => rt.sub(),
     MUL = 0x52 => (rt) => rt.mul(),
@@ -70,11 +90,28 @@ instructions!(
     NEG = 0x54 => (rt) => rt.neg(),
     INC = 0x55 => (rt) => rt.inc(),
     MOD = 0x56 => (rt) => rt.modulo(),
+    TO_INT = 0x57 => (rt) => rt.cast_int(),
+    TO_FLOAT = 0x58 => (rt) => rt.cast_float(),
+    BIT_AND = 0x59 => (rt) => rt.bit_and(),
+    BIT_OR = 0x5A => (rt) => rt.bit_or(),
+    BIT_XOR = 0x5B => (rt) => rt.bit_xor(),
+    SHL = 0x5C => (rt) => rt.shl(),
+    SHR = 0x5D => (rt) => rt.shr(),
+    BIT_NOT = 0x5E => (rt) => rt.bit_not(),
+    TO_CHAR = 0x5F => (rt) => rt.cast_char(),
     JMP(ca: CodeAddress<u64>) = 0x60 => (rt) => rt.bytecode.jump_to(CodeAddress(ca).0 as usize),
     JMP_IF_TRUE(ca: CodeAddress<u64>) = 0x61 => (rt) => rt.jmp_condition(CodeAddress(ca), true),
     JMP_IF_FALSE(ca: CodeAddress<u64>) = 0x62 => (rt) => rt.jmp_condition(CodeAddress(ca), false),
+    JMP_TABLE(len: TableLength<u32>) = 0x63 => (rt) => rt.jmp_table(len),
     CALL(fi: FunctionIndex<u32>) = 0x70 => (rt) => rt.call(FunctionIndex(fi)),
     RET = 0x71 => (rt) => rt.ret(),
+    FUNC_REF(fi: FunctionIndex<u32>) = 0x72 => (rt) => {
+        rt.func_ref(FunctionIndex(fi));
+        Ok(())
+    },
+    CLOSURE(fi: FunctionIndex<u32>) = 0x73 => (rt) => rt.closure(FunctionIndex(fi)),
+    CALL_INDIRECT = 0x74 => (rt) => rt.call_indirect(),
+    CALL_VIRTUAL(idx: VirtualIndex<u32>) = 0x75 => (rt) => rt.call_virtual(VirtualIndex(idx)),
     EQ = 0x80 => (rt) => rt.eq(),
     NEQ = 0x81 => (rt) => rt.ne(),
     LT = 0x82 => (rt) => rt.lt(),
@@ -86,5 +123,13 @@ instructions!(
     NOT = 0x88 => (rt) => rt.not(),
     POP = 0x90 => (rt) => { rt.pop() },
     DUP = 0x91 => (rt) => rt.dup(),
+    SWAP = 0x92 => (rt) => rt.swap(),
+    ADD_WRAP = 0xA0 => (rt) => rt.add_wrap(),
+    SUB_WRAP = 0xA1 => (rt) => rt.sub_wrap(),
+    MUL_WRAP = 0xA2 => (rt) => rt.mul_wrap(),
+    ADD_SAT = 0xA3 => (rt) => rt.add_sat(),
+    SUB_SAT = 0xA4 => (rt) => rt.sub_sat(),
+    MUL_SAT = 0xA5 => (rt) => rt.mul_sat(),
+    TO_SIZED(id: PrimitiveId<u8>) = 0xA6 => (rt) => rt.cast_sized(id),
     HALT = 0xFF => (rt) => rt.halt()
 );
@@ -13,7 +13,7 @@ pub struct OperandStack {
 impl OperandStack {
     pub fn new() -> Self {
         Self {
-            data: [VmValue::Int(0); MAX_STACK_SIZE],
+            data: std::array::from_fn(|_| VmValue::Int(0)),
             len: 0,
         }
     }
@@ -35,7 +35,34 @@ impl OperandStack {
             return Err(RuntimeError::BadStack);
         }
         self.len -= 1;
-        unsafe { Ok(*self.data.get_unchecked(self.len)) }
+        unsafe { Ok(std::mem::replace(self.data.get_unchecked_mut(self.len), VmValue::Int(0))) }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    // drops every value above the first `len`
+    pub fn truncate(&mut self, len: usize) {
+        while self.len > len {
+            self.len -= 1;
+            self.data[self.len] = VmValue::Int(0);
+        }
+    }
+
+    pub fn peek(&self) -> Result<&VmValue> {
+        if self.len == 0 {
+            return Err(RuntimeError::BadStack);
+        }
+        Ok(&self.data[self.len - 1])
+    }
+
+    // `depth` values below the top
+    pub fn peek_at(&self, depth: usize) -> Result<&VmValue> {
+        if depth >= self.len {
+            return Err(RuntimeError::BadStack);
+        }
+        Ok(&self.data[self.len - 1 - depth])
     }
 
     // GC support methods
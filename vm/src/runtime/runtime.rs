@@ -1,3 +1,5 @@
+use std::cmp::Ordering;
+
 use crate::{
     aliases::Result,
     err::RuntimeError,
@@ -7,12 +9,17 @@ use crate::{
         function_table::{CodeAddress, FunctionIndex, FunctionTable, LocalIndex},
         global_table::{GlobalIndex, GlobalTable},
         header::Header,
-        type_table::{TypeId, TypeTable},
+        type_table::{EnumLayout, PrimitiveType, Type, TypeId, TypeSize, TypeTable},
+        vtable::{VirtualIndex, VirtualTable},
     },
 };
 
 use super::{
-    gc::GarbageCollector, heap::Heap, operand_stack::OperandStack, stack::Stack, values::VmValue,
+    gc::GarbageCollector,
+    heap::{Heap, VectorHeader},
+    operand_stack::OperandStack,
+    stack::Stack,
+    values::{HeapAddress, VmValue},
 };
 
 static STACK_SIZE: usize = 1048576;
@@ -28,18 +35,42 @@ macro_rules! binary_op_runtime {
     };
 }
 
+// like binary_op_runtime, but strings are compared by their contents
+macro_rules! comparison_op_runtime {
+    ($name:ident, $ordering:pat) => {
+        pub(in crate::runtime) fn $name(&mut self) -> Result<()> {
+            let b = self.operand_stack.pop()?;
+            let a = self.operand_stack.pop()?;
+            let result = match (&a, &b) {
+                (VmValue::StringHandle { .. }, VmValue::StringHandle { .. }) => {
+                    let ordering = self.string_bytes(&a)?.cmp(self.string_bytes(&b)?);
+                    VmValue::Bool(matches!(ordering, $ordering))
+                }
+                _ => a.$name(b)?,
+            };
+            self.operand_stack.push(result);
+            Ok(())
+        }
+    };
+}
+
 #[derive(Debug)]
 pub struct Runtime<'bytecode> {
+    #[allow(dead_code)]
     header: Header,
     type_table: TypeTable,
     global_table: GlobalTable,
     const_pool: ConstPool<'bytecode>,
     function_table: FunctionTable,
+    vtable: VirtualTable,
     pub(in crate::runtime) bytecode: ByteReader<'bytecode>,
 
     stack: Stack,
     heap: Heap,
     operand_stack: OperandStack,
+    // the operand stack height at each active call, restored on return so a
+    // return from the middle of an expression leaves nothing behind
+    call_bases: Vec<usize>,
     gc: GarbageCollector,
 }
 
@@ -50,6 +81,7 @@ impl<'bytecode> Runtime<'bytecode> {
         global_table: GlobalTable,
         const_pool: ConstPool<'bytecode>,
         function_table: FunctionTable,
+        vtable: VirtualTable,
         bytecode: &'bytecode [u8],
     ) -> Result<Self> {
         let bytecode = ByteReader::new(bytecode, bytecode.len());
@@ -60,16 +92,18 @@ impl<'bytecode> Runtime<'bytecode> {
             global_table,
             const_pool,
             function_table,
+            vtable,
             bytecode,
             heap: Heap::new(),
             header,
             operand_stack: OperandStack::new(),
+            call_bases: vec![],
             gc: GarbageCollector::new(),
         })
     }
 
     pub(in crate::runtime) fn load_const(&mut self, const_id: ConstIndex) {
-        self.operand_stack.push(self.const_pool[const_id]);
+        self.operand_stack.push(self.const_pool[const_id].clone());
     }
 
     pub(in crate::runtime) fn push_addr_local(&mut self, local_id: LocalIndex) {
@@ -101,6 +135,11 @@ impl<'bytecode> Runtime<'bytecode> {
         Ok(())
     }
 
+    pub(in crate::runtime) fn push_addr_global(&mut self, global_id: GlobalIndex) {
+        let addr = self.global_table.get_global_address(global_id);
+        self.operand_stack.push(addr);
+    }
+
     pub(in crate::runtime) fn load(&mut self) -> Result<()> {
         let pointer = self.operand_stack.pop()?;
 
@@ -112,6 +151,9 @@ impl<'bytecode> Runtime<'bytecode> {
                 let info = &self.type_table[type_id];
                 self.stack.read_at(stack_addr, info)?
             }
+            VmValue::GlobalRef(offset, type_id) => {
+                self.global_table.read_at(offset, type_id, &self.type_table)?
+            }
             _ => return Err(RuntimeError::InvalidOperation),
         };
 
@@ -133,14 +175,170 @@ impl<'bytecode> Runtime<'bytecode> {
                 let type_info = &self.type_table[type_id];
                 self.heap.write_value(heap_addr, value, type_info.size());
             }
+            VmValue::GlobalRef(offset, type_id) => {
+                let type_info = &self.type_table[type_id];
+                self.global_table.write_at(offset, value, type_info.size());
+            }
             _ => return Err(RuntimeError::InvalidOperation),
         }
 
         Ok(())
     }
 
-    pub(in crate::runtime) fn box_alloc(&mut self, type_id: TypeId) -> Result<()> {
-        // Check if we should trigger garbage collection
+    pub(in crate::runtime) fn field(&mut self, index: usize) -> Result<()> {
+        let value = self.operand_stack.pop()?;
+
+        let container = match &value {
+            VmValue::Pointer(_, type_id)
+            | VmValue::Ref(_, type_id)
+            | VmValue::GlobalRef(_, type_id)
+            | VmValue::Aggregate(type_id, _) => *type_id,
+            _ => return Err(RuntimeError::InvalidOperation),
+        };
+
+        let Some(layout) = self.type_table[container].fields() else {
+            return Err(RuntimeError::InvalidOperation);
+        };
+        let field = layout.field(index)?;
+
+        let result = match value {
+            VmValue::Aggregate(_, bytes) => {
+                let info = &self.type_table[field.type_id];
+                let mut reader = ByteReader::new(&bytes[field.offset..], info.size().0);
+                info.construct(&mut reader)?
+            }
+            address => address.offset_by(field.offset, field.type_id)?,
+        };
+
+        self.operand_stack.push(result);
+        Ok(())
+    }
+
+    pub(in crate::runtime) fn set_field(&mut self, index: usize) -> Result<()> {
+        let value = self.operand_stack.pop()?;
+        let VmValue::Aggregate(type_id, mut bytes) = self.operand_stack.pop()? else {
+            return Err(RuntimeError::InvalidOperation);
+        };
+
+        let Some(layout) = self.type_table[type_id].fields() else {
+            return Err(RuntimeError::InvalidOperation);
+        };
+        let field = layout.field(index)?;
+        let size = self.type_table[field.type_id].size().0;
+        value.write_bytes(&mut bytes[field.offset..field.offset + size]);
+
+        self.operand_stack.push(VmValue::Aggregate(type_id, bytes));
+        Ok(())
+    }
+
+    pub(in crate::runtime) fn is_variant(&mut self, variant: usize) -> Result<()> {
+        let VmValue::Aggregate(_, bytes) = self.operand_stack.pop()? else {
+            return Err(RuntimeError::InvalidOperation);
+        };
+
+        self.operand_stack
+            .push(VmValue::Bool(EnumLayout::tag(&bytes) == variant));
+        Ok(())
+    }
+
+    pub(in crate::runtime) fn set_variant(&mut self, variant: usize) -> Result<()> {
+        let VmValue::Aggregate(type_id, mut bytes) = self.operand_stack.pop()? else {
+            return Err(RuntimeError::InvalidOperation);
+        };
+
+        if !matches!(
+            self.type_table[type_id],
+            Type::Enum(_) | Type::Optional(_) | Type::Result(_)
+        ) {
+            return Err(RuntimeError::InvalidOperation);
+        }
+        bytes[0..EnumLayout::TAG_SIZE].copy_from_slice(&variant.to_be_bytes());
+
+        self.operand_stack.push(VmValue::Aggregate(type_id, bytes));
+        Ok(())
+    }
+
+    pub(in crate::runtime) fn index(&mut self) -> Result<()> {
+        let value = self.operand_stack.pop()?;
+        let VmValue::Int(index) = self.operand_stack.pop()? else {
+            return Err(RuntimeError::InvalidOperation);
+        };
+
+        if let VmValue::Vector(addr, type_id) = value {
+            return self.vec_index(addr, type_id, index);
+        }
+
+        let container = match &value {
+            VmValue::Pointer(_, type_id)
+            | VmValue::Ref(_, type_id)
+            | VmValue::GlobalRef(_, type_id)
+            | VmValue::Aggregate(type_id, _) => *type_id,
+            _ => return Err(RuntimeError::InvalidOperation),
+        };
+
+        let Type::Array(layout) = &self.type_table[container] else {
+            return Err(RuntimeError::InvalidOperation);
+        };
+        let offset = layout.element_offset(index)?;
+
+        let result = match value {
+            VmValue::Aggregate(_, bytes) => {
+                let info = &self.type_table[layout.element];
+                let mut reader = ByteReader::new(&bytes[offset..], info.size().0);
+                info.construct(&mut reader)?
+            }
+            address => address.offset_by(offset, layout.element)?,
+        };
+
+        self.operand_stack.push(result);
+        Ok(())
+    }
+
+    pub(in crate::runtime) fn set_index(&mut self, index: usize) -> Result<()> {
+        let value = self.operand_stack.pop()?;
+        let VmValue::Aggregate(type_id, mut bytes) = self.operand_stack.pop()? else {
+            return Err(RuntimeError::InvalidOperation);
+        };
+
+        let Type::Array(layout) = &self.type_table[type_id] else {
+            return Err(RuntimeError::InvalidOperation);
+        };
+        let offset = layout.element_offset(index as i64)?;
+        value.write_bytes(&mut bytes[offset..offset + layout.element_size]);
+
+        self.operand_stack.push(VmValue::Aggregate(type_id, bytes));
+        Ok(())
+    }
+
+    pub(in crate::runtime) fn fill(&mut self) -> Result<()> {
+        let value = self.operand_stack.pop()?;
+        let VmValue::Aggregate(type_id, mut bytes) = self.operand_stack.pop()? else {
+            return Err(RuntimeError::InvalidOperation);
+        };
+
+        let Type::Array(layout) = &self.type_table[type_id] else {
+            return Err(RuntimeError::InvalidOperation);
+        };
+        if layout.element_size > 0 {
+            for element in bytes.chunks_exact_mut(layout.element_size) {
+                value.clone().write_bytes(element);
+            }
+        }
+
+        self.operand_stack.push(VmValue::Aggregate(type_id, bytes));
+        Ok(())
+    }
+
+    pub(in crate::runtime) fn new_aggregate(&mut self, type_id: TypeId) -> Result<()> {
+        let size = self.type_table[type_id].size().0;
+        self.operand_stack
+            .push(VmValue::Aggregate(type_id, vec![0; size].into()));
+        Ok(())
+    }
+
+    // must run before an allocating instruction pops its operands, so the
+    // collector sees and updates every live value
+    fn collect_garbage_if_needed(&mut self) -> Result<()> {
         if self.gc.should_collect(self.heap.bytes_allocated()) {
             self.gc.collect(
                 &mut self.heap,
@@ -149,9 +347,13 @@ impl<'bytecode> Runtime<'bytecode> {
                 &mut self.global_table,
                 &self.function_table,
                 &self.type_table,
-                &self.header,
             )?;
         }
+        Ok(())
+    }
+
+    pub(in crate::runtime) fn box_alloc(&mut self, type_id: TypeId) -> Result<()> {
+        self.collect_garbage_if_needed()?;
 
         let type_info = &self.type_table[type_id];
         let size = type_info.size();
@@ -165,11 +367,217 @@ impl<'bytecode> Runtime<'bytecode> {
         Ok(())
     }
 
-    binary_op_runtime!(add);
+    fn pop_vector(&mut self) -> Result<(HeapAddress, TypeId)> {
+        match self.operand_stack.pop()? {
+            VmValue::Vector(addr, type_id) => Ok((addr, type_id)),
+            _ => Err(RuntimeError::InvalidOperation),
+        }
+    }
+
+    fn vector_element(&self, type_id: TypeId) -> Result<(TypeId, usize)> {
+        match &self.type_table[type_id] {
+            Type::Vector(layout) => Ok((layout.element, layout.element_size)),
+            _ => Err(RuntimeError::InvalidOperation),
+        }
+    }
+
+    pub(in crate::runtime) fn vec_new(&mut self, type_id: TypeId) -> Result<()> {
+        self.collect_garbage_if_needed()?;
+
+        let addr = self.heap.alloc(VectorHeader::SIZE);
+        self.heap.write_vector(
+            addr,
+            VectorHeader {
+                len: 0,
+                capacity: 0,
+                data: HeapAddress(0),
+            },
+        );
+
+        self.operand_stack.push(VmValue::Vector(addr, type_id));
+        Ok(())
+    }
+
+    pub(in crate::runtime) fn vec_push(&mut self) -> Result<()> {
+        self.collect_garbage_if_needed()?;
+
+        let value = self.operand_stack.pop()?;
+        let (addr, type_id) = self.pop_vector()?;
+        let (_, element_size) = self.vector_element(type_id)?;
+        let mut header = self.heap.read_vector(addr);
+
+        if header.len == header.capacity {
+            let capacity = (header.capacity * 2).max(4);
+            let data = self.heap.alloc(TypeSize(capacity * element_size));
+            self.heap
+                .copy_within(header.data, data, header.len * element_size);
+            header.capacity = capacity;
+            header.data = data;
+        }
+
+        let slot = HeapAddress(header.data.0 + header.len * element_size);
+        self.heap.write_value(slot, value, TypeSize(element_size));
+        header.len += 1;
+        self.heap.write_vector(addr, header);
+        Ok(())
+    }
+
+    pub(in crate::runtime) fn vec_pop(&mut self) -> Result<()> {
+        let (addr, type_id) = self.pop_vector()?;
+        let (element, element_size) = self.vector_element(type_id)?;
+        let mut header = self.heap.read_vector(addr);
+
+        if header.len == 0 {
+            return Err(RuntimeError::EmptyVector);
+        }
+
+        header.len -= 1;
+        self.heap.write_vector(addr, header);
+
+        let slot = HeapAddress(header.data.0 + header.len * element_size);
+        let value = self.heap.read_value(slot, element, &self.type_table)?;
+        self.operand_stack.push(value);
+        Ok(())
+    }
+
+    pub(in crate::runtime) fn vec_len(&mut self) -> Result<()> {
+        let (addr, _) = self.pop_vector()?;
+        let header = self.heap.read_vector(addr);
+        self.operand_stack.push(VmValue::Int(header.len as i64));
+        Ok(())
+    }
+
+    // yields the address of the element, which is only valid until the next allocation
+    fn vec_index(&mut self, addr: HeapAddress, type_id: TypeId, index: i64) -> Result<()> {
+        let (element, element_size) = self.vector_element(type_id)?;
+        let header = self.heap.read_vector(addr);
+
+        if index < 0 || index as usize >= header.len {
+            return Err(RuntimeError::IndexOutOfBounds(index, header.len));
+        }
+
+        let slot = HeapAddress(header.data.0 + index as usize * element_size);
+        self.operand_stack.push(VmValue::Pointer(slot, element));
+        Ok(())
+    }
+
+    fn string_bytes(&self, value: &VmValue) -> Result<&[u8]> {
+        match value {
+            VmValue::StringHandle { len, ptr } if ptr.0 == 0 => self
+                .const_pool
+                .get_string(*len)
+                .map(str::as_bytes)
+                .ok_or(RuntimeError::InvalidOperation),
+            VmValue::StringHandle { len, ptr } => Ok(self.heap.read_bytes(*ptr, *len)),
+            _ => Err(RuntimeError::InvalidOperation),
+        }
+    }
+
+    fn alloc_string(&mut self, bytes: &[u8]) -> VmValue {
+        let ptr = self.heap.alloc_bytes(bytes);
+        VmValue::heap_string(bytes.len(), ptr)
+    }
+
+    pub(in crate::runtime) fn add(&mut self) -> Result<()> {
+        let concat = matches!(self.operand_stack.peek()?, VmValue::StringHandle { .. });
+        if concat {
+            self.collect_garbage_if_needed()?;
+        }
+
+        let b = self.operand_stack.pop()?;
+        let a = self.operand_stack.pop()?;
+
+        let result = if concat {
+            let bytes = [self.string_bytes(&a)?, self.string_bytes(&b)?].concat();
+            self.alloc_string(&bytes)
+        } else {
+            a.add(b)?
+        };
+
+        self.operand_stack.push(result);
+        Ok(())
+    }
+
+    // the length in bytes of the utf-8 text, not in chars
+    pub(in crate::runtime) fn str_len(&mut self) -> Result<()> {
+        let s = self.operand_stack.pop()?;
+        let len = self.string_bytes(&s)?.len();
+        self.operand_stack.push(VmValue::Int(len as i64));
+        Ok(())
+    }
+
+    // the indices are byte offsets, like `len`, and both must fall on a char boundary
+    pub(in crate::runtime) fn str_slice(&mut self) -> Result<()> {
+        self.collect_garbage_if_needed()?;
+
+        let VmValue::Int(end) = self.operand_stack.pop()? else {
+            return Err(RuntimeError::InvalidOperation);
+        };
+        let VmValue::Int(start) = self.operand_stack.pop()? else {
+            return Err(RuntimeError::InvalidOperation);
+        };
+        let s = self.operand_stack.pop()?;
+        let bytes = self.string_bytes(&s)?;
+
+        if end < 0 || end as usize > bytes.len() {
+            return Err(RuntimeError::IndexOutOfBounds(end, bytes.len()));
+        }
+        if start < 0 || start > end {
+            return Err(RuntimeError::IndexOutOfBounds(start, bytes.len()));
+        }
+        // continuation bytes of a char look like 0b10xxxxxx
+        let inside_char = |i: i64| bytes.get(i as usize).is_some_and(|b| b & 0xC0 == 0x80);
+        if let Some(index) = [start, end].into_iter().find(|i| inside_char(*i)) {
+            return Err(RuntimeError::NotCharBoundary(index));
+        }
+
+        let bytes = bytes[start as usize..end as usize].to_vec();
+        let result = self.alloc_string(&bytes);
+        self.operand_stack.push(result);
+        Ok(())
+    }
+
+    // decodes the char starting at a byte offset, pushing it and the offset of
+    // the one after it
+    pub(in crate::runtime) fn str_next(&mut self) -> Result<()> {
+        let VmValue::Int(offset) = self.operand_stack.pop()? else {
+            return Err(RuntimeError::InvalidOperation);
+        };
+        let s = self.operand_stack.pop()?;
+        let bytes = self.string_bytes(&s)?;
+
+        if offset < 0 || offset as usize >= bytes.len() {
+            return Err(RuntimeError::IndexOutOfBounds(offset, bytes.len()));
+        }
+
+        // a char is at most 4 bytes long
+        let rest = &bytes[offset as usize..bytes.len().min(offset as usize + 4)];
+        let valid = match std::str::from_utf8(rest) {
+            Ok(s) => s,
+            Err(e) => std::str::from_utf8(&rest[..e.valid_up_to()]).unwrap_or_default(),
+        };
+        let c = valid.chars().next().ok_or(RuntimeError::InvalidOperation)?;
+
+        self.operand_stack.push(VmValue::Char(c));
+        self.operand_stack.push(VmValue::Int(offset + c.len_utf8() as i64));
+        Ok(())
+    }
+
     binary_op_runtime!(sub);
     binary_op_runtime!(mul);
     binary_op_runtime!(div);
     binary_op_runtime!(modulo);
+    binary_op_runtime!(bit_and);
+    binary_op_runtime!(bit_or);
+    binary_op_runtime!(bit_xor);
+    binary_op_runtime!(shl);
+    binary_op_runtime!(shr);
+    binary_op_runtime!(add_wrap);
+    binary_op_runtime!(sub_wrap);
+    binary_op_runtime!(mul_wrap);
+    binary_op_runtime!(add_sat);
+    binary_op_runtime!(sub_sat);
+    binary_op_runtime!(mul_sat);
 
     pub(in crate::runtime) fn neg(&mut self) -> Result<()> {
         let v1 = self.operand_stack.pop()?;
@@ -178,6 +586,13 @@ impl<'bytecode> Runtime<'bytecode> {
         Ok(())
     }
 
+    pub(in crate::runtime) fn bit_not(&mut self) -> Result<()> {
+        let v1 = self.operand_stack.pop()?;
+
+        self.operand_stack.push(v1.bit_not()?);
+        Ok(())
+    }
+
     pub(in crate::runtime) fn inc(&mut self) -> Result<()> {
         let v1 = self.operand_stack.pop()?;
 
@@ -185,6 +600,40 @@ impl<'bytecode> Runtime<'bytecode> {
         Ok(())
     }
 
+    pub(in crate::runtime) fn cast_int(&mut self) -> Result<()> {
+        let v1 = self.operand_stack.pop()?;
+
+        self.operand_stack.push(v1.into_int()?);
+        Ok(())
+    }
+
+    pub(in crate::runtime) fn cast_float(&mut self) -> Result<()> {
+        let v1 = self.operand_stack.pop()?;
+
+        self.operand_stack.push(v1.into_float()?);
+        Ok(())
+    }
+
+    // `id` is the type id of the int type to cast to
+    pub(in crate::runtime) fn cast_sized(&mut self, id: u8) -> Result<()> {
+        let mut reader = ByteReader::new(std::slice::from_ref(&id), 1);
+        let kind = reader
+            .read_safe::<PrimitiveType>()?
+            .int_kind()
+            .ok_or(RuntimeError::InvalidOperation)?;
+        let v1 = self.operand_stack.pop()?;
+
+        self.operand_stack.push(v1.into_sized(kind)?);
+        Ok(())
+    }
+
+    pub(in crate::runtime) fn cast_char(&mut self) -> Result<()> {
+        let v1 = self.operand_stack.pop()?;
+
+        self.operand_stack.push(v1.into_char()?);
+        Ok(())
+    }
+
     pub(in crate::runtime) fn jmp_condition(
         &mut self,
         address: CodeAddress,
@@ -199,6 +648,26 @@ impl<'bytecode> Runtime<'bytecode> {
         Ok(())
     }
 
+    // the table follows the instruction: the lowest value it covers, the
+    // address to take outside of it, then one address per value from there
+    pub(in crate::runtime) fn jmp_table(&mut self, len: u32) -> Result<()> {
+        let Some((v, _)) = self.operand_stack.pop()?.as_int() else {
+            return Err(RuntimeError::InvalidOperation);
+        };
+        let min = self.bytecode.read::<i64>()?;
+        let default = self.bytecode.read::<u64>()?;
+
+        let offset = v - min as i128;
+        if !(0..len as i128).contains(&offset) {
+            return self.bytecode.jump_to(default as usize);
+        }
+
+        let entry = self.bytecode.position() + offset as usize * 8;
+        self.bytecode.jump_to(entry)?;
+        let address = self.bytecode.read::<u64>()?;
+        self.bytecode.jump_to(address as usize)
+    }
+
     pub(in crate::runtime) fn call(&mut self, id: FunctionIndex) -> Result<()> {
         let return_address = self.bytecode.position();
 
@@ -212,13 +681,82 @@ impl<'bytecode> Runtime<'bytecode> {
             self.stack
                 .write_local(&self.function_table, LocalIndex(i), arg);
         }
+        self.call_bases.push(self.operand_stack.len());
 
         self.bytecode.jump_to(func_info.code_offset.0 as usize)
     }
 
+    pub(in crate::runtime) fn func_ref(&mut self, id: FunctionIndex) {
+        self.operand_stack
+            .push(VmValue::Closure(id, HeapAddress(0), TypeId(0)));
+    }
+
+    pub(in crate::runtime) fn closure(&mut self, id: FunctionIndex) -> Result<()> {
+        let VmValue::Pointer(env, type_id) = self.operand_stack.pop()? else {
+            return Err(RuntimeError::InvalidOperation);
+        };
+
+        self.operand_stack.push(VmValue::Closure(id, env, type_id));
+        Ok(())
+    }
+
+    // captured values are copied out of the environment into the callee's
+    // trailing locals, after its parameters have been written
+    pub(in crate::runtime) fn call_indirect(&mut self) -> Result<()> {
+        let VmValue::Closure(id, env, type_id) = self.operand_stack.pop()? else {
+            return Err(RuntimeError::InvalidOperation);
+        };
+
+        self.call(id)?;
+        if env.0 == 0 {
+            return Ok(());
+        }
+
+        let VmValue::Aggregate(_, bytes) = self.heap.read_value(env, type_id, &self.type_table)?
+        else {
+            return Err(RuntimeError::InvalidOperation);
+        };
+        let Some(layout) = self.type_table[type_id].fields() else {
+            return Err(RuntimeError::InvalidOperation);
+        };
+
+        let first = self.function_table[id].local_types.len() - layout.fields.len();
+        for (i, field) in layout.fields.iter().enumerate() {
+            let info = &self.type_table[field.type_id];
+            let mut reader = ByteReader::new(&bytes[field.offset..], info.size().0);
+            let value = info.construct(&mut reader)?;
+            self.stack
+                .write_local(&self.function_table, LocalIndex(first + i), value);
+        }
+
+        Ok(())
+    }
+
+    // the receiver is the box of the trait object, below the arguments
+    pub(in crate::runtime) fn call_virtual(&mut self, method: VirtualIndex) -> Result<()> {
+        let depth = self.vtable.param_count(method)? - 1;
+        let VmValue::Pointer(_, type_id) = self.operand_stack.peek_at(depth)? else {
+            return Err(RuntimeError::InvalidOperation);
+        };
+
+        let id = self.vtable.get(*type_id, method)?;
+        self.call(id)
+    }
+
     pub(in crate::runtime) fn ret(&mut self) -> Result<()> {
         let return_addr = self.stack.get_return_address();
 
+        let returns_value = self.function_table[self.stack.current_function].return_size > 0;
+        if let Some(base) = self.call_bases.pop() {
+            if returns_value && self.operand_stack.len() > base {
+                let value = self.operand_stack.pop()?;
+                self.operand_stack.truncate(base);
+                self.operand_stack.push(value);
+            } else {
+                self.operand_stack.truncate(base);
+            }
+        }
+
         self.stack.pop_frame()?;
         self.bytecode.jump_to(return_addr)?;
         Ok(())
@@ -226,11 +764,19 @@ impl<'bytecode> Runtime<'bytecode> {
 
     pub(in crate::runtime) fn dup(&mut self) -> Result<()> {
         let v = self.operand_stack.pop()?;
-        self.operand_stack.push(v);
+        self.operand_stack.push(v.clone());
         self.operand_stack.push(v);
         Ok(())
     }
 
+    pub(in crate::runtime) fn swap(&mut self) -> Result<()> {
+        let a = self.operand_stack.pop()?;
+        let b = self.operand_stack.pop()?;
+        self.operand_stack.push(a);
+        self.operand_stack.push(b);
+        Ok(())
+    }
+
     pub(in crate::runtime) fn not(&mut self) -> Result<()> {
         let a = self.operand_stack.pop()?;
         self.operand_stack.push(a.not()?);
@@ -238,9 +784,6 @@ impl<'bytecode> Runtime<'bytecode> {
     }
 
     pub(in crate::runtime) fn halt(&mut self) -> Result<()> {
-        if let Ok(v) = self.operand_stack.pop() {
-            println!("{}", v.display_with_const_pool(&self.const_pool));
-        }
         Err(RuntimeError::Halt)
     }
 
@@ -251,14 +794,15 @@ impl<'bytecode> Runtime<'bytecode> {
 
     binary_op_runtime!(and);
     binary_op_runtime!(or);
-    binary_op_runtime!(lt);
-    binary_op_runtime!(le);
-    binary_op_runtime!(gt);
-    binary_op_runtime!(ge);
-    binary_op_runtime!(eq);
-    binary_op_runtime!(ne);
-
-    pub fn run(&'bytecode mut self) -> Result<()> {
+    comparison_op_runtime!(lt, Ordering::Less);
+    comparison_op_runtime!(le, Ordering::Less | Ordering::Equal);
+    comparison_op_runtime!(gt, Ordering::Greater);
+    comparison_op_runtime!(ge, Ordering::Greater | Ordering::Equal);
+    comparison_op_runtime!(eq, Ordering::Equal);
+    comparison_op_runtime!(ne, Ordering::Less | Ordering::Greater);
+
+    // what main returned, as it is printed
+    pub fn run(&'bytecode mut self) -> Result<Option<String>> {
         loop {
             let opcode = self.bytecode.read::<u8>()?;
             match crate::runtime::instructions::EXECUTE_TABLE[opcode as usize](self) {
@@ -268,6 +812,7 @@ impl<'bytecode> Runtime<'bytecode> {
             }
         }
 
-        Ok(())
+        let result = self.operand_stack.pop().ok();
+        Ok(result.map(|v| v.display_with_const_pool(&self.const_pool, &self.type_table, &self.heap)))
     }
 }
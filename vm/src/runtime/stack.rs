@@ -116,7 +116,7 @@ impl Stack {
         self.stack_pointer = StackAddress(self.frame_pointer.0 + frame_size);
         self.current_function = func;
 
-        //self.data[self.frame_pointer.0..self.stack_pointer.0].fill(0);
+        self.data[self.frame_pointer.0..self.stack_pointer.0].fill(0);
 
         Ok(())
     }
@@ -156,7 +156,7 @@ impl Stack {
         &self.data[addr..addr + len]
     }
 
-    pub fn read_data_at(&self, addr: usize, len: usize) -> &[u8] {
-        &self.data[addr..addr + len]
+    pub fn data_at_mut(&mut self, addr: usize, len: usize) -> &mut [u8] {
+        &mut self.data[addr..addr + len]
     }
 }
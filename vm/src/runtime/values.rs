@@ -1,9 +1,16 @@
 use crate::{
     aliases::Result,
     err::RuntimeError,
-    parser::{byte_reader::ReadSafe, type_table::TypeId, const_pool::ConstPool},
+    parser::{
+        byte_reader::{ByteReader, ReadSafe},
+        const_pool::ConstPool,
+        function_table::FunctionIndex,
+        type_table::{EnumLayout, Type, TypeId, TypeTable},
+    },
 };
 
+use super::heap::Heap;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct HeapAddress(pub usize);
 
@@ -24,25 +31,142 @@ impl ReadSafe for StackAddress {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+// a slot of a reference type holds an address into the stack, the globals or
+// the heap (the box a method was called on), told apart by the high bits of
+// the type id stored next to it
+const STACK_ADDRESS: usize = 1 << 63;
+const GLOBAL_ADDRESS: usize = 1 << 62;
+
+// the int types; `int` is I64 and keeps its own value, the others are held
+// widened to 64 bits, sign extended if they are signed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IntKind {
+    I8,
+    I16,
+    I32,
+    I64,
+    U8,
+    U16,
+    U32,
+    U64,
+}
+
+// what happens when the result of an int op doesn't fit its type
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Overflow {
+    Checked,
+    Wrapping,
+    Saturating,
+}
+
+impl IntKind {
+    pub fn bits(self) -> u32 {
+        match self {
+            Self::I8 | Self::U8 => 8,
+            Self::I16 | Self::U16 => 16,
+            Self::I32 | Self::U32 => 32,
+            Self::I64 | Self::U64 => 64,
+        }
+    }
+
+    pub fn signed(self) -> bool {
+        matches!(self, Self::I8 | Self::I16 | Self::I32 | Self::I64)
+    }
+
+    fn range(self) -> (i128, i128) {
+        let bits = self.bits();
+        match self.signed() {
+            true => (-(1 << (bits - 1)), (1 << (bits - 1)) - 1),
+            false => (0, (1 << bits) - 1),
+        }
+    }
+
+    // keeps only the low bits of `value`
+    fn wrap(self, value: i128) -> i128 {
+        let bits = self.bits();
+        let value = value & ((1 << bits) - 1);
+        match self.signed() && value >> (bits - 1) != 0 {
+            true => value - (1 << bits),
+            false => value,
+        }
+    }
+
+    fn fit(self, value: i128, overflow: Overflow) -> Result<VmValue> {
+        let (min, max) = self.range();
+        let value = match overflow {
+            Overflow::Checked if !(min..=max).contains(&value) => {
+                return Err(RuntimeError::IntegerOverflow)
+            }
+            Overflow::Checked => value,
+            Overflow::Wrapping => self.wrap(value),
+            Overflow::Saturating => value.clamp(min, max),
+        };
+
+        Ok(match self {
+            Self::I64 => VmValue::Int(value as i64),
+            _ => VmValue::SizedInt(value as i64, self),
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
 pub enum VmValue {
     Int(i64),
+    // u64 values above i64::MAX are kept as their bits
+    SizedInt(i64, IntKind),
     Float(f64),
     Bool(bool),
+    Char(char),
     StringHandle { len: usize, ptr: HeapAddress },
     Pointer(HeapAddress, TypeId),
     Ref(StackAddress, TypeId),
+    GlobalRef(usize, TypeId),
+    Aggregate(TypeId, Box<[u8]>),
+    Vector(HeapAddress, TypeId),
+    // a function together with its captured environment; functions that
+    // capture nothing have a null environment
+    Closure(FunctionIndex, HeapAddress, TypeId),
 }
 
+// ints are computed in an i128, which holds the exact result of any two of
+// them added, subtracted or divided; multiplying saturates before it's fit
+// back into the int type unless the op wraps anyway
 macro_rules! arithmetic_op {
-      ($name:ident, $op:tt) => {
+      ($name:ident, $op:tt, $int_op:expr) => {
           pub fn $name(self, other: VmValue) -> Result<VmValue> {
               use VmValue::*;
-              Ok(match (self, other) {
-                  (Int(a), Int(b)) => Int(a $op b),
-                  (Float(a), Float(b)) => Float(a $op b),
-                  _ => return Err(RuntimeError::InvalidOperation),
-              })
+              match (self, other) {
+                  (Float(a), Float(b)) => Ok(Float(a $op b)),
+                  (a, b) => a.int_op(b, $int_op, Overflow::Checked),
+              }
+          }
+      };
+      ($name:ident, $int_op:expr, $overflow:ident) => {
+          pub fn $name(self, other: VmValue) -> Result<VmValue> {
+              self.int_op(other, $int_op, Overflow::$overflow)
+          }
+      };
+  }
+
+macro_rules! bitwise_op {
+      ($name:ident, $op:tt) => {
+          pub fn $name(self, other: VmValue) -> Result<VmValue> {
+              self.int_op(other, |a, b| Some(a $op b), Overflow::Wrapping)
+          }
+      };
+  }
+
+// shifting by a negative amount or by the whole width of an int is an error
+// rather than wrapping around; the amount can be of any int type
+macro_rules! shift_op {
+      ($name:ident, $op:tt) => {
+          pub fn $name(self, other: VmValue) -> Result<VmValue> {
+              match (self.as_int(), other.as_int()) {
+                  (Some((a, kind)), Some((b, _))) if (0..kind.bits() as i128).contains(&b) => {
+                      kind.fit(a $op b, Overflow::Wrapping)
+                  }
+                  _ => Err(RuntimeError::InvalidOperation),
+              }
           }
       };
   }
@@ -51,9 +175,16 @@ macro_rules! comparison_op {
       ($name:ident, $op:tt) => {
           pub fn $name(self, other: VmValue) -> Result<VmValue> {
               use VmValue::*;
+              if let (Some((a, kind)), Some((b, other_kind))) = (self.as_int(), other.as_int()) {
+                  if kind != other_kind {
+                      return Err(RuntimeError::InvalidOperation);
+                  }
+                  return Ok(Bool(a $op b));
+              }
+
               let result = match (self, other) {
-                  (Int(a), Int(b)) => a $op b,
                   (Float(a), Float(b)) => a $op b,
+                  (Char(a), Char(b)) => a $op b,
                   _ => return Err(RuntimeError::InvalidOperation),
               };
               Ok(Bool(result))
@@ -65,8 +196,13 @@ impl VmValue {
     pub fn write_bytes(self, memory: &mut [u8]) {
         match self {
             Self::Int(i) => memory.copy_from_slice(&i.to_be_bytes()),
+            Self::SizedInt(i, kind) => {
+                let bytes = i.to_be_bytes();
+                memory.copy_from_slice(&bytes[8 - kind.bits() as usize / 8..]);
+            }
             Self::Float(f) => memory.copy_from_slice(&f.to_be_bytes()),
             Self::Bool(b) => memory[0] = b as u8,
+            Self::Char(c) => memory.copy_from_slice(&(c as u32).to_be_bytes()),
             Self::StringHandle { len, ptr } => {
                 memory[0..8].copy_from_slice(&len.to_be_bytes());
                 memory[8..16].copy_from_slice(&ptr.0.to_be_bytes());
@@ -76,12 +212,47 @@ impl VmValue {
                 memory[8..16].copy_from_slice(&ti.0.to_be_bytes());
             }
             Self::Ref(address, ti) => {
+                memory[0..8].copy_from_slice(&address.0.to_be_bytes());
+                memory[8..16].copy_from_slice(&(ti.0 | STACK_ADDRESS).to_be_bytes());
+            }
+            Self::GlobalRef(offset, ti) => {
+                memory[0..8].copy_from_slice(&offset.to_be_bytes());
+                memory[8..16].copy_from_slice(&(ti.0 | GLOBAL_ADDRESS).to_be_bytes());
+            }
+            Self::Aggregate(_, bytes) => memory.copy_from_slice(&bytes),
+            Self::Vector(address, ti) => {
                 memory[0..8].copy_from_slice(&address.0.to_be_bytes());
                 memory[8..16].copy_from_slice(&ti.0.to_be_bytes());
             }
+            Self::Closure(func, env, ti) => {
+                memory[0..8].copy_from_slice(&func.0.to_be_bytes());
+                memory[8..16].copy_from_slice(&env.0.to_be_bytes());
+                memory[16..24].copy_from_slice(&ti.0.to_be_bytes());
+            }
+        }
+    }
+
+    // the value of a reference slot written by `write_bytes`
+    pub fn address(address: usize, tagged_type: usize) -> Self {
+        let type_id = TypeId(tagged_type & !(STACK_ADDRESS | GLOBAL_ADDRESS));
+        if tagged_type & STACK_ADDRESS != 0 {
+            Self::Ref(StackAddress(address), type_id)
+        } else if tagged_type & GLOBAL_ADDRESS != 0 {
+            Self::GlobalRef(address, type_id)
+        } else {
+            Self::Pointer(HeapAddress(address), type_id)
         }
     }
 
+    pub fn offset_by(self, offset: usize, type_id: TypeId) -> Result<VmValue> {
+        Ok(match self {
+            Self::Pointer(address, _) => Self::Pointer(HeapAddress(address.0 + offset), type_id),
+            Self::Ref(address, _) => Self::Ref(StackAddress(address.0 + offset), type_id),
+            Self::GlobalRef(address, _) => Self::GlobalRef(address + offset, type_id),
+            _ => return Err(RuntimeError::InvalidOperation),
+        })
+    }
+
     pub fn const_string(stringid: usize) -> Self {
         VmValue::StringHandle {
             len: stringid,
@@ -89,36 +260,116 @@ impl VmValue {
         }
     }
 
-    #[allow(unused)]
     pub fn heap_string(len: usize, ptr: HeapAddress) -> Self {
         VmValue::StringHandle { len, ptr }
     }
 
-    arithmetic_op!(add, +);
-    arithmetic_op!(sub, -);
-    arithmetic_op!(mul, *);
-    arithmetic_op!(div, /);
-    arithmetic_op!(modulo, %);
+    // the value and type of any int
+    pub fn as_int(&self) -> Option<(i128, IntKind)> {
+        match self {
+            Self::Int(i) => Some((*i as i128, IntKind::I64)),
+            Self::SizedInt(i, IntKind::U64) => Some((*i as u64 as i128, IntKind::U64)),
+            Self::SizedInt(i, kind) => Some((*i as i128, *kind)),
+            _ => None,
+        }
+    }
+
+    // ints of different types never meet; the analyzer doesn't let them.
+    // `op` gives `None` when dividing by zero
+    fn int_op(self, other: VmValue, op: fn(i128, i128) -> Option<i128>, overflow: Overflow) -> Result<VmValue> {
+        let (Some((a, kind)), Some((b, other_kind))) = (self.as_int(), other.as_int()) else {
+            return Err(RuntimeError::InvalidOperation);
+        };
+        if kind != other_kind {
+            return Err(RuntimeError::InvalidOperation);
+        }
+
+        let value = op(a, b).ok_or(RuntimeError::DivisionByZero)?;
+        kind.fit(value, overflow)
+    }
+
+    arithmetic_op!(add, +, |a, b| Some(a + b));
+    arithmetic_op!(sub, -, |a, b| Some(a - b));
+    arithmetic_op!(mul, *, |a, b| Some(a.saturating_mul(b)));
+    arithmetic_op!(div, /, i128::checked_div);
+    arithmetic_op!(modulo, %, i128::checked_rem);
+
+    arithmetic_op!(add_wrap, |a, b| Some(a + b), Wrapping);
+    arithmetic_op!(sub_wrap, |a, b| Some(a - b), Wrapping);
+    arithmetic_op!(mul_wrap, |a, b| Some(a.wrapping_mul(b)), Wrapping);
+    arithmetic_op!(add_sat, |a, b| Some(a + b), Saturating);
+    arithmetic_op!(sub_sat, |a, b| Some(a - b), Saturating);
+    arithmetic_op!(mul_sat, |a, b| Some(a.saturating_mul(b)), Saturating);
+
+    bitwise_op!(bit_and, &);
+    bitwise_op!(bit_or, |);
+    bitwise_op!(bit_xor, ^);
+    shift_op!(shl, <<);
+    shift_op!(shr, >>);
 
     pub fn neg(self) -> Result<VmValue> {
-        match self {
-            Self::Int(i) => Ok(VmValue::Int(-i)),
-            Self::Float(f) => Ok(VmValue::Float(-f)),
+        match (self.as_int(), self) {
+            (_, Self::Float(f)) => Ok(VmValue::Float(-f)),
+            (Some((i, kind)), _) if kind.signed() => kind.fit(-i, Overflow::Checked),
             _ => Err(RuntimeError::InvalidOperation),
         }
     }
 
+    pub fn bit_not(self) -> Result<VmValue> {
+        match self.as_int() {
+            Some((i, kind)) => kind.fit(!i, Overflow::Wrapping),
+            None => Err(RuntimeError::InvalidOperation),
+        }
+    }
+
     pub fn inc(self) -> Result<VmValue> {
-        match self {
-            Self::Int(i) => Ok(VmValue::Int(i + 1)),
-            Self::Float(f) => Ok(VmValue::Float(f + 1.)),
+        match (self.as_int(), self) {
+            (_, Self::Float(f)) => Ok(VmValue::Float(f + 1.)),
+            (Some((i, kind)), _) => kind.fit(i + 1, Overflow::Checked),
+            _ => Err(RuntimeError::InvalidOperation),
+        }
+    }
+
+    pub fn into_int(self) -> Result<VmValue> {
+        self.into_sized(IntKind::I64)
+    }
+
+    // floats are truncated towards zero, saturating at the ends of the int
+    // range; other ints and chars keep their low bits
+    pub fn into_sized(self, kind: IntKind) -> Result<VmValue> {
+        match (self.as_int(), self) {
+            (_, Self::Float(f)) => kind.fit(f as i128, Overflow::Saturating),
+            (_, Self::Bool(b)) => kind.fit(b as i128, Overflow::Wrapping),
+            (_, Self::Char(c)) => kind.fit(c as i128, Overflow::Wrapping),
+            (Some((i, _)), _) => kind.fit(i, Overflow::Wrapping),
+            _ => Err(RuntimeError::InvalidOperation),
+        }
+    }
+
+    // ints outside of the unicode scalar values are an error
+    pub fn into_char(self) -> Result<VmValue> {
+        match (self.as_int(), self) {
+            (_, Self::Char(c)) => Ok(VmValue::Char(c)),
+            (Some((i, _)), _) => u32::try_from(i)
+                .ok()
+                .and_then(char::from_u32)
+                .map(VmValue::Char)
+                .ok_or(RuntimeError::InvalidOperation),
             _ => Err(RuntimeError::InvalidOperation),
         }
     }
 
-    pub fn as_bool(self) -> Result<bool> {
+    pub fn into_float(self) -> Result<VmValue> {
+        match (self.as_int(), self) {
+            (_, Self::Float(f)) => Ok(VmValue::Float(f)),
+            (Some((i, _)), _) => Ok(VmValue::Float(i as f64)),
+            _ => Err(RuntimeError::InvalidOperation),
+        }
+    }
+
+    pub fn as_bool(&self) -> Result<bool> {
         if let Self::Bool(b) = self {
-            return Ok(b);
+            return Ok(*b);
         }
 
         Err(RuntimeError::InvalidOperation)
@@ -155,10 +406,14 @@ impl VmValue {
 
     pub fn eq(self, other: VmValue) -> Result<VmValue> {
         use VmValue::*;
+        if let (Some((a, kind)), Some((b, other_kind))) = (self.as_int(), other.as_int()) {
+            return Ok(Bool(kind == other_kind && a == b));
+        }
+
         let result = match (self, other) {
-            (Int(a), Int(b)) => a == b,
             (Float(a), Float(b)) => a == b,
             (Bool(a), Bool(b)) => a == b,
+            (Char(a), Char(b)) => a == b,
             _ => return Err(RuntimeError::InvalidOperation),
         };
         Ok(Bool(result))
@@ -168,22 +423,99 @@ impl VmValue {
         Ok(Self::Bool(!self.eq(other)?.as_bool()?))
     }
 
-    pub fn display_with_const_pool(&self, const_pool: &ConstPool) -> String {
+    pub fn display_with_const_pool(
+        &self,
+        const_pool: &ConstPool,
+        type_table: &TypeTable,
+        heap: &Heap,
+    ) -> String {
         match self {
             VmValue::Int(i) => i.to_string(),
+            VmValue::SizedInt(..) => self.as_int().map(|(i, _)| i.to_string()).unwrap_or_default(),
             VmValue::Float(f) => f.to_string(),
             VmValue::Bool(b) => b.to_string(),
+            VmValue::Char(c) => format!("'{c}'"),
             VmValue::StringHandle { len, ptr } => {
                 if ptr.0 == 0 {
                     const_pool.get_string(*len)
                         .map(|s| format!("\"{}\"", s))
                         .unwrap_or_else(|| format!("StringHandle {{ len: {}, ptr: {:?} }}", len, ptr))
                 } else {
-                    format!("StringHandle {{ len: {}, ptr: {:?} }}", len, ptr)
+                    format!("\"{}\"", String::from_utf8_lossy(heap.read_bytes(*ptr, *len)))
                 }
             }
             VmValue::Pointer(addr, type_id) => format!("Pointer({:?}, {:?})", addr, type_id),
             VmValue::Ref(addr, type_id) => format!("Ref({:?}, {:?})", addr, type_id),
+            VmValue::GlobalRef(addr, type_id) => format!("GlobalRef({:?}, {:?})", addr, type_id),
+            VmValue::Aggregate(type_id, bytes) => {
+                let display_at = |offset: usize, type_id: TypeId| {
+                    let ty = &type_table[type_id];
+                    let mut reader = ByteReader::new(&bytes[offset..], ty.size().0);
+                    ty.construct(&mut reader)
+                        .map(|v| v.display_with_const_pool(const_pool, type_table, heap))
+                        .unwrap_or_else(|_| "?".to_owned())
+                };
+
+                match &type_table[*type_id] {
+                    Type::Custom(layout) => {
+                        let fields = layout
+                            .fields
+                            .iter()
+                            .map(|field| display_at(field.offset, field.type_id))
+                            .collect::<Vec<_>>();
+                        format!("{{ {} }}", fields.join(", "))
+                    }
+                    Type::Tuple(layout) => {
+                        let elements = layout
+                            .fields
+                            .iter()
+                            .map(|field| display_at(field.offset, field.type_id))
+                            .collect::<Vec<_>>();
+                        format!("({})", elements.join(", "))
+                    }
+                    Type::Enum(e) => {
+                        let fields = e
+                            .active_fields(bytes)
+                            .iter()
+                            .map(|field| display_at(field.offset, field.type_id))
+                            .collect::<Vec<_>>();
+                        format!("#{}({})", EnumLayout::tag(bytes), fields.join(", "))
+                    }
+                    Type::Optional(e) => match e.active_fields(bytes).first() {
+                        Some(field) => format!("some({})", display_at(field.offset, field.type_id)),
+                        None => "none".to_owned(),
+                    },
+                    Type::Result(e) => match (EnumLayout::tag(bytes), e.active_fields(bytes).first()) {
+                        (0, Some(field)) => format!("ok({})", display_at(field.offset, field.type_id)),
+                        (_, Some(field)) => format!("err({})", display_at(field.offset, field.type_id)),
+                        (tag, None) => format!("#{tag}"),
+                    },
+                    Type::Array(layout) => {
+                        let elements = (0..layout.len)
+                            .map(|i| display_at(i * layout.element_size, layout.element))
+                            .collect::<Vec<_>>();
+                        format!("[{}]", elements.join(", "))
+                    }
+                    _ => format!("Aggregate({:?}, {:?})", type_id, bytes),
+                }
+            }
+            VmValue::Vector(addr, type_id) => {
+                let Type::Vector(layout) = &type_table[*type_id] else {
+                    return format!("Vector({:?}, {:?})", addr, type_id);
+                };
+
+                let header = heap.read_vector(*addr);
+                let elements = (0..header.len)
+                    .map(|i| {
+                        let slot = HeapAddress(header.data.0 + i * layout.element_size);
+                        heap.read_value(slot, layout.element, type_table)
+                            .map(|v| v.display_with_const_pool(const_pool, type_table, heap))
+                            .unwrap_or_else(|_| "?".to_owned())
+                    })
+                    .collect::<Vec<_>>();
+                format!("vec[{}]", elements.join(", "))
+            }
+            VmValue::Closure(func, _, _) => format!("fn#{}", func.0),
         }
     }
 }